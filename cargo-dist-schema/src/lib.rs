@@ -358,6 +358,9 @@ pub enum ArtifactKind {
     /// An updater executable
     #[serde(rename = "updater")]
     Updater,
+    /// A zsync file for delta updates of another artifact
+    #[serde(rename = "zsync")]
+    Zsync,
     /// Unknown to this version of cargo-dist-schema
     ///
     /// This is a fallback for forward/backward-compat
@@ -523,7 +526,10 @@ impl DistManifest {
     ///
     /// This lets you know what system dependencies an entire archive of binaries requires
     pub fn linkage_for_artifact(&self, artifact_id: &ArtifactId) -> Linkage {
-        let mut output = Linkage::default();
+        let mut output = Linkage {
+            artifact_id: Some(artifact_id.clone()),
+            ..Linkage::default()
+        };
 
         let Some(artifact) = self.artifacts.get(artifact_id) else {
             return output;
@@ -596,6 +602,114 @@ impl Hosting {
 /// Information about dynamic libraries used by a binary
 #[derive(Clone, Default, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Linkage {
+    /// The artifact the analyzed binary ships in, if it was checked as part of one
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<ArtifactId>,
+    /// The outcome of the linkage checks for this binary, if they were run
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<LinkageStatus>,
+    /// An identifier for the exact build of the binary, for matching it to its debug symbols
+    ///
+    /// This is the GNU build-id on ELF, the LC_UUID on Mach-O, and the PDB GUID and age on Windows.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+    /// The separate debug file the binary refers to, if any
+    ///
+    /// This is the .gnu_debuglink filename on ELF and the PDB path on Windows.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_file: Option<String>,
+    /// Debug symbols found alongside the binary (a .dSYM bundle or a .pdb file)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_symbols: Option<String>,
+    /// If the binary that was checked is a symlink, the real file it points to
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+    /// The binary's container format ("ELF", "Mach-O", "fat Mach-O", "PE" or "WASM")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Whether an ELF binary is 32-bit or 64-bit ("ELF32" or "ELF64")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elf_class: Option<String>,
+    /// The byte order of an ELF binary ("little-endian" or "big-endian")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endianness: Option<String>,
+    /// The name a shared library declares for itself, which is what binaries linking it record
+    ///
+    /// This is the DT_SONAME on ELF and the LC_ID_DYLIB install name on Mach-O.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soname: Option<String>,
+    /// The architecture this linkage is for, if it's one slice of a universal binary
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// The linkage of each architecture in a universal binary
+    ///
+    /// Each slice is linked separately, so their dependencies can differ; the other
+    /// fields of this Linkage are the union of all of them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub slices: Vec<Linkage>,
+    /// Potential problems noticed with the binary that don't fit in any of the other fields
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// The newest glibc version the binary requires symbols from (e.g. "2.17")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_glibc: Option<String>,
+    /// A symbol that requires min_glibc (e.g. "memcpy@GLIBC_2.14")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_glibc_symbol: Option<String>,
+    /// The oldest macOS version the binary runs on (e.g. "11.0")
+    ///
+    /// For a universal binary, this is the oldest any of its architectures runs on;
+    /// each slice records its own.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_macos: Option<String>,
+    /// The directories the binary asks the dynamic loader to search for libraries
+    ///
+    /// These come from DT_RPATH and DT_RUNPATH on Linux, and LC_RPATH on macOS.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rpaths: Vec<String>,
+    /// The exploit mitigations the binary was built with
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardening: Option<Hardening>,
+    /// The Windows subsystem the binary runs under (e.g. "console" or "gui")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pe_subsystem: Option<String>,
+    /// The Visual C++ runtime DLLs the binary needs that aren't bundled with it
+    ///
+    /// These come with the Visual C++ Redistributable rather than Windows itself, so
+    /// users need it installed to run the binary.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vc_redist: Vec<String>,
+    /// The C++ standard library the binary links, if any (e.g. "libstdc++" or "libc++")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cxx_runtime: Option<String>,
+    /// The version of the C++ standard library the binary needs
+    ///
+    /// For libstdc++ this is the newest GLIBCXX symbol version it uses (e.g. "3.4.30");
+    /// otherwise it's the library's ABI version (e.g. "1" for libc++.1.dylib).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cxx_runtime_version: Option<String>,
     /// Libraries included with the operating system
     #[serde(default)]
     #[serde(skip_serializing_if = "SortedSet::is_empty")]
@@ -616,27 +730,309 @@ pub struct Linkage {
     #[serde(default)]
     #[serde(skip_serializing_if = "SortedSet::is_empty")]
     pub frameworks: SortedSet<Library>,
+    /// Runtime libraries the toolchain linked in (e.g. libgcc_s or sanitizer runtimes),
+    /// rather than ones any dependency asked for
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub toolchain: SortedSet<Library>,
+    /// Language runtimes the binary embeds (e.g. libpython or libruby), which tie it to
+    /// one specific version of that language's interpreter
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub language_runtimes: SortedSet<Library>,
+    /// Graphics and display libraries (e.g. libX11, libwayland-client, libGL or libvulkan),
+    /// which mean the binary needs a windowing system or GPU driver to run
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub graphics: SortedSet<Library>,
+    /// Imports a WebAssembly module requires from its host, only used for wasm
+    ///
+    /// The path is the imported field, and the source is the module it's imported from.
+    /// For components, the path is the import's name, and the source is its kind
+    /// (like `instance` or `func`).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub wasm_imports: SortedSet<Library>,
+    /// Libraries that seem to be statically linked into the binary, inferred from its symbols
+    ///
+    /// This is a guess, and is only filled in when a symbol scan was asked for.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub static_bundled: SortedSet<Library>,
+    /// Libraries the binary may load at runtime (e.g. with dlopen or LoadLibrary), inferred
+    /// from the file names in it
+    ///
+    /// This is a guess, and is only filled in when a string scan was asked for.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedSet::is_empty")]
+    pub runtime_loaded: SortedSet<Library>,
+    /// The tree of libraries the binary loads, and the libraries those load in turn
+    ///
+    /// This is only filled in by a deep check.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependency_tree: Vec<DependencyNode>,
+}
+
+/// A library in a binary's dependency tree
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DependencyNode {
+    /// The library's path, or just its name if it couldn't be found
+    pub library: String,
+    /// The libraries this one loads
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyNode>,
+    /// Whether the tree was cut off here, so the library's dependencies weren't followed
+    #[serde(default)]
+    pub depth_limited: bool,
+    /// Whether the library was already listed earlier in the tree, along with its dependencies
+    #[serde(default)]
+    pub repeated: bool,
+}
+
+/// The exploit mitigations a binary was built with
+///
+/// Each is only recorded for the formats it applies to, and is `None` for the rest.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct Hardening {
+    /// Whether the binary is position-independent, so it can be loaded at a random address
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pie: Option<bool>,
+    /// How much of the binary's relocation data is made read-only once it's loaded (ELF)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relro: Option<Relro>,
+    /// Whether the binary's stack and data can't be executed (DEP on Windows)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nx: Option<bool>,
+    /// Whether the binary checks stack canaries to catch buffer overflows
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_canary: Option<bool>,
+    /// Whether the binary uses libc's bounds-checked functions (_FORTIFY_SOURCE) (ELF)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fortify: Option<bool>,
+    /// Whether Windows may load the binary at a random address (PE)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aslr: Option<bool>,
+    /// Whether that random address can be anywhere in the 64-bit address space (PE)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high_entropy_va: Option<bool>,
+    /// Whether the binary uses Control Flow Guard (PE)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cfg: Option<bool>,
+}
+
+/// How much of an ELF binary's relocation data is made read-only once it's loaded
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub enum Relro {
+    /// None of it, so an attacker can overwrite the GOT
+    #[serde(rename = "none")]
+    None,
+    /// Everything except the GOT entries that are resolved lazily
+    #[serde(rename = "partial")]
+    Partial,
+    /// All of it, since every symbol is resolved when the binary is loaded
+    #[serde(rename = "full")]
+    Full,
+}
+
+/// The outcome of the linkage checks for a binary
+///
+/// These are ordered by severity, so the overall status of several
+/// binaries is the max of their statuses.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LinkageStatus {
+    /// Nothing noteworthy was found
+    #[serde(rename = "ok")]
+    Ok,
+    /// Something was found that may cause problems for users
+    #[serde(rename = "warning")]
+    Warning,
+    /// A linkage policy was violated
+    #[serde(rename = "violation")]
+    Violation,
+}
+
+impl std::fmt::Display for LinkageStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkageStatus::Ok => write!(f, "ok"),
+            LinkageStatus::Warning => write!(f, "warning"),
+            LinkageStatus::Violation => write!(f, "violation"),
+        }
+    }
 }
 
 /// Represents a dynamic library located somewhere on the system
+///
+/// Linkage keeps these in sorted sets, ordered by path, then source, then the rest of the
+/// fields in the order they're declared here, so the linkage report and its JSON come out in
+/// the same order every run. Entries that share a path and source but differ in anything else
+/// (e.g. the reason the library is linked) are listed separately.
 #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Library {
     /// The path to the library; on platforms without that information, it will be a basename instead
     pub path: String,
     /// The package from which a library comes, if relevant
+    ///
+    /// If several packages ship the library, they're all listed, separated by ", ".
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// How sure we are that the source is correct
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// Why the binary links against this library, if the binary format records it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<LinkReason>,
+    /// Where the library will be loaded from at runtime (only recorded for Windows, unless
+    /// libraries are required to be bundled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<DllLocation>,
+    /// The license of the package the library comes from, if it was looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The language runtime the library provides, and its version if known (e.g. `Python 3.11`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// The newest versioned symbol the binary needs from the library (e.g. `GLIBCXX_3.4.30`)
+    ///
+    /// A system's copy of the library must provide this version for the binary to run there.
+    /// Only ELF binaries record this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_symbol_version: Option<String>,
+}
+
+/// How reliable the attribution of a library to a package is
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum Confidence {
+    /// A guess, e.g. from the shape of the library's path
+    #[serde(rename = "low")]
+    Low,
+    /// Probably right, but not verified with the package manager
+    #[serde(rename = "medium")]
+    Medium,
+    /// The package manager itself says it owns the library
+    #[serde(rename = "high")]
+    High,
+}
+
+/// Where a library dependency (originally, a Windows DLL) will be found at runtime
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum DllLocation {
+    /// The library ships alongside the binary (and on Windows, will be loaded from there first)
+    #[serde(rename = "bundled")]
+    Bundled,
+    /// The library is provided by the operating system itself
+    #[serde(rename = "system")]
+    System,
+    /// The library wasn't found alongside the binary or recognized as part of the
+    /// operating system, so users may be missing it
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+impl std::fmt::Display for DllLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DllLocation::Bundled => write!(f, "bundled"),
+            DllLocation::System => write!(f, "system"),
+            DllLocation::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// The way a binary links against a library (currently only recorded for Mach-O and Windows)
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LinkReason {
+    /// A normal load of the library
+    #[serde(rename = "load")]
+    Load,
+    /// The library is weakly linked, and may be missing at runtime
+    #[serde(rename = "weak")]
+    Weak,
+    /// The binary re-exports the library's symbols as its own
+    #[serde(rename = "reexport")]
+    Reexport,
+    /// An upward link, used to resolve circular dependencies between libraries
+    #[serde(rename = "upward")]
+    Upward,
+    /// The library is only loaded when one of its symbols is first used
+    #[serde(rename = "lazy")]
+    Lazy,
+    /// The DLL is delay-loaded, only being loaded when one of its functions is first called
+    #[serde(rename = "delay")]
+    Delay,
+}
+
+impl std::fmt::Display for LinkReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkReason::Load => write!(f, "load"),
+            LinkReason::Weak => write!(f, "weak"),
+            LinkReason::Reexport => write!(f, "reexport"),
+            LinkReason::Upward => write!(f, "upward"),
+            LinkReason::Lazy => write!(f, "lazy"),
+            LinkReason::Delay => write!(f, "delay"),
+        }
+    }
 }
 
 impl Linkage {
     /// merge another linkage into this one
+    ///
+    /// min_glibc becomes the newer of the two, so the result runs wherever all of them do.
     pub fn extend(&mut self, val: &Linkage) {
         let Linkage {
+            artifact_id: _,
+            status: _,
+            build_id: _,
+            debug_file: _,
+            debug_symbols: _,
+            symlink_target: _,
+            format: _,
+            elf_class: _,
+            endianness: _,
+            soname: _,
+            arch: _,
+            slices: _,
+            warnings: _,
+            min_glibc,
+            min_glibc_symbol,
+            min_macos: _,
+            rpaths: _,
+            hardening: _,
+            pe_subsystem: _,
+            vc_redist: _,
+            cxx_runtime: _,
+            cxx_runtime_version: _,
             system,
             homebrew,
             public_unmanaged,
             other,
             frameworks,
+            toolchain,
+            language_runtimes,
+            graphics,
+            wasm_imports,
+            static_bundled,
+            runtime_loaded,
+            dependency_tree: _,
         } = val;
         self.system.extend(system.iter().cloned());
         self.homebrew.extend(homebrew.iter().cloned());
@@ -644,23 +1040,146 @@ impl Linkage {
             .extend(public_unmanaged.iter().cloned());
         self.other.extend(other.iter().cloned());
         self.frameworks.extend(frameworks.iter().cloned());
+        self.toolchain.extend(toolchain.iter().cloned());
+        self.language_runtimes
+            .extend(language_runtimes.iter().cloned());
+        self.graphics.extend(graphics.iter().cloned());
+        self.wasm_imports.extend(wasm_imports.iter().cloned());
+        self.static_bundled.extend(static_bundled.iter().cloned());
+        self.runtime_loaded.extend(runtime_loaded.iter().cloned());
+        if let Some(version) = min_glibc {
+            let newer = match &self.min_glibc {
+                Some(current) => glibc_version(version) > glibc_version(current),
+                None => true,
+            };
+            if newer {
+                self.min_glibc = Some(version.clone());
+                self.min_glibc_symbol = min_glibc_symbol.clone();
+            }
+        }
+    }
+
+    /// Iterate over every library in every category
+    ///
+    /// Wasm imports aren't libraries, so they aren't included.
+    pub fn libraries(&self) -> impl Iterator<Item = &Library> {
+        self.system
+            .iter()
+            .chain(&self.homebrew)
+            .chain(&self.public_unmanaged)
+            .chain(&self.other)
+            .chain(&self.frameworks)
+            .chain(&self.toolchain)
+            .chain(&self.language_runtimes)
+            .chain(&self.graphics)
+    }
+
+    /// Whether the binary has no dynamic linkage at all
+    pub fn is_static(&self) -> bool {
+        self.libraries().next().is_none()
+    }
+
+    /// Count how many libraries the binary links in each category, and how
+    pub fn histogram(&self) -> LinkageHistogram {
+        let libraries = || self.libraries();
+        let linked_by = |reasons: &[LinkReason]| {
+            libraries()
+                .filter(|l| l.reason.is_some_and(|r| reasons.contains(&r)))
+                .count()
+        };
+        LinkageHistogram {
+            system: self.system.len(),
+            homebrew: self.homebrew.len(),
+            public_unmanaged: self.public_unmanaged.len(),
+            frameworks: self.frameworks.len(),
+            toolchain: self.toolchain.len(),
+            language_runtimes: self.language_runtimes.len(),
+            graphics: self.graphics.len(),
+            other: self.other.len(),
+            weak: linked_by(&[LinkReason::Weak]),
+            deferred: linked_by(&[LinkReason::Lazy, LinkReason::Delay]),
+            total: libraries().count(),
+        }
     }
 }
 
+/// How many libraries a binary links in each category, as counted by [`Linkage::histogram`][]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LinkageHistogram {
+    /// Libraries that come with the operating system
+    pub system: usize,
+    /// Libraries from Homebrew
+    pub homebrew: usize,
+    /// Libraries in public locations that no package manager provides
+    pub public_unmanaged: usize,
+    /// macOS frameworks
+    pub frameworks: usize,
+    /// Runtime libraries linked in by the toolchain
+    pub toolchain: usize,
+    /// Embedded language runtimes
+    pub language_runtimes: usize,
+    /// Graphics and display libraries
+    pub graphics: usize,
+    /// Everything else
+    pub other: usize,
+    /// How many of all those libraries are weakly linked, so may be missing at runtime
+    pub weak: usize,
+    /// How many of all those libraries are lazily or delay-loaded
+    pub deferred: usize,
+    /// How many libraries the binary links in total (wasm imports aren't libraries)
+    pub total: usize,
+}
+
 impl Library {
     /// Make a new Library with the given path and no source
     pub fn new(path: String) -> Self {
-        Self { path, source: None }
+        Self {
+            path,
+            source: None,
+            confidence: None,
+            reason: None,
+            location: None,
+            license: None,
+            runtime: None,
+            min_symbol_version: None,
+        }
     }
 }
 
 impl std::fmt::Display for Library {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
         if let Some(package) = &self.source {
-            write!(f, "{} ({package})", self.path)
-        } else {
-            write!(f, "{}", self.path)
+            // Call out guesses so they're not mistaken for verified ownership
+            if self.confidence == Some(Confidence::Low) {
+                write!(f, " ({package}?)")?;
+            } else {
+                write!(f, " ({package})")?;
+            }
+        }
+        let mut notes = vec![];
+        // Plain loads are the overwhelmingly common case, so only call out the unusual ones
+        if let Some(reason) = self.reason {
+            if reason != LinkReason::Load {
+                notes.push(reason.to_string());
+            }
+        }
+        if let Some(location) = self.location {
+            notes.push(location.to_string());
+        }
+        if let Some(runtime) = &self.runtime {
+            notes.push(runtime.clone());
+        }
+        if let Some(min_symbol_version) = &self.min_symbol_version {
+            notes.push(format!("needs {min_symbol_version}"));
         }
+        if let Some(license) = &self.license {
+            notes.push(license.clone());
+        }
+        if !notes.is_empty() {
+            write!(f, " [{}]", notes.join(", "))?;
+        }
+        Ok(())
     }
 }
 
@@ -686,6 +1205,14 @@ pub fn check_version(input: &str) -> Option<VersionInfo> {
     Some(VersionInfo { version, format })
 }
 
+/// Parse a glibc version like "2.17" so versions compare numerically (2.9 < 2.17)
+fn glibc_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
 /// Get the format for a given version
 pub fn format_of_version(version: &Version) -> Format {
     let epoch1 = Version::parse(DIST_EPOCH_1_MAX).unwrap();
@@ -708,3 +1235,65 @@ fn emit() {
     let json_schema = serde_json::to_string_pretty(&schema).unwrap();
     insta::assert_snapshot!(json_schema);
 }
+
+#[test]
+fn linkage_library_order() {
+    let library = |path: &str, source: Option<&str>| Library {
+        source: source.map(ToOwned::to_owned),
+        ..Library::new(path.to_owned())
+    };
+    let mut linkage = Linkage::default();
+    linkage.system.insert(library("libz.so.1", Some("zlib1g")));
+    linkage.system.insert(library("libc.so.6", Some("libc6")));
+    linkage
+        .system
+        .insert(library("libc.so.6", Some("libc-bin")));
+    linkage.system.insert(library("libc.so.6", None));
+    linkage.system.insert(Library {
+        reason: Some(LinkReason::Weak),
+        ..library("libc.so.6", None)
+    });
+
+    let json = serde_json::to_value(&linkage).unwrap();
+    let system = json["system"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|library| {
+            (
+                library["path"].as_str().unwrap(),
+                library["source"].as_str(),
+                library["reason"].as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        system,
+        [
+            ("libc.so.6", None, None),
+            ("libc.so.6", None, Some("weak")),
+            ("libc.so.6", Some("libc-bin"), None),
+            ("libc.so.6", Some("libc6"), None),
+            ("libz.so.1", Some("zlib1g"), None),
+        ]
+    );
+}
+
+#[test]
+fn linkage_extend_min_glibc() {
+    let linkage = |version: &str, symbol: &str| Linkage {
+        min_glibc: Some(version.to_owned()),
+        min_glibc_symbol: Some(symbol.to_owned()),
+        ..Linkage::default()
+    };
+    let mut merged = Linkage::default();
+    merged.extend(&linkage("2.9", "memcpy@GLIBC_2.9"));
+    merged.extend(&linkage("2.17", "clock_gettime@GLIBC_2.17"));
+    merged.extend(&linkage("2.14", "memcpy@GLIBC_2.14"));
+    merged.extend(&Linkage::default());
+    assert_eq!(merged.min_glibc.as_deref(), Some("2.17"));
+    assert_eq!(
+        merged.min_glibc_symbol.as_deref(),
+        Some("clock_gettime@GLIBC_2.17")
+    );
+}
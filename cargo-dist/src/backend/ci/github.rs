@@ -10,10 +10,10 @@ use serde::Serialize;
 use tracing::warn;
 
 use crate::{
-    backend::{diff_files, templates::TEMPLATE_CI_GITHUB},
+    backend::{diff_files, installer::InstallerImpl, templates::TEMPLATE_CI_GITHUB},
     config::{DependencyKind, HostingStyle, ProductionMode, SystemDependencies},
     errors::DistResult,
-    DistGraph, SortedMap, SortedSet, TargetTriple,
+    ArtifactKind, DistGraph, SortedMap, SortedSet, TargetTriple,
 };
 
 const GITHUB_CI_DIR: &str = ".github/workflows/";
@@ -42,6 +42,8 @@ pub struct GithubCiInfo {
     pub global_task: GithubMatrixEntry,
     /// homebrew tap
     pub tap: Option<String>,
+    /// scoop bucket
+    pub bucket: Option<String>,
     /// plan jobs
     pub plan_jobs: Vec<String>,
     /// local artifacts jobs
@@ -64,6 +66,56 @@ pub struct GithubCiInfo {
     pub hosting_providers: Vec<HostingStyle>,
     /// whether to prefix release.yml and the tag pattern
     pub tag_namespace: Option<String>,
+    /// whether to upload each build's linkage problems to code scanning
+    pub upload_linkage_sarif: bool,
+    /// GPG key to sign rpms with
+    pub rpm_signing_key: Option<String>,
+    /// GitHub repository to host an APT repository on the gh-pages branch of
+    pub apt_repository: Option<String>,
+    /// S3 URL to host an APT repository at
+    pub apt_s3_bucket: Option<String>,
+    /// GPG key to sign the APT repository with
+    pub apt_signing_key: Option<String>,
+    /// The APT repository's suite
+    pub apt_suite: String,
+    /// The APT repository's component
+    pub apt_component: String,
+    /// GitHub repository to host a YUM repository on the gh-pages branch of
+    pub yum_repository: Option<String>,
+    /// S3 URL to host a YUM repository at
+    pub yum_s3_bucket: Option<String>,
+    /// URL users fetch the YUM repository from
+    pub yum_url: Option<String>,
+    /// The YUM repository's id in .repo files
+    pub yum_repo_id: Option<String>,
+    /// GPG key to sign the YUM repository with
+    pub yum_signing_key: Option<String>,
+    /// GitHub repository to host an APK repository on the gh-pages branch of
+    pub apk_repository: Option<String>,
+    /// S3 URL to host an APK repository at
+    pub apk_s3_bucket: Option<String>,
+    /// Name of the RSA key to sign the APK repository with
+    pub apk_signing_key: Option<String>,
+    /// whether any builds make AppImages, and so need appimagetool
+    pub build_appimages: bool,
+    /// app ids of the Flatpak manifests to build .flatpak bundles from
+    pub flatpak_bundles: Vec<String>,
+    /// names of the snaps to build, and the Snap Store channels to release them to
+    pub snaps: SortedMap<String, String>,
+    /// Nix flakes to publish, and the dirs of the repository to publish them to
+    pub nix_flakes: SortedMap<String, String>,
+    /// Chocolatey packages to push, and the feeds to push them to
+    pub chocolatey_packages: SortedMap<String, String>,
+    /// whether any builds make signed pkgs, and so need a signing certificate
+    pub sign_pkgs: bool,
+    /// whether any builds codesign macOS binaries or dmgs, and so need a signing certificate
+    pub codesign_macos: bool,
+    /// whether any builds notarize macOS binaries, dmgs or pkgs, and so need Apple credentials
+    pub notarize_macos: bool,
+    /// whether any builds make MSIX packages, and so need the Windows SDK's tools
+    pub build_msix: bool,
+    /// whether any MSIX packages are signed, and so need a signing certificate
+    pub sign_msix: bool,
 }
 
 impl GithubCiInfo {
@@ -84,6 +136,101 @@ impl GithubCiInfo {
         let create_release = dist.create_release;
         let ssldotcom_windows_sign = dist.ssldotcom_windows_sign.clone();
         let tag_namespace = dist.tag_namespace.clone();
+        let upload_linkage_sarif = dist.linkage.upload_sarif == Some(true);
+        let rpm_signing_key = dist.rpm.signing_key.clone();
+        let apt_repository = dist.apt.repository.clone();
+        let apt_s3_bucket = dist.apt.s3_bucket.clone();
+        let apt_signing_key = dist.apt.signing_key.clone();
+        let apt_suite = dist
+            .apt
+            .suite
+            .clone()
+            .unwrap_or_else(|| "stable".to_owned());
+        let apt_component = dist
+            .apt
+            .component
+            .clone()
+            .unwrap_or_else(|| "main".to_owned());
+        let yum_repository = dist.yum.repository.clone();
+        let yum_s3_bucket = dist.yum.s3_bucket.clone();
+        let yum_url = dist.yum.public_url();
+        let yum_repo_id = dist.yum.repo_id();
+        let yum_signing_key = dist.yum.signing_key.clone();
+        let apk_repository = dist.apk.repository.clone();
+        let apk_s3_bucket = dist.apk.s3_bucket.clone();
+        let apk_signing_key = dist.apk.signing_key.clone();
+        let build_appimages = dist.artifacts.iter().any(|artifact| {
+            matches!(
+                artifact.kind,
+                ArtifactKind::Installer(InstallerImpl::AppImage(..))
+            )
+        });
+        let flatpak_bundles = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Flatpak(info)) if info.bundle => {
+                    Some(info.app_id.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        let snaps = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Snap(info)) => {
+                    Some((info.name.clone(), info.channel.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        let sign_pkgs = dist.artifacts.iter().any(|artifact| {
+            matches!(
+                &artifact.kind,
+                ArtifactKind::Installer(InstallerImpl::Pkg(info)) if info.signing_identity.is_some()
+            )
+        });
+        let dmgs = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Dmg(info)) => Some(info),
+                _ => None,
+            });
+        let codesign_macos = dist.macos_sign.identity.is_some()
+            || dmgs.clone().any(|info| info.signing_identity.is_some());
+        let notarize_macos =
+            dist.macos_sign.notarize.unwrap_or(false) || dmgs.clone().any(|info| info.notarize);
+        let msixes = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Msix(info)) => Some(info),
+                _ => None,
+            });
+        let build_msix = msixes.clone().next().is_some();
+        let sign_msix = msixes.clone().any(|info| info.sign);
+        let nix_flakes = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Nix(info)) => {
+                    Some((artifact.id.clone(), info.flake_dir.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        let chocolatey_packages = dist
+            .artifacts
+            .iter()
+            .filter_map(|artifact| match &artifact.kind {
+                ArtifactKind::Installer(InstallerImpl::Chocolatey(info)) => {
+                    Some((artifact.id.clone(), info.source.clone()))
+                }
+                _ => None,
+            })
+            .collect();
         let mut dependencies = SystemDependencies::default();
 
         // Figure out what builds we need to do
@@ -124,6 +271,7 @@ impl GithubCiInfo {
         let pr_run_mode = dist.pr_run_mode;
 
         let tap = dist.tap.clone();
+        let bucket = dist.bucket.clone();
         let plan_jobs = dist.plan_jobs.clone();
         let local_artifacts_jobs = dist.local_artifacts_jobs.clone();
         let global_artifacts_jobs = dist.global_artifacts_jobs.clone();
@@ -164,6 +312,7 @@ impl GithubCiInfo {
             build_local_artifacts,
             dispatch_releases,
             tap,
+            bucket,
             plan_jobs,
             local_artifacts_jobs,
             global_artifacts_jobs,
@@ -177,6 +326,31 @@ impl GithubCiInfo {
             create_release,
             ssldotcom_windows_sign,
             hosting_providers,
+            upload_linkage_sarif,
+            rpm_signing_key,
+            apt_repository,
+            apt_s3_bucket,
+            apt_signing_key,
+            apt_suite,
+            apt_component,
+            yum_repository,
+            yum_s3_bucket,
+            yum_url,
+            yum_repo_id,
+            yum_signing_key,
+            apk_repository,
+            apk_s3_bucket,
+            apk_signing_key,
+            build_appimages,
+            flatpak_bundles,
+            snaps,
+            nix_flakes,
+            chocolatey_packages,
+            sign_pkgs,
+            codesign_macos,
+            notarize_macos,
+            build_msix,
+            sign_msix,
         }
     }
 
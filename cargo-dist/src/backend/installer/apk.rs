@@ -0,0 +1,228 @@
+//! Alpine apk installer
+
+use camino::{Utf8Path, Utf8PathBuf};
+use flate2::{write::GzEncoder, Compression};
+use sha2::Digest;
+use tracing::info;
+
+use super::linux::{build_timestamp, gzip, package_files, tar_header, PackageFile, PackageLayout};
+use crate::{errors::*, tasks::StaticAssetKind, to_hex, SortedMap, SortedSet};
+
+/// Info needed to build an apk
+#[derive(Debug, Clone)]
+pub struct ApkInstallerInfo {
+    /// Final file path of the apk
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The name of the package
+    pub package: String,
+    /// The Alpine version of the package (e.g. "1.2.3-r0")
+    pub version: String,
+    /// The Alpine architecture of the package (e.g. "x86_64")
+    pub arch: String,
+    /// The packager, from the first entry of "authors"
+    pub packager: Option<String>,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// The license of the app
+    pub license: Option<String>,
+    /// The app's homepage
+    pub homepage: Option<String>,
+    /// Binaries we'll be baking into the apk
+    pub binaries: Vec<String>,
+    /// Static assets to install alongside the binaries (docs, man pages, completions...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+}
+
+impl ApkInstallerInfo {
+    /// Build the apk installer
+    ///
+    /// An apk is a gzipped tarball of the package's metadata, glued to a gzipped tarball
+    /// of its files. It isn't signed: apk trusts packages in a repository through the
+    /// checksums in the repository's index, which the apk publish job signs.
+    pub fn build(&self) -> DistResult<()> {
+        info!("building an apk: {}", self.file_path);
+
+        let layout = PackageLayout {
+            package: &self.package,
+            zsh_completions_dir: "usr/share/zsh/site-functions",
+            license_dir: Some(format!("usr/share/licenses/{}", self.package)),
+        };
+        let files = package_files(
+            &layout,
+            &self.package_dir,
+            &self.binaries,
+            &self.static_assets,
+        )?;
+
+        let mtime = build_timestamp();
+        let data = data_segment(&files, mtime)?;
+        let installed_size = files.iter().map(|file| file.contents.len() as u64).sum();
+        let datahash = to_hex(&sha2::Sha256::digest(&data));
+        let pkginfo = self.pkginfo(installed_size, &datahash, mtime);
+        let mut apk = control_segment(&pkginfo, mtime)?;
+        apk.extend_from_slice(&data);
+        std::fs::write(&self.file_path, apk)?;
+
+        Ok(())
+    }
+
+    /// Compute the contents of the .PKGINFO file
+    fn pkginfo(&self, installed_size: u64, datahash: &str, builddate: u64) -> String {
+        let desc = self.desc.as_deref().unwrap_or(&self.package).trim();
+        let desc = desc.lines().next().unwrap_or_default();
+
+        let mut pkginfo = String::new();
+        pkginfo.push_str(&format!("pkgname = {}\n", self.package));
+        pkginfo.push_str(&format!("pkgver = {}\n", self.version));
+        pkginfo.push_str(&format!("pkgdesc = {desc}\n"));
+        if let Some(homepage) = &self.homepage {
+            pkginfo.push_str(&format!("url = {homepage}\n"));
+        }
+        pkginfo.push_str(&format!("builddate = {builddate}\n"));
+        if let Some(packager) = &self.packager {
+            pkginfo.push_str(&format!("packager = {packager}\n"));
+        }
+        pkginfo.push_str(&format!("size = {installed_size}\n"));
+        pkginfo.push_str(&format!("arch = {}\n", self.arch));
+        pkginfo.push_str(&format!("origin = {}\n", self.package));
+        if let Some(license) = &self.license {
+            pkginfo.push_str(&format!("license = {license}\n"));
+        }
+        pkginfo.push_str(&format!("datahash = {datahash}\n"));
+        pkginfo
+    }
+}
+
+/// Get the Alpine architecture for a target triple, if Alpine has one
+pub fn apk_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    let apk_arch = match arch {
+        "x86_64" => "x86_64",
+        "i586" | "i686" => "x86",
+        "aarch64" => "aarch64",
+        "armv7" => "armv7",
+        "arm" if target.ends_with("hf") => "armhf",
+        "powerpc64le" => "ppc64le",
+        "s390x" => "s390x",
+        "riscv64gc" => "riscv64",
+        "loongarch64" => "loongarch64",
+        _ => return None,
+    };
+    Some(apk_arch)
+}
+
+/// Turn a semver version into an Alpine version
+///
+/// Alpine only understands a few kinds of prerelease (`_alpha`, `_beta`, `_pre` and
+/// `_rc`, each with an optional number), so anything else is treated as `_pre`.
+pub fn apk_version(version: &semver::Version) -> String {
+    let mut apk_version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        let mut kind = "pre";
+        let mut number = "";
+        for identifier in version.pre.split(['.', '-']) {
+            if identifier.chars().all(|c| c.is_ascii_digit()) {
+                if number.is_empty() {
+                    number = identifier;
+                }
+            } else if let Some(known) = ["alpha", "beta", "pre", "rc"]
+                .into_iter()
+                .find(|known| identifier.eq_ignore_ascii_case(known))
+            {
+                kind = known;
+            }
+        }
+        apk_version.push_str(&format!("_{kind}{number}"));
+    }
+    apk_version.push_str("-r0");
+    apk_version
+}
+
+/// Make the gzipped tarball of the package's metadata
+fn control_segment(pkginfo: &str, mtime: u64) -> DistResult<Vec<u8>> {
+    let mut builder = tar::Builder::new(vec![]);
+    let size = pkginfo.len() as u64;
+    let mut header = tar_header(tar::EntryType::Regular, 0o644, size, mtime);
+    builder.append_data(&mut header, ".PKGINFO", pkginfo.as_bytes())?;
+    let mut tar = builder.into_inner()?;
+    // The data follows in the same "archive", so this part can't have an end marker
+    tar.truncate(tar.len() - 1024);
+    gzip(&tar)
+}
+
+/// Make the gzipped tarball of the package's files
+///
+/// apk checks every file against a SHA1 recorded in a pax header before it.
+fn data_segment(files: &[PackageFile], mtime: u64) -> DistResult<Vec<u8>> {
+    let mut dirs = SortedSet::new();
+    for file in files {
+        let mut path = Utf8Path::new(&file.path);
+        while let Some(parent) = path.parent() {
+            if parent.as_str().is_empty() {
+                break;
+            }
+            dirs.insert(parent.to_string());
+            path = parent;
+        }
+    }
+
+    let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::best()));
+    for dir in dirs {
+        let mut header = tar_header(tar::EntryType::Directory, 0o755, 0, mtime);
+        builder.append_data(&mut header, format!("{dir}/"), std::io::empty())?;
+    }
+    // Sorting keeps the archive the same between runs
+    let files = files
+        .iter()
+        .map(|file| (&file.path, file))
+        .collect::<SortedMap<_, _>>();
+    for (path, file) in files {
+        let checksum = to_hex(&sha1::Sha1::digest(&file.contents));
+        let record = pax_record("APK-TOOLS.checksum.SHA1", &checksum);
+        let size = record.len() as u64;
+        let mut header = tar_header(tar::EntryType::XHeader, 0o644, size, mtime);
+        builder.append_data(&mut header, format!("PaxHeaders/{path}"), record.as_bytes())?;
+
+        let size = file.contents.len() as u64;
+        let mut header = tar_header(tar::EntryType::Regular, file.mode, size, mtime);
+        builder.append_data(&mut header, path, &file.contents[..])?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Format a pax extended header record, which starts with its own length
+fn pax_record(key: &str, value: &str) -> String {
+    let rest = format!(" {key}={value}\n");
+    // Adding the length can make the length longer, so go until it settles
+    let mut len = rest.len();
+    loop {
+        let total = len.to_string().len() + rest.len();
+        if total == len {
+            break;
+        }
+        len = total;
+    }
+    format!("{len}{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apk_version, pax_record};
+
+    #[test]
+    fn versions() {
+        let version = |v: &str| apk_version(&semver::Version::parse(v).unwrap());
+        assert_eq!(version("1.2.3"), "1.2.3-r0");
+        assert_eq!(version("1.0.0-beta.2"), "1.0.0_beta2-r0");
+        assert_eq!(version("1.0.0-rc"), "1.0.0_rc-r0");
+        assert_eq!(version("1.0.0-dev.3+build.5"), "1.0.0_pre3-r0");
+    }
+
+    #[test]
+    fn pax_records() {
+        assert_eq!(pax_record("a", "b"), "6 a=b\n");
+        assert_eq!(pax_record("key", "value"), "13 key=value\n");
+    }
+}
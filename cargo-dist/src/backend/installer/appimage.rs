@@ -0,0 +1,162 @@
+//! AppImage installer
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::Utf8PathBuf;
+use temp_dir::TempDir;
+use tracing::info;
+
+use super::linux::{desktop_entry, package_files, PackageFile, PackageLayout};
+use super::set_mode;
+use crate::{errors::*, tasks::StaticAssetKind};
+
+/// Info needed to build an AppImage
+#[derive(Debug, Clone)]
+pub struct AppImageInstallerInfo {
+    /// Final file path of the AppImage
+    pub file_path: Utf8PathBuf,
+    /// Final file path of the zsync file, if the AppImage can update itself
+    pub zsync_path: Option<Utf8PathBuf>,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The name of the app
+    pub app_name: String,
+    /// The AppImage architecture (e.g. "x86_64")
+    pub arch: String,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// Binaries we'll be baking into the AppImage (the first one is what it runs)
+    pub binaries: Vec<String>,
+    /// Static assets to include alongside the binaries (docs, man pages, completions...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+    /// The icon to show for the AppImage
+    pub icon: Utf8PathBuf,
+    /// The desktop entry categories
+    pub categories: Vec<String>,
+    /// Whether the app runs in a terminal
+    pub terminal: bool,
+    /// Where the AppImage looks for updates (e.g. `gh-releases-zsync|...`)
+    pub update_info: Option<String>,
+}
+
+impl AppImageInstallerInfo {
+    /// Build the AppImage installer
+    ///
+    /// This stages an AppDir and hands it to `appimagetool`, which also makes the
+    /// zsync file if the AppImage knows where to find updates.
+    pub fn build(&self) -> DistResult<()> {
+        info!("building an AppImage: {}", self.file_path);
+
+        let layout = PackageLayout {
+            package: &self.app_name,
+            zsh_completions_dir: "usr/share/zsh/site-functions",
+            license_dir: None,
+        };
+        let mut files = package_files(
+            &layout,
+            &self.package_dir,
+            &self.binaries,
+            &self.static_assets,
+        )?;
+        files.push(PackageFile {
+            path: "AppRun".to_owned(),
+            contents: self.app_run().into_bytes(),
+            mode: 0o755,
+        });
+        let binary = self.binaries.first().unwrap_or(&self.app_name);
+        let desktop_entry = desktop_entry(
+            &self.app_name,
+            self.desc.as_deref(),
+            binary,
+            &self.app_name,
+            self.terminal,
+            &self.categories,
+        );
+        files.push(PackageFile {
+            path: format!("{}.desktop", self.app_name),
+            contents: desktop_entry.into_bytes(),
+            mode: 0o644,
+        });
+        let icon = std::fs::read(&self.icon)?;
+        let icon_ext = self.icon.extension().unwrap_or("png");
+        files.push(PackageFile {
+            path: format!("{}.{icon_ext}", self.app_name),
+            contents: icon.clone(),
+            mode: 0o644,
+        });
+        files.push(PackageFile {
+            path: ".DirIcon".to_owned(),
+            contents: icon,
+            mode: 0o644,
+        });
+
+        let temp = TempDir::new()?;
+        let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+        let app_dir = temp_path.join(format!("{}.AppDir", self.app_name));
+        for file in &files {
+            let dest = app_dir.join(&file.path);
+            if let Some(parent) = dest.parent() {
+                LocalAsset::create_dir_all(parent)?;
+            }
+            std::fs::write(&dest, &file.contents)?;
+            set_mode(&dest, file.mode)?;
+        }
+
+        let out_dir = self
+            .file_path
+            .parent()
+            .expect("AppImage had no parent dir!?");
+        let mut cmd = Cmd::new("appimagetool", "build an AppImage");
+        cmd.stdout_to_stderr();
+        cmd.env("ARCH", &self.arch);
+        // The zsync file is written to the working dir
+        cmd.current_dir(out_dir);
+        if let Some(update_info) = &self.update_info {
+            cmd.arg("--updateinformation").arg(update_info);
+        }
+        cmd.arg(&app_dir).arg(&self.file_path);
+        cmd.run()?;
+
+        Ok(())
+    }
+
+    /// Compute the script that runs the app
+    fn app_run(&self) -> String {
+        let binary = self.binaries.first().unwrap_or(&self.app_name);
+        let mut app_run = String::new();
+        app_run.push_str("#!/bin/sh\n");
+        app_run.push_str("HERE=\"$(dirname \"$(readlink -f \"$0\")\")\"\n");
+        app_run.push_str(&format!("exec \"$HERE/usr/bin/{binary}\" \"$@\"\n"));
+        app_run
+    }
+}
+
+/// Get the AppImage architecture for a target triple, if we can make AppImages for it
+pub fn appimage_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    match arch {
+        "x86_64" => Some("x86_64"),
+        "aarch64" => Some("aarch64"),
+        "i686" => Some("i686"),
+        "armv7" => Some("armhf"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::appimage_architecture;
+
+    #[test]
+    fn architectures() {
+        assert_eq!(
+            appimage_architecture("x86_64-unknown-linux-gnu"),
+            Some("x86_64")
+        );
+        assert_eq!(
+            appimage_architecture("armv7-unknown-linux-gnueabihf"),
+            Some("armhf")
+        );
+        assert_eq!(appimage_architecture("s390x-unknown-linux-gnu"), None);
+    }
+}
@@ -0,0 +1,261 @@
+//! Code for generating an AUR PKGBUILD
+
+use axoasset::LocalAsset;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo};
+use crate::{
+    backend::templates::{Templates, TEMPLATE_INSTALLER_PKGBUILD},
+    config::ChecksumStyle,
+    errors::DistResult,
+};
+
+/// Info about an AUR PKGBUILD
+#[derive(Debug, Clone, Serialize)]
+pub struct AurInstallerInfo {
+    /// The name of the AUR package
+    pub package: String,
+    /// The pacman version of the package
+    pub pkgver: String,
+    /// The Maintainer comment, from the first entry of "authors"
+    pub maintainer: Option<String>,
+    /// A brief description of the application
+    pub desc: Option<String>,
+    /// The application's license, in SPDX format
+    pub license: Option<String>,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// Binaries to install from the archives
+    pub binaries: Vec<String>,
+    /// License files to install from the archives
+    pub licenses: Vec<String>,
+    /// Other docs (README, CHANGELOG...) to install from the archives
+    pub docs: Vec<String>,
+    /// The archive to fetch for each architecture
+    pub sources: Vec<AurSource>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// An archive the PKGBUILD fetches for an architecture
+#[derive(Debug, Clone, Serialize)]
+pub struct AurSource {
+    /// The pacman architecture (e.g. "x86_64")
+    pub arch: String,
+    /// The archive
+    pub artifact: ExecutableZipFragment,
+    /// The dir the archive unpacks to, if it has one
+    pub root: Option<String>,
+    /// sha256 of the archive, filled in from the manifest
+    pub sha256: Option<String>,
+    /// Packages the binaries need at runtime
+    pub depends: Vec<String>,
+}
+
+pub(crate) fn write_aur_pkgbuild(
+    templates: &Templates,
+    source_info: &AurInstallerInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let mut info = source_info.clone();
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    for source in &mut info.sources {
+        source.sha256 = manifest
+            .artifacts
+            .get(&source.artifact.id)
+            .and_then(|a| a.checksums.get(checksum_key))
+            .cloned();
+    }
+
+    // These end up in double quotes, and pkgdesc is only one line
+    info.desc = info
+        .desc
+        .as_deref()
+        .and_then(|desc| desc.trim().lines().next())
+        .map(bash_escape);
+    info.homepage = info.homepage.as_deref().map(bash_escape);
+
+    let script = templates.render_file_to_clean_string(TEMPLATE_INSTALLER_PKGBUILD, &info)?;
+    LocalAsset::write_new(&script, &info.inner.dest_path)?;
+    Ok(())
+}
+
+/// Get the pacman architecture for a target triple, if we can make packages for it
+pub fn pacman_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    match arch {
+        "x86_64" => Some("x86_64"),
+        "aarch64" => Some("aarch64"),
+        "i686" => Some("i686"),
+        "armv7" => Some("armv7h"),
+        _ => None,
+    }
+}
+
+/// Turn a semver version into a pkgver
+///
+/// pkgver can't contain hyphens, so the prerelease is joined straight onto the
+/// patch version (`1.0.0-rc.1` becomes `1.0.0rc.1`). pacman only considers a
+/// version older than the release when the word directly follows a number; a
+/// separator in between (`1.0.0.rc.1`) would make it newer.
+pub fn aur_version(version: &semver::Version) -> String {
+    let semver::Version {
+        major,
+        minor,
+        patch,
+        pre,
+        build,
+    } = version;
+    let mut pkgver = format!("{major}.{minor}.{patch}");
+    if !pre.is_empty() {
+        // A numeric prerelease would just extend the patch number, so give it a word
+        if pre.starts_with(|c: char| c.is_ascii_digit()) {
+            pkgver.push_str("pre.");
+        }
+        pkgver.push_str(&pre.replace('-', "."));
+    }
+    if !build.is_empty() {
+        pkgver.push('+');
+        pkgver.push_str(&build.replace('-', "."));
+    }
+    pkgver
+}
+
+/// Escape text for a double-quoted bash string
+fn bash_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aur_version, bash_escape};
+
+    #[test]
+    fn versions() {
+        let version = |v: &str| aur_version(&semver::Version::parse(v).unwrap());
+        assert_eq!(version("1.2.3"), "1.2.3");
+        assert_eq!(version("1.0.0-rc.1"), "1.0.0rc.1");
+        assert_eq!(version("1.0.0-1"), "1.0.0pre.1");
+        assert_eq!(version("1.0.0-rc-1+build.5"), "1.0.0rc.1+build.5");
+    }
+
+    #[test]
+    fn version_ordering() {
+        let version = |v: &str| aur_version(&semver::Version::parse(v).unwrap());
+        // Prereleases must upgrade to each other, and then to the release
+        let ordered = [
+            "0.9.0",
+            "1.0.0-alpha.1",
+            "1.0.0-beta.1",
+            "1.0.0-rc.1",
+            "1.0.0-rc.2",
+            "1.0.0",
+            "1.0.1-rc.1",
+            "1.0.1",
+        ];
+        for pair in ordered.windows(2) {
+            let (older, newer) = (version(pair[0]), version(pair[1]));
+            assert_eq!(
+                vercmp(&older, &newer),
+                std::cmp::Ordering::Less,
+                "{older} should be older than {newer}"
+            );
+            assert_eq!(vercmp(&newer, &older), std::cmp::Ordering::Greater);
+        }
+        assert_eq!(
+            vercmp(&version("1.0.0-1"), &version("1.0.0")),
+            std::cmp::Ordering::Less
+        );
+    }
+
+    /// A port of pacman's `alpm_pkg_vercmp` (rpmvercmp), for versions without an epoch or pkgrel
+    fn vercmp(one: &str, two: &str) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        let (one, two) = (one.as_bytes(), two.as_bytes());
+        let (mut i, mut j) = (0, 0);
+        while i < one.len() && j < two.len() {
+            let (sep_start1, sep_start2) = (i, j);
+            while i < one.len() && !one[i].is_ascii_alphanumeric() {
+                i += 1;
+            }
+            while j < two.len() && !two[j].is_ascii_alphanumeric() {
+                j += 1;
+            }
+            if i >= one.len() || j >= two.len() {
+                break;
+            }
+            if i - sep_start1 != j - sep_start2 {
+                return (i - sep_start1).cmp(&(j - sep_start2));
+            }
+
+            let (start1, start2) = (i, j);
+            let numeric = one[i].is_ascii_digit();
+            let in_segment = |c: u8| {
+                if numeric {
+                    c.is_ascii_digit()
+                } else {
+                    c.is_ascii_alphabetic()
+                }
+            };
+            while i < one.len() && in_segment(one[i]) {
+                i += 1;
+            }
+            while j < two.len() && in_segment(two[j]) {
+                j += 1;
+            }
+            if j == start2 {
+                // Segments of different types: numbers are newer than words
+                return if numeric {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+            }
+
+            let (mut seg1, mut seg2) = (&one[start1..i], &two[start2..j]);
+            if numeric {
+                while seg1.len() > 1 && seg1[0] == b'0' {
+                    seg1 = &seg1[1..];
+                }
+                while seg2.len() > 1 && seg2[0] == b'0' {
+                    seg2 = &seg2[1..];
+                }
+                let ord = seg1.len().cmp(&seg2.len());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            let ord = seg1.cmp(seg2);
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+
+        let (rest1, rest2) = (&one[i..], &two[j..]);
+        if rest1.is_empty() && rest2.is_empty() {
+            Ordering::Equal
+        } else if (rest1.is_empty() && !rest2[0].is_ascii_alphabetic())
+            || rest1.first().is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(bash_escape(r#"a "$cool" app"#), r#"a \"\$cool\" app"#);
+    }
+}
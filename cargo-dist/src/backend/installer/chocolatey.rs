@@ -0,0 +1,147 @@
+//! Code for generating a Chocolatey package
+
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{xml_escape, InstallerInfo};
+use crate::{
+    backend::templates::{Templates, TEMPLATE_INSTALLER_CHOCOLATEY},
+    config::{ChecksumStyle, ChocolateyInstaller},
+    errors::DistResult,
+};
+
+/// Info about a Chocolatey package
+#[derive(Debug, Clone, Serialize)]
+pub struct ChocolateyInstallerInfo {
+    /// The package's id
+    pub package_id: String,
+    /// Dir the package's files are written to before being packed
+    pub package_dir: Utf8PathBuf,
+    /// The package's title
+    pub title: String,
+    /// The package's authors, comma-separated
+    pub authors: String,
+    /// A description of the package
+    pub desc: String,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// The URL to the application's source repository
+    pub repository: Option<String>,
+    /// Tags for the package, space-separated
+    pub tags: Option<String>,
+    /// Whether the package installs the archives or the msis
+    pub installer: ChocolateyInstaller,
+    /// The name the msi registers itself under, for uninstalling it
+    pub software_name: String,
+    /// The 32-bit download, if any
+    pub x86: Option<ChocolateySource>,
+    /// The 64-bit download, if any
+    pub x64: Option<ChocolateySource>,
+    /// The feed to push the package to
+    pub source: String,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// A file the package downloads for an architecture
+#[derive(Debug, Clone, Serialize)]
+pub struct ChocolateySource {
+    /// The artifact's id (and file name)
+    pub id: String,
+    /// sha256 of the file, filled in from the manifest
+    pub sha256: Option<String>,
+}
+
+pub(crate) fn write_chocolatey_package(
+    templates: &Templates,
+    source_info: &ChocolateyInstallerInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let mut info = source_info.clone();
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    for source in [&mut info.x86, &mut info.x64].into_iter().flatten() {
+        source.sha256 = manifest
+            .artifacts
+            .get(&source.id)
+            .and_then(|a| a.checksums.get(checksum_key))
+            .cloned();
+    }
+
+    // The nuspec isn't an .xml file, so it doesn't get escaped for us
+    info.title = xml_escape(&info.title);
+    info.authors = xml_escape(&info.authors);
+    info.desc = xml_escape(&info.desc);
+    info.homepage = info.homepage.as_deref().map(xml_escape);
+    info.repository = info.repository.as_deref().map(xml_escape);
+    info.tags = info.tags.as_deref().map(xml_escape);
+    // This ends up in a single-quoted PowerShell string
+    info.software_name = info.software_name.replace('\'', "''");
+
+    let results = templates.render_dir_to_clean_strings(TEMPLATE_INSTALLER_CHOCOLATEY, &info)?;
+    for (relpath, rendered) in results {
+        // NuGet expects the nuspec to be named after the package
+        let relpath = if relpath == "package.nuspec" {
+            Utf8PathBuf::from(format!("{}.nuspec", info.package_id))
+        } else {
+            relpath
+        };
+        LocalAsset::write_new_all(&rendered, info.package_dir.join(relpath))?;
+    }
+
+    Ok(())
+}
+
+/// Get the Chocolatey architecture for a windows target triple, if Chocolatey supports it
+pub fn chocolatey_architecture(target: &str) -> Option<&'static str> {
+    if !target.contains("windows") {
+        None
+    } else if target.starts_with("x86_64") {
+        Some("x64")
+    } else if target.starts_with("i686") {
+        Some("x86")
+    } else {
+        None
+    }
+}
+
+/// Make a name into a valid package id, which is lowercase with words separated by dashes
+pub fn chocolatey_package_id(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chocolatey_architecture, chocolatey_package_id};
+
+    #[test]
+    fn architectures() {
+        assert_eq!(
+            chocolatey_architecture("x86_64-pc-windows-msvc"),
+            Some("x64")
+        );
+        assert_eq!(chocolatey_architecture("i686-pc-windows-msvc"), Some("x86"));
+        // Chocolatey has no arm64 downloads
+        assert_eq!(chocolatey_architecture("aarch64-pc-windows-msvc"), None);
+        assert_eq!(chocolatey_architecture("x86_64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn package_ids() {
+        assert_eq!(chocolatey_package_id("axolotlsay"), "axolotlsay");
+        assert_eq!(chocolatey_package_id("AxolotlSay"), "axolotlsay");
+        assert_eq!(chocolatey_package_id("axolotl_say"), "axolotl-say");
+        assert_eq!(chocolatey_package_id("Axolotl Say.CLI"), "axolotl-say.cli");
+    }
+}
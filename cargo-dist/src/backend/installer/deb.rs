@@ -0,0 +1,399 @@
+//! deb installer
+
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{DistManifest, Library, Linkage};
+use flate2::{write::GzEncoder, Compression};
+use tracing::info;
+
+use super::linux::{build_timestamp, package_files, tar_header, PackageFile, PackageLayout};
+use crate::{errors::*, tasks::StaticAssetKind, SortedMap, SortedSet};
+
+/// Info needed to build a deb
+#[derive(Debug, Clone)]
+pub struct DebInstallerInfo {
+    /// The id of the deb artifact, for looking up its linkage
+    pub artifact_id: String,
+    /// Final file path of the deb
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The name of the Debian package
+    pub package: String,
+    /// The Debian version of the package
+    pub version: String,
+    /// The Debian architecture of the package (e.g. "amd64")
+    pub arch: String,
+    /// The Maintainer field, from the first entry of "authors" (or the GitHub owner)
+    pub maintainer: Option<String>,
+    /// Whether the binaries are built against glibc, so they need libc6 at the least
+    pub glibc: bool,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// The license of the app
+    pub license: Option<String>,
+    /// The app's homepage
+    pub homepage: Option<String>,
+    /// Binaries we'll be baking into the deb
+    pub binaries: Vec<String>,
+    /// Static assets to install alongside the binaries (docs, man pages, completions...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+}
+
+impl DebInstallerInfo {
+    /// Build the deb installer
+    pub fn build(&self, manifest: &DistManifest) -> DistResult<()> {
+        info!("building a deb: {}", self.file_path);
+
+        let files = self.data_files()?;
+        let linkage = manifest.linkage_for_artifact(&self.artifact_id);
+        let depends = deb_depends(&linkage, self.glibc);
+        let installed_size = files
+            .iter()
+            .map(|file| file.contents.len() as u64)
+            .sum::<u64>()
+            .div_ceil(1024);
+        let control = self.control_file(&depends, installed_size);
+
+        let mtime = build_timestamp();
+        let control_tar = tar_gz(
+            &[PackageFile {
+                path: "control".to_owned(),
+                contents: control.into_bytes(),
+                mode: 0o644,
+            }],
+            mtime,
+        )?;
+        let data_tar = tar_gz(&files, mtime)?;
+
+        let deb = ar_archive(
+            &[
+                ("debian-binary", &b"2.0\n"[..]),
+                ("control.tar.gz", &control_tar[..]),
+                ("data.tar.gz", &data_tar[..]),
+            ],
+            mtime,
+        );
+        std::fs::write(&self.file_path, deb)?;
+
+        Ok(())
+    }
+
+    /// Compute the contents of the DEBIAN/control file
+    fn control_file(&self, depends: &[String], installed_size: u64) -> String {
+        let mut control = String::new();
+        control.push_str(&format!("Package: {}\n", self.package));
+        control.push_str(&format!("Version: {}\n", self.version));
+        control.push_str(&format!("Architecture: {}\n", self.arch));
+        if let Some(maintainer) = &self.maintainer {
+            control.push_str(&format!("Maintainer: {maintainer}\n"));
+        }
+        control.push_str(&format!("Installed-Size: {installed_size}\n"));
+        if !depends.is_empty() {
+            control.push_str(&format!("Depends: {}\n", depends.join(", ")));
+        }
+        control.push_str("Section: utils\n");
+        control.push_str("Priority: optional\n");
+        if let Some(homepage) = &self.homepage {
+            control.push_str(&format!("Homepage: {homepage}\n"));
+        }
+        let desc = self.desc.as_deref().unwrap_or(&self.package);
+        control.push_str(&format!("Description: {}\n", control_description(desc)));
+        control
+    }
+
+    /// Lay out the binaries and static assets the way Debian expects them
+    fn data_files(&self) -> DistResult<Vec<PackageFile>> {
+        let layout = PackageLayout {
+            package: &self.package,
+            zsh_completions_dir: "usr/share/zsh/vendor-completions",
+            license_dir: None,
+        };
+        let mut files = package_files(
+            &layout,
+            &self.package_dir,
+            &self.binaries,
+            &self.static_assets,
+        )?;
+        files.push(PackageFile {
+            path: format!("{}/copyright", layout.doc_dir()),
+            contents: self.copyright_file().into_bytes(),
+            mode: 0o644,
+        });
+        Ok(files)
+    }
+
+    /// Compute a machine-readable debian/copyright file
+    fn copyright_file(&self) -> String {
+        let mut copyright = String::new();
+        copyright.push_str(
+            "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n",
+        );
+        copyright.push_str(&format!("Upstream-Name: {}\n", self.package));
+        if let Some(homepage) = &self.homepage {
+            copyright.push_str(&format!("Source: {homepage}\n"));
+        }
+        copyright.push_str("\nFiles: *\n");
+        if let Some(maintainer) = &self.maintainer {
+            copyright.push_str(&format!("Copyright: {maintainer}\n"));
+        }
+        let license = self.license.as_deref().unwrap_or("UNKNOWN");
+        copyright.push_str(&format!("License: {license}\n"));
+        copyright
+    }
+}
+
+/// Get the Debian architecture for a target triple, if Debian has one
+pub fn deb_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    let deb_arch = match arch {
+        "x86_64" => "amd64",
+        "i586" | "i686" => "i386",
+        "aarch64" => "arm64",
+        "armv7" | "thumbv7neon" => "armhf",
+        "arm" if target.ends_with("hf") => "armhf",
+        "arm" => "armel",
+        "powerpc64le" => "ppc64el",
+        "s390x" => "s390x",
+        "riscv64gc" => "riscv64",
+        "loongarch64" => "loong64",
+        _ => return None,
+    };
+    Some(deb_arch)
+}
+
+/// Turn a package name into a valid Debian package name
+///
+/// Debian only allows lowercase alphanumerics and `+-.`
+pub fn deb_package_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c == '_' {
+                '-'
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        .collect()
+}
+
+/// Compute the Depends field from the packages the linkage report found
+///
+/// If several packages ship a library, any of them will do. glibc also gets a minimum
+/// version, so the package can't be installed somewhere the binaries won't run. If
+/// there's no report to go on, glibc builds still need libc6.
+fn deb_depends(linkage: &Linkage, glibc: bool) -> Vec<String> {
+    let mut depends = SortedSet::new();
+    for library in &linkage.system {
+        let Some(source) = &library.source else {
+            continue;
+        };
+        let packages = source.split(", ").collect::<Vec<_>>();
+        let min_version = linkage
+            .min_glibc
+            .as_ref()
+            .filter(|_| is_libc(library))
+            .map(|version| format!(" (>= {version})"))
+            .unwrap_or_default();
+        let alternatives = packages
+            .iter()
+            .map(|package| format!("{package}{min_version}"))
+            .collect::<Vec<_>>();
+        depends.insert(alternatives.join(" | "));
+    }
+    if depends.is_empty() && glibc {
+        depends.insert("libc6".to_owned());
+    }
+    depends.into_iter().collect()
+}
+
+/// Whether this library is glibc's libc
+fn is_libc(library: &Library) -> bool {
+    let file_name = library.path.rsplit('/').next().unwrap_or(&library.path);
+    file_name.starts_with("libc.so")
+}
+
+/// Format a description for the Description field
+///
+/// The first line is the synopsis, and the rest is an indented extended description
+/// where blank lines are written as " .".
+fn control_description(desc: &str) -> String {
+    let mut lines = desc.trim().lines();
+    let mut output = lines.next().unwrap_or_default().trim().to_owned();
+    for line in lines {
+        output.push('\n');
+        if line.trim().is_empty() {
+            output.push_str(" .");
+        } else {
+            output.push(' ');
+            output.push_str(line.trim_end());
+        }
+    }
+    output
+}
+
+/// Make a .tar.gz of these files, owned by root and with all their parent dirs
+fn tar_gz(files: &[PackageFile], mtime: u64) -> DistResult<Vec<u8>> {
+    let mut dirs = SortedSet::new();
+    for file in files {
+        let mut path = Utf8Path::new(&file.path);
+        while let Some(parent) = path.parent() {
+            if parent.as_str().is_empty() {
+                break;
+            }
+            dirs.insert(parent.to_string());
+            path = parent;
+        }
+    }
+
+    let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::best()));
+    for dir in dirs {
+        let mut header = tar_header(tar::EntryType::Directory, 0o755, 0, mtime);
+        builder.append_data(&mut header, format!("./{dir}/"), std::io::empty())?;
+    }
+    // Sorting keeps the archive the same between runs
+    let files = files
+        .iter()
+        .map(|file| (&file.path, file))
+        .collect::<SortedMap<_, _>>();
+    for (path, file) in files {
+        let size = file.contents.len() as u64;
+        let mut header = tar_header(tar::EntryType::Regular, file.mode, size, mtime);
+        builder.append_data(&mut header, format!("./{path}"), &file.contents[..])?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Make an `ar` archive, which is the outer container of a deb
+fn ar_archive(members: &[(&str, &[u8])], mtime: u64) -> Vec<u8> {
+    let mut output = b"!<arch>\n".to_vec();
+    for (name, contents) in members {
+        // name, mtime, uid, gid, mode (octal), size, magic
+        let header = format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            name,
+            mtime,
+            0,
+            0,
+            "100644",
+            contents.len()
+        );
+        output.extend_from_slice(header.as_bytes());
+        output.extend_from_slice(contents);
+        // Members are aligned to 2 bytes
+        if contents.len() % 2 == 1 {
+            output.push(b'\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use cargo_dist_schema::{
+        Artifact, ArtifactKind, Asset, AssetInfo, AssetKind, DistManifest, ExecutableAsset,
+        Library, Linkage,
+    };
+
+    use super::{control_description, deb_architecture, deb_depends};
+
+    #[test]
+    fn architectures() {
+        assert_eq!(deb_architecture("x86_64-unknown-linux-gnu"), Some("amd64"));
+        assert_eq!(
+            deb_architecture("aarch64-unknown-linux-musl"),
+            Some("arm64")
+        );
+        assert_eq!(
+            deb_architecture("armv7-unknown-linux-gnueabihf"),
+            Some("armhf")
+        );
+        assert_eq!(deb_architecture("arm-unknown-linux-gnueabi"), Some("armel"));
+        assert_eq!(deb_architecture("sparc64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(control_description("a cool app"), "a cool app");
+        assert_eq!(
+            control_description("a cool app\n\nit does things\n"),
+            "a cool app\n .\n it does things"
+        );
+    }
+
+    #[test]
+    fn depends() {
+        let mut linkage = Linkage {
+            min_glibc: Some("2.34".to_owned()),
+            ..Linkage::default()
+        };
+        let mut libc = Library::new("/lib/x86_64-linux-gnu/libc.so.6".to_owned());
+        libc.source = Some("libc6".to_owned());
+        linkage.system.insert(libc);
+        let mut ssl = Library::new("/lib/x86_64-linux-gnu/libssl.so.3".to_owned());
+        ssl.source = Some("libssl3, libssl3t64".to_owned());
+        linkage.system.insert(ssl);
+        assert_eq!(
+            deb_depends(&linkage, true),
+            vec!["libc6 (>= 2.34)", "libssl3 | libssl3t64"]
+        );
+    }
+
+    #[test]
+    fn depends_on_merged_linkage() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let mut assets = vec![];
+        for (name, min_glibc) in [("app", "2.17"), ("helper", "2.34"), ("tool", "2.9")] {
+            let mut libc = Library::new("/lib/x86_64-linux-gnu/libc.so.6".to_owned());
+            libc.source = Some("libc6".to_owned());
+            let mut linkage = Linkage {
+                min_glibc: Some(min_glibc.to_owned()),
+                min_glibc_symbol: Some(format!("memcpy@GLIBC_{min_glibc}")),
+                ..Linkage::default()
+            };
+            linkage.system.insert(libc);
+            manifest.assets.insert(
+                name.to_owned(),
+                AssetInfo {
+                    id: name.to_owned(),
+                    name: name.to_owned(),
+                    system: String::new(),
+                    target_triples: vec!["x86_64-unknown-linux-gnu".to_owned()],
+                    linkage: Some(linkage),
+                },
+            );
+            assets.push(Asset {
+                id: Some(name.to_owned()),
+                name: Some(name.to_owned()),
+                path: Some(name.to_owned()),
+                kind: AssetKind::Executable(ExecutableAsset {
+                    symbols_artifact: None,
+                }),
+            });
+        }
+        let artifact_id = "app-x86_64-unknown-linux-gnu.tar.xz".to_owned();
+        manifest.artifacts.insert(
+            artifact_id.clone(),
+            Artifact {
+                name: Some(artifact_id.clone()),
+                kind: ArtifactKind::ExecutableZip,
+                target_triples: vec!["x86_64-unknown-linux-gnu".to_owned()],
+                path: None,
+                assets,
+                install_hint: None,
+                description: None,
+                checksum: None,
+                checksums: Default::default(),
+            },
+        );
+
+        let linkage = manifest.linkage_for_artifact(&artifact_id);
+        assert_eq!(deb_depends(&linkage, true), vec!["libc6 (>= 2.34)"]);
+    }
+
+    #[test]
+    fn depends_without_linkage() {
+        assert_eq!(deb_depends(&Linkage::default(), true), vec!["libc6"]);
+        assert!(deb_depends(&Linkage::default(), false).is_empty());
+    }
+}
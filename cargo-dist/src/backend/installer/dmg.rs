@@ -0,0 +1,205 @@
+//! macOS dmg installer
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use temp_dir::TempDir;
+use tracing::info;
+
+use super::set_mode;
+use crate::errors::*;
+use crate::sign::{codesign, notarize, staple};
+
+/// Info needed to build a dmg
+#[derive(Debug, Clone)]
+pub struct DmgInstallerInfo {
+    /// Final file path of the dmg
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The name of the mounted volume
+    pub volume_name: String,
+    /// Binaries we'll be baking into the dmg
+    pub binaries: Vec<String>,
+    /// An image to show behind the files when the dmg is opened
+    pub background: Option<Utf8PathBuf>,
+    /// Whether to add a symlink to /Applications to drag the files onto
+    pub applications_symlink: bool,
+    /// The identity to sign the binaries and dmg with (e.g. "Developer ID Application: ...")
+    pub signing_identity: Option<String>,
+    /// Whether to notarize the dmg and staple the ticket to it
+    pub notarize: bool,
+}
+
+impl DmgInstallerInfo {
+    /// Build the dmg installer
+    ///
+    /// This stages the volume's contents and hands them to `hdiutil`, which only
+    /// exists on macOS. Backgrounds are set by having Finder lay out a writable
+    /// copy of the image, which is then compressed.
+    pub fn build(&self) -> DistResult<()> {
+        info!("building a dmg: {}", self.file_path);
+
+        let temp = TempDir::new()?;
+        let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+        let stage = temp_path.join("stage");
+        LocalAsset::create_dir_all(&stage)?;
+        for binary in &self.binaries {
+            let dest = stage.join(binary);
+            std::fs::copy(self.package_dir.join(binary), &dest)?;
+            set_mode(&dest, 0o755)?;
+            if let Some(identity) = &self.signing_identity {
+                codesign(identity, &dest, true)?;
+            }
+        }
+        if self.applications_symlink {
+            symlink("/Applications", &stage.join("Applications"))?;
+        }
+        let background_file = if let Some(background) = &self.background {
+            let file_name = background.file_name().unwrap_or("background.png");
+            LocalAsset::create_dir_all(stage.join(".background"))?;
+            LocalAsset::copy_named(background, stage.join(".background").join(file_name))?;
+            Some(file_name)
+        } else {
+            None
+        };
+
+        if let Some(background_file) = background_file {
+            let rw_dmg = temp_path.join("rw.dmg");
+            let mount_point = temp_path.join("mnt");
+            let mut cmd = Cmd::new("hdiutil", "create a writable dmg");
+            cmd.stdout_to_stderr();
+            cmd.arg("create")
+                .arg("-volname")
+                .arg(&self.volume_name)
+                .arg("-srcfolder")
+                .arg(&stage)
+                .arg("-fs")
+                .arg("HFS+")
+                .arg("-format")
+                .arg("UDRW")
+                .arg(&rw_dmg);
+            cmd.run()?;
+
+            let mut cmd = Cmd::new("hdiutil", "mount the writable dmg");
+            cmd.stdout_to_stderr();
+            cmd.arg("attach")
+                .arg("-readwrite")
+                .arg("-noverify")
+                .arg("-noautoopen")
+                .arg("-mountpoint")
+                .arg(&mount_point)
+                .arg(&rw_dmg);
+            cmd.run()?;
+            let mut cmd = Cmd::new("osascript", "lay out the dmg's window");
+            cmd.stdout_to_stderr();
+            cmd.arg("-e").arg(self.finder_script(background_file));
+            let laid_out = cmd.run();
+            let mut cmd = Cmd::new("hdiutil", "unmount the writable dmg");
+            cmd.stdout_to_stderr();
+            cmd.arg("detach").arg(&mount_point);
+            cmd.run()?;
+            laid_out?;
+
+            let mut cmd = Cmd::new("hdiutil", "compress the dmg");
+            cmd.stdout_to_stderr();
+            cmd.arg("convert")
+                .arg(&rw_dmg)
+                .arg("-format")
+                .arg("UDZO")
+                .arg("-ov")
+                .arg("-o")
+                .arg(&self.file_path);
+            cmd.run()?;
+        } else {
+            let mut cmd = Cmd::new("hdiutil", "build a dmg");
+            cmd.stdout_to_stderr();
+            cmd.arg("create")
+                .arg("-volname")
+                .arg(&self.volume_name)
+                .arg("-srcfolder")
+                .arg(&stage)
+                .arg("-format")
+                .arg("UDZO")
+                .arg("-ov")
+                .arg(&self.file_path);
+            cmd.run()?;
+        }
+
+        if let Some(identity) = &self.signing_identity {
+            codesign(identity, &self.file_path, false)?;
+        }
+        if self.notarize {
+            notarize(&self.file_path)?;
+            staple(&self.file_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compute the AppleScript that has Finder show the background
+    fn finder_script(&self, background_file: &str) -> String {
+        let volume = self.volume_name.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut script = String::new();
+        script.push_str("tell application \"Finder\"\n");
+        script.push_str(&format!("  tell disk \"{volume}\"\n"));
+        script.push_str("    open\n");
+        script.push_str("    set current view of container window to icon view\n");
+        script.push_str("    set toolbar visible of container window to false\n");
+        script.push_str("    set statusbar visible of container window to false\n");
+        script.push_str("    set the bounds of container window to {100, 100, 740, 500}\n");
+        script.push_str("    set viewOptions to the icon view options of container window\n");
+        script.push_str("    set arrangement of viewOptions to not arranged\n");
+        script.push_str("    set icon size of viewOptions to 96\n");
+        script.push_str(&format!(
+            "    set background picture of viewOptions to file \".background:{background_file}\"\n"
+        ));
+        script.push_str("    close\n");
+        script.push_str("  end tell\n");
+        script.push_str("end tell\n");
+        script
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &str, link: &Utf8Path) -> DistResult<()> {
+    std::os::unix::fs::symlink(target, link)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn symlink(_target: &str, _link: &Utf8Path) -> DistResult<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+
+    use super::DmgInstallerInfo;
+
+    #[test]
+    fn finder_script() {
+        let info = DmgInstallerInfo {
+            file_path: Utf8PathBuf::from("target/distrib/axolotlsay.dmg"),
+            package_dir: Utf8PathBuf::from("target/distrib/axolotlsay_dmg"),
+            volume_name: r#"Axolotl "Say" C:\ 0.2.0"#.to_owned(),
+            binaries: vec!["axolotlsay".to_owned()],
+            background: Some(Utf8PathBuf::from("assets/background.png")),
+            applications_symlink: true,
+            signing_identity: None,
+            notarize: false,
+        };
+        let script = info.finder_script("background.png");
+        assert!(script.contains(
+            r#"  tell disk "Axolotl \"Say\" C:\\ 0.2.0"
+"#
+        ));
+        assert!(script.contains(
+            r#"    set background picture of viewOptions to file ".background:background.png"
+"#
+        ));
+        assert!(script.starts_with("tell application \"Finder\"\n"));
+        assert!(script.ends_with("  end tell\nend tell\n"));
+    }
+}
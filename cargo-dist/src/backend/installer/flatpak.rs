@@ -0,0 +1,241 @@
+//! Code for generating a Flatpak manifest and the files Flathub wants with it
+
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{
+    linux::{build_timestamp, desktop_entry},
+    ExecutableZipFragment, InstallerInfo,
+};
+use crate::{
+    backend::templates::{Templates, TEMPLATE_INSTALLER_FLATPAK, TEMPLATE_INSTALLER_METAINFO},
+    config::ChecksumStyle,
+    errors::DistResult,
+};
+
+/// Info about a Flatpak manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct FlatpakInstallerInfo {
+    /// The app id (e.g. "io.github.axodotdev.axolotlsay")
+    pub app_id: String,
+    /// Dir the manifest and its files are written to before archiving
+    pub layout_dir: Utf8PathBuf,
+    /// The runtime the app runs on
+    pub runtime: String,
+    /// The version of the runtime
+    pub runtime_version: String,
+    /// The SDK that goes with the runtime
+    pub sdk: String,
+    /// Sandbox permissions to grant the app
+    pub finish_args: Vec<String>,
+    /// Binaries to install from the archives (the first one is the app's command)
+    pub binaries: Vec<String>,
+    /// The archive to fetch for each architecture
+    pub sources: Vec<FlatpakSource>,
+    /// The icon to show for the app
+    pub icon: Utf8PathBuf,
+    /// The desktop entry categories
+    pub categories: Vec<String>,
+    /// Whether the app runs in a terminal
+    pub terminal: bool,
+    /// Whether to also build a .flatpak bundle in CI
+    pub bundle: bool,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// The app's license, in SPDX format
+    pub license: Option<String>,
+    /// The app's homepage
+    pub homepage: Option<String>,
+    /// The developer, from the first entry of "authors"
+    pub developer: Option<String>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// An archive the manifest fetches for an architecture
+#[derive(Debug, Clone, Serialize)]
+pub struct FlatpakSource {
+    /// The Flatpak architecture (e.g. "x86_64")
+    pub arch: String,
+    /// The archive
+    pub artifact: ExecutableZipFragment,
+    /// Leading dirs to strip when unpacking the archive
+    pub strip_components: u32,
+    /// sha256 of the archive, filled in from the manifest
+    pub sha256: Option<String>,
+}
+
+/// Everything the templates need, on top of the installer info
+#[derive(Serialize)]
+struct FlatpakContext<'a> {
+    #[serde(flatten)]
+    info: &'a FlatpakInstallerInfo,
+    build_commands: Vec<String>,
+    icon_file: String,
+    summary: String,
+    description: Vec<String>,
+    developer_id: String,
+    component_type: &'static str,
+    release_date: String,
+}
+
+pub(crate) fn write_flatpak_layout(
+    templates: &Templates,
+    source_info: &FlatpakInstallerInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let mut info = source_info.clone();
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    for source in &mut info.sources {
+        source.sha256 = manifest
+            .artifacts
+            .get(&source.artifact.id)
+            .and_then(|a| a.checksums.get(checksum_key))
+            .cloned();
+    }
+
+    let app_id = &info.app_id;
+    let icon_ext = info.icon.extension().unwrap_or("png");
+    let icon_file = format!("{app_id}.{icon_ext}");
+    let icon_size = if icon_ext == "svg" {
+        "scalable"
+    } else {
+        "256x256"
+    };
+    let mut build_commands = info
+        .binaries
+        .iter()
+        .map(|binary| format!("install -Dm755 {binary} /app/bin/{binary}"))
+        .collect::<Vec<_>>();
+    build_commands.push(format!(
+        "install -Dm644 {app_id}.desktop /app/share/applications/{app_id}.desktop"
+    ));
+    build_commands.push(format!(
+        "install -Dm644 {app_id}.metainfo.xml /app/share/metainfo/{app_id}.metainfo.xml"
+    ));
+    build_commands.push(format!(
+        "install -Dm644 {icon_file} /app/share/icons/hicolor/{icon_size}/apps/{icon_file}"
+    ));
+
+    let desc = info.desc.as_deref().unwrap_or(&info.inner.app_name).trim();
+    let summary = desc.lines().next().unwrap_or_default().to_owned();
+    let description = desc
+        .split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+    let developer_id = app_id
+        .rsplit_once('.')
+        .map(|(prefix, _)| prefix)
+        .unwrap_or(app_id)
+        .to_owned();
+    let component_type = if info.terminal {
+        "console-application"
+    } else {
+        "desktop-application"
+    };
+    let context = FlatpakContext {
+        info: &info,
+        build_commands,
+        icon_file: icon_file.clone(),
+        summary,
+        description,
+        developer_id,
+        component_type,
+        release_date: iso_date(build_timestamp()),
+    };
+
+    let dir = &info.layout_dir;
+    LocalAsset::create_dir_all(dir)?;
+    let flatpak_manifest =
+        templates.render_file_to_clean_string(TEMPLATE_INSTALLER_FLATPAK, &context)?;
+    LocalAsset::write_new(&flatpak_manifest, dir.join(format!("{app_id}.yml")))?;
+    let metainfo = templates.render_file_to_clean_string(TEMPLATE_INSTALLER_METAINFO, &context)?;
+    LocalAsset::write_new(&metainfo, dir.join(format!("{app_id}.metainfo.xml")))?;
+    let command = info.binaries.first().unwrap_or(&info.inner.app_name);
+    let desktop_entry = desktop_entry(
+        &info.inner.app_name,
+        info.desc.as_deref(),
+        command,
+        app_id,
+        info.terminal,
+        &info.categories,
+    );
+    LocalAsset::write_new(&desktop_entry, dir.join(format!("{app_id}.desktop")))?;
+    LocalAsset::copy_named(&info.icon, dir.join(&icon_file))?;
+    // Flathub only builds for the architectures we have archives for
+    let arches = info
+        .sources
+        .iter()
+        .map(|source| format!("\"{}\"", source.arch))
+        .collect::<Vec<_>>();
+    let flathub_json = format!("{{\n  \"only-arches\": [{}]\n}}\n", arches.join(", "));
+    LocalAsset::write_new(&flathub_json, dir.join("flathub.json"))?;
+
+    Ok(())
+}
+
+/// Get the Flatpak architecture for a target triple, if Flathub builds for it
+pub fn flatpak_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    match arch {
+        "x86_64" => Some("x86_64"),
+        "aarch64" => Some("aarch64"),
+        _ => None,
+    }
+}
+
+/// Compute the default app id for an app hosted on GitHub
+///
+/// Flathub recommends underscores instead of hyphens in app ids.
+pub fn flatpak_app_id(owner: &str, app_name: &str) -> String {
+    format!("io.github.{owner}.{app_name}").replace('-', "_")
+}
+
+/// Format a unix timestamp as a date (YYYY-MM-DD)
+fn iso_date(timestamp: u64) -> String {
+    // Howard Hinnant's civil_from_days
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatpak_app_id, iso_date};
+
+    #[test]
+    fn app_ids() {
+        assert_eq!(
+            flatpak_app_id("axodotdev", "axolotlsay"),
+            "io.github.axodotdev.axolotlsay"
+        );
+        assert_eq!(
+            flatpak_app_id("my-org", "cool-app"),
+            "io.github.my_org.cool_app"
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(iso_date(0), "1970-01-01");
+        assert_eq!(iso_date(951782400), "2000-02-29");
+        assert_eq!(iso_date(1735646400), "2024-12-31");
+    }
+}
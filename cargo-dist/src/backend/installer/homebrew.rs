@@ -6,7 +6,7 @@ use serde::Serialize;
 
 use super::InstallerInfo;
 use crate::{
-    backend::templates::{Templates, TEMPLATE_INSTALLER_RB},
+    backend::templates::{Templates, TEMPLATE_INSTALLER_CASK, TEMPLATE_INSTALLER_RB},
     config::ChecksumStyle,
     errors::DistResult,
     installer::ExecutableZipFragment,
@@ -48,6 +48,52 @@ pub struct HomebrewInstallerInfo {
     pub inner: InstallerInfo,
     /// Additional packages to specify as dependencies
     pub dependencies: Vec<String>,
+    /// Homebrew packages the macOS binaries link, from the linkage report
+    pub macos_dependencies: Vec<String>,
+    /// Homebrew packages the Linux binaries link, from the linkage report
+    pub linux_dependencies: Vec<String>,
+}
+
+/// Info about a Homebrew cask
+#[derive(Debug, Clone, Serialize)]
+pub struct HomebrewCaskInfo {
+    /// The cask's token
+    pub token: String,
+    /// The application's name
+    pub name: String,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// A brief description of the application
+    pub desc: Option<String>,
+    /// A GitHub repository to write the cask to, in owner/name format
+    pub tap: Option<String>,
+    /// The release's id, which prefixes the names of its macOS bundles
+    pub release_id: String,
+    /// The kind of bundle the cask installs ("dmg" or "pkg")
+    pub bundle: String,
+    /// macOS ARM64 bundle
+    pub arm64_macos: CaskBundle,
+    /// macOS AMD64 bundle
+    pub x86_64_macos: CaskBundle,
+    /// Whether there's no AMD64 bundle, so the cask only supports ARM64
+    pub arm64_only: bool,
+    /// Binaries in the bundle
+    pub binaries: Vec<String>,
+    /// The pkg identifier, used to uninstall a pkg
+    pub pkg_identifier: Option<String>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// A dmg or pkg a Homebrew cask can install
+#[derive(Debug, Clone, Serialize)]
+pub struct CaskBundle {
+    /// The artifact's id
+    pub id: String,
+    /// The target triple of the artifact
+    pub target: String,
+    /// sha256 of the artifact
+    pub sha256: Option<String>,
 }
 
 pub(crate) fn write_homebrew_formula(
@@ -58,13 +104,17 @@ pub(crate) fn write_homebrew_formula(
 ) -> DistResult<()> {
     let mut info = source_info.clone();
 
-    // Fetch any detected dependencies from the linkage data
-    // FIXME: ideally this would be writing to per-target dependencies,
-    // but for now we just shove them all into one big list.
-    use_linkage(manifest, &info.arm64_macos, &mut info.dependencies);
-    use_linkage(manifest, &info.x86_64_macos, &mut info.dependencies);
-    use_linkage(manifest, &info.arm64_linux, &mut info.dependencies);
-    use_linkage(manifest, &info.x86_64_linux, &mut info.dependencies);
+    // Fetch any detected dependencies from the linkage data, so Homebrew installs
+    // the libraries the binaries link instead of them failing to load
+    use_linkage(manifest, &info.arm64_macos, &mut info.macos_dependencies);
+    use_linkage(manifest, &info.x86_64_macos, &mut info.macos_dependencies);
+    use_linkage(manifest, &info.arm64_linux, &mut info.linux_dependencies);
+    use_linkage(manifest, &info.x86_64_linux, &mut info.linux_dependencies);
+    // Anything that's already a dependency everywhere doesn't need listing again
+    info.macos_dependencies
+        .retain(|dep| !source_info.dependencies.contains(dep));
+    info.linux_dependencies
+        .retain(|dep| !source_info.dependencies.contains(dep));
 
     // Grab checksums
     use_sha256_checksum(manifest, &info.arm64_macos, &mut info.arm64_macos_sha256);
@@ -77,6 +127,28 @@ pub(crate) fn write_homebrew_formula(
     Ok(())
 }
 
+pub(crate) fn write_homebrew_cask(
+    templates: &Templates,
+    source_info: &HomebrewCaskInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let mut info = source_info.clone();
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    for bundle in [&mut info.arm64_macos, &mut info.x86_64_macos] {
+        bundle.sha256 = manifest
+            .artifacts
+            .get(&bundle.id)
+            .and_then(|a| a.checksums.get(checksum_key))
+            .cloned();
+    }
+
+    let script = templates.render_file_to_clean_string(TEMPLATE_INSTALLER_CASK, &info)?;
+    LocalAsset::write_new(&script, &info.inner.dest_path)?;
+    Ok(())
+}
+
 /// Grab the sha256 checksum for this artifact from the manifest
 fn use_sha256_checksum(
     manifest: &DistManifest,
@@ -105,7 +177,11 @@ fn use_linkage(
             .homebrew
             .iter()
             .filter_map(|lib| lib.source.clone());
-        dependencies.extend(homebrew_deps);
+        for dep in homebrew_deps {
+            if !dependencies.contains(&dep) {
+                dependencies.push(dep);
+            }
+        }
     }
 }
 
@@ -175,9 +251,30 @@ pub fn to_class_case(app_name: &str) -> String {
     chars.iter().collect()
 }
 
+/// Converts the provided app name into a Homebrew cask token,
+/// which is lowercase with words separated by dashes.
+// Homebrew's rules for this are documented at:
+// https://docs.brew.sh/Cask-Cookbook#token-reference
+pub fn to_cask_token(app_name: &str) -> String {
+    let mut out = String::new();
+    for char in app_name.to_lowercase().chars() {
+        match char {
+            'a'..='z' | '0'..='9' => out.push(char),
+            '+' => out.push_str("-plus-"),
+            '@' => out.push_str("-at-"),
+            _ => out.push('-'),
+        }
+    }
+    // Collapse any runs of dashes that the replacements produced
+    out.split('-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::to_class_case;
+    use super::{to_cask_token, to_class_case};
 
     fn run_comparison(in_str: &str, expected: &str) {
         let out_str = to_class_case(in_str);
@@ -262,4 +359,15 @@ mod tests {
     fn ampersand_but_no_digit() {
         run_comparison("openssl@blah", "Openssl@blah");
     }
+
+    #[test]
+    fn cask_token_basic() {
+        assert_eq!(to_cask_token("My_App"), "my-app");
+    }
+
+    #[test]
+    fn cask_token_special_chars() {
+        assert_eq!(to_cask_token("c++"), "c-plus-plus");
+        assert_eq!(to_cask_token("app@2.0"), "app-at-2-0");
+    }
 }
@@ -0,0 +1,268 @@
+//! Shared logic for Linux system packages (deb, rpm, apk), AppImages and Flatpaks
+
+use std::io::Write;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{errors::*, tasks::StaticAssetKind};
+
+/// A file to install with a system package
+pub struct PackageFile {
+    /// Path relative to the root of the filesystem
+    pub path: String,
+    /// The contents of the file
+    pub contents: Vec<u8>,
+    /// The permissions of the file
+    pub mode: u32,
+}
+
+/// Where a distro wants the parts of a package that they disagree on
+pub struct PackageLayout<'a> {
+    /// The name of the package
+    pub package: &'a str,
+    /// Where zsh completions go
+    pub zsh_completions_dir: &'static str,
+    /// Where license files go, if they get their own dir instead of going with the docs
+    pub license_dir: Option<String>,
+}
+
+impl PackageLayout<'_> {
+    /// Where documentation goes
+    pub fn doc_dir(&self) -> String {
+        format!("usr/share/doc/{}", self.package)
+    }
+}
+
+/// Lay out the binaries and static assets the way Linux distros expect them
+///
+/// Binaries go in `usr/bin`, and README/CHANGELOG/LICENSE with the docs. Included files
+/// that look like man pages or shell completions go where man and the shells look for
+/// them; everything else is documentation.
+pub fn package_files(
+    layout: &PackageLayout,
+    package_dir: &Utf8Path,
+    binaries: &[String],
+    static_assets: &[(StaticAssetKind, Utf8PathBuf)],
+) -> DistResult<Vec<PackageFile>> {
+    let doc_dir = layout.doc_dir();
+    let mut files = vec![];
+
+    for binary in binaries {
+        files.push(PackageFile {
+            path: format!("usr/bin/{binary}"),
+            contents: std::fs::read(package_dir.join(binary))?,
+            mode: 0o755,
+        });
+    }
+
+    for (kind, src_path) in static_assets {
+        let file_name = src_path.file_name().unwrap_or(src_path.as_str());
+        match kind {
+            StaticAssetKind::Readme | StaticAssetKind::Changelog => {
+                files.push(PackageFile {
+                    path: format!("{doc_dir}/{file_name}"),
+                    contents: std::fs::read(src_path)?,
+                    mode: 0o644,
+                });
+            }
+            StaticAssetKind::License => {
+                let license_dir = layout.license_dir.as_deref().unwrap_or(&doc_dir);
+                files.push(PackageFile {
+                    path: format!("{license_dir}/{file_name}"),
+                    contents: std::fs::read(src_path)?,
+                    mode: 0o644,
+                });
+            }
+            StaticAssetKind::Other => {
+                // Includes can be whole dirs of man pages or completions
+                let base = src_path.parent().unwrap_or(Utf8Path::new(""));
+                for path in files_under(src_path)? {
+                    let relative = path.strip_prefix(base).unwrap_or(path.as_path());
+                    files.push(place_included_file(layout, &path, relative)?);
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Get every file at this path, recursing into dirs
+fn files_under(path: &Utf8Path) -> DistResult<Vec<Utf8PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_owned()]);
+    }
+    let mut files = vec![];
+    let mut entries = path
+        .read_dir_utf8()?
+        .map(|entry| entry.map(|entry| entry.into_path()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        files.extend(files_under(&entry)?);
+    }
+    Ok(files)
+}
+
+/// Decide where an included file belongs
+fn place_included_file(
+    layout: &PackageLayout,
+    src_path: &Utf8Path,
+    relative: &Utf8Path,
+) -> DistResult<PackageFile> {
+    let file_name = src_path.file_name().unwrap_or(src_path.as_str());
+    let contents = std::fs::read(src_path)?;
+    let zsh_dir = layout.zsh_completions_dir;
+
+    let path = if let Some(section) = man_section(file_name) {
+        // Distros want man pages compressed
+        if file_name.ends_with(".gz") {
+            format!("usr/share/man/man{section}/{file_name}")
+        } else {
+            return Ok(PackageFile {
+                path: format!("usr/share/man/man{section}/{file_name}.gz"),
+                contents: gzip(&contents)?,
+                mode: 0o644,
+            });
+        }
+    } else if let Some(command) = file_name.strip_suffix(".bash") {
+        format!("usr/share/bash-completion/completions/{command}")
+    } else if let Some(command) = file_name.strip_suffix(".zsh") {
+        let command = command.strip_prefix('_').unwrap_or(command);
+        format!("{zsh_dir}/_{command}")
+    } else if file_name.starts_with('_') && !file_name.contains('.') {
+        format!("{zsh_dir}/{file_name}")
+    } else if file_name.ends_with(".fish") {
+        format!("usr/share/fish/vendor_completions.d/{file_name}")
+    } else {
+        format!("{}/{relative}", layout.doc_dir())
+    };
+
+    Ok(PackageFile {
+        path,
+        contents,
+        mode: 0o644,
+    })
+}
+
+/// Get the man section of a file named like a man page (`app.1` or `app.1.gz`)
+fn man_section(file_name: &str) -> Option<char> {
+    let name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    let (stem, ext) = name.rsplit_once('.')?;
+    let mut chars = ext.chars();
+    let section = chars.next()?;
+    let is_section = ('1'..='9').contains(&section) && chars.all(|c| c.is_ascii_lowercase());
+    (is_section && !stem.is_empty()).then_some(section)
+}
+
+/// Gzip some bytes
+pub fn gzip(contents: &[u8]) -> DistResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::best());
+    encoder.write_all(contents)?;
+    Ok(encoder.finish()?)
+}
+
+/// Make a tar header for a root-owned entry
+pub fn tar_header(kind: tar::EntryType, mode: u32, size: u64, mtime: u64) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(kind);
+    header.set_mode(mode);
+    header.set_size(size);
+    header.set_mtime(mtime);
+    header.set_uid(0);
+    header.set_gid(0);
+    // These only fail if the names are too long
+    let _ = header.set_username("root");
+    let _ = header.set_groupname("root");
+    header
+}
+
+/// Turn a semver version into a version that sorts the same way in dpkg and rpm
+///
+/// Prereleases sort before the release in semver, which both spell with a `~`.
+pub fn package_version(version: &semver::Version) -> String {
+    let mut package_version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        package_version.push('~');
+        package_version.push_str(&version.pre.replace('-', "."));
+    }
+    if !version.build.is_empty() {
+        package_version.push('+');
+        package_version.push_str(&version.build.replace('-', "."));
+    }
+    package_version
+}
+
+/// Compute a desktop entry that launches an app
+pub fn desktop_entry(
+    name: &str,
+    desc: Option<&str>,
+    exec: &str,
+    icon: &str,
+    terminal: bool,
+    categories: &[String],
+) -> String {
+    let mut entry = String::new();
+    entry.push_str("[Desktop Entry]\n");
+    entry.push_str("Type=Application\n");
+    entry.push_str(&format!("Name={name}\n"));
+    if let Some(desc) = desc {
+        let comment = desc.trim().lines().next().unwrap_or_default();
+        entry.push_str(&format!("Comment={comment}\n"));
+    }
+    entry.push_str(&format!("Exec={exec}\n"));
+    entry.push_str(&format!("Icon={icon}\n"));
+    entry.push_str(&format!("Terminal={terminal}\n"));
+    entry.push_str(&format!("Categories={}\n", desktop_list(categories)));
+    entry
+}
+
+/// Format a desktop entry list, which ends with a `;`
+fn desktop_list(items: &[String]) -> String {
+    items.iter().flat_map(|item| [item.as_str(), ";"]).collect()
+}
+
+/// The timestamp to give everything in a package
+///
+/// This respects SOURCE_DATE_EPOCH, so builds can be reproducible.
+pub fn build_timestamp() -> u64 {
+    if let Some(epoch) = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+    {
+        return epoch;
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{desktop_list, man_section, package_version};
+
+    #[test]
+    fn versions_sort_like_semver() {
+        let version = |v: &str| package_version(&semver::Version::parse(v).unwrap());
+        assert_eq!(version("1.2.3"), "1.2.3");
+        assert_eq!(version("1.0.0-beta.1"), "1.0.0~beta.1");
+        assert_eq!(version("1.0.0-rc-1+build-5"), "1.0.0~rc.1+build.5");
+    }
+
+    #[test]
+    fn man_pages() {
+        assert_eq!(man_section("app.1"), Some('1'));
+        assert_eq!(man_section("app.3ssl.gz"), Some('3'));
+        assert_eq!(man_section("config.toml"), None);
+        assert_eq!(man_section("README.md"), None);
+        assert_eq!(man_section(".1"), None);
+    }
+
+    #[test]
+    fn desktop_lists() {
+        let categories = vec!["Utility".to_owned(), "Development".to_owned()];
+        assert_eq!(desktop_list(&categories), "Utility;Development;");
+    }
+}
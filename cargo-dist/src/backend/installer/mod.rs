@@ -2,23 +2,51 @@
 //!
 //! In the future this might get split up into submodules.
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use serde::Serialize;
 
 use crate::{
     config::{JinjaInstallPathStrategy, ZipStyle},
+    errors::DistResult,
     InstallReceipt, TargetTriple,
 };
 
-use self::homebrew::HomebrewInstallerInfo;
+use self::apk::ApkInstallerInfo;
+use self::appimage::AppImageInstallerInfo;
+use self::aur::AurInstallerInfo;
+use self::chocolatey::ChocolateyInstallerInfo;
+use self::deb::DebInstallerInfo;
+use self::dmg::DmgInstallerInfo;
+use self::flatpak::FlatpakInstallerInfo;
+use self::homebrew::{HomebrewCaskInfo, HomebrewInstallerInfo};
 use self::msi::MsiInstallerInfo;
+use self::msix::MsixInstallerInfo;
+use self::nix::NixInstallerInfo;
 use self::npm::NpmInstallerInfo;
+use self::pkg::PkgInstallerInfo;
+use self::rpm::RpmInstallerInfo;
+use self::scoop::ScoopInstallerInfo;
+use self::snap::SnapInstallerInfo;
 
+pub mod apk;
+pub mod appimage;
+pub mod aur;
+pub mod chocolatey;
+pub mod deb;
+pub mod dmg;
+pub mod flatpak;
 pub mod homebrew;
+pub mod linux;
 pub mod msi;
+pub mod msix;
+pub mod nix;
 pub mod npm;
+pub mod pkg;
 pub mod powershell;
+pub mod rpm;
+pub mod scoop;
 pub mod shell;
+pub mod snap;
 
 /// A kind of an installer
 #[derive(Debug, Clone)]
@@ -32,8 +60,36 @@ pub enum InstallerImpl {
     Npm(NpmInstallerInfo),
     /// Homebrew formula
     Homebrew(HomebrewInstallerInfo),
+    /// Homebrew cask
+    HomebrewCask(HomebrewCaskInfo),
     /// Windows msi installer
     Msi(MsiInstallerInfo),
+    /// Debian package
+    Deb(DebInstallerInfo),
+    /// RPM package
+    Rpm(RpmInstallerInfo),
+    /// AUR PKGBUILD
+    Aur(AurInstallerInfo),
+    /// Alpine package
+    Apk(ApkInstallerInfo),
+    /// AppImage
+    AppImage(AppImageInstallerInfo),
+    /// Flatpak manifest
+    Flatpak(FlatpakInstallerInfo),
+    /// snapcraft.yaml
+    Snap(SnapInstallerInfo),
+    /// Nix flake
+    Nix(NixInstallerInfo),
+    /// Scoop manifest
+    Scoop(ScoopInstallerInfo),
+    /// macOS pkg
+    Pkg(PkgInstallerInfo),
+    /// macOS dmg
+    Dmg(DmgInstallerInfo),
+    /// Windows MSIX package
+    Msix(MsixInstallerInfo),
+    /// Chocolatey package
+    Chocolatey(ChocolateyInstallerInfo),
 }
 
 /// Generic info about an installer
@@ -84,3 +140,25 @@ pub struct UpdaterFragment {
     /// The binary the artifact contains (name, assumed at root)
     pub binary: String,
 }
+
+/// Set the unix permissions of a file we're packaging (a no-op elsewhere)
+#[cfg(unix)]
+pub(crate) fn set_mode(path: &Utf8Path, mode: u32) -> DistResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn set_mode(_path: &Utf8Path, _mode: u32) -> DistResult<()> {
+    Ok(())
+}
+
+/// Escape a value for use in XML, either as text or in a quoted attribute
+pub(crate) fn xml_escape(val: &str) -> String {
+    val.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
@@ -5,7 +5,11 @@ use camino::{Utf8Path, Utf8PathBuf};
 use tracing::info;
 use wix::print::{wxs::WxsRenders, RenderOutput};
 
-use crate::{backend::diff_files, config, errors::*};
+use crate::{
+    backend::{diff_files, installer::xml_escape},
+    config::{self, MsiPathScope, MsiService, MsiServiceStart},
+    errors::*,
+};
 
 const METADATA_WIX: &str = "wix";
 const WIX_GUID_KEYS: &[&str] = &["upgrade-guid", "path-guid"];
@@ -26,6 +30,20 @@ pub struct MsiInstallerInfo {
     pub wxs_path: Utf8PathBuf,
     /// Path to the package Cargo.toml associated with this msi
     pub manifest_path: Utf8PathBuf,
+    /// The app's name, used for its Start Menu folder
+    pub app_name: String,
+    /// Binaries to add Start Menu shortcuts for
+    pub shortcuts: Vec<String>,
+    /// Which PATH to add the binaries to
+    pub path: MsiPathScope,
+    /// Binaries to register as Windows services
+    pub services: Vec<MsiService>,
+    /// Extra WiX source files to compile into the msi
+    pub fragments: Vec<Utf8PathBuf>,
+    /// Ids of ComponentGroups to install with the binaries
+    pub component_groups: Vec<String>,
+    /// The UpgradeCode GUID, overriding the one persisted in package.metadata.wix
+    pub upgrade_code: Option<String>,
 }
 
 impl MsiInstallerInfo {
@@ -49,6 +67,10 @@ impl MsiInstallerInfo {
         b.output(Some(self.file_path.as_str()));
         // Binaries are over here
         b.target_bin_dir(Some(self.package_dir.as_str()));
+        // Compile the user's own WiX sources alongside main.wxs
+        if !self.fragments.is_empty() {
+            b.includes(Some(self.fragments.iter().map(|f| f.as_str()).collect()));
+        }
         // FIXME: Would love to give users better output from WiX but it prints to stdout,
         // which messes up `--output-format=json`
         b.capture_output(true);
@@ -75,22 +97,160 @@ impl MsiInstallerInfo {
             .join("wix")
             .join("main.wxs");
         b.output(Some(output.as_str()));
+        if let Some(upgrade_code) = &self.upgrade_code {
+            b.upgrade_guid(Some(upgrade_code));
+        }
         let exec = b.build();
-        let renders = exec.render().map_err(|e| DistError::WixInit {
+        let mut renders = exec.render().map_err(|e| DistError::WixInit {
             package: self.pkg_spec.clone(),
             details: e,
         })?;
+        renders.wxs.rendered = self.customize_wxs(&renders.wxs.rendered)?;
         Ok(renders)
     }
 
+    /// Apply our msi settings to the main.wxs cargo-wix rendered
+    ///
+    /// cargo-wix doesn't know about any of these, so we add them at well-known places
+    /// in its template.
+    fn customize_wxs(&self, wxs: &str) -> DistResult<String> {
+        let mut editor = WxsEditor {
+            wxs: wxs.to_owned(),
+            package: &self.pkg_spec,
+        };
+
+        // Shortcuts go in their binary's component, so they come and go with it
+        if !self.shortcuts.is_empty() {
+            let app_name = xml_escape(&self.app_name);
+            editor.insert_after(
+                &["<Directory Id='TARGETDIR' Name='SourceDir'>"],
+                &format!(
+                    "
+            <Directory Id='ProgramMenuFolder'>
+                <Directory Id='ApplicationProgramsFolder' Name='{app_name}'/>
+            </Directory>"
+                ),
+            )?;
+        }
+        for (idx, binary) in self.shortcuts.iter().enumerate() {
+            let name = xml_escape(binary);
+            let mut xml = format!(
+                "
+                            <Shortcut
+                                Id='shortcut{idx}'
+                                Directory='ApplicationProgramsFolder'
+                                Name='{name}'
+                                Target='[Bin]{name}.exe'
+                                WorkingDirectory='Bin'/>"
+            );
+            if idx == 0 {
+                xml.push_str(
+                    "
+                            <RemoveFolder
+                                Id='ApplicationProgramsFolder'
+                                Directory='ApplicationProgramsFolder'
+                                On='uninstall'/>",
+                );
+            }
+            editor.insert_after(&[&format!("Name='{name}.exe'"), "KeyPath='yes'/>"], &xml)?;
+        }
+
+        // Services have to live in the component of the binary they run
+        for (idx, service) in self.services.iter().enumerate() {
+            let binary = xml_escape(&service.binary);
+            let name = xml_escape(service.name.as_deref().unwrap_or(&service.binary));
+            let display_name = service
+                .display_name
+                .as_deref()
+                .map(xml_escape)
+                .unwrap_or_else(|| name.clone());
+            let start = service.start.unwrap_or_default();
+            let mut xml = format!(
+                "
+                            <ServiceInstall
+                                Id='service{idx}'
+                                Name='{name}'
+                                DisplayName='{display_name}'"
+            );
+            if let Some(description) = &service.description {
+                xml.push_str(&format!(
+                    "
+                                Description='{}'",
+                    xml_escape(description)
+                ));
+            }
+            if let Some(arguments) = &service.arguments {
+                xml.push_str(&format!(
+                    "
+                                Arguments='{}'",
+                    xml_escape(arguments)
+                ));
+            }
+            let start_attr = match start {
+                MsiServiceStart::Auto => "auto",
+                MsiServiceStart::Demand => "demand",
+                MsiServiceStart::Disabled => "disabled",
+            };
+            xml.push_str(&format!(
+                "
+                                Type='ownProcess'
+                                Start='{start_attr}'
+                                ErrorControl='normal'/>
+                            <ServiceControl
+                                Id='servicecontrol{idx}'
+                                Name='{name}'"
+            ));
+            if start == MsiServiceStart::Auto {
+                xml.push_str(
+                    "
+                                Start='install'",
+                );
+            }
+            xml.push_str(
+                "
+                                Stop='both'
+                                Remove='uninstall'
+                                Wait='yes'/>",
+            );
+            editor.insert_after(&[&format!("Name='{binary}.exe'"), "KeyPath='yes'/>"], &xml)?;
+        }
+
+        if !self.component_groups.is_empty() {
+            let mut xml = String::new();
+            for group in &self.component_groups {
+                xml.push_str(&format!(
+                    "
+            <ComponentGroupRef Id='{}'/>",
+                    xml_escape(group)
+                ));
+            }
+            editor.insert_after(&["<!--<ComponentRef Id='License'/>-->"], &xml)?;
+        }
+
+        match self.path {
+            MsiPathScope::System => {}
+            MsiPathScope::User => {
+                editor.replace_after(&["<Environment"], "System='yes'", "System='no'")?
+            }
+            // Disabled features don't get installed, or shown in the installer
+            MsiPathScope::None => {
+                editor.replace_after(&["Id='Environment'"], "Level='1'", "Level='0'")?
+            }
+        }
+
+        Ok(editor.wxs)
+    }
+
     /// msi's impl of `cargo dist genenerate --check`
     pub fn check_config(&self) -> DistResult<()> {
+        self.check_upgrade_code()?;
         self.check_wix_guids()?;
         self.check_wxs()?;
         Ok(())
     }
     /// msi's impl of `cargo dist genenerate`
     pub fn write_config_to_disk(&self) -> DistResult<()> {
+        self.check_upgrade_code()?;
         self.write_wix_guids_to_disk()?;
         self.write_wxs_to_disk()?;
         Ok(())
@@ -126,6 +286,19 @@ impl MsiInstallerInfo {
         Ok(())
     }
 
+    /// Check that msi.upgrade-code is a GUID, as WiX doesn't catch that until it builds
+    fn check_upgrade_code(&self) -> DistResult<()> {
+        if let Some(upgrade_code) = &self.upgrade_code {
+            if uuid::Uuid::parse_str(upgrade_code).is_err() {
+                return Err(DistError::InvalidUpgradeCode {
+                    package: self.pkg_spec.clone(),
+                    upgrade_code: upgrade_code.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Check that wix GUIDs are set in the package's Cargo.toml
     fn check_wix_guids(&self) -> DistResult<()> {
         // Ok we have changes to make, let's load the toml
@@ -150,6 +323,45 @@ impl MsiInstallerInfo {
     }
 }
 
+/// Edits a main.wxs by finding known bits of cargo-wix's template
+struct WxsEditor<'a> {
+    wxs: String,
+    package: &'a str,
+}
+
+impl WxsEditor<'_> {
+    /// Find the end of the last anchor, looking for each one after the one before it
+    fn find(&self, anchors: &[&str]) -> DistResult<usize> {
+        let mut pos = 0;
+        for anchor in anchors {
+            let Some(idx) = self.wxs[pos..].find(anchor) else {
+                return Err(DistError::WixCustomize {
+                    package: self.package.to_owned(),
+                    anchor: (*anchor).to_owned(),
+                });
+            };
+            pos += idx + anchor.len();
+        }
+        Ok(pos)
+    }
+
+    /// Insert some text after the given anchors
+    fn insert_after(&mut self, anchors: &[&str], text: &str) -> DistResult<()> {
+        let pos = self.find(anchors)?;
+        self.wxs.insert_str(pos, text);
+        Ok(())
+    }
+
+    /// Replace the first `old` after the given anchors
+    fn replace_after(&mut self, anchors: &[&str], old: &str, new: &str) -> DistResult<()> {
+        let mut anchors = anchors.to_vec();
+        anchors.push(old);
+        let end = self.find(&anchors)?;
+        self.wxs.replace_range(end - old.len()..end, new);
+        Ok(())
+    }
+}
+
 fn write_render(render: Option<RenderOutput>) -> DistResult<()> {
     let Some(render) = render else {
         return Ok(());
@@ -209,3 +421,175 @@ fn update_wix_metadata(package_toml: &mut toml_edit::Document) -> bool {
 
     modified
 }
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+
+    use super::MsiInstallerInfo;
+    use crate::{
+        config::{MsiPathScope, MsiService, MsiServiceStart},
+        errors::DistError,
+    };
+
+    /// What `cargo wix print wxs` renders for axolotlsay
+    const MAIN_WXS: &str = include_str!("../../../tests/fixtures/axolotlsay-main.wxs");
+
+    fn info() -> MsiInstallerInfo {
+        MsiInstallerInfo {
+            pkg_spec: "axolotlsay".to_owned(),
+            target: "x86_64-pc-windows-msvc".to_owned(),
+            file_path: Utf8PathBuf::from("target/distrib/axolotlsay-x86_64-pc-windows-msvc.msi"),
+            package_dir: Utf8PathBuf::from("target/distrib/axolotlsay-x86_64-pc-windows-msvc"),
+            wxs_path: Utf8PathBuf::from("wix/main.wxs"),
+            manifest_path: Utf8PathBuf::from("Cargo.toml"),
+            app_name: "axolotlsay".to_owned(),
+            shortcuts: vec![],
+            path: MsiPathScope::System,
+            services: vec![],
+            fragments: vec![],
+            component_groups: vec![],
+            upgrade_code: None,
+        }
+    }
+
+    /// The component cargo-wix made for the given binary
+    fn component<'a>(wxs: &'a str, binary: &str) -> &'a str {
+        let file = wxs.find(&format!("Name='{binary}.exe'")).unwrap();
+        let start = wxs[..file].rfind("<Component ").unwrap();
+        let end = file + wxs[file..].find("</Component>").unwrap();
+        &wxs[start..end]
+    }
+
+    /// The Feature element with the given id, up to its first child
+    fn feature<'a>(wxs: &'a str, id: &str) -> &'a str {
+        let start = wxs.find(&format!("Id='{id}'")).unwrap();
+        let end = start + wxs[start..].find('>').unwrap();
+        &wxs[start..end]
+    }
+
+    #[test]
+    fn defaults() {
+        let wxs = info().customize_wxs(MAIN_WXS).unwrap();
+        assert_eq!(wxs, MAIN_WXS);
+    }
+
+    #[test]
+    fn shortcuts() {
+        let info = MsiInstallerInfo {
+            app_name: "Axolotl & Friends".to_owned(),
+            shortcuts: vec!["axolotlsay".to_owned()],
+            ..info()
+        };
+        let wxs = info.customize_wxs(MAIN_WXS).unwrap();
+
+        let targetdir = wxs.find("<Directory Id='TARGETDIR'").unwrap();
+        let programs = wxs.find("<Directory Id='ProgramMenuFolder'>").unwrap();
+        let bin = wxs.find("<Directory Id='Bin'").unwrap();
+        assert!(targetdir < programs && programs < bin);
+        assert!(wxs
+            .contains("<Directory Id='ApplicationProgramsFolder' Name='Axolotl &amp; Friends'/>"));
+
+        let component = component(&wxs, "axolotlsay");
+        assert!(component.contains("Id='shortcut0'"));
+        assert!(component.contains("Target='[Bin]axolotlsay.exe'"));
+        assert!(component.contains("<RemoveFolder"));
+        // The shortcut comes after the File, not inside it
+        let key_path = component.find("KeyPath='yes'/>").unwrap();
+        assert!(key_path < component.find("<Shortcut").unwrap());
+    }
+
+    #[test]
+    fn services() {
+        let info = MsiInstallerInfo {
+            services: vec![
+                MsiService {
+                    binary: "axolotlsay".to_owned(),
+                    description: Some("Says \"hi\" & more".to_owned()),
+                    arguments: Some("--serve".to_owned()),
+                    ..MsiService::default()
+                },
+                MsiService {
+                    binary: "axolotlsay".to_owned(),
+                    name: Some("axolotl-worker".to_owned()),
+                    display_name: Some("Axolotl Worker".to_owned()),
+                    start: Some(MsiServiceStart::Demand),
+                    ..MsiService::default()
+                },
+            ],
+            ..info()
+        };
+        let wxs = info.customize_wxs(MAIN_WXS).unwrap();
+        let component = component(&wxs, "axolotlsay");
+
+        assert!(component.contains("Id='service0'"));
+        assert!(component.contains("DisplayName='axolotlsay'"));
+        assert!(component.contains("Description='Says &quot;hi&quot; &amp; more'"));
+        assert!(component.contains("Arguments='--serve'"));
+        assert!(component.contains("Start='auto'"));
+        assert!(component.contains("Start='install'"));
+
+        assert!(component.contains("Id='service1'"));
+        assert!(component.contains("Name='axolotl-worker'"));
+        assert!(component.contains("DisplayName='Axolotl Worker'"));
+        assert!(component.contains("Start='demand'"));
+        // Services that start on demand aren't started by the installer
+        let control = &component[component.find("Id='servicecontrol1'").unwrap()..];
+        let control = &control[..control.find("/>").unwrap()];
+        assert!(!control.contains("Start='install'"));
+    }
+
+    #[test]
+    fn component_groups() {
+        let info = MsiInstallerInfo {
+            component_groups: vec!["Docs".to_owned(), "Extras".to_owned()],
+            ..info()
+        };
+        let wxs = info.customize_wxs(MAIN_WXS).unwrap();
+
+        let binaries = wxs.find("<Feature\n            Id='Binaries'").unwrap();
+        let docs = wxs.find("<ComponentGroupRef Id='Docs'/>").unwrap();
+        let extras = wxs.find("<ComponentGroupRef Id='Extras'/>").unwrap();
+        let environment = wxs.find("Id='Environment'").unwrap();
+        assert!(binaries < docs && docs < extras && extras < environment);
+    }
+
+    #[test]
+    fn path_scope() {
+        let system = info().customize_wxs(MAIN_WXS).unwrap();
+        assert!(system.contains("System='yes'"));
+        assert!(feature(&system, "Environment").contains("Level='1'"));
+
+        let info_for = |path| MsiInstallerInfo { path, ..info() };
+
+        let user = info_for(MsiPathScope::User)
+            .customize_wxs(MAIN_WXS)
+            .unwrap();
+        assert!(!user.contains("System='yes'"));
+        assert!(user.contains("System='no'"));
+        assert!(feature(&user, "Environment").contains("Level='1'"));
+
+        let none = info_for(MsiPathScope::None)
+            .customize_wxs(MAIN_WXS)
+            .unwrap();
+        assert!(none.contains("System='yes'"));
+        assert!(feature(&none, "Environment").contains("Level='0'"));
+        // Only the PATH feature is disabled, not the binaries
+        assert!(feature(&none, "Binaries").contains("Level='1'"));
+    }
+
+    #[test]
+    fn missing_binary() {
+        let info = MsiInstallerInfo {
+            shortcuts: vec!["axolotlsay-helper".to_owned()],
+            ..info()
+        };
+        match info.customize_wxs(MAIN_WXS).unwrap_err() {
+            DistError::WixCustomize { package, anchor } => {
+                assert_eq!(package, "axolotlsay");
+                assert_eq!(anchor, "Name='axolotlsay-helper.exe'");
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Windows MSIX installer
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::Utf8PathBuf;
+use semver::Version;
+use serde::Serialize;
+use temp_dir::TempDir;
+use tracing::info;
+
+use crate::backend::templates::{Templates, TEMPLATE_INSTALLER_APPXMANIFEST};
+use crate::errors::*;
+
+/// Info needed to build an MSIX package
+#[derive(Debug, Clone, Serialize)]
+pub struct MsixInstallerInfo {
+    /// Final file path of the package
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The package's identity name
+    pub identity_name: String,
+    /// The publisher's distinguished name, matching the signing certificate's subject
+    pub publisher: String,
+    /// The publisher's name shown to users
+    pub publisher_display_name: String,
+    /// The app's name shown to users
+    pub display_name: String,
+    /// A description of the app
+    pub description: String,
+    /// The four-part version of the package
+    pub version: String,
+    /// The processor architecture (e.g. "x64")
+    pub architecture: String,
+    /// The binaries we'll be baking into the package
+    pub applications: Vec<MsixApplication>,
+    /// A png to use for the package's logos
+    pub logo: Utf8PathBuf,
+    /// Whether to sign the package
+    pub sign: bool,
+}
+
+/// A binary in an MSIX package
+#[derive(Debug, Clone, Serialize)]
+pub struct MsixApplication {
+    /// The Application Id
+    pub id: String,
+    /// The binary's name
+    pub name: String,
+    /// The binary's file name, which is also the alias it's run with
+    pub file_name: String,
+}
+
+impl MsixInstallerInfo {
+    /// Build the MSIX package
+    ///
+    /// This stages the binaries, logo and AppxManifest.xml and hands them to `makeappx`,
+    /// and then `signtool`, which come with the Windows SDK.
+    pub fn build(&self, templates: &Templates) -> DistResult<()> {
+        info!("building an msix: {}", self.file_path);
+
+        let temp = TempDir::new()?;
+        let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+        let stage = temp_path.join("stage");
+        let assets = stage.join("Assets");
+        LocalAsset::create_dir_all(&assets)?;
+        for app in &self.applications {
+            LocalAsset::copy_named(
+                &self.package_dir.join(&app.file_name),
+                &stage.join(&app.file_name),
+            )?;
+        }
+        LocalAsset::copy_named(&self.logo, assets.join("logo.png"))?;
+        let manifest =
+            templates.render_file_to_clean_string(TEMPLATE_INSTALLER_APPXMANIFEST, self)?;
+        LocalAsset::write_new(&manifest, stage.join("AppxManifest.xml"))?;
+
+        let mut cmd = Cmd::new("makeappx", "build an msix");
+        cmd.stdout_to_stderr();
+        cmd.arg("pack")
+            .arg("/d")
+            .arg(&stage)
+            .arg("/p")
+            .arg(&self.file_path)
+            .arg("/o");
+        cmd.run()?;
+
+        if self.sign {
+            self.sign()?;
+        }
+
+        Ok(())
+    }
+
+    /// Sign the package with the certificate in `MSIX_SIGNING_CERTIFICATE_FILE`
+    fn sign(&self) -> DistResult<()> {
+        let credential = |var: &str| {
+            std::env::var(var).map_err(|_| DistError::MissingMsixCertificate {
+                artifact: self.file_path.clone(),
+                var: var.to_owned(),
+            })
+        };
+        let certificate = credential("MSIX_SIGNING_CERTIFICATE_FILE")?;
+        let password = credential("MSIX_SIGNING_CERTIFICATE_PASSWORD")?;
+
+        let mut cmd = Cmd::new("signtool", "sign an msix");
+        cmd.stdout_to_stderr();
+        // The password is on the command line, so don't log it
+        cmd.log(None);
+        cmd.arg("sign")
+            .arg("/fd")
+            .arg("SHA256")
+            .arg("/f")
+            .arg(&certificate)
+            .arg("/p")
+            .arg(&password)
+            // Timestamp it, so the signature outlives the certificate
+            .arg("/tr")
+            .arg("http://timestamp.digicert.com")
+            .arg("/td")
+            .arg("SHA256")
+            .arg(&self.file_path);
+        cmd.run()?;
+        Ok(())
+    }
+}
+
+/// Get the MSIX processor architecture for a target triple
+pub fn msix_architecture(target: &str) -> Option<&'static str> {
+    if target.starts_with("x86_64") {
+        Some("x64")
+    } else if target.starts_with("aarch64") {
+        Some("arm64")
+    } else if target.starts_with("i686") {
+        Some("x86")
+    } else {
+        None
+    }
+}
+
+/// Convert a version to the four numbers MSIX wants
+///
+/// Prereleases can't be expressed, and the Store requires the last number be 0.
+pub fn msix_version(version: &Version) -> String {
+    format!("{}.{}.{}.0", version.major, version.minor, version.patch)
+}
+
+/// Make a name into a valid identity name, which only allows letters, digits, `.` and `-`
+pub fn msix_identity_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Make a binary name into a valid Application Id, which is letters and digits starting with a letter
+pub fn msix_application_id(name: &str) -> String {
+    let id: String = name.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    if id.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        id
+    } else {
+        format!("App{id}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::{msix_application_id, msix_version};
+
+    #[test]
+    fn versions() {
+        assert_eq!(msix_version(&Version::parse("1.2.3").unwrap()), "1.2.3.0");
+        assert_eq!(
+            msix_version(&Version::parse("0.1.0-prerelease.1").unwrap()),
+            "0.1.0.0"
+        );
+    }
+
+    #[test]
+    fn application_ids() {
+        assert_eq!(msix_application_id("axolotlsay"), "axolotlsay");
+        assert_eq!(msix_application_id("axolotl-say"), "axolotlsay");
+        assert_eq!(msix_application_id("7zip"), "App7zip");
+    }
+}
@@ -0,0 +1,142 @@
+//! Code for generating a Nix flake
+
+use axoasset::LocalAsset;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo};
+use crate::{
+    backend::templates::{Templates, TEMPLATE_INSTALLER_FLAKE},
+    config::ChecksumStyle,
+    errors::DistResult,
+};
+
+/// Info about a Nix flake
+#[derive(Debug, Clone, Serialize)]
+pub struct NixInstallerInfo {
+    /// The name of the package
+    pub pname: String,
+    /// The nixpkgs the flake builds with
+    pub nixpkgs: String,
+    /// The dir of the repository the flake is published to
+    pub flake_dir: String,
+    /// A brief description of the application
+    pub desc: Option<String>,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// The application's licenses, as SPDX ids
+    pub licenses: Vec<String>,
+    /// Binaries to install from the archives (the first one is what `nix run` runs)
+    pub binaries: Vec<String>,
+    /// The archive to fetch for each system
+    pub sources: Vec<NixSource>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// An archive the flake fetches for a system
+#[derive(Debug, Clone, Serialize)]
+pub struct NixSource {
+    /// The Nix system (e.g. "x86_64-linux")
+    pub system: String,
+    /// The archive
+    pub artifact: ExecutableZipFragment,
+    /// The dir the archive unpacks to, if it has one
+    pub root: Option<String>,
+    /// sha256 of the archive, filled in from the manifest
+    pub sha256: Option<String>,
+}
+
+pub(crate) fn write_nix_flake(
+    templates: &Templates,
+    source_info: &NixInstallerInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let mut info = source_info.clone();
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    for source in &mut info.sources {
+        source.sha256 = manifest
+            .artifacts
+            .get(&source.artifact.id)
+            .and_then(|a| a.checksums.get(checksum_key))
+            .cloned();
+    }
+
+    // These end up in double quotes, and descriptions are only one line
+    info.desc = info
+        .desc
+        .as_deref()
+        .and_then(|desc| desc.trim().lines().next())
+        .map(nix_escape);
+    info.homepage = info.homepage.as_deref().map(nix_escape);
+
+    let flake = templates.render_file_to_clean_string(TEMPLATE_INSTALLER_FLAKE, &info)?;
+    LocalAsset::write_new(&flake, &info.inner.dest_path)?;
+    Ok(())
+}
+
+/// Get the Nix system for a target triple, if nixpkgs supports it
+pub fn nix_system(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    let os = if target.contains("linux") {
+        "linux"
+    } else if target.contains("darwin") {
+        "darwin"
+    } else {
+        return None;
+    };
+    match (arch, os) {
+        ("x86_64", "linux") => Some("x86_64-linux"),
+        ("aarch64", "linux") => Some("aarch64-linux"),
+        ("i686", "linux") => Some("i686-linux"),
+        ("armv7", "linux") => Some("armv7l-linux"),
+        ("x86_64", "darwin") => Some("x86_64-darwin"),
+        ("aarch64", "darwin") => Some("aarch64-darwin"),
+        _ => None,
+    }
+}
+
+/// Split an SPDX license expression into the ids in it
+///
+/// nixpkgs can look licenses up by SPDX id, but not whole expressions, so
+/// `MIT OR Apache-2.0` becomes `["MIT", "Apache-2.0"]`.
+pub fn spdx_ids(license: &str) -> Vec<String> {
+    license
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '/')
+        .filter(|id| !id.is_empty() && !matches!(*id, "OR" | "AND" | "WITH"))
+        .map(|id| id.to_owned())
+        .collect()
+}
+
+/// Escape text for a double-quoted Nix string
+fn nix_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nix_escape, nix_system, spdx_ids};
+
+    #[test]
+    fn systems() {
+        assert_eq!(nix_system("x86_64-unknown-linux-gnu"), Some("x86_64-linux"));
+        assert_eq!(nix_system("aarch64-apple-darwin"), Some("aarch64-darwin"));
+        assert_eq!(nix_system("x86_64-pc-windows-msvc"), None);
+    }
+
+    #[test]
+    fn licenses() {
+        assert_eq!(spdx_ids("MIT"), vec!["MIT"]);
+        assert_eq!(spdx_ids("MIT OR Apache-2.0"), vec!["MIT", "Apache-2.0"]);
+        assert_eq!(spdx_ids("MIT/Apache-2.0"), vec!["MIT", "Apache-2.0"]);
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(nix_escape(r#"a "${cool}" app"#), r#"a \"\${cool}\" app"#);
+    }
+}
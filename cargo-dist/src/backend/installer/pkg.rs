@@ -0,0 +1,188 @@
+//! macOS pkg installer
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use temp_dir::TempDir;
+use tracing::info;
+
+use super::set_mode;
+use crate::errors::*;
+use crate::sign::{notarize, staple};
+
+/// Info needed to build a pkg
+#[derive(Debug, Clone)]
+pub struct PkgInstallerInfo {
+    /// Final file path of the pkg
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The package identifier (e.g. "io.github.axodotdev.axolotlsay")
+    pub identifier: String,
+    /// The version of the package
+    pub version: String,
+    /// The prefix the package installs to (binaries go in its `bin` dir)
+    pub install_location: String,
+    /// Binaries we'll be baking into the pkg
+    pub binaries: Vec<String>,
+    /// Script to run before installing
+    pub preinstall: Option<Utf8PathBuf>,
+    /// Script to run after installing
+    pub postinstall: Option<Utf8PathBuf>,
+    /// The identity to sign the pkg with (e.g. "Developer ID Installer: ...")
+    pub signing_identity: Option<String>,
+    /// Whether to notarize the pkg and staple the ticket to it
+    pub notarize: bool,
+}
+
+impl PkgInstallerInfo {
+    /// Build the pkg installer
+    ///
+    /// This stages the binaries in a payload root and hands it to `pkgbuild`,
+    /// which only exists on macOS.
+    pub fn build(&self) -> DistResult<()> {
+        info!("building a pkg: {}", self.file_path);
+
+        let temp = TempDir::new()?;
+        let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+        let (root, scripts_dir) = self.stage(&temp_path)?;
+
+        let mut cmd = Cmd::new("pkgbuild", "build a pkg");
+        cmd.stdout_to_stderr();
+        cmd.arg("--root").arg(&root);
+        cmd.arg("--identifier").arg(&self.identifier);
+        cmd.arg("--version").arg(&self.version);
+        cmd.arg("--install-location").arg(&self.install_location);
+        if let Some(scripts_dir) = &scripts_dir {
+            cmd.arg("--scripts").arg(scripts_dir);
+        }
+        if let Some(identity) = &self.signing_identity {
+            cmd.arg("--sign").arg(identity);
+        }
+        cmd.arg(&self.file_path);
+        cmd.run()?;
+
+        if self.notarize {
+            notarize(&self.file_path)?;
+            staple(&self.file_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lay out the payload root and install scripts for pkgbuild in the given dir
+    ///
+    /// Returns the root, and the dir of scripts if there are any.
+    fn stage(&self, dir: &Utf8Path) -> DistResult<(Utf8PathBuf, Option<Utf8PathBuf>)> {
+        let root = dir.join("root");
+        let bin_dir = root.join("bin");
+        LocalAsset::create_dir_all(&bin_dir)?;
+        for binary in &self.binaries {
+            let dest = bin_dir.join(binary);
+            std::fs::copy(self.package_dir.join(binary), &dest)?;
+            set_mode(&dest, 0o755)?;
+        }
+
+        // pkgbuild wants the scripts in a dir, with these exact names
+        let scripts_dir = dir.join("scripts");
+        let scripts = [
+            ("preinstall", &self.preinstall),
+            ("postinstall", &self.postinstall),
+        ];
+        let mut has_scripts = false;
+        for (name, script) in scripts {
+            if let Some(script) = script {
+                LocalAsset::create_dir_all(&scripts_dir)?;
+                let dest = scripts_dir.join(name);
+                std::fs::copy(script, &dest)?;
+                set_mode(&dest, 0o755)?;
+                has_scripts = true;
+            }
+        }
+
+        Ok((root, has_scripts.then_some(scripts_dir)))
+    }
+}
+
+/// Compute the default identifier of a pkg for an app hosted on GitHub
+pub fn pkg_identifier(owner: &str, app_name: &str) -> String {
+    format!("io.github.{owner}.{app_name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use temp_dir::TempDir;
+
+    use super::{pkg_identifier, PkgInstallerInfo};
+
+    fn info(dir: &Utf8PathBuf) -> PkgInstallerInfo {
+        PkgInstallerInfo {
+            file_path: dir.join("axolotlsay.pkg"),
+            package_dir: dir.join("axolotlsay_pkg"),
+            identifier: pkg_identifier("axodotdev", "axolotlsay"),
+            version: "0.2.0".to_owned(),
+            install_location: "/usr/local".to_owned(),
+            binaries: vec!["axolotlsay".to_owned()],
+            preinstall: None,
+            postinstall: None,
+            signing_identity: None,
+            notarize: false,
+        }
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(
+            pkg_identifier("axodotdev", "axolotlsay"),
+            "io.github.axodotdev.axolotlsay"
+        );
+    }
+
+    #[test]
+    fn staging() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::from_path_buf(temp.path().to_owned()).unwrap();
+        let info = info(&dir);
+        std::fs::create_dir_all(&info.package_dir).unwrap();
+        std::fs::write(info.package_dir.join("axolotlsay"), "binary").unwrap();
+
+        let (root, scripts_dir) = info.stage(&dir.join("stage")).unwrap();
+        assert_eq!(root, dir.join("stage/root"));
+        let binary = std::fs::read_to_string(root.join("bin/axolotlsay")).unwrap();
+        assert_eq!(binary, "binary");
+        // No scripts means no scripts dir, or pkgbuild complains
+        assert_eq!(scripts_dir, None);
+        assert!(!dir.join("stage/scripts").exists());
+    }
+
+    #[test]
+    fn staging_scripts() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::from_path_buf(temp.path().to_owned()).unwrap();
+        let info = PkgInstallerInfo {
+            postinstall: Some(dir.join("after-install.sh")),
+            ..info(&dir)
+        };
+        std::fs::create_dir_all(&info.package_dir).unwrap();
+        std::fs::write(info.package_dir.join("axolotlsay"), "binary").unwrap();
+        std::fs::write(dir.join("after-install.sh"), "#!/bin/sh").unwrap();
+
+        let (_, scripts_dir) = info.stage(&dir.join("stage")).unwrap();
+        let scripts_dir = scripts_dir.unwrap();
+        // pkgbuild only runs scripts with these exact names
+        assert!(scripts_dir.join("postinstall").exists());
+        assert!(!scripts_dir.join("preinstall").exists());
+        assert!(!scripts_dir.join("after-install.sh").exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(scripts_dir.join("postinstall"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+    }
+}
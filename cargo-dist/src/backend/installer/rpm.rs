@@ -0,0 +1,256 @@
+//! rpm installer
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use temp_dir::TempDir;
+use tracing::info;
+
+use super::linux::{package_files, PackageFile, PackageLayout};
+use crate::{errors::*, tasks::StaticAssetKind, SortedSet};
+
+/// Info needed to build an rpm
+#[derive(Debug, Clone)]
+pub struct RpmInstallerInfo {
+    /// Final file path of the rpm
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries are copied to before packaging
+    pub package_dir: Utf8PathBuf,
+    /// The name of the package
+    pub package: String,
+    /// The rpm version of the package
+    pub version: String,
+    /// The rpm architecture of the package (e.g. "x86_64")
+    pub arch: String,
+    /// The Packager field, from the first entry of "authors"
+    pub packager: Option<String>,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// The license of the app
+    pub license: Option<String>,
+    /// The app's homepage
+    pub homepage: Option<String>,
+    /// Binaries we'll be baking into the rpm
+    pub binaries: Vec<String>,
+    /// Static assets to install alongside the binaries (docs, man pages, completions...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+    /// The GPG key to sign the rpm with, if any
+    pub signing_key: Option<String>,
+}
+
+impl RpmInstallerInfo {
+    /// Build the rpm installer
+    ///
+    /// This generates a spec file and hands it to `rpmbuild`, which also works out what
+    /// the package Requires from the libraries the binaries link.
+    pub fn build(&self) -> DistResult<()> {
+        info!("building an rpm: {}", self.file_path);
+
+        let layout = PackageLayout {
+            package: &self.package,
+            zsh_completions_dir: "usr/share/zsh/site-functions",
+            license_dir: Some(format!("usr/share/licenses/{}", self.package)),
+        };
+        let files = package_files(
+            &layout,
+            &self.package_dir,
+            &self.binaries,
+            &self.static_assets,
+        )?;
+
+        // Stage the files where the spec's %install can copy them from
+        let temp = TempDir::new()?;
+        let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+        let root = temp_path.join("root");
+        for file in &files {
+            let dest = root.join(&file.path);
+            if let Some(parent) = dest.parent() {
+                LocalAsset::create_dir_all(parent)?;
+            }
+            std::fs::write(&dest, &file.contents)?;
+        }
+        let spec_path = temp_path.join(format!("{}.spec", self.package));
+        LocalAsset::write_new(&self.spec_file(&layout, &files, &root), &spec_path)?;
+
+        let out_dir = self.file_path.parent().expect("rpm had no parent dir!?");
+        let file_name = self.file_path.file_name().expect("rpm had no file name!?");
+        let mut cmd = Cmd::new("rpmbuild", "build an rpm");
+        cmd.stdout_to_stderr();
+        cmd.arg("-bb")
+            .arg("--target")
+            .arg(&self.arch)
+            .arg("--define")
+            .arg(format!("_topdir {}", temp_path.join("rpmbuild")))
+            .arg("--define")
+            .arg(format!("_rpmdir {out_dir}"))
+            .arg("--define")
+            .arg(format!("_build_name_fmt {file_name}"))
+            .arg(&spec_path);
+        cmd.run()?;
+
+        if let Some(key) = &self.signing_key {
+            info!("signing {file_name} with {key}");
+            let mut cmd = Cmd::new("rpmsign", "sign an rpm");
+            cmd.stdout_to_stderr();
+            cmd.arg("--addsign")
+                .arg("--define")
+                .arg(format!("_gpg_name {key}"))
+                .arg(&self.file_path);
+            cmd.run()?;
+        }
+
+        Ok(())
+    }
+
+    /// Compute the spec file that packages these staged files
+    fn spec_file(&self, layout: &PackageLayout, files: &[PackageFile], root: &Utf8Path) -> String {
+        let desc = self.desc.as_deref().unwrap_or(&self.package).trim();
+        let summary = desc.lines().next().unwrap_or_default();
+        let license = self.license.as_deref().unwrap_or("Unknown");
+
+        let mut spec = String::new();
+        // The binaries are already built (and stripped, if you asked for that)
+        spec.push_str("%global debug_package %{nil}\n");
+        spec.push_str("%global __os_install_post %{nil}\n\n");
+        spec.push_str(&format!("Name: {}\n", self.package));
+        spec.push_str(&format!("Version: {}\n", self.version));
+        spec.push_str("Release: 1\n");
+        spec.push_str(&format!("Summary: {}\n", spec_escape(summary)));
+        spec.push_str(&format!("License: {}\n", spec_escape(license)));
+        if let Some(homepage) = &self.homepage {
+            spec.push_str(&format!("URL: {}\n", spec_escape(homepage)));
+        }
+        if let Some(packager) = &self.packager {
+            spec.push_str(&format!("Packager: {}\n", spec_escape(packager)));
+        }
+        spec.push_str(&format!("\n%description\n{}\n", spec_escape(desc)));
+        spec.push_str(&format!("\n%install\ncp -a \"{root}/.\" %{{buildroot}}/\n"));
+
+        spec.push_str("\n%files\n%defattr(-,root,root,-)\n");
+        // The package's own doc dirs should go away when it's uninstalled
+        let owned_dirs = [Some(layout.doc_dir()), layout.license_dir.clone()];
+        let mut dirs = SortedSet::new();
+        for file in files {
+            let mut path = Utf8Path::new(&file.path);
+            while let Some(parent) = path.parent() {
+                if owned_dirs
+                    .iter()
+                    .flatten()
+                    .any(|owned| parent.starts_with(owned))
+                {
+                    dirs.insert(parent.to_string());
+                }
+                path = parent;
+            }
+        }
+        for dir in dirs {
+            spec.push_str(&format!("%dir \"/{}\"\n", spec_escape(&dir)));
+        }
+        for file in files {
+            spec.push_str(&format!(
+                "%attr({:o},root,root) \"/{}\"\n",
+                file.mode,
+                spec_escape(&file.path)
+            ));
+        }
+        spec
+    }
+}
+
+/// Get the rpm architecture for a target triple, if we can make rpms for it
+pub fn rpm_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    match arch {
+        "x86_64" => Some("x86_64"),
+        "aarch64" => Some("aarch64"),
+        _ => None,
+    }
+}
+
+/// Keep rpm from expanding macros in text we put in a spec file
+fn spec_escape(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::{Utf8Path, Utf8PathBuf};
+
+    use super::{rpm_architecture, RpmInstallerInfo};
+    use crate::backend::installer::linux::{PackageFile, PackageLayout};
+
+    #[test]
+    fn architectures() {
+        assert_eq!(rpm_architecture("x86_64-unknown-linux-gnu"), Some("x86_64"));
+        assert_eq!(
+            rpm_architecture("aarch64-unknown-linux-musl"),
+            Some("aarch64")
+        );
+        assert_eq!(rpm_architecture("armv7-unknown-linux-gnueabihf"), None);
+    }
+
+    #[test]
+    fn spec_file() {
+        let info = RpmInstallerInfo {
+            file_path: Utf8PathBuf::from("target/distrib/axolotlsay.rpm"),
+            package_dir: Utf8PathBuf::from("target/distrib/axolotlsay_rpm"),
+            package: "axolotlsay".to_owned(),
+            version: "0.2.0~rc.1".to_owned(),
+            arch: "x86_64".to_owned(),
+            packager: Some("Axo Developer Co <hello@axo.dev>".to_owned()),
+            desc: Some("Says hi with 100% more axolotl\n\nIt's great".to_owned()),
+            license: None,
+            homepage: Some("https://github.com/axodotdev/axolotlsay".to_owned()),
+            binaries: vec!["axolotlsay".to_owned()],
+            static_assets: vec![],
+            signing_key: None,
+        };
+        let layout = PackageLayout {
+            package: "axolotlsay",
+            zsh_completions_dir: "usr/share/zsh/site-functions",
+            license_dir: Some("usr/share/licenses/axolotlsay".to_owned()),
+        };
+        let file = |path: &str, mode| PackageFile {
+            path: path.to_owned(),
+            contents: vec![],
+            mode,
+        };
+        let files = [
+            file("usr/bin/axolotlsay", 0o755),
+            file("usr/share/doc/axolotlsay/README.md", 0o644),
+            file("usr/share/licenses/axolotlsay/LICENSE", 0o644),
+        ];
+
+        let spec = info.spec_file(&layout, &files, Utf8Path::new("/tmp/root"));
+        assert_eq!(
+            spec,
+            r#"%global debug_package %{nil}
+%global __os_install_post %{nil}
+
+Name: axolotlsay
+Version: 0.2.0~rc.1
+Release: 1
+Summary: Says hi with 100%% more axolotl
+License: Unknown
+URL: https://github.com/axodotdev/axolotlsay
+Packager: Axo Developer Co <hello@axo.dev>
+
+%description
+Says hi with 100%% more axolotl
+
+It's great
+
+%install
+cp -a "/tmp/root/." %{buildroot}/
+
+%files
+%defattr(-,root,root,-)
+%dir "/usr/share/doc/axolotlsay"
+%dir "/usr/share/licenses/axolotlsay"
+%attr(755,root,root) "/usr/bin/axolotlsay"
+%attr(644,root,root) "/usr/share/doc/axolotlsay/README.md"
+%attr(644,root,root) "/usr/share/licenses/axolotlsay/LICENSE"
+"#
+        );
+    }
+}
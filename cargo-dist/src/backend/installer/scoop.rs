@@ -0,0 +1,181 @@
+//! Code for generating a Scoop manifest
+
+use axoasset::LocalAsset;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo};
+use crate::{config::ChecksumStyle, errors::DistResult, SortedMap};
+
+/// Info about a Scoop manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoopInstallerInfo {
+    /// A brief description of the application
+    pub desc: Option<String>,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// The application's license
+    pub license: Option<String>,
+    /// The GitHub repository to check for new versions, if it's hosted there
+    pub github_repo_url: Option<String>,
+    /// Binaries to add shims for
+    pub binaries: Vec<String>,
+    /// The archive to fetch for each architecture
+    pub sources: Vec<ScoopSource>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// An archive the manifest fetches for an architecture
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoopSource {
+    /// The Scoop architecture (e.g. "64bit")
+    pub arch: String,
+    /// The archive
+    pub artifact: ExecutableZipFragment,
+    /// The dir the archive unpacks to, if it has one
+    pub root: Option<String>,
+}
+
+/// The JSON Scoop reads, see <https://github.com/ScoopInstaller/Scoop/wiki/App-Manifests>
+#[derive(Serialize)]
+struct ScoopManifest {
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+    architecture: SortedMap<String, ScoopArchitecture>,
+    bin: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checkver: Option<ScoopCheckver>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autoupdate: Option<ScoopAutoupdate>,
+}
+
+#[derive(Serialize)]
+struct ScoopArchitecture {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extract_dir: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ScoopCheckver {
+    github: String,
+}
+
+#[derive(Serialize)]
+struct ScoopAutoupdate {
+    architecture: SortedMap<String, ScoopArchitecture>,
+}
+
+pub(crate) fn write_scoop_manifest(
+    source_info: &ScoopInstallerInfo,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let info = source_info;
+    let version = &info.inner.app_version;
+    let url = |source: &ScoopSource| format!("{}/{}", info.inner.base_url, source.artifact.id);
+
+    // Grab checksums
+    let checksum_key = ChecksumStyle::Sha256.ext();
+    let architecture = info
+        .sources
+        .iter()
+        .map(|source| {
+            let hash = manifest
+                .artifacts
+                .get(&source.artifact.id)
+                .and_then(|a| a.checksums.get(checksum_key))
+                .cloned();
+            let arch = ScoopArchitecture {
+                url: url(source),
+                hash,
+                extract_dir: source.root.clone(),
+            };
+            (source.arch.clone(), arch)
+        })
+        .collect();
+
+    // Scoop's autoupdate fills in $version and recomputes the hashes itself
+    let (checkver, autoupdate) = if let Some(github) = &info.github_repo_url {
+        let architecture = info
+            .sources
+            .iter()
+            .map(|source| {
+                let arch = ScoopArchitecture {
+                    url: url(source).replace(version.as_str(), "$version"),
+                    hash: None,
+                    extract_dir: source
+                        .root
+                        .as_ref()
+                        .map(|root| root.replace(version.as_str(), "$version")),
+                };
+                (source.arch.clone(), arch)
+            })
+            .collect();
+        (
+            Some(ScoopCheckver {
+                github: github.clone(),
+            }),
+            Some(ScoopAutoupdate { architecture }),
+        )
+    } else {
+        (None, None)
+    };
+
+    let scoop = ScoopManifest {
+        version: version.clone(),
+        // Descriptions are only one line
+        description: info
+            .desc
+            .as_deref()
+            .and_then(|desc| desc.trim().lines().next())
+            .map(|desc| desc.to_owned()),
+        homepage: info.homepage.clone(),
+        license: info.license.clone(),
+        architecture,
+        bin: info.binaries.clone(),
+        checkver,
+        autoupdate,
+    };
+    let mut contents = serde_json::to_string_pretty(&scoop).unwrap();
+    contents.push('\n');
+    LocalAsset::write_new(&contents, &info.inner.dest_path)?;
+    Ok(())
+}
+
+/// Get the Scoop architecture for a windows target triple, if Scoop supports it
+pub fn scoop_architecture(target: &str) -> Option<&'static str> {
+    if !target.contains("windows") {
+        None
+    } else if target.starts_with("x86_64") {
+        Some("64bit")
+    } else if target.starts_with("aarch64") {
+        Some("arm64")
+    } else if target.starts_with("i686") {
+        Some("32bit")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scoop_architecture;
+
+    #[test]
+    fn architectures() {
+        assert_eq!(scoop_architecture("x86_64-pc-windows-msvc"), Some("64bit"));
+        assert_eq!(scoop_architecture("x86_64-pc-windows-gnu"), Some("64bit"));
+        assert_eq!(scoop_architecture("aarch64-pc-windows-msvc"), Some("arm64"));
+        assert_eq!(scoop_architecture("i686-pc-windows-msvc"), Some("32bit"));
+        assert_eq!(scoop_architecture("x86_64-unknown-linux-gnu"), None);
+        assert_eq!(scoop_architecture("aarch64-apple-darwin"), None);
+    }
+}
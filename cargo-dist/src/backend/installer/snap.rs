@@ -0,0 +1,124 @@
+//! Code for generating a snapcraft.yaml
+
+use axoasset::LocalAsset;
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo};
+use crate::{
+    backend::templates::{Templates, TEMPLATE_INSTALLER_SNAPCRAFT},
+    errors::DistResult,
+};
+
+/// Info about a snapcraft.yaml
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapInstallerInfo {
+    /// The name of the snap
+    pub name: String,
+    /// The base snap providing the runtime (e.g. "core22")
+    pub base: String,
+    /// The grade of the snap ("stable" or "devel")
+    pub grade: String,
+    /// The confinement of the snap ("strict" or "classic")
+    pub confinement: String,
+    /// Interfaces the apps connect to
+    pub plugs: Vec<String>,
+    /// The Snap Store channel to release to
+    pub channel: String,
+    /// Binaries to expose as apps
+    pub binaries: Vec<String>,
+    /// The archive to fetch for each architecture
+    pub sources: Vec<SnapSource>,
+    /// A brief description of the app
+    pub desc: Option<String>,
+    /// The app's license, in SPDX format
+    pub license: Option<String>,
+    /// The app's homepage
+    pub homepage: Option<String>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// An archive the snap fetches for an architecture
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapSource {
+    /// The snap architecture (e.g. "amd64")
+    pub arch: String,
+    /// The archive
+    pub artifact: ExecutableZipFragment,
+}
+
+/// Everything the template needs, on top of the installer info
+#[derive(Serialize)]
+struct SnapContext<'a> {
+    #[serde(flatten)]
+    info: &'a SnapInstallerInfo,
+    summary: String,
+    description: String,
+}
+
+pub(crate) fn write_snapcraft_yaml(
+    templates: &Templates,
+    info: &SnapInstallerInfo,
+) -> DistResult<()> {
+    let desc = info.desc.as_deref().unwrap_or(&info.inner.app_name).trim();
+    // The store cuts summaries off at 78 characters
+    let summary = desc
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(78)
+        .collect();
+    let context = SnapContext {
+        info,
+        summary,
+        description: desc.to_owned(),
+    };
+
+    let snapcraft =
+        templates.render_file_to_clean_string(TEMPLATE_INSTALLER_SNAPCRAFT, &context)?;
+    LocalAsset::write_new(&snapcraft, &info.inner.dest_path)?;
+    Ok(())
+}
+
+/// Get the snap architecture for a target triple, if snaps can be built for it
+pub fn snap_architecture(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next().unwrap_or(target);
+    match arch {
+        "x86_64" => Some("amd64"),
+        "aarch64" => Some("arm64"),
+        "armv7" => Some("armhf"),
+        "powerpc64le" => Some("ppc64el"),
+        "s390x" => Some("s390x"),
+        "riscv64gc" => Some("riscv64"),
+        _ => None,
+    }
+}
+
+/// Compute the default name of the snap for an app
+///
+/// Snap names can only have lowercase letters, digits and hyphens.
+pub fn snap_name(app_name: &str) -> String {
+    app_name.to_lowercase().replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{snap_architecture, snap_name};
+
+    #[test]
+    fn architectures() {
+        assert_eq!(snap_architecture("x86_64-unknown-linux-gnu"), Some("amd64"));
+        assert_eq!(
+            snap_architecture("aarch64-unknown-linux-musl"),
+            Some("arm64")
+        );
+        assert_eq!(snap_architecture("i686-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(snap_name("axolotlsay"), "axolotlsay");
+        assert_eq!(snap_name("Cool_App"), "cool-app");
+    }
+}
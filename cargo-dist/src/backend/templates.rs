@@ -17,8 +17,24 @@ pub const TEMPLATE_INSTALLER_PS1: TemplateId = "installer/installer.ps1";
 pub const TEMPLATE_INSTALLER_SH: TemplateId = "installer/installer.sh";
 /// Template key for Homebrew formula
 pub const TEMPLATE_INSTALLER_RB: TemplateId = "installer/homebrew.rb";
+/// Template key for Homebrew cask
+pub const TEMPLATE_INSTALLER_CASK: TemplateId = "installer/cask.rb";
 /// Template key for the npm installer dir
 pub const TEMPLATE_INSTALLER_NPM: TemplateId = "installer/npm";
+/// Template key for AUR PKGBUILD
+pub const TEMPLATE_INSTALLER_PKGBUILD: TemplateId = "installer/PKGBUILD";
+/// Template key for Flatpak manifest
+pub const TEMPLATE_INSTALLER_FLATPAK: TemplateId = "installer/flatpak.yml";
+/// Template key for the AppStream metainfo that goes with a Flatpak
+pub const TEMPLATE_INSTALLER_METAINFO: TemplateId = "installer/flatpak.metainfo.xml";
+/// Template key for snapcraft.yaml
+pub const TEMPLATE_INSTALLER_SNAPCRAFT: TemplateId = "installer/snapcraft.yml";
+/// Template key for Nix flake
+pub const TEMPLATE_INSTALLER_FLAKE: TemplateId = "installer/flake.nix";
+/// Template key for the AppxManifest.xml in an MSIX package
+pub const TEMPLATE_INSTALLER_APPXMANIFEST: TemplateId = "installer/AppxManifest.xml";
+/// Template key for the chocolatey package dir
+pub const TEMPLATE_INSTALLER_CHOCOLATEY: TemplateId = "installer/chocolatey";
 /// Template key for the github ci.yml
 pub const TEMPLATE_CI_GITHUB: TemplateId = "ci/github_ci.yml";
 
@@ -311,8 +327,32 @@ mod test {
 
         templates.get_template_file(TEMPLATE_INSTALLER_SH).unwrap();
         templates.get_template_file(TEMPLATE_INSTALLER_RB).unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_CASK)
+            .unwrap();
         templates.get_template_file(TEMPLATE_INSTALLER_PS1).unwrap();
         templates.get_template_dir(TEMPLATE_INSTALLER_NPM).unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_PKGBUILD)
+            .unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_FLATPAK)
+            .unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_METAINFO)
+            .unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_SNAPCRAFT)
+            .unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_FLAKE)
+            .unwrap();
+        templates
+            .get_template_file(TEMPLATE_INSTALLER_APPXMANIFEST)
+            .unwrap();
+        templates
+            .get_template_dir(TEMPLATE_INSTALLER_CHOCOLATEY)
+            .unwrap();
 
         templates.get_template_file(TEMPLATE_CI_GITHUB).unwrap();
     }
@@ -1,13 +1,16 @@
 //! Compiling Things
 
 use axoproject::PackageId;
-use camino::Utf8PathBuf;
-use cargo_dist_schema::{AssetInfo, DistManifest};
-use tracing::info;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{AssetInfo, DistManifest, Linkage};
+use tracing::{info, warn};
 
 use crate::{
-    copy_file, linkage::determine_linkage, Binary, BinaryIdx, DistError, DistGraph, DistResult,
-    SortedMap, TargetTriple,
+    config::{LinkageBuildCheck, LinkageConfig},
+    copy_file,
+    linkage::{self, check_linkage_policy, BinaryToCheck, LinkageOptions},
+    sign::{codesign, notarize_binaries},
+    Binary, BinaryIdx, DistError, DistGraph, DistResult, SortedMap, SortedSet, TargetTriple,
 };
 
 pub mod cargo;
@@ -136,14 +139,16 @@ impl BuildExpectations {
     ///
     /// * checking src_path was set by found_bin
     /// * computing linkage for the binary
+    /// * signing (and notarizing) macOS binaries
     /// * copying the binary and symbols to their final homes
     ///
     /// In the future this may also include:
     ///
-    /// * code signing / hashing
+    /// * hashing
     /// * stripping
     pub fn process_bins(&self, dist: &DistGraph, manifest: &mut DistManifest) -> DistResult<()> {
         let mut missing = vec![];
+        let mut found = vec![];
         for (pkg_id, pkg) in &self.packages {
             for (bin_name, result_bin) in &pkg.binaries {
                 // If the src_path is missing, everything is bad
@@ -155,14 +160,27 @@ impl BuildExpectations {
                     missing.push((pkg_id.to_owned(), bin_name.to_owned()));
                     continue;
                 }
-                let bin = dist.binary(result_bin.idx);
+                found.push((result_bin, src_path));
+            }
+        }
+
+        // compute linkage for the binaries, all together so they share the lookups
+        self.compute_linkage(dist, manifest, &found)?;
 
-                // compute linkage for the binary
-                self.compute_linkage(dist, manifest, result_bin, &bin.target)?;
+        let mut signed = vec![];
+        for (result_bin, src_path) in found {
+            let bin = dist.binary(result_bin.idx);
 
-                // copy files to their final homes
-                self.copy_assets(result_bin, bin)?;
+            // sign macOS binaries before they get copied into anything
+            if let Some(identity) = &dist.macos_sign.identity {
+                if bin.target.contains("apple-darwin") && !self.fake {
+                    codesign(identity, src_path, true)?;
+                    signed.push((bin.target.clone(), src_path.to_owned()));
+                }
             }
+
+            // copy files to their final homes
+            self.copy_assets(result_bin, bin)?;
         }
 
         // FIXME: properly bulk these together instead of just returning the first
@@ -171,45 +189,84 @@ impl BuildExpectations {
             return Err(DistError::MissingBinaries { pkg_name, bin_name });
         }
 
+        // Submit all the signed binaries together, as each submission takes a while
+        if dist.macos_sign.notarize.unwrap_or(false) && !self.fake && !signed.is_empty() {
+            notarize_binaries(&signed)?;
+        }
+
         Ok(())
     }
 
-    // Compute the linkage info for this binary
+    // Compute the linkage info for these binaries
     fn compute_linkage(
         &self,
         dist: &DistGraph,
         manifest: &mut DistManifest,
-        src: &ExpectedBinary,
-        target: &TargetTriple,
+        found: &[(&ExpectedBinary, &Utf8Path)],
     ) -> DistResult<()> {
-        let src_path = src
-            .src_path
-            .as_ref()
-            .expect("bin src_path should have been checked by caller");
+        let binaries = found
+            .iter()
+            .map(|(src, src_path)| {
+                let bin = dist.binary(src.idx);
+                BinaryToCheck {
+                    id: bin.id.clone(),
+                    name: bin.name.clone(),
+                    path: src_path.to_path_buf(),
+                    target: bin.target.clone(),
+                    artifact_id: None,
+                    system: dist.system_id.clone(),
+                    checksums: SortedMap::new(),
+                    // The binary hasn't been copied next to what it ships with yet
+                    bundled_files: Some(bundled_files(dist, bin)),
+                }
+            })
+            .collect::<Vec<_>>();
 
-        // If we're faking it, don't run the linkage stuff
-        let linkage = if self.fake {
+        let check = dist.linkage.build_check.unwrap_or_default();
+        if self.fake {
+            // If we're faking it, don't run the linkage stuff
             // FIXME: fake this more interestingly!
-            let mut linkage = cargo_dist_schema::Linkage::default();
-            linkage.other.insert(cargo_dist_schema::Library {
-                path: "fakelib".to_owned(),
-                source: None,
-            });
-            linkage
+            for binary in &binaries {
+                let mut linkage = cargo_dist_schema::Linkage::default();
+                linkage
+                    .other
+                    .insert(cargo_dist_schema::Library::new("fakelib".to_owned()));
+                manifest.assets.insert(
+                    binary.id.clone(),
+                    AssetInfo {
+                        id: binary.id.clone(),
+                        name: binary.name.clone(),
+                        system: binary.system.clone(),
+                        linkage: Some(linkage),
+                        target_triples: vec![binary.target.clone()],
+                    },
+                );
+            }
         } else {
-            determine_linkage(src_path, target)?
-        };
-        let bin = dist.binary(src.idx);
-        manifest.assets.insert(
-            bin.id.clone(),
-            AssetInfo {
-                id: bin.id.clone(),
-                name: bin.name.clone(),
-                system: dist.system_id.clone(),
-                linkage: Some(linkage),
-                target_triples: vec![target.clone()],
-            },
-        );
+            // The linkage is always recorded, since installers like Homebrew formulae and
+            // debs derive their dependencies from it; build-check only decides what
+            // happens about the policy
+            let options = LinkageOptions {
+                config: dist.linkage.clone(),
+                allow_failures: check != LinkageBuildCheck::Error,
+                ..LinkageOptions::default()
+            };
+            linkage::compute_linkage(manifest, &binaries, &options)?;
+        }
+
+        for binary in &binaries {
+            let asset = &manifest.assets[&binary.id];
+            if let Some(linkage) = &asset.linkage {
+                // Catch policy violations now, rather than after the whole release is built
+                apply_build_check(
+                    check,
+                    &binary.name,
+                    &asset.target_triples,
+                    linkage,
+                    &dist.linkage,
+                )?;
+            }
+        }
         Ok(())
     }
 
@@ -238,3 +295,104 @@ impl BuildExpectations {
 fn package_id_string(id: Option<&PackageId>) -> String {
     id.map(ToString::to_string).unwrap_or_default()
 }
+
+/// The (lowercased) names of the files a binary ships alongside
+///
+/// A library only counts if it's next to the binary in every package the binary goes in.
+fn bundled_files(dist: &DistGraph, bin: &Binary) -> SortedSet<String> {
+    let mut bundled: Option<SortedSet<String>> = None;
+    for dest in &bin.copy_exe_to {
+        let Some(dir) = dest.parent() else {
+            continue;
+        };
+        for artifact in &dist.artifacts {
+            let Some(archive) = artifact.archive.as_ref().filter(|a| a.dir_path == dir) else {
+                continue;
+            };
+            let binaries = artifact.required_binaries.values().filter(|p| *p != dest);
+            let static_assets = archive.static_assets.iter().map(|(_, p)| p);
+            let files = binaries
+                .chain(static_assets)
+                .filter_map(|p| p.file_name())
+                .map(str::to_lowercase)
+                .collect::<SortedSet<_>>();
+            bundled = Some(match bundled {
+                Some(bundled) => bundled.intersection(&files).cloned().collect(),
+                None => files,
+            });
+        }
+    }
+    bundled.unwrap_or_default()
+}
+
+/// Check a freshly built binary's linkage against the linkage policy, failing or
+/// just warning as the build-check setting says
+fn apply_build_check(
+    check: LinkageBuildCheck,
+    name: &str,
+    target_triples: &[String],
+    linkage: &Linkage,
+    config: &LinkageConfig,
+) -> DistResult<()> {
+    if check == LinkageBuildCheck::Off {
+        return Ok(());
+    }
+    let violations = match check_linkage_policy(name, target_triples, linkage, config) {
+        Ok(violations) => violations,
+        Err(e) if check == LinkageBuildCheck::Warn => {
+            warn!("couldn't check the linkage policy for {name}: {e}");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    if check == LinkageBuildCheck::Warn {
+        for violation in &violations {
+            warn!("{violation}");
+        }
+    } else if !violations.is_empty() {
+        return Err(DistError::LinkageViolations { violations });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LinkageLibraryList;
+    use cargo_dist_schema::Library;
+
+    #[test]
+    fn build_check_modes() {
+        let mut config = LinkageConfig {
+            deny: vec![LinkageLibraryList {
+                targets: vec![],
+                libraries: vec!["libssl".to_owned()],
+            }],
+            ..LinkageConfig::default()
+        };
+        let mut linkage = Linkage::default();
+        linkage
+            .system
+            .insert(Library::new("/usr/lib/libssl.so.3".to_owned()));
+        let targets = ["x86_64-unknown-linux-gnu".to_owned()];
+        let check = |check, config: &LinkageConfig| {
+            apply_build_check(check, "app", &targets, &linkage, config)
+        };
+
+        assert!(check(LinkageBuildCheck::Off, &config).is_ok());
+        assert!(check(LinkageBuildCheck::Warn, &config).is_ok());
+        assert!(matches!(
+            check(LinkageBuildCheck::Error, &config),
+            Err(DistError::LinkageViolations { .. })
+        ));
+
+        // A policy that can't be applied is only a warning too
+        config.max_glibc = Some("latest".to_owned());
+        assert!(check(LinkageBuildCheck::Off, &config).is_ok());
+        assert!(check(LinkageBuildCheck::Warn, &config).is_ok());
+        assert!(matches!(
+            check(LinkageBuildCheck::Error, &config),
+            Err(DistError::InvalidGlibcVersion { .. })
+        ));
+    }
+}
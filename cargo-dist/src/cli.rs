@@ -304,12 +304,17 @@ pub struct GenerateCiArgs {
 }
 #[derive(Args, Clone, Debug)]
 pub struct LinkageArgs {
-    /// Print human-readable output
-    #[clap(long)]
+    /// How to print the report
+    ///
+    /// Defaults to human, unless --print-json is passed.
+    #[clap(long, value_enum)]
+    pub format: Option<LinkageFormat>,
+    /// Print human-readable output (deprecated in favour of --format human)
+    #[clap(long, conflicts_with_all = ["format", "print_json"])]
     #[clap(default_value_t = false)]
     pub print_output: bool,
-    /// Print output as JSON
-    #[clap(long)]
+    /// Print output as JSON (deprecated in favour of --format json)
+    #[clap(long, conflicts_with = "format")]
     #[clap(default_value_t = false)]
     pub print_json: bool,
     #[clap(long)]
@@ -317,8 +322,231 @@ pub struct LinkageArgs {
     #[clap(default_value = "")]
     pub artifacts: String,
     /// Read linkage data from JSON rather than parsing from binaries
+    ///
+    /// This can either be a dist manifest, or just an array of linkage reports.
     #[clap(long)]
     pub from_json: Option<String>,
+    /// How to group binaries in the human-readable output
+    #[clap(long, value_enum)]
+    #[clap(default_value_t = LinkageGroupBy::Binary)]
+    pub group_by: LinkageGroupBy,
+    /// Fail if a binary built for a musl target dynamically links anything
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub strict_static: bool,
+    /// Fail if a binary links a sanitizer or profiling runtime
+    ///
+    /// These mean the binary was built with instrumentation that doesn't belong in a release.
+    /// Without this, they're only reported as warnings.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub deny_instrumentation: bool,
+    /// Keep running, and re-check whenever the binaries change
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub watch: bool,
+    /// Draw tables with plain ascii characters instead of unicode
+    ///
+    /// This is the default if your terminal doesn't seem to support unicode.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub ascii: bool,
+    /// Resolve Linux libraries against this directory instead of the host system
+    ///
+    /// Use this when checking binaries built for another system, e.g. pointing at the
+    /// root filesystem of the distro they'll run on, so libraries are attributed to the
+    /// packages that provide them there.
+    #[clap(long)]
+    pub sysroot: Option<Utf8PathBuf>,
+    /// Only report binaries with warnings or policy violations, hiding the clean ones
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub only_problems: bool,
+    /// Look up the license of the package each library comes from
+    ///
+    /// This asks the package manager about every package, so it's slower.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub licenses: bool,
+    /// Instead of the full report, explain which binaries link this library, and how
+    ///
+    /// The library can be given as a path, a file name (libssl.so.3), or just its name
+    /// (libssl) to match any version of it. This is only printed in the human format.
+    #[clap(long)]
+    pub explain: Option<String>,
+    /// Check that each artifact matches its recorded checksum before checking its binaries
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub verify_checksums: bool,
+    /// Leave system libraries out of the human-readable report
+    ///
+    /// They're usually expected and uninteresting. The JSON output always includes them.
+    #[clap(long, overrides_with = "include_system")]
+    #[clap(default_value_t = false)]
+    pub exclude_system: bool,
+    /// Include system libraries in the human-readable report (the default)
+    #[clap(long, overrides_with = "exclude_system")]
+    #[clap(default_value_t = false)]
+    pub include_system: bool,
+    /// Also write the linkage of every binary to this file, as a baseline
+    ///
+    /// This is an array of linkage reports, which --from-json can read back in.
+    #[clap(long)]
+    pub write_baseline: Option<Utf8PathBuf>,
+    /// Guess which C libraries are statically linked in, from each binary's symbols
+    ///
+    /// This looks for symbols like SSL_* or sqlite3_*, so it can't see anything in
+    /// stripped binaries, and what it finds is only a guess.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub scan_symbols: bool,
+    /// Guess which libraries are loaded at runtime, from file names in each binary
+    ///
+    /// Libraries loaded with dlopen or LoadLibrary don't show up as linked, but they're
+    /// usually named by a string like "libfoo.so.1". This misses names built at runtime,
+    /// and what it finds is only a guess.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub scan_strings: bool,
+    /// What to do when a binary hasn't been built, or a target has no artifacts
+    #[clap(long, value_enum)]
+    #[clap(default_value_t = OnMissing::Warn)]
+    pub on_missing: OnMissing,
+    /// Instead of checking anything, report which tools the check can use on this machine
+    ///
+    /// This lists the external tools (like ldd and dpkg) with their versions, and the
+    /// built-in parsers used when they're missing, to explain why reports differ
+    /// between machines. Use --format json for machine-readable output.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub probe_tools: bool,
+    /// Instead of the full report, count how many libraries each binary links of each kind
+    ///
+    /// This prints one compact table, which makes it easy to spot the binary with far
+    /// more dependencies than the rest. With --format json, the counts are printed as JSON.
+    #[clap(long, conflicts_with = "explain")]
+    #[clap(default_value_t = false)]
+    pub histogram: bool,
+    /// Read more linkage policy from this JSON or TOML file
+    ///
+    /// The file has the same shape as [workspace.metadata.dist.linkage], and is merged
+    /// with it; the workspace's own settings take priority.
+    #[clap(long)]
+    pub policy: Option<Utf8PathBuf>,
+    /// Instead of the full report, compare two builds of the same binary
+    ///
+    /// This lists the libraries each build links that the other doesn't, and fails if
+    /// there are any. It's useful for catching a dependency that only the debug or
+    /// release profile pulls in.
+    ///
+    /// Give either the paths to two binaries, which are analyzed as built for --target
+    /// (or this machine), or the ids of two artifacts, to compare each binary they both
+    /// ship as built for each artifact's target.
+    #[clap(long, num_args = 2, value_names = ["BINARY", "OTHER_BINARY"])]
+    pub compare: Vec<Utf8PathBuf>,
+    /// Instead of checking anything, compare an old linkage report to a new one
+    ///
+    /// Both reports are JSON, like the ones --format=json or --write-baseline produce. This
+    /// lists the libraries each binary added, removed, or now links from a different path,
+    /// and fails if any binary links a library it didn't before.
+    #[clap(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    pub diff: Vec<Utf8PathBuf>,
+    /// Fail if a binary links a library that isn't bundled alongside it or part of the OS
+    ///
+    /// This is the same as setting require-bundled in [workspace.metadata.dist.linkage].
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub require_bundled: bool,
+    /// Also find the libraries each library loads, and show the whole dependency tree
+    ///
+    /// Libraries are followed as far as they can be found on this machine (or in the
+    /// --sysroot), so libraries only known by name end the tree.
+    #[clap(long)]
+    #[clap(default_value_t = false)]
+    pub deep: bool,
+    /// How many levels of libraries --deep follows before cutting the tree off
+    #[clap(long, requires = "deep")]
+    #[clap(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    #[clap(default_value_t = 8)]
+    pub max_depth: usize,
+}
+
+/// How to print the linkage report
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum LinkageFormat {
+    /// Human-readable tables, printed to stderr
+    Human,
+    /// The dist manifest containing the report, printed to stdout
+    Json,
+    /// Just the summary counts and the binaries with problems, as JSON printed to stdout
+    JsonSummary,
+    /// Markdown tables printed to stdout, e.g. for a GitHub job summary
+    Markdown,
+    /// Problems found as SARIF printed to stdout, e.g. for GitHub code scanning
+    Sarif,
+    /// A row for each library each binary links, as CSV printed to stdout
+    Csv,
+}
+
+impl LinkageFormat {
+    /// Convert the application version of this enum to the library version
+    pub fn to_lib(self) -> cargo_dist::linkage::LinkageFormat {
+        match self {
+            LinkageFormat::Human => cargo_dist::linkage::LinkageFormat::Human,
+            LinkageFormat::Json => cargo_dist::linkage::LinkageFormat::Json,
+            LinkageFormat::JsonSummary => cargo_dist::linkage::LinkageFormat::JsonSummary,
+            LinkageFormat::Markdown => cargo_dist::linkage::LinkageFormat::Markdown,
+            LinkageFormat::Sarif => cargo_dist::linkage::LinkageFormat::Sarif,
+            LinkageFormat::Csv => cargo_dist::linkage::LinkageFormat::Csv,
+        }
+    }
+}
+
+/// How to group binaries in the linkage report
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum LinkageGroupBy {
+    /// Print a table for each binary
+    Binary,
+    /// Print binaries grouped under the artifact that ships them
+    Artifact,
+    /// Print every artifact in the manifest, with the linkage of each of its assets
+    Manifest,
+    /// Print one deduplicated list of every library the whole release depends on
+    Footprint,
+}
+
+impl LinkageGroupBy {
+    /// Convert the application version of this enum to the library version
+    pub fn to_lib(self) -> cargo_dist::linkage::LinkageGroupBy {
+        match self {
+            LinkageGroupBy::Binary => cargo_dist::linkage::LinkageGroupBy::Binary,
+            LinkageGroupBy::Artifact => cargo_dist::linkage::LinkageGroupBy::Artifact,
+            LinkageGroupBy::Manifest => cargo_dist::linkage::LinkageGroupBy::Manifest,
+            LinkageGroupBy::Footprint => cargo_dist::linkage::LinkageGroupBy::Footprint,
+        }
+    }
+}
+
+/// What to do when a binary the linkage check expected is missing
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum OnMissing {
+    /// Quietly leave it out of the report
+    Skip,
+    /// Print a warning and leave it out of the report
+    Warn,
+    /// Fail the check
+    Error,
+}
+
+impl OnMissing {
+    /// Convert the application version of this enum to the library version
+    pub fn to_lib(self) -> cargo_dist::linkage::OnMissing {
+        match self {
+            OnMissing::Skip => cargo_dist::linkage::OnMissing::Skip,
+            OnMissing::Warn => cargo_dist::linkage::OnMissing::Warn,
+            OnMissing::Error => cargo_dist::linkage::OnMissing::Error,
+        }
+    }
 }
 
 #[derive(Args, Clone, Debug)]
@@ -351,8 +579,35 @@ pub enum InstallerStyle {
     Npm,
     /// Generates a Homebrew formula
     Homebrew,
+    /// Generates a Scoop manifest
+    Scoop,
     /// Generates an msi for each windows platform
     Msi,
+    /// Generates a deb for each linux platform
+    Deb,
+    /// Generates an rpm for each x86_64/aarch64 linux platform
+    Rpm,
+    /// Generates a PKGBUILD for the AUR
+    Aur,
+    /// Generates an Alpine apk for each musl linux platform
+    Apk,
+    /// Generates an AppImage for each linux platform
+    #[value(name = "appimage")]
+    AppImage,
+    /// Generates a Flatpak manifest and the files to submit it to Flathub
+    Flatpak,
+    /// Generates a snapcraft.yaml and builds a snap from it in CI
+    Snap,
+    /// Generates a Nix flake
+    Nix,
+    /// Generates a pkg for each macOS platform
+    Pkg,
+    /// Generates a dmg for each macOS platform
+    Dmg,
+    /// Generates an MSIX package for each Windows platform
+    Msix,
+    /// Generates a Chocolatey package
+    Chocolatey,
 }
 
 impl InstallerStyle {
@@ -363,7 +618,20 @@ impl InstallerStyle {
             InstallerStyle::Powershell => cargo_dist::config::InstallerStyle::Powershell,
             InstallerStyle::Npm => cargo_dist::config::InstallerStyle::Npm,
             InstallerStyle::Homebrew => cargo_dist::config::InstallerStyle::Homebrew,
+            InstallerStyle::Scoop => cargo_dist::config::InstallerStyle::Scoop,
             InstallerStyle::Msi => cargo_dist::config::InstallerStyle::Msi,
+            InstallerStyle::Deb => cargo_dist::config::InstallerStyle::Deb,
+            InstallerStyle::Rpm => cargo_dist::config::InstallerStyle::Rpm,
+            InstallerStyle::Aur => cargo_dist::config::InstallerStyle::Aur,
+            InstallerStyle::Apk => cargo_dist::config::InstallerStyle::Apk,
+            InstallerStyle::AppImage => cargo_dist::config::InstallerStyle::AppImage,
+            InstallerStyle::Flatpak => cargo_dist::config::InstallerStyle::Flatpak,
+            InstallerStyle::Snap => cargo_dist::config::InstallerStyle::Snap,
+            InstallerStyle::Nix => cargo_dist::config::InstallerStyle::Nix,
+            InstallerStyle::Pkg => cargo_dist::config::InstallerStyle::Pkg,
+            InstallerStyle::Dmg => cargo_dist::config::InstallerStyle::Dmg,
+            InstallerStyle::Msix => cargo_dist::config::InstallerStyle::Msix,
+            InstallerStyle::Chocolatey => cargo_dist::config::InstallerStyle::Chocolatey,
         }
     }
 }
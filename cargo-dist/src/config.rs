@@ -96,6 +96,17 @@ pub struct DistMetadata {
     pub tap: Option<String>,
     /// Customize the name of the Homebrew formula
     pub formula: Option<String>,
+    /// Whether to publish a Homebrew formula or a cask
+    ///
+    /// Casks install the package's dmg or pkg, so one of those installers must be enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homebrew_style: Option<HomebrewStyle>,
+    /// A Scoop bucket to push the Scoop manifest to, if built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    /// Settings for the AUR package
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aur: Option<AurConfig>,
 
     /// A set of packages to install before building
     #[serde(rename = "dependencies")]
@@ -337,6 +348,66 @@ pub struct DistMetadata {
     /// Whether to install an updater program alongside the software
     #[serde(skip_serializing_if = "Option::is_none")]
     pub install_updater: Option<bool>,
+
+    /// Settings for the linkage checker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkage: Option<LinkageConfig>,
+
+    /// Settings for rpm installers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<RpmConfig>,
+
+    /// Settings for the APT repository the "apt" publish job maintains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apt: Option<AptConfig>,
+
+    /// Settings for the YUM/DNF repository the "yum" publish job maintains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yum: Option<YumConfig>,
+
+    /// Settings for the Alpine repository the "apk" publish job maintains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apk: Option<ApkConfig>,
+
+    /// Settings for signing and notarizing macOS binaries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macos_sign: Option<MacosSignConfig>,
+
+    /// Settings for AppImages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appimage: Option<AppImageConfig>,
+
+    /// Settings for Flatpaks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flatpak: Option<FlatpakConfig>,
+
+    /// Settings for snaps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snap: Option<SnapConfig>,
+
+    /// Settings for Nix flakes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nix: Option<NixConfig>,
+
+    /// Settings for macOS pkgs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pkg: Option<PkgConfig>,
+
+    /// Settings for macOS dmgs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dmg: Option<DmgConfig>,
+
+    /// Settings for Windows msis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msi: Option<MsiConfig>,
+
+    /// Settings for Windows MSIX packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msix: Option<MsixConfig>,
+
+    /// Settings for Chocolatey packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chocolatey: Option<ChocolateyConfig>,
 }
 
 impl DistMetadata {
@@ -351,6 +422,9 @@ impl DistMetadata {
             installers: _,
             tap: _,
             formula: _,
+            homebrew_style: _,
+            bucket: _,
+            aur: _,
             system_dependencies: _,
             targets: _,
             include,
@@ -385,12 +459,56 @@ impl DistMetadata {
             github_custom_runners: _,
             tag_namespace: _,
             install_updater: _,
+            linkage,
+            rpm: _,
+            apt: _,
+            yum: _,
+            apk: _,
+            macos_sign: _,
+            appimage,
+            flatpak,
+            snap: _,
+            nix: _,
+            pkg,
+            dmg,
+            msi,
+            msix,
+            chocolatey: _,
         } = self;
         if let Some(include) = include {
             for include in include {
                 *include = base_path.join(&*include);
             }
         }
+        if let Some(policy) = linkage.as_mut().and_then(|linkage| linkage.policy.as_mut()) {
+            *policy = base_path.join(&*policy);
+        }
+        if let Some(icon) = appimage
+            .as_mut()
+            .and_then(|appimage| appimage.icon.as_mut())
+        {
+            *icon = base_path.join(&*icon);
+        }
+        if let Some(icon) = flatpak.as_mut().and_then(|flatpak| flatpak.icon.as_mut()) {
+            *icon = base_path.join(&*icon);
+        }
+        if let Some(pkg) = pkg {
+            let scripts = [&mut pkg.preinstall, &mut pkg.postinstall];
+            for script in scripts.into_iter().flatten() {
+                *script = base_path.join(&*script);
+            }
+        }
+        if let Some(background) = dmg.as_mut().and_then(|dmg| dmg.background.as_mut()) {
+            *background = base_path.join(&*background);
+        }
+        if let Some(fragments) = msi.as_mut().and_then(|msi| msi.fragments.as_mut()) {
+            for fragment in fragments {
+                *fragment = base_path.join(&*fragment);
+            }
+        }
+        if let Some(logo) = msix.as_mut().and_then(|msix| msix.logo.as_mut()) {
+            *logo = base_path.join(&*logo);
+        }
     }
 
     /// Merge a workspace config into a package config (self)
@@ -408,6 +526,9 @@ impl DistMetadata {
             installers,
             tap,
             formula,
+            homebrew_style,
+            bucket,
+            aur,
             system_dependencies,
             targets,
             include,
@@ -442,6 +563,21 @@ impl DistMetadata {
             github_custom_runners,
             tag_namespace,
             install_updater,
+            linkage,
+            rpm,
+            apt,
+            yum,
+            apk,
+            macos_sign,
+            appimage,
+            flatpak,
+            snap,
+            nix,
+            pkg,
+            dmg,
+            msi,
+            msix,
+            chocolatey,
         } = self;
 
         // Check for global settings on local packages
@@ -513,6 +649,24 @@ impl DistMetadata {
         if tag_namespace.is_some() {
             warn!("package.metadata.dist.tag-namespace is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if linkage.is_some() {
+            warn!("package.metadata.dist.linkage is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if rpm.is_some() {
+            warn!("package.metadata.dist.rpm is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if apt.is_some() {
+            warn!("package.metadata.dist.apt is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if yum.is_some() {
+            warn!("package.metadata.dist.yum is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if apk.is_some() {
+            warn!("package.metadata.dist.apk is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if macos_sign.is_some() {
+            warn!("package.metadata.dist.macos-sign is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
 
         // Merge non-global settings
         if installers.is_none() {
@@ -557,6 +711,42 @@ impl DistMetadata {
         if formula.is_none() {
             *formula = workspace_config.formula.clone();
         }
+        if homebrew_style.is_none() {
+            *homebrew_style = workspace_config.homebrew_style;
+        }
+        if bucket.is_none() {
+            *bucket = workspace_config.bucket.clone();
+        }
+        if aur.is_none() {
+            *aur = workspace_config.aur.clone();
+        }
+        if appimage.is_none() {
+            *appimage = workspace_config.appimage.clone();
+        }
+        if flatpak.is_none() {
+            *flatpak = workspace_config.flatpak.clone();
+        }
+        if snap.is_none() {
+            *snap = workspace_config.snap.clone();
+        }
+        if nix.is_none() {
+            *nix = workspace_config.nix.clone();
+        }
+        if pkg.is_none() {
+            *pkg = workspace_config.pkg.clone();
+        }
+        if dmg.is_none() {
+            *dmg = workspace_config.dmg.clone();
+        }
+        if msi.is_none() {
+            *msi = workspace_config.msi.clone();
+        }
+        if msix.is_none() {
+            *msix = workspace_config.msix.clone();
+        }
+        if chocolatey.is_none() {
+            *chocolatey = workspace_config.chocolatey.clone();
+        }
         if system_dependencies.is_none() {
             *system_dependencies = workspace_config.system_dependencies.clone();
         }
@@ -676,8 +866,35 @@ pub enum InstallerStyle {
     Npm,
     /// Generate a Homebrew formula that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
     Homebrew,
+    /// Generate a Scoop manifest that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
+    Scoop,
     /// Generate an msi installer that embeds the binary
     Msi,
+    /// Generate a Debian package that embeds the binary
+    Deb,
+    /// Generate an rpm package that embeds the binary
+    Rpm,
+    /// Generate an Alpine package that embeds the binary
+    Apk,
+    /// Generate an AUR PKGBUILD that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
+    Aur,
+    /// Generate an AppImage that embeds the binary
+    #[serde(rename = "appimage")]
+    AppImage,
+    /// Generate a Flatpak manifest that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
+    Flatpak,
+    /// Generate a snapcraft.yaml that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
+    Snap,
+    /// Generate a Nix flake that fetches from [`cargo_dist_schema::Release::artifact_download_url`][]
+    Nix,
+    /// Generate a macOS pkg that embeds the binary
+    Pkg,
+    /// Generate a macOS dmg that embeds the binary
+    Dmg,
+    /// Generate a Windows MSIX package that embeds the binary
+    Msix,
+    /// Generate a Chocolatey package that installs the Windows archive or msi
+    Chocolatey,
 }
 
 impl std::fmt::Display for InstallerStyle {
@@ -687,7 +904,20 @@ impl std::fmt::Display for InstallerStyle {
             InstallerStyle::Powershell => "powershell",
             InstallerStyle::Npm => "npm",
             InstallerStyle::Homebrew => "homebrew",
+            InstallerStyle::Scoop => "scoop",
             InstallerStyle::Msi => "msi",
+            InstallerStyle::Deb => "deb",
+            InstallerStyle::Rpm => "rpm",
+            InstallerStyle::Apk => "apk",
+            InstallerStyle::Aur => "aur",
+            InstallerStyle::AppImage => "appimage",
+            InstallerStyle::Flatpak => "flatpak",
+            InstallerStyle::Snap => "snap",
+            InstallerStyle::Nix => "nix",
+            InstallerStyle::Pkg => "pkg",
+            InstallerStyle::Dmg => "dmg",
+            InstallerStyle::Msix => "msix",
+            InstallerStyle::Chocolatey => "chocolatey",
         };
         string.fmt(f)
     }
@@ -719,6 +949,22 @@ impl std::fmt::Display for HostingStyle {
 pub enum PublishStyle {
     /// Publish a Homebrew formula to a tap repository
     Homebrew,
+    /// Publish debs to an APT repository
+    Apt,
+    /// Publish rpms to a YUM/DNF repository
+    Yum,
+    /// Publish a PKGBUILD to the AUR
+    Aur,
+    /// Publish Alpine packages to an APK repository
+    Apk,
+    /// Publish snaps to the Snap Store
+    Snap,
+    /// Commit Nix flakes to the repository
+    Nix,
+    /// Publish a Scoop manifest to a bucket repository
+    Scoop,
+    /// Push Chocolatey packages to a NuGet feed
+    Chocolatey,
     /// User-supplied value
     User(String),
 }
@@ -730,6 +976,22 @@ impl std::str::FromStr for PublishStyle {
             Ok(Self::User(slug.to_owned()))
         } else if s == "homebrew" {
             Ok(Self::Homebrew)
+        } else if s == "apt" {
+            Ok(Self::Apt)
+        } else if s == "yum" {
+            Ok(Self::Yum)
+        } else if s == "aur" {
+            Ok(Self::Aur)
+        } else if s == "apk" {
+            Ok(Self::Apk)
+        } else if s == "snap" {
+            Ok(Self::Snap)
+        } else if s == "nix" {
+            Ok(Self::Nix)
+        } else if s == "scoop" {
+            Ok(Self::Scoop)
+        } else if s == "chocolatey" {
+            Ok(Self::Chocolatey)
         } else {
             Err(DistError::UnrecognizedStyle {
                 style: s.to_owned(),
@@ -754,6 +1016,14 @@ impl std::fmt::Display for PublishStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PublishStyle::Homebrew => write!(f, "homebrew"),
+            PublishStyle::Apt => write!(f, "apt"),
+            PublishStyle::Yum => write!(f, "yum"),
+            PublishStyle::Aur => write!(f, "aur"),
+            PublishStyle::Apk => write!(f, "apk"),
+            PublishStyle::Snap => write!(f, "snap"),
+            PublishStyle::Nix => write!(f, "nix"),
+            PublishStyle::Scoop => write!(f, "scoop"),
+            PublishStyle::Chocolatey => write!(f, "chocolatey"),
             PublishStyle::User(s) => write!(f, "./{s}"),
         }
     }
@@ -1025,6 +1295,27 @@ impl ChecksumStyle {
     }
 }
 
+/// What kind of Homebrew package to publish
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HomebrewStyle {
+    /// A formula that installs the binaries from an archive
+    #[default]
+    Formula,
+    /// A cask that installs the binaries from a dmg or pkg
+    Cask,
+}
+
+impl std::fmt::Display for HomebrewStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            HomebrewStyle::Formula => "formula",
+            HomebrewStyle::Cask => "cask",
+        };
+        string.fmt(f)
+    }
+}
+
 /// Which style(s) of configuration to generate
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum GenerateMode {
@@ -1107,6 +1398,686 @@ impl SystemDependencies {
     }
 }
 
+/// Settings for rpm installers
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpmConfig {
+    /// The GPG key (id or user id) to sign rpm installers with
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+/// Settings for the APT repository the "apt" publish job maintains
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AptConfig {
+    /// A GitHub repository (owner/name) to host the APT repository on the gh-pages branch of
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// An S3 URL (s3://bucket/path) to host the APT repository at
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s3_bucket: Option<String>,
+    /// The GPG key (id or user id) to sign the repository's indices with
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+    /// The distribution users add to their sources (defaults to "stable")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suite: Option<String>,
+    /// The component packages are published in (defaults to "main")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+/// Settings for the YUM/DNF repository the "yum" publish job maintains
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct YumConfig {
+    /// A GitHub repository (owner/name) to host the YUM repository on the gh-pages branch of
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// An S3 URL (s3://bucket/path) to host the YUM repository at
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s3_bucket: Option<String>,
+    /// The URL users fetch the repository from, if it can't be worked out from where it's hosted
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The GPG key (id or user id) to sign the repository's metadata with
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+impl YumConfig {
+    /// The URL users fetch the repository from
+    ///
+    /// GitHub Pages and S3 both have predictable URLs, so this only needs to be set for
+    /// custom domains.
+    pub fn public_url(&self) -> Option<String> {
+        if let Some(url) = &self.url {
+            return Some(url.trim_end_matches('/').to_owned());
+        }
+        if let Some((owner, name)) = self.repository.as_deref().and_then(|r| r.split_once('/')) {
+            return Some(format!("https://{owner}.github.io/{name}"));
+        }
+        let bucket_path = self.s3_bucket.as_deref()?.strip_prefix("s3://")?;
+        let (bucket, path) = bucket_path.split_once('/').unwrap_or((bucket_path, ""));
+        let path = path.trim_end_matches('/');
+        if path.is_empty() {
+            Some(format!("https://{bucket}.s3.amazonaws.com"))
+        } else {
+            Some(format!("https://{bucket}.s3.amazonaws.com/{path}"))
+        }
+    }
+
+    /// The id of the repository in users' .repo files
+    pub fn repo_id(&self) -> Option<String> {
+        let location = self.repository.as_deref().or(self.s3_bucket.as_deref())?;
+        let name = location.trim_end_matches('/').rsplit('/').next()?;
+        Some(name.to_owned())
+    }
+}
+
+/// Settings for the Alpine repository the "apk" publish job maintains
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ApkConfig {
+    /// A GitHub repository (owner/name) to host the APK repository on the gh-pages branch of
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// An S3 URL (s3://bucket/path) to host the APK repository at
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s3_bucket: Option<String>,
+    /// The name of the RSA key to sign packages and the index with (e.g. "releases@example.com")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+/// Settings for signing and notarizing macOS binaries
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MacosSignConfig {
+    /// The identity to sign binaries and dmgs with (e.g. "Developer ID Application: Axo (TEAMID)")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// The identity to sign pkgs with (e.g. "Developer ID Installer: Axo (TEAMID)")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installer_identity: Option<String>,
+    /// Whether to notarize signed binaries, dmgs and pkgs (defaults to false)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notarize: Option<bool>,
+}
+
+/// Settings for the AUR package
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AurConfig {
+    /// The name of the AUR package (defaults to "{app-name}-bin")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+}
+
+/// Settings for AppImages
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AppImageConfig {
+    /// The icon to show for the AppImage (a .png or .svg)
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Utf8PathBuf>,
+    /// The desktop entry categories the app belongs in (defaults to "Utility")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    /// Whether the app runs in a terminal (defaults to true)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<bool>,
+}
+
+/// Settings for Flatpaks
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FlatpakConfig {
+    /// The Flatpak app id (defaults to `io.github.{owner}.{app}` on GitHub)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// The runtime the app runs on (defaults to "org.freedesktop.Platform")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// The version of the runtime (defaults to "23.08")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_version: Option<String>,
+    /// The SDK that goes with the runtime (defaults to "org.freedesktop.Sdk")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdk: Option<String>,
+    /// The icon to show for the app (a .png or .svg)
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Utf8PathBuf>,
+    /// The desktop entry categories the app belongs in (defaults to "Utility")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    /// Whether the app runs in a terminal (defaults to true)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<bool>,
+    /// Sandbox permissions to grant the app (e.g. "--share=network")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_args: Option<Vec<String>>,
+    /// Whether to also build a .flatpak bundle in CI
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<bool>,
+}
+
+/// Settings for snaps
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SnapConfig {
+    /// The name of the snap (defaults to the app name)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The base snap providing the runtime (defaults to "core22")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// The grade of the snap, "stable" or "devel" (defaults to "stable")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    /// The confinement of the snap, "strict" or "classic" (defaults to "strict")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confinement: Option<String>,
+    /// Interfaces the apps connect to (defaults to "home" and "network")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugs: Option<Vec<String>>,
+    /// The Snap Store channel to release to (defaults to "stable")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+/// Settings for Nix flakes
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NixConfig {
+    /// The dir of the repository to publish the flake to (defaults to the root)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    /// The nixpkgs the flake builds with (defaults to "github:NixOS/nixpkgs/nixos-unstable")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nixpkgs: Option<String>,
+}
+
+/// Settings for macOS pkgs
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PkgConfig {
+    /// The package identifier (defaults to `io.github.{owner}.{app}` on GitHub)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// The prefix to install to, binaries go in its `bin` dir (defaults to "/usr/local")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_location: Option<String>,
+    /// A script to run before installing
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preinstall: Option<Utf8PathBuf>,
+    /// A script to run after installing
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postinstall: Option<Utf8PathBuf>,
+    /// The identity to sign the pkg with (e.g. "Developer ID Installer: Axo (TEAMID)")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_identity: Option<String>,
+}
+
+/// Settings for macOS dmgs
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DmgConfig {
+    /// The name of the mounted volume (defaults to "{app} {version}")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_name: Option<String>,
+    /// An image to show behind the files when the dmg is opened
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Utf8PathBuf>,
+    /// Whether to add a symlink to /Applications to drag the files onto (defaults to true)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applications_symlink: Option<bool>,
+    /// The identity to sign the binaries and dmg with (e.g. "Developer ID Application: Axo (TEAMID)")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_identity: Option<String>,
+    /// Whether to notarize the dmg and staple the ticket to it (defaults to false)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notarize: Option<bool>,
+}
+
+/// Settings for Windows msis
+///
+/// These customize the main.wxs that `cargo dist generate` writes, so regenerate it after changing them
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MsiConfig {
+    /// Binaries to add Start Menu shortcuts for
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcuts: Option<Vec<String>>,
+    /// Which PATH to add the binaries to (defaults to the system's)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<MsiPathScope>,
+    /// Binaries to register as Windows services
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<MsiService>>,
+    /// Extra WiX source files to compile into the msi
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragments: Option<Vec<Utf8PathBuf>>,
+    /// Ids of ComponentGroups (usually from the fragments) to install with the binaries
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_groups: Option<Vec<String>>,
+    /// The UpgradeCode GUID, overriding the one persisted in package.metadata.wix
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upgrade_code: Option<String>,
+}
+
+/// Which PATH an msi adds its binaries to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MsiPathScope {
+    /// The system-wide PATH
+    #[default]
+    System,
+    /// The PATH of the user installing the msi
+    User,
+    /// Don't modify PATH
+    None,
+}
+
+/// A binary an msi registers as a Windows service
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MsiService {
+    /// The binary to run as the service
+    pub binary: String,
+    /// The service's name (defaults to the binary's)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The name shown in the Services app (defaults to the service's name)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// A description shown in the Services app
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Arguments to run the binary with
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+    /// When the service starts (defaults to automatically, at boot)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<MsiServiceStart>,
+}
+
+/// When an msi's service starts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MsiServiceStart {
+    /// At boot (and right after installing)
+    #[default]
+    Auto,
+    /// When something starts it
+    Demand,
+    /// Never
+    Disabled,
+}
+
+/// Settings for Windows MSIX packages
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MsixConfig {
+    /// The package's identity name (defaults to the app's name)
+    ///
+    /// For the Microsoft Store, use the one Partner Center reserved for the app.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_name: Option<String>,
+    /// The publisher's distinguished name (e.g. "CN=Axo Developer Co, O=Axo Developer Co, C=US")
+    ///
+    /// This has to match the subject of the certificate the package is signed with.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// The publisher's name shown to users (defaults to the first of the package's authors)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher_display_name: Option<String>,
+    /// The app's name shown to users (defaults to the app's name)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// A png to use for the package's logos
+    ///
+    /// Paths are relative to the Cargo.toml this is defined in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Utf8PathBuf>,
+    /// Whether to sign the package with the certificate in `MSIX_SIGNING_CERTIFICATE_FILE`
+    ///
+    /// Packages submitted to the Microsoft Store don't need this, as the Store signs them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign: Option<bool>,
+}
+
+/// Settings for Chocolatey packages
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChocolateyConfig {
+    /// The package's id (defaults to the app's name, lowercased)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
+    /// Whether the package installs the Windows archive or the msi (defaults to "zip")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installer: Option<ChocolateyInstaller>,
+    /// The feed to push the package to (defaults to "https://push.chocolatey.org/")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// What a Chocolatey package installs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChocolateyInstaller {
+    /// Unpack the Windows archive and shim its binaries
+    #[default]
+    Zip,
+    /// Run the msi (requires the msi installer)
+    Msi,
+}
+
+/// Settings for the linkage checker
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkageConfig {
+    /// Extra rules for classifying libraries, consulted in order before the built-in ones
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<LinkageRule>,
+    /// The newest glibc version binaries may require (e.g. "2.17")
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_glibc: Option<String>,
+    /// Extra libraries to warn about depending on, in addition to the built-in ones
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub advisories: Vec<LinkageAdvisory>,
+    /// Groups of targets whose binaries should all link the same libraries
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub consistent_targets: Vec<Vec<String>>,
+    /// Require every library a binary links to be bundled alongside it, or part of the OS
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_bundled: Option<bool>,
+    /// Libraries binaries may link; if any of these lists apply to a target, its binaries
+    /// may only link the libraries they name
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<LinkageLibraryList>,
+    /// Libraries binaries must not link
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<LinkageLibraryList>,
+    /// A file with more linkage policy, in the same shape as this table, to merge in
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<Utf8PathBuf>,
+    /// Where Homebrew is installed, if it's somewhere other than the usual places
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homebrew_prefix: Option<Utf8PathBuf>,
+    /// Fail if a Windows binary needs the Visual C++ Redistributable
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny_vc_redist: Option<bool>,
+    /// Targets whose binaries must be fully static, with no dynamic linkage at all
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub require_static: Vec<String>,
+    /// Exploit mitigations every binary must be built with, wherever they apply
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub require_hardening: Vec<HardeningFeature>,
+    /// Upload each build's linkage problems to GitHub code scanning, as SARIF
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_sarif: Option<bool>,
+    /// What `cargo dist build` does about each binary's linkage
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_check: Option<LinkageBuildCheck>,
+}
+
+impl LinkageConfig {
+    /// Merge in the policy from a JSON or TOML file shaped like this config
+    ///
+    /// Settings here take priority: our rules are checked before the file's, and our
+    /// max-glibc wins if both set one. A `policy` inside the file isn't followed.
+    pub fn merge_policy_file(&mut self, path: &Utf8Path) -> DistResult<()> {
+        let file = SourceFile::load_local(path)?;
+        let policy: LinkageConfig = if path.extension() == Some("json") {
+            file.deserialize_json()?
+        } else {
+            file.deserialize_toml()?
+        };
+        let LinkageConfig {
+            rules,
+            max_glibc,
+            advisories,
+            consistent_targets,
+            require_bundled,
+            allow,
+            deny,
+            policy: _,
+            homebrew_prefix,
+            deny_vc_redist,
+            require_static,
+            require_hardening,
+            upload_sarif,
+            build_check,
+        } = policy;
+        self.rules.extend(rules);
+        self.max_glibc = self.max_glibc.take().or(max_glibc);
+        self.advisories.extend(advisories);
+        self.consistent_targets.extend(consistent_targets);
+        self.require_bundled = self.require_bundled.or(require_bundled);
+        self.allow.extend(allow);
+        self.deny.extend(deny);
+        self.homebrew_prefix = self.homebrew_prefix.take().or(homebrew_prefix);
+        self.deny_vc_redist = self.deny_vc_redist.or(deny_vc_redist);
+        self.require_static.extend(require_static);
+        self.require_hardening.extend(require_hardening);
+        self.upload_sarif = self.upload_sarif.or(upload_sarif);
+        self.build_check = self.build_check.or(build_check);
+        Ok(())
+    }
+}
+
+/// A library that binaries probably shouldn't depend on, and why
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkageAdvisory {
+    /// The library's name, without directories or version suffixes (e.g. "libssl")
+    pub library: String,
+    /// The versions to warn about (e.g. "1.1" also matches "1.1.1"); all versions if empty
+    #[serde(default)]
+    pub versions: Vec<String>,
+    /// What's wrong with depending on the library
+    pub message: String,
+}
+
+/// A list of libraries to allow or deny, and the targets it applies to
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkageLibraryList {
+    /// The targets the list applies to; all targets if empty
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// The libraries' names, without directories or version suffixes (e.g. "libssl")
+    pub libraries: Vec<String>,
+}
+
+impl LinkageLibraryList {
+    /// Whether the list applies to a binary built for any of these targets
+    pub fn applies_to(&self, target_triples: &[String]) -> bool {
+        self.targets.is_empty() || target_triples.iter().any(|t| self.targets.contains(t))
+    }
+
+    /// Whether the list names a library, by its canonical name
+    pub fn contains(&self, name: &str) -> bool {
+        self.libraries.iter().any(|l| l.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A rule for classifying libraries by where they're installed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkageRule {
+    /// Libraries whose path starts with this prefix match the rule
+    pub prefix: String,
+    /// The category matching libraries are put in
+    pub category: LinkageCategory,
+}
+
+/// The categories the linkage checker sorts libraries into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkageCategory {
+    /// Libraries included with the operating system
+    System,
+    /// Libraries provided by the Homebrew package manager
+    Homebrew,
+    /// Public libraries not provided by the system and not managed by any package manager
+    PublicUnmanaged,
+    /// Frameworks, only used on macOS
+    Frameworks,
+    /// Runtime libraries linked in by the toolchain rather than any dependency
+    Toolchain,
+    /// Language runtimes (e.g. libpython), which tie a binary to one interpreter version
+    LanguageRuntime,
+    /// Display and GPU libraries (e.g. libX11 or libvulkan), which need a windowing system
+    Graphics,
+    /// Libraries which don't fall into any other categories
+    Other,
+}
+
+/// What `cargo dist build` does about each binary's linkage
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkageBuildCheck {
+    /// Check it, and fail the build if it breaks the linkage policy
+    #[default]
+    Error,
+    /// Check it, but only warn if it breaks the linkage policy (or can't be checked)
+    Warn,
+    /// Don't check it against the linkage policy (it's still recorded in the dist manifest)
+    Off,
+}
+
+/// The exploit mitigations the linkage checker can require binaries to be built with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HardeningFeature {
+    /// Position-independent executables, on Linux and macOS
+    Pie,
+    /// Full RELRO, on Linux
+    Relro,
+    /// A non-executable stack (DEP on Windows)
+    Nx,
+    /// Stack canaries, on Linux and macOS
+    StackCanary,
+    /// _FORTIFY_SOURCE, on Linux
+    Fortify,
+    /// ASLR, on Windows
+    Aslr,
+    /// High-entropy ASLR, on Windows
+    HighEntropyVa,
+    /// Control Flow Guard, on Windows
+    Cfg,
+}
+
+impl std::fmt::Display for HardeningFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            HardeningFeature::Pie => "pie",
+            HardeningFeature::Relro => "relro",
+            HardeningFeature::Nx => "nx",
+            HardeningFeature::StackCanary => "stack-canary",
+            HardeningFeature::Fortify => "fortify",
+            HardeningFeature::Aslr => "aslr",
+            HardeningFeature::HighEntropyVa => "high-entropy-va",
+            HardeningFeature::Cfg => "cfg",
+        };
+        string.fmt(f)
+    }
+}
+
 /// Represents a package from a system package manager
 // newtype wrapper to hang a manual derive impl off of
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -77,6 +77,26 @@ pub enum DistError {
         details: wix::Error,
     },
 
+    /// The main.wxs cargo-wix generated didn't look like we expected
+    #[error("Couldn't apply the msi settings to {package}'s main.wxs: couldn't find {anchor}")]
+    #[diagnostic(help("cargo-wix may have changed its template; please file an issue"))]
+    WixCustomize {
+        /// The package
+        package: String,
+        /// The bit of main.wxs we looked for
+        anchor: String,
+    },
+
+    /// msi.upgrade-code wasn't a GUID
+    #[error("msi.upgrade-code for {package} isn't a valid GUID: {upgrade_code}")]
+    #[diagnostic(help("it should look like B36177BE-EA4D-44FB-B05C-EDDABDAA95CA"))]
+    InvalidUpgradeCode {
+        /// The package
+        package: String,
+        /// The value we got
+        upgrade_code: String,
+    },
+
     /// Error parsing metadata in Cargo.toml (json because it's from cargo-metadata)
     #[error("Malformed metadata.dist in {manifest_path}")]
     #[diagnostic(help("you can find a reference for the configuration schema at https://opensource.axo.dev/cargo-dist/book/reference/config.html"))]
@@ -223,17 +243,220 @@ pub enum DistError {
         /// Name of the msi
         style: String,
     },
-    /// Linkage report can't be run for this combination of OS and target
-    #[error("unable to run linkage report for {target} on {host}")]
-    LinkageCheckInvalidOS {
-        /// The OS the check was run on
-        host: String,
-        /// The OS being checked
-        target: String,
-    },
     /// Linkage report can't be run for this target
     #[error("unable to run linkage report for this type of binary")]
     LinkageCheckUnsupportedBinary {},
+    /// Linkage report couldn't parse a wasm module or component
+    #[error("unable to parse {path} as WebAssembly: {details}")]
+    LinkageCheckMalformedWasm {
+        /// The module or component we tried to parse
+        path: Utf8PathBuf,
+        /// What went wrong
+        details: String,
+    },
+    /// Linkage report was asked to check a binary that isn't in its target's format
+    #[error("expected {expected} for target {target}, but the file magic of {path} is {found}")]
+    #[diagnostic(help("make sure the binary was actually built for {target}"))]
+    LinkageCheckFormatMismatch {
+        /// The binary we tried to check
+        path: Utf8PathBuf,
+        /// The target it was supposed to be built for
+        target: String,
+        /// The format binaries for that target use
+        expected: &'static str,
+        /// The format the binary is actually in
+        found: &'static str,
+    },
+    /// A binary (or the archive it was packaged in) doesn't match its recorded checksum
+    #[error("{path} doesn't match its recorded {algorithm} checksum")]
+    #[diagnostic(help(
+        "expected {expected}, but got {actual}; rebuild or re-download it so you're checking the binary you released"
+    ))]
+    LinkageChecksumMismatch {
+        /// The file that was checked
+        path: Utf8PathBuf,
+        /// The checksum algorithm
+        algorithm: String,
+        /// The recorded checksum
+        expected: String,
+        /// The file's actual checksum
+        actual: String,
+    },
+    /// A binary was expected to be fully static but isn't, either because it's for a musl
+    /// target or because the linkage config requires it
+    #[error("{binary} was built for {target} but is not statically linked")]
+    #[diagnostic(help("it dynamically links against: {libraries}"))]
+    LinkageNotStatic {
+        /// Name of the binary
+        binary: String,
+        /// The target it was built for
+        target: String,
+        /// The libraries it links against
+        libraries: String,
+    },
+    /// A binary links libraries that the linkage deny list forbids
+    #[error("{binary} ({target}) links denied libraries: {libraries}")]
+    #[diagnostic(help("these are on a deny list in your linkage config"))]
+    LinkageDenied {
+        /// Name of the binary
+        binary: String,
+        /// The targets it was built for
+        target: String,
+        /// The denied libraries
+        libraries: String,
+    },
+    /// A binary links libraries that aren't on the linkage allow list for its target
+    #[error("{binary} ({target}) links libraries that aren't allowed: {libraries}")]
+    #[diagnostic(help(
+        "remove the dependency that pulls these in, or add them to an allow list for this target \
+        in your linkage config"
+    ))]
+    LinkageNotAllowed {
+        /// Name of the binary
+        binary: String,
+        /// The targets it was built for
+        target: String,
+        /// The libraries that aren't allowed
+        libraries: String,
+    },
+    /// A binary links libraries that aren't bundled with it, and don't come with the OS
+    #[error("{binary} links libraries that aren't bundled with it: {libraries}")]
+    #[diagnostic(help(
+        "users without these installed won't be able to run it; ship them alongside the binary"
+    ))]
+    LinkageNotBundled {
+        /// Name of the binary
+        binary: String,
+        /// The libraries that would be missing on a clean system
+        libraries: String,
+    },
+    /// A binary links sanitizer or profiling runtimes, which don't belong in a release
+    #[error("{binary} links instrumentation runtimes: {runtimes}")]
+    #[diagnostic(help(
+        "it was built with sanitizers or profiling enabled (e.g. -Z sanitizer=address); \
+        drop those flags from your release build"
+    ))]
+    LinkageInstrumented {
+        /// Name of the binary
+        binary: String,
+        /// The runtimes it links
+        runtimes: String,
+    },
+    /// A Windows binary needs the Visual C++ Redistributable, and deny-vc-redist is set
+    #[error("{binary} needs the Visual C++ Redistributable for {dlls}")]
+    #[diagnostic(help(
+        "link the C runtime statically with -C target-feature=+crt-static, or ship the DLLs alongside the binary"
+    ))]
+    LinkageNeedsVcRedist {
+        /// Name of the binary
+        binary: String,
+        /// The runtime DLLs it needs
+        dlls: String,
+    },
+    /// A binary wasn't built with exploit mitigations that require-hardening asks for
+    #[error("{binary} wasn't built with {missing}")]
+    #[diagnostic(help(
+        "check the compiler and linker flags for its target, or drop these from require-hardening"
+    ))]
+    LinkageNotHardened {
+        /// Name of the binary
+        binary: String,
+        /// The mitigations it's missing
+        missing: String,
+    },
+    /// No artifact is built for a target we were asked to check the linkage of
+    #[error("No matching artifact for target {target}")]
+    LinkageNoArtifact {
+        /// The target
+        target: String,
+    },
+    /// A binary we were asked to check the linkage of hasn't been built
+    #[error("Binary {path} missing")]
+    #[diagnostic(help("run `cargo dist build` first"))]
+    LinkageBinaryMissing {
+        /// Where the binary should be
+        path: Utf8PathBuf,
+    },
+    /// A binary requires a newer glibc than the configured maximum
+    #[error("{binary} requires glibc {required}, but the maximum allowed is {max}")]
+    #[diagnostic(help("it uses {symbol}, so it won't run on systems with an older glibc"))]
+    LinkageGlibcTooNew {
+        /// Name of the binary
+        binary: String,
+        /// The glibc version it requires
+        required: String,
+        /// The configured maximum
+        max: String,
+        /// A symbol that requires the newer version
+        symbol: String,
+    },
+    /// A binary links different libraries on targets that were expected to match
+    #[error("{binary} links different libraries for {target} than for {other_target}")]
+    #[diagnostic(help(
+        "only {target} links: {only_target}\nonly {other_target} links: {only_other_target}"
+    ))]
+    LinkageInconsistent {
+        /// Name of the binary
+        binary: String,
+        /// The first target in the group the binary was built for
+        target: String,
+        /// The target that differs from it
+        other_target: String,
+        /// Libraries only the first target links
+        only_target: String,
+        /// Libraries only the other target links
+        only_other_target: String,
+    },
+    /// Two builds of the same binary link different libraries
+    #[error("{binary} and {other_binary} link different libraries")]
+    #[diagnostic(help("the libraries only one of them links are listed above"))]
+    LinkageBuildsDiffer {
+        /// The first build
+        binary: Utf8PathBuf,
+        /// The build it was compared to
+        other_binary: Utf8PathBuf,
+    },
+    /// --compare was given binaries to analyze, but more than one target to analyze them for
+    #[error("--compare can only analyze binaries for one target, but was given {targets}")]
+    #[diagnostic(help("pass the target both builds are for with --target"))]
+    LinkageCompareTargets {
+        /// The targets that were given
+        targets: String,
+    },
+    /// A newer linkage report links libraries an older one didn't
+    #[error("{new} links libraries that {old} didn't")]
+    #[diagnostic(help("the new libraries are listed above with a +"))]
+    LinkageLibrariesAdded {
+        /// The old report
+        old: Utf8PathBuf,
+        /// The new report
+        new: Utf8PathBuf,
+    },
+    /// A linkage mode was asked for an output format it can't print
+    #[error("{mode} can't print its output as {format}")]
+    #[diagnostic(help("pass --format {supported}"))]
+    LinkageFormatUnsupported {
+        /// The flag selecting the mode
+        mode: String,
+        /// The format that was asked for
+        format: String,
+        /// The formats the mode can print
+        supported: String,
+    },
+    /// max-glibc wasn't a version number
+    #[error("linkage.max-glibc = \"{version}\" isn't a valid glibc version")]
+    #[diagnostic(help("glibc versions look like \"2.17\""))]
+    InvalidGlibcVersion {
+        /// The value that was given
+        version: String,
+    },
+    /// One or more binaries failed the linkage checks
+    #[error("{} binaries failed linkage checks", violations.len())]
+    LinkageViolations {
+        /// The individual failures
+        #[related]
+        violations: Vec<DistError>,
+    },
 
     /// random i/o error
     #[error(transparent)]
@@ -296,6 +519,42 @@ pub enum DistError {
         extension: String,
     },
 
+    /// Credentials for notarizing were missing
+    #[error("{var} must be set to notarize {artifact}")]
+    #[diagnostic(help(
+        "notarizing needs an Apple ID (APPLE_ID), its team (APPLE_TEAM_ID) and an app-specific password (APPLE_APP_SPECIFIC_PASSWORD)"
+    ))]
+    MissingNotarizationCredentials {
+        /// The artifact being notarized
+        artifact: Utf8PathBuf,
+        /// The env var that wasn't set
+        var: String,
+    },
+
+    /// Notarizing was turned on without anything to sign with
+    #[error("{setting} is set, but Apple only notarizes signed {what}")]
+    #[diagnostic(help("set {identity} in Cargo.toml, or stop notarizing"))]
+    NotarizeWithoutIdentity {
+        /// The config that turned notarizing on
+        setting: String,
+        /// What would have been notarized
+        what: String,
+        /// The config that would give it an identity to sign with
+        identity: String,
+    },
+
+    /// The certificate for signing an MSIX package was missing
+    #[error("{var} must be set to sign {artifact}")]
+    #[diagnostic(help(
+        "signing needs a .pfx certificate (MSIX_SIGNING_CERTIFICATE_FILE) and its password (MSIX_SIGNING_CERTIFICATE_PASSWORD)"
+    ))]
+    MissingMsixCertificate {
+        /// The artifact being signed
+        artifact: Utf8PathBuf,
+        /// The env var that wasn't set
+        var: String,
+    },
+
     /// Binaries were missing
     #[error("failed to find bin {bin_name} for {pkg_name}")]
     #[diagnostic(help("did the above build fail?"))]
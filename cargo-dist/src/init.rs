@@ -230,6 +230,9 @@ fn get_new_dist_metadata(
             installers: None,
             tap: None,
             formula: None,
+            homebrew_style: None,
+            bucket: None,
+            aur: None,
             system_dependencies: None,
             targets: None,
             dist: None,
@@ -265,6 +268,21 @@ fn get_new_dist_metadata(
             github_custom_runners: None,
             tag_namespace: None,
             install_updater: None,
+            linkage: None,
+            rpm: None,
+            apt: None,
+            yum: None,
+            apk: None,
+            macos_sign: None,
+            appimage: None,
+            flatpak: None,
+            snap: None,
+            nix: None,
+            pkg: None,
+            dmg: None,
+            msi: None,
+            msix: None,
+            chocolatey: None,
         }
     };
 
@@ -550,11 +568,33 @@ fn get_new_dist_metadata(
                 InstallerStyle::Powershell,
                 InstallerStyle::Npm,
                 InstallerStyle::Homebrew,
+                InstallerStyle::Scoop,
                 InstallerStyle::Msi,
+                InstallerStyle::Deb,
+                InstallerStyle::Rpm,
+                InstallerStyle::Aur,
+                InstallerStyle::Apk,
+                InstallerStyle::AppImage,
+                InstallerStyle::Flatpak,
+                InstallerStyle::Snap,
+                InstallerStyle::Nix,
+                InstallerStyle::Pkg,
+                InstallerStyle::Dmg,
+                InstallerStyle::Msix,
+                InstallerStyle::Chocolatey,
             ]
         } else {
             eprintln!("{notice} no CI backends enabled, most installers have been hidden");
-            &[InstallerStyle::Msi]
+            &[
+                InstallerStyle::Msi,
+                InstallerStyle::Deb,
+                InstallerStyle::Rpm,
+                InstallerStyle::Apk,
+                InstallerStyle::AppImage,
+                InstallerStyle::Pkg,
+                InstallerStyle::Dmg,
+                InstallerStyle::Msix,
+            ]
         };
         let mut defaults = vec![];
         let mut keys = vec![];
@@ -578,7 +618,20 @@ fn get_new_dist_metadata(
                 InstallerStyle::Powershell => "powershell",
                 InstallerStyle::Npm => "npm",
                 InstallerStyle::Homebrew => "homebrew",
+                InstallerStyle::Scoop => "scoop",
                 InstallerStyle::Msi => "msi",
+                InstallerStyle::Deb => "deb",
+                InstallerStyle::Rpm => "rpm",
+                InstallerStyle::Aur => "aur",
+                InstallerStyle::Apk => "apk",
+                InstallerStyle::AppImage => "appimage",
+                InstallerStyle::Flatpak => "flatpak",
+                InstallerStyle::Snap => "snap",
+                InstallerStyle::Nix => "nix",
+                InstallerStyle::Pkg => "pkg",
+                InstallerStyle::Dmg => "dmg",
+                InstallerStyle::Msix => "msix",
+                InstallerStyle::Chocolatey => "chocolatey",
             });
         }
 
@@ -656,6 +709,100 @@ fn get_new_dist_metadata(
         }
     }
 
+    // Special handling of the Scoop installer
+    if meta
+        .installers
+        .as_deref()
+        .unwrap_or_default()
+        .contains(&InstallerStyle::Scoop)
+    {
+        let scoop_is_new = !orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Scoop);
+
+        if scoop_is_new {
+            let prompt = r#"you've enabled Scoop support; if you want cargo-dist
+    to automatically push package updates to a bucket (repository) for you,
+    please enter the bucket name (in GitHub owner/name format)"#;
+            let default = "".to_string();
+
+            let bucket: String = if args.yes {
+                default
+            } else {
+                let res = Input::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .allow_empty(true)
+                    .interact_text()?;
+                eprintln!();
+                res
+            };
+            let bucket = bucket.trim();
+            if bucket.is_empty() {
+                eprintln!("Scoop packages will not be automatically published");
+                meta.bucket = None;
+            } else {
+                meta.bucket = Some(bucket.to_owned());
+                publish_jobs.push(PublishStyle::Scoop);
+
+                eprintln!("{check} Scoop package will be published to {bucket}");
+
+                eprintln!(
+                    r#"{check} You must provision a GitHub token and expose it as a secret named
+    SCOOP_BUCKET_TOKEN in GitHub Actions. For more information,
+    see the documentation:
+    https://opensource.axo.dev/cargo-dist/book/installers/scoop.html"#
+                );
+            }
+        }
+    }
+
+    // Special handling of the Chocolatey installer
+    if meta
+        .installers
+        .as_deref()
+        .unwrap_or_default()
+        .contains(&InstallerStyle::Chocolatey)
+    {
+        let chocolatey_is_new = !orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Chocolatey);
+
+        if chocolatey_is_new && !publish_jobs.contains(&PublishStyle::Chocolatey) {
+            let prompt = r#"you've enabled Chocolatey support; do you want cargo-dist
+    to automatically push new packages to chocolatey.org for you?"#;
+            let default = false;
+
+            let publish = if args.yes {
+                default
+            } else {
+                let res = Confirm::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .default(default)
+                    .interact()?;
+                eprintln!();
+                res
+            };
+            if publish {
+                publish_jobs.push(PublishStyle::Chocolatey);
+
+                eprintln!("{check} Chocolatey packages will be pushed to chocolatey.org");
+
+                eprintln!(
+                    r#"{check} You must provision a Chocolatey API key and expose it as a secret named
+    CHOCOLATEY_API_KEY in GitHub Actions. For more information,
+    see the documentation:
+    https://opensource.axo.dev/cargo-dist/book/installers/chocolatey.html"#
+                );
+            } else {
+                eprintln!("Chocolatey packages will not be automatically published");
+            }
+        }
+    }
+
     meta.publish_jobs = if publish_jobs.is_empty() {
         None
     } else {
@@ -786,6 +933,9 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         installers,
         tap,
         formula,
+        homebrew_style,
+        bucket,
+        aur: _,
         system_dependencies: _,
         targets,
         include,
@@ -820,6 +970,21 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         extra_artifacts: _,
         github_custom_runners: _,
         install_updater,
+        linkage: _,
+        rpm: _,
+        apt: _,
+        yum: _,
+        apk: _,
+        macos_sign: _,
+        appimage: _,
+        flatpak: _,
+        snap: _,
+        nix: _,
+        pkg: _,
+        dmg: _,
+        msi: _,
+        msix: _,
+        chocolatey: _,
     } = &meta;
 
     apply_optional_value(
@@ -859,6 +1024,20 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         formula.clone(),
     );
 
+    apply_optional_value(
+        table,
+        "homebrew-style",
+        "# Whether to publish a Homebrew formula or a cask\n",
+        homebrew_style.map(|s| s.to_string()),
+    );
+
+    apply_optional_value(
+        table,
+        "bucket",
+        "# A GitHub repo to push Scoop manifests to\n",
+        bucket.clone(),
+    );
+
     apply_string_list(
         table,
         "targets",
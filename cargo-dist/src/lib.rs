@@ -16,7 +16,10 @@ use axoasset::{LocalAsset, RemoteAsset};
 use axoprocess::Cmd;
 use backend::{
     ci::CiInfo,
-    installer::{self, msi::MsiInstallerInfo, InstallerImpl},
+    installer::{
+        self, appimage::AppImageInstallerInfo, dmg::DmgInstallerInfo, msi::MsiInstallerInfo,
+        msix::MsixInstallerInfo, pkg::PkgInstallerInfo, rpm::RpmInstallerInfo, InstallerImpl,
+    },
 };
 use build::generic::{build_generic_target, run_extra_artifacts_build};
 use build::{
@@ -48,6 +51,7 @@ pub mod host;
 mod init;
 pub mod linkage;
 pub mod manifest;
+pub mod sign;
 pub mod tasks;
 #[cfg(test)]
 mod tests;
@@ -289,6 +293,18 @@ fn build_fake(
         BuildStep::GenerateInstaller(installer) => match installer {
             // MSI, unlike other installers, isn't safe to generate on any platform
             InstallerImpl::Msi(msi) => generate_fake_msi(dist_graph, msi, manifest)?,
+            // Nor is rpm, which needs rpmbuild
+            InstallerImpl::Rpm(rpm) => generate_fake_rpm(dist_graph, rpm, manifest)?,
+            // Or AppImage, which needs appimagetool
+            InstallerImpl::AppImage(appimage) => {
+                generate_fake_appimage(dist_graph, appimage, manifest)?
+            }
+            // Or pkg, which needs pkgbuild
+            InstallerImpl::Pkg(pkg) => generate_fake_pkg(dist_graph, pkg, manifest)?,
+            // Or dmg, which needs hdiutil
+            InstallerImpl::Dmg(dmg) => generate_fake_dmg(dist_graph, dmg, manifest)?,
+            // Or msix, which needs makeappx
+            InstallerImpl::Msix(msix) => generate_fake_msix(dist_graph, msix, manifest)?,
             _ => generate_installer(dist_graph, installer, manifest)?,
         },
         BuildStep::Checksum(ChecksumImpl {
@@ -341,6 +357,59 @@ fn generate_fake_msi(
     Ok(())
 }
 
+fn generate_fake_rpm(
+    _dist: &DistGraph,
+    rpm: &RpmInstallerInfo,
+    _manifest: &DistManifest,
+) -> Result<()> {
+    LocalAsset::write_new_all("", &rpm.file_path)?;
+
+    Ok(())
+}
+
+fn generate_fake_appimage(
+    _dist: &DistGraph,
+    appimage: &AppImageInstallerInfo,
+    _manifest: &DistManifest,
+) -> Result<()> {
+    LocalAsset::write_new_all("", &appimage.file_path)?;
+    if let Some(zsync_path) = &appimage.zsync_path {
+        LocalAsset::write_new_all("", zsync_path)?;
+    }
+
+    Ok(())
+}
+
+fn generate_fake_pkg(
+    _dist: &DistGraph,
+    pkg: &PkgInstallerInfo,
+    _manifest: &DistManifest,
+) -> Result<()> {
+    LocalAsset::write_new_all("", &pkg.file_path)?;
+
+    Ok(())
+}
+
+fn generate_fake_dmg(
+    _dist: &DistGraph,
+    dmg: &DmgInstallerInfo,
+    _manifest: &DistManifest,
+) -> Result<()> {
+    LocalAsset::write_new_all("", &dmg.file_path)?;
+
+    Ok(())
+}
+
+fn generate_fake_msix(
+    _dist: &DistGraph,
+    msix: &MsixInstallerInfo,
+    _manifest: &DistManifest,
+) -> Result<()> {
+    LocalAsset::write_new_all("", &msix.file_path)?;
+
+    Ok(())
+}
+
 /// Generate a checksum for the src_path to dest_path
 fn generate_and_write_checksum(
     manifest: &mut DistManifest,
@@ -365,7 +434,6 @@ fn generate_and_write_checksum(
 fn generate_checksum(checksum: &ChecksumStyle, src_path: &Utf8Path) -> DistResult<String> {
     info!("generating {checksum:?} for {src_path}");
     use sha2::Digest;
-    use std::fmt::Write;
 
     let file_bytes = axoasset::LocalAsset::load_bytes(src_path.as_str())?;
 
@@ -384,11 +452,18 @@ fn generate_checksum(checksum: &ChecksumStyle, src_path: &Utf8Path) -> DistResul
             unreachable!()
         }
     };
+    Ok(to_hex(&hash))
+}
+
+/// Render bytes as lowercase hex
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
     let mut output = String::new();
-    for byte in hash {
-        write!(&mut output, "{:02x}", byte).unwrap();
+    for byte in bytes {
+        write!(&mut output, "{byte:02x}").unwrap();
     }
-    Ok(output)
+    output
 }
 
 /// Creates a source code tarball from the git archive from
@@ -677,7 +752,31 @@ fn generate_installer(
         InstallerImpl::Homebrew(info) => {
             installer::homebrew::write_homebrew_formula(&dist.templates, dist, info, manifest)?
         }
+        InstallerImpl::HomebrewCask(info) => {
+            installer::homebrew::write_homebrew_cask(&dist.templates, info, manifest)?
+        }
         InstallerImpl::Msi(info) => info.build()?,
+        InstallerImpl::Deb(info) => info.build(manifest)?,
+        InstallerImpl::Rpm(info) => info.build()?,
+        InstallerImpl::Aur(info) => {
+            installer::aur::write_aur_pkgbuild(&dist.templates, info, manifest)?
+        }
+        InstallerImpl::Apk(info) => info.build()?,
+        InstallerImpl::AppImage(info) => info.build()?,
+        InstallerImpl::Flatpak(info) => {
+            installer::flatpak::write_flatpak_layout(&dist.templates, info, manifest)?
+        }
+        InstallerImpl::Snap(info) => installer::snap::write_snapcraft_yaml(&dist.templates, info)?,
+        InstallerImpl::Nix(info) => {
+            installer::nix::write_nix_flake(&dist.templates, info, manifest)?
+        }
+        InstallerImpl::Scoop(info) => installer::scoop::write_scoop_manifest(info, manifest)?,
+        InstallerImpl::Pkg(info) => info.build()?,
+        InstallerImpl::Dmg(info) => info.build()?,
+        InstallerImpl::Msix(info) => info.build(&dist.templates)?,
+        InstallerImpl::Chocolatey(info) => {
+            installer::chocolatey::write_chocolatey_package(&dist.templates, info, manifest)?
+        }
     }
     Ok(())
 }
@@ -0,0 +1,708 @@
+//! Reading the linkage of ELF binaries, with ldd or on our own
+
+use std::{fs, path::Path, time::Duration};
+
+use axoprocess::Cmd;
+use camino::Utf8Path;
+use cargo_dist_schema::{Hardening, Library, Linkage, Relro};
+use goblin::{
+    elf::dynamic::{DF_1_NOW, DF_BIND_NOW, DT_BIND_NOW},
+    elf::header::{EM_386, EM_AARCH64, EM_X86_64, ET_DYN},
+    elf::program_header::{PF_X, PT_GNU_RELRO, PT_GNU_STACK},
+    elf::Elf,
+    Object,
+};
+use tracing::{debug, warn};
+
+use crate::{errors::*, SortedMap, SortedSet};
+
+use super::parse_version;
+
+/// How many times to try ldd before giving up on it
+///
+/// Under heavy parallel load or emulation (e.g. qemu), ldd can occasionally fail to run
+/// the loader at all, and just trying again is often enough.
+const LDD_ATTEMPTS: u64 = 3;
+
+/// The libraries an ELF binary needs: the name each is asked for by (e.g. `libstdc++.so.6`),
+/// and where it was found (e.g. `/usr/lib/x86_64-linux-gnu/libstdc++.so.6.0.30`)
+///
+/// Libraries that weren't found are only known by name, so that's both.
+pub(crate) type NeededLibraries = Vec<(String, String)>;
+
+/// Get the libraries an ELF binary needs from ldd, which includes indirect dependencies
+///
+/// If ldd never gives us anything we recognize, we fall back to reading the binary's
+/// ELF headers ourselves, and return a warning saying so.
+pub(crate) fn do_ldd(
+    path: &Path,
+    object: &Object,
+) -> DistResult<(NeededLibraries, Option<String>)> {
+    retry_ldd(path, object, || {
+        // We ignore the status here because for whatever reason arm64 glibc ldd can decide
+        // to return non-zero status on binaries with no dynamic linkage (e.g. musl-static).
+        // This was observed both in arm64 ubuntu and asahi (both glibc ldd).
+        // x64 glibc ldd is perfectly fine with this and returns 0, so... *shrug* compilers!
+        // Instead we judge whether it worked by whether its output makes sense.
+        let output = Cmd::new("ldd", "get linkage info from ldd")
+            .arg(path)
+            .check(false)
+            .output()?;
+        // Depending on the ldd, "not a dynamic executable" may be printed to stderr
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok((format!("{stdout}\n{stderr}"), output.status.to_string()))
+    })
+}
+
+/// Run ldd up to [`LDD_ATTEMPTS`][] times, as for [`do_ldd`][]
+///
+/// `run_ldd` returns everything ldd printed, and its exit status.
+fn retry_ldd(
+    path: &Path,
+    object: &Object,
+    mut run_ldd: impl FnMut() -> DistResult<(String, String)>,
+) -> DistResult<(NeededLibraries, Option<String>)> {
+    let needs_libraries = !do_elf_needed(object)?.is_empty();
+    let mut last_status = None;
+    for attempt in 1..=LDD_ATTEMPTS {
+        let (output, status) = run_ldd()?;
+        match parse_ldd_output(&output)? {
+            // ldd also says "not a dynamic executable" about binaries the host's loader
+            // can't load (e.g. ones for another architecture), so only believe it when
+            // the binary really doesn't need anything. Asking again won't change its mind.
+            Some(libraries) if libraries.is_empty() && needs_libraries => {
+                debug!(
+                    "ldd reported no libraries for {}, but it has DT_NEEDED entries",
+                    path.display()
+                );
+                last_status = Some(status);
+                break;
+            }
+            Some(libraries) => return Ok((libraries, None)),
+            None => {}
+        }
+        debug!(
+            "ldd gave no usable output for {} (attempt {attempt}, {status})",
+            path.display()
+        );
+        last_status = Some(status);
+        if attempt < LDD_ATTEMPTS {
+            std::thread::sleep(Duration::from_millis(100 * attempt));
+        }
+    }
+
+    // Reading the headers ourselves only finds direct dependencies, but it's
+    // enough to tell a genuinely static binary apart from ldd misbehaving
+    let libraries = do_elf_in_sysroot(object, Utf8Path::new("/"))?;
+    if libraries.is_empty() {
+        return Ok((libraries, None));
+    }
+    warn!(
+        "ldd didn't report the linkage of {} (last attempt {}), \
+        so falling back to reading its ELF headers",
+        path.display(),
+        last_status.unwrap_or_default()
+    );
+    let libraries = libraries
+        .into_iter()
+        .map(|(name, lib)| match fs::canonicalize(&lib) {
+            Ok(realpath) => (name, realpath.to_string_lossy().to_string()),
+            Err(_) => (name, lib),
+        })
+        .collect();
+    let warning = "ldd didn't report this binary's linkage, so only its direct \
+        dependencies (read from its ELF headers) are listed"
+        .to_owned();
+    Ok((libraries, Some(warning)))
+}
+
+/// Get the libraries listed in ldd's output
+///
+/// Returns `None` if nothing in the output looks like it came from the dynamic loader,
+/// meaning ldd didn't actually manage to inspect the binary.
+fn parse_ldd_output(output: &str) -> DistResult<Option<NeededLibraries>> {
+    let mut libraries = vec![];
+    let mut recognized = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        // There's no dynamic linkage at all; there will be nothing useful to us here.
+        if line.starts_with("not a dynamic executable") || line.starts_with("statically linked") {
+            return Ok(Some(vec![]));
+        }
+
+        // Not a library that actually concerns us
+        if line.starts_with("linux-vdso") || line.starts_with("linux-gate") {
+            recognized = true;
+            continue;
+        }
+
+        // Format: libname.so.1 => /path/to/libname.so.1 (address)
+        if let Some((name, path)) = line.split_once(" => ") {
+            recognized = true;
+            // The binary still needs it even if the loader couldn't find it here
+            if path.starts_with("not found") {
+                libraries.push((name.to_owned(), name.to_owned()));
+                continue;
+            }
+            // This may be a symlink rather than the actual underlying library;
+            // we resolve the symlink here so that we return the real paths,
+            // making it easier to map them to their packages later.
+            let lib = path.split(' ').next().unwrap_or(path);
+            let realpath = fs::canonicalize(lib)?;
+            libraries.push((name.to_owned(), realpath.to_string_lossy().to_string()));
+        } else if line.starts_with('/') && line.ends_with(')') {
+            // The dynamic loader itself: /lib64/ld-linux-x86-64.so.2 (address)
+            recognized = true;
+        }
+    }
+
+    Ok(recognized.then_some(libraries))
+}
+
+/// Whether an ELF binary is for the machine we're running on, so the host's dynamic
+/// loader (and so ldd) can load it
+pub(crate) fn elf_matches_host(object: &Object) -> bool {
+    let Object::Elf(elf) = object else {
+        return false;
+    };
+    let host = match std::env::consts::ARCH {
+        "x86" => EM_386,
+        "x86_64" => EM_X86_64,
+        "aarch64" => EM_AARCH64,
+        _ => return false,
+    };
+    elf.header.e_machine == host
+}
+
+/// Directories the dynamic loader searches by default, relative to the root filesystem
+const DEFAULT_LIBRARY_DIRS: &[&str] = &["/lib", "/usr/lib", "/lib64", "/usr/lib64"];
+
+/// Get the names of the libraries an ELF binary needs (its DT_NEEDED entries)
+///
+/// This works on any host, but says nothing about where the libraries would be found.
+pub(crate) fn do_elf_needed(object: &Object) -> DistResult<Vec<String>> {
+    let Object::Elf(elf) = object else {
+        return Err(DistError::LinkageCheckUnsupportedBinary {});
+    };
+    Ok(elf.libraries.iter().map(|name| name.to_string()).collect())
+}
+
+/// Get the libraries an ELF binary needs, as they'd be found within a sysroot
+///
+/// This approximates the dynamic loader's search: the binary's own runpath, then any
+/// multiarch directories (e.g. `/usr/lib/x86_64-linux-gnu`), then the default ones.
+/// Unlike `ldd`, only direct dependencies are reported. Paths are relative to the
+/// sysroot, so they can be classified as if they were on the host; libraries that
+/// can't be found are reported by name.
+pub(crate) fn do_elf_in_sysroot(
+    object: &Object,
+    sysroot: &Utf8Path,
+) -> DistResult<NeededLibraries> {
+    let Object::Elf(elf) = object else {
+        return Err(DistError::LinkageCheckUnsupportedBinary {});
+    };
+    Ok(resolve_elf_libraries(elf, sysroot))
+}
+
+/// Find the libraries an ELF file needs within a sysroot, as for [`do_elf_in_sysroot`][]
+pub(crate) fn resolve_elf_libraries(elf: &Elf, sysroot: &Utf8Path) -> NeededLibraries {
+    // $ORIGIN refers to wherever the binary ends up installed, which we can't know
+    let mut search_dirs = elf
+        .runpaths
+        .iter()
+        .chain(&elf.rpaths)
+        .flat_map(|paths| paths.split(':'))
+        .filter(|dir| !dir.is_empty() && !dir.contains("$ORIGIN"))
+        .map(|dir| dir.to_owned())
+        .collect::<Vec<_>>();
+    for dir in ["/lib", "/usr/lib"] {
+        let Ok(entries) = sysroot.join(dir.trim_start_matches('/')).read_dir_utf8() else {
+            continue;
+        };
+        let mut multiarch = entries
+            .flatten()
+            .filter(|entry| entry.file_name().contains("-linux-"))
+            .map(|entry| format!("{dir}/{}", entry.file_name()))
+            .collect::<Vec<_>>();
+        multiarch.sort();
+        search_dirs.extend(multiarch);
+    }
+    search_dirs.extend(DEFAULT_LIBRARY_DIRS.iter().map(|dir| dir.to_string()));
+
+    elf.libraries
+        .iter()
+        .map(|name| {
+            let path = search_dirs
+                .iter()
+                .map(|dir| format!("{}/{name}", dir.trim_end_matches('/')))
+                .find(|lib| sysroot.join(lib.trim_start_matches('/')).exists())
+                .unwrap_or_else(|| name.to_string());
+            (name.to_string(), path)
+        })
+        .collect()
+}
+
+/// Find the newest glibc version an ELF binary requires, and a symbol that requires it
+///
+/// This is the real portability floor for a Linux binary: it won't run on any system
+/// with an older glibc, whatever else is installed there.
+pub(crate) fn determine_min_glibc(object: &Object) -> Option<(String, Option<String>)> {
+    newest_symbol_version(object, "GLIBC_")
+}
+
+/// Find the newest version with the given prefix an ELF binary requires symbols from,
+/// and a symbol that requires it
+///
+/// Libraries like glibc and libstdc++ version every symbol they export (GLIBC_2.17,
+/// GLIBCXX_3.4.30...), and binaries record the versions they need.
+pub(crate) fn newest_symbol_version(
+    object: &Object,
+    prefix: &str,
+) -> Option<(String, Option<String>)> {
+    let Object::Elf(elf) = object else {
+        return None;
+    };
+    let verneed = elf.verneed.as_ref()?;
+
+    // The newest version, its name, and the index the symbol version table refers to it by
+    let mut newest: Option<(Vec<u32>, &str, u16)> = None;
+    for need in verneed.iter() {
+        for aux in need.iter() {
+            let Some(name) = elf.dynstrtab.get_at(aux.vna_name) else {
+                continue;
+            };
+            // GLIBC_PRIVATE and friends aren't versions
+            let Some(version) = name.strip_prefix(prefix).and_then(parse_version) else {
+                continue;
+            };
+            let is_newer = match &newest {
+                Some((newest, _, _)) => version > *newest,
+                None => true,
+            };
+            if is_newer {
+                newest = Some((version, name, aux.vna_other));
+            }
+        }
+    }
+    let (_, name, index) = newest?;
+
+    let symbol = elf.versym.as_ref().and_then(|versym| {
+        elf.dynsyms
+            .iter()
+            .enumerate()
+            .find(|(idx, _)| versym.get_at(*idx).map(|v| v.version()) == Some(index))
+            .and_then(|(_, sym)| elf.dynstrtab.get_at(sym.st_name))
+            .map(|sym| format!("{sym}@{name}"))
+    });
+    let version = name.trim_start_matches(prefix).to_owned();
+    Some((version, symbol))
+}
+
+/// Find the newest symbol version an ELF binary requires from each library it links,
+/// keyed by the library's file name (e.g. `libstdc++.so.6` needs `GLIBCXX_3.4.30`)
+///
+/// A library can have more than one series of versions (libstdc++ has both GLIBCXX and
+/// CXXABI), so the newest version of whichever series the binary uses most is picked.
+pub(crate) fn determine_min_symbol_versions(object: &Object) -> SortedMap<String, String> {
+    let mut versions = SortedMap::new();
+    let Object::Elf(elf) = object else {
+        return versions;
+    };
+    let Some(verneed) = &elf.verneed else {
+        return versions;
+    };
+
+    for need in verneed.iter() {
+        let Some(file) = elf.dynstrtab.get_at(need.vn_file) else {
+            continue;
+        };
+        // For each series: how many versions the binary needs from it, and the newest
+        let mut series = SortedMap::<&str, (usize, Vec<u32>, &str)>::new();
+        for aux in need.iter() {
+            let Some(name) = elf.dynstrtab.get_at(aux.vna_name) else {
+                continue;
+            };
+            // GLIBC_PRIVATE and friends aren't versions
+            let Some((prefix, version)) = name.rsplit_once('_') else {
+                continue;
+            };
+            let Some(version) = parse_version(version) else {
+                continue;
+            };
+            let entry = series.entry(prefix).or_insert((0, vec![], name));
+            entry.0 += 1;
+            if version > entry.1 {
+                entry.1 = version;
+                entry.2 = name;
+            }
+        }
+        let main_series = series.values().max_by_key(|(count, _, _)| *count);
+        if let Some((_, _, newest)) = main_series {
+            versions.insert(file.to_owned(), (*newest).to_owned());
+        }
+    }
+    versions
+}
+
+/// Record the newest symbol version the binary needs on each library it links
+///
+/// `versions` is keyed by the name the binary asks for each library by, which is often
+/// not the name of the file it's found in (`libstdc++.so.6` is really
+/// `libstdc++.so.6.0.30`), so `needed_names` maps each library's path back to that name.
+pub(crate) fn add_min_symbol_versions(
+    linkage: &mut Linkage,
+    versions: &SortedMap<String, String>,
+    needed_names: &SortedMap<String, String>,
+) {
+    let with_versions = |libraries: &SortedSet<Library>| {
+        libraries
+            .iter()
+            .cloned()
+            .map(|mut library| {
+                let name = match needed_names.get(&library.path) {
+                    Some(name) => name.as_str(),
+                    None => library.path.rsplit('/').next().unwrap_or(&library.path),
+                };
+                library.min_symbol_version = versions.get(name).cloned();
+                library
+            })
+            .collect::<SortedSet<_>>()
+    };
+    linkage.system = with_versions(&linkage.system);
+    linkage.homebrew = with_versions(&linkage.homebrew);
+    linkage.public_unmanaged = with_versions(&linkage.public_unmanaged);
+    linkage.toolchain = with_versions(&linkage.toolchain);
+    linkage.language_runtimes = with_versions(&linkage.language_runtimes);
+    linkage.graphics = with_versions(&linkage.graphics);
+    linkage.other = with_versions(&linkage.other);
+}
+
+/// Find out which exploit mitigations an ELF binary was built with
+pub(crate) fn elf_hardening(elf: &Elf) -> Hardening {
+    let has_header = |kind| elf.program_headers.iter().find(|ph| ph.p_type == kind);
+    let bind_now = elf.dynamic.as_ref().is_some_and(|dynamic| {
+        dynamic.info.flags & DF_BIND_NOW != 0
+            || dynamic.info.flags_1 & DF_1_NOW != 0
+            || dynamic.dyns.iter().any(|d| d.d_tag == DT_BIND_NOW)
+    });
+    let relro = match (has_header(PT_GNU_RELRO), bind_now) {
+        (None, _) => Relro::None,
+        (Some(_), false) => Relro::Partial,
+        (Some(_), true) => Relro::Full,
+    };
+    // Without a PT_GNU_STACK header, the loader assumes the stack has to be executable
+    let nx = has_header(PT_GNU_STACK).is_some_and(|ph| ph.p_flags & PF_X == 0);
+
+    let names = elf
+        .syms
+        .iter()
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name))
+        .chain(
+            elf.dynsyms
+                .iter()
+                .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name)),
+        )
+        .collect::<Vec<_>>();
+    let stack_canary = names
+        .iter()
+        .any(|name| *name == "__stack_chk_fail" || *name == "__stack_chk_guard");
+    // glibc's fortified functions are all named like __memcpy_chk
+    let fortify = names
+        .iter()
+        .any(|name| name.starts_with("__") && name.ends_with("_chk") && !name.contains("stack"));
+
+    Hardening {
+        pie: Some(elf.header.e_type == ET_DYN),
+        relro: Some(relro),
+        nx: Some(nx),
+        stack_canary: Some(stack_canary),
+        fortify: Some(fortify),
+        ..Hardening::default()
+    }
+}
+
+/// Functions that load libraries at runtime
+const DYNAMIC_LOADING_SYMBOLS: &[&str] = &["dlmopen", "dlopen", "dlsym"];
+
+/// Find which functions for loading libraries at runtime an ELF binary references
+///
+/// glibc's name resolution (NSS) does this internally, which is how static binaries
+/// that call e.g. `getaddrinfo` end up needing files on the user's system anyway.
+pub(crate) fn find_dynamic_loading(object: &Object, buf: &[u8]) -> Vec<&'static str> {
+    let Object::Elf(elf) = object else {
+        return vec![];
+    };
+
+    let mut found = SortedSet::new();
+    let names = elf
+        .syms
+        .iter()
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name))
+        .chain(
+            elf.dynsyms
+                .iter()
+                .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name)),
+        );
+    for name in names {
+        if let Some(symbol) = DYNAMIC_LOADING_SYMBOLS.iter().find(|s| **s == name) {
+            found.insert(*symbol);
+        }
+    }
+    // Stripped binaries have no symbol table, so settle for finding the names anywhere
+    if elf.syms.is_empty() {
+        for symbol in DYNAMIC_LOADING_SYMBOLS {
+            let needle = format!("\0{symbol}\0");
+            if buf
+                .windows(needle.len())
+                .any(|window| window == needle.as_bytes())
+            {
+                found.insert(*symbol);
+            }
+        }
+    }
+
+    found.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::linkage::tests::{elf_fixture, versioned_elf_fixture};
+
+    use super::*;
+
+    #[test]
+    fn ldd_output() {
+        let dir = TempDir::new().unwrap();
+        let lib = dir.path().join("libfoo.so.1");
+        fs::write(&lib, "").unwrap();
+        let realpath = fs::canonicalize(&lib).unwrap();
+        let output = format!(
+            "\tlinux-vdso.so.1 (0x00007ffc2a5f3000)\n\
+            \tlibfoo.so.1 => {} (0x00007f1f2c000000)\n\
+            \tlibmissing.so.2 => not found\n\
+            \t/lib64/ld-linux-x86-64.so.2 (0x00007f1f2c400000)\n",
+            lib.display()
+        );
+        assert_eq!(
+            parse_ldd_output(&output).unwrap(),
+            Some(vec![
+                (
+                    "libfoo.so.1".to_owned(),
+                    realpath.to_string_lossy().into_owned()
+                ),
+                ("libmissing.so.2".to_owned(), "libmissing.so.2".to_owned()),
+            ])
+        );
+
+        let empty = Some(vec![]);
+        assert_eq!(
+            parse_ldd_output("\tnot a dynamic executable\n").unwrap(),
+            empty
+        );
+        assert_eq!(parse_ldd_output("\tstatically linked\n").unwrap(), empty);
+        assert_eq!(
+            parse_ldd_output("\tlinux-vdso.so.1 (0x0)\n").unwrap(),
+            empty
+        );
+
+        // Nothing from the loader means ldd didn't get to look at the binary
+        assert_eq!(parse_ldd_output("").unwrap(), None);
+        assert_eq!(
+            parse_ldd_output("ldd: exited with unknown exit code (139)\n").unwrap(),
+            None
+        );
+        assert_eq!(parse_ldd_output("\tlibfoo.so.1 =>").unwrap(), None);
+
+        // A library that's gone by the time we look is an error, not a panic
+        let gone = "\tlibgone.so.1 => /nonexistent/cargo-dist/libgone.so.1 (0x0)\n";
+        assert!(parse_ldd_output(gone).is_err());
+    }
+
+    #[test]
+    fn ldd_retries() {
+        let buf = elf_fixture(&["libcargo-dist-fixture.so.1"]);
+        let object = Object::parse(&buf).unwrap();
+        let static_buf = elf_fixture(&[]);
+        let static_object = Object::parse(&static_buf).unwrap();
+        let path = Path::new("app");
+        // It can't be found, so it's only known by name
+        let fixture = "libcargo-dist-fixture.so.1".to_owned();
+
+        // ldd that only works the last time it's asked
+        let mut runs = 0;
+        let (libraries, warning) = retry_ldd(path, &static_object, || {
+            runs += 1;
+            let output = if runs < LDD_ATTEMPTS {
+                ""
+            } else {
+                "\tstatically linked"
+            };
+            Ok((output.to_owned(), "exit status: 1".to_owned()))
+        })
+        .unwrap();
+        assert_eq!(runs, LDD_ATTEMPTS);
+        assert!(libraries.is_empty());
+        assert_eq!(warning, None);
+
+        // ldd that never works falls back to the ELF headers, with a warning
+        let mut runs = 0;
+        let (libraries, warning) = retry_ldd(path, &object, || {
+            runs += 1;
+            Ok(("".to_owned(), "signal: 11 (SIGSEGV)".to_owned()))
+        })
+        .unwrap();
+        assert_eq!(runs, LDD_ATTEMPTS);
+        assert_eq!(libraries, vec![(fixture.clone(), fixture.clone())]);
+        assert!(warning.is_some());
+
+        // Unless the headers don't need anything either
+        let (libraries, warning) =
+            retry_ldd(path, &static_object, || Ok(("".to_owned(), "".to_owned()))).unwrap();
+        assert!(libraries.is_empty());
+        assert_eq!(warning, None);
+
+        // ldd calling a binary with DT_NEEDED entries static (as it does for ones the host
+        // can't load) isn't believed, or asked again
+        let mut runs = 0;
+        let (libraries, warning) = retry_ldd(path, &object, || {
+            runs += 1;
+            Ok((
+                "\tnot a dynamic executable".to_owned(),
+                "exit status: 1".to_owned(),
+            ))
+        })
+        .unwrap();
+        assert_eq!(runs, 1);
+        assert_eq!(libraries, vec![(fixture.clone(), fixture)]);
+        assert!(warning.is_some());
+
+        // Not being able to run ldd at all isn't retried
+        let mut runs = 0;
+        let result = retry_ldd(path, &static_object, || {
+            runs += 1;
+            Err(DistError::LinkageCheckUnsupportedBinary {})
+        });
+        assert!(result.is_err());
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn min_glibc() {
+        let buf = versioned_elf_fixture(
+            &["libc.so.6"],
+            &[
+                ("_dl_find_object", "libc.so.6", "GLIBC_PRIVATE"),
+                ("memcpy", "libc.so.6", "GLIBC_2.9"),
+                ("clock_gettime", "libc.so.6", "GLIBC_2.17"),
+                ("fcntl", "libc.so.6", "GLIBC_2.2.5"),
+            ],
+        );
+        let object = Object::parse(&buf).unwrap();
+        // 2.17 is newer than 2.9 and 2.2.5, even though it sorts before them as text
+        assert_eq!(
+            determine_min_glibc(&object),
+            Some((
+                "2.17".to_owned(),
+                Some("clock_gettime@GLIBC_2.17".to_owned())
+            ))
+        );
+
+        // Private versions alone don't say anything
+        let buf = versioned_elf_fixture(
+            &["libc.so.6"],
+            &[("_dl_find_object", "libc.so.6", "GLIBC_PRIVATE")],
+        );
+        let object = Object::parse(&buf).unwrap();
+        assert_eq!(determine_min_glibc(&object), None);
+
+        // Nor does a binary with no symbol versions at all
+        let buf = elf_fixture(&["libc.so.6"]);
+        let object = Object::parse(&buf).unwrap();
+        assert_eq!(determine_min_glibc(&object), None);
+    }
+
+    #[test]
+    fn min_symbol_versions() {
+        let buf = versioned_elf_fixture(
+            &["libc.so.6", "libstdc++.so.6", "libssl.so.3"],
+            &[
+                ("memcpy", "libc.so.6", "GLIBC_2.14"),
+                ("_dl_find_object", "libc.so.6", "GLIBC_PRIVATE"),
+                ("clock_gettime", "libc.so.6", "GLIBC_2.9"),
+                ("_ZSt4cout", "libstdc++.so.6", "GLIBCXX_3.4"),
+                (
+                    "_ZNSt6thread6_StateD2Ev",
+                    "libstdc++.so.6",
+                    "GLIBCXX_3.4.22",
+                ),
+                (
+                    "_ZSt28__throw_bad_array_new_lengthv",
+                    "libstdc++.so.6",
+                    "GLIBCXX_3.4.29",
+                ),
+                ("__cxa_thread_atexit", "libstdc++.so.6", "CXXABI_1.3.7"),
+                ("SSL_new", "libssl.so.3", "OPENSSL_3.0.0"),
+            ],
+        );
+        let object = Object::parse(&buf).unwrap();
+        let versions = determine_min_symbol_versions(&object);
+        assert_eq!(
+            versions.into_iter().collect::<Vec<_>>(),
+            [
+                ("libc.so.6".to_owned(), "GLIBC_2.14".to_owned()),
+                ("libssl.so.3".to_owned(), "OPENSSL_3.0.0".to_owned()),
+                // GLIBCXX is the series libstdc++ is mostly needed for, not CXXABI
+                ("libstdc++.so.6".to_owned(), "GLIBCXX_3.4.29".to_owned()),
+            ]
+        );
+
+        // The binary asks for libstdc++.so.6, but the file is named for its full version
+        let libstdcxx = "/usr/lib/x86_64-linux-gnu/libstdc++.so.6.0.30";
+        let needed_names = SortedMap::from([(libstdcxx.to_owned(), "libstdc++.so.6".to_owned())]);
+        let mut linkage = Linkage::default();
+        linkage.system.insert(Library::new(libstdcxx.to_owned()));
+        let libc = "/usr/lib/x86_64-linux-gnu/libc.so.6";
+        linkage.system.insert(Library::new(libc.to_owned()));
+        linkage.other.insert(Library::new("libz.so.1".to_owned()));
+        let versions = determine_min_symbol_versions(&object);
+        add_min_symbol_versions(&mut linkage, &versions, &needed_names);
+        let min_version = |libraries: &SortedSet<Library>| {
+            libraries
+                .iter()
+                .map(|library| library.min_symbol_version.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            min_version(&linkage.system),
+            [
+                Some("GLIBC_2.14".to_owned()),
+                Some("GLIBCXX_3.4.29".to_owned())
+            ]
+        );
+        assert_eq!(min_version(&linkage.other), [None]);
+    }
+
+    #[test]
+    fn host_architecture() {
+        // The fixture is an x86_64 binary
+        let buf = elf_fixture(&["libc.so.6"]);
+        let object = Object::parse(&buf).unwrap();
+        assert_eq!(
+            elf_matches_host(&object),
+            std::env::consts::ARCH == "x86_64"
+        );
+
+        let mut buf = buf;
+        buf[18..20].copy_from_slice(&EM_AARCH64.to_le_bytes());
+        let object = Object::parse(&buf).unwrap();
+        assert_eq!(
+            elf_matches_host(&object),
+            std::env::consts::ARCH == "aarch64"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Reading the linkage of Mach-O binaries
+
+use std::io::Cursor;
+
+use cargo_dist_schema::{Hardening, LinkReason};
+use goblin::{
+    mach::{load_command::CommandVariant, Mach, MachO, SingleArch},
+    Object,
+};
+use mach_object::{
+    cpu_type_t, LoadCommand, MachCommand, OFile, CPU_TYPE_ARM, CPU_TYPE_ARM64, CPU_TYPE_X86,
+    CPU_TYPE_X86_64,
+};
+
+use super::LinkedLibraries;
+
+/// Get the libraries a Mach-O binary links against, for each architecture it contains
+///
+/// Thin binaries produce a single entry with no architecture. Universal binaries
+/// (made with `lipo`) produce an entry for each slice, since each slice is linked separately
+/// and their dependencies can differ.
+pub(crate) fn do_otool(buf: &[u8]) -> Vec<(Option<String>, LinkedLibraries)> {
+    let mut cur = Cursor::new(buf);
+    match OFile::parse(&mut cur) {
+        Ok(OFile::MachFile {
+            header: _,
+            commands,
+        }) => vec![(None, dylibs_from_commands(&commands))],
+        Ok(OFile::FatFile { magic: _, files }) => files
+            .iter()
+            .filter_map(|(arch, file)| match file {
+                OFile::MachFile {
+                    header: _,
+                    commands,
+                } => Some((
+                    Some(arch_name(arch.cputype)),
+                    dylibs_from_commands(commands),
+                )),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![(None, vec![])],
+    }
+}
+
+/// Get the libraries referenced by the load commands of a (thin) Mach-O binary
+fn dylibs_from_commands(commands: &[MachCommand]) -> LinkedLibraries {
+    let mut libraries = vec![];
+
+    let commands = commands
+        .iter()
+        .map(|load| load.command())
+        .cloned()
+        .collect::<Vec<LoadCommand>>();
+
+    for command in commands {
+        match command {
+            LoadCommand::IdDyLib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), None));
+            }
+            LoadCommand::LoadDyLib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), Some(LinkReason::Load)));
+            }
+            LoadCommand::LoadWeakDyLib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), Some(LinkReason::Weak)));
+            }
+            LoadCommand::ReexportDyLib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), Some(LinkReason::Reexport)));
+            }
+            LoadCommand::LoadUpwardDylib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), Some(LinkReason::Upward)));
+            }
+            LoadCommand::LazyLoadDylib(ref dylib) => {
+                libraries.push((dylib.name.to_string(), Some(LinkReason::Lazy)));
+            }
+            _ => {}
+        }
+    }
+
+    libraries
+}
+
+/// Get the name Apple's tools use for a Mach-O cpu type
+fn arch_name(cputype: cpu_type_t) -> String {
+    match cputype {
+        CPU_TYPE_X86_64 => "x86_64".to_owned(),
+        CPU_TYPE_ARM64 => "arm64".to_owned(),
+        CPU_TYPE_X86 => "i386".to_owned(),
+        CPU_TYPE_ARM => "arm".to_owned(),
+        other => format!("cputype {other}"),
+    }
+}
+
+/// The `LC_BUILD_VERSION` platform number for macOS
+const PLATFORM_MACOS: u32 = 1;
+
+/// Work out the oldest macOS version a Mach-O binary runs on, for each architecture
+///
+/// This comes from its `LC_BUILD_VERSION` load command, or `LC_VERSION_MIN_MACOSX` for
+/// binaries built to run on macOS older than 10.14. Thin binaries have a single entry
+/// with no architecture.
+pub(crate) fn determine_min_macos(object: &Object) -> Vec<(Option<String>, String)> {
+    match object {
+        Object::Mach(Mach::Binary(macho)) => min_macos_version(macho)
+            .map(|version| (None, version))
+            .into_iter()
+            .collect(),
+        Object::Mach(Mach::Fat(fat)) => {
+            let mut versions = vec![];
+            for (idx, arch) in fat.iter_arches().enumerate() {
+                let (Ok(arch), Ok(SingleArch::MachO(macho))) = (arch, fat.get(idx)) else {
+                    continue;
+                };
+                if let Some(version) = min_macos_version(&macho) {
+                    versions.push((Some(arch_name(arch.cputype as cpu_type_t)), version));
+                }
+            }
+            versions
+        }
+        _ => vec![],
+    }
+}
+
+/// Get the oldest macOS version a thin Mach-O binary runs on from its load commands
+fn min_macos_version(macho: &MachO) -> Option<String> {
+    let version = macho
+        .load_commands
+        .iter()
+        .find_map(|load| match &load.command {
+            CommandVariant::BuildVersion(command) if command.platform == PLATFORM_MACOS => {
+                Some(command.minos)
+            }
+            CommandVariant::VersionMinMacosx(command) => Some(command.version),
+            _ => None,
+        })?;
+    Some(format_macos_version(version))
+}
+
+/// Format a Mach-O version number, which packs it as `xxxx.yy.zz` in nibbles
+///
+/// The patch version is left off when it's 0, as Apple does (e.g. "10.13", "14.2.1").
+fn format_macos_version(version: u32) -> String {
+    let major = version >> 16;
+    let minor = (version >> 8) & 0xff;
+    let patch = version & 0xff;
+    if patch == 0 {
+        format!("{major}.{minor}")
+    } else {
+        format!("{major}.{minor}.{patch}")
+    }
+}
+
+/// Find out which exploit mitigations a Mach-O binary was built with
+pub(crate) fn macho_hardening(macho: &MachO) -> Hardening {
+    // The MH_PIE and MH_ALLOW_STACK_EXECUTION header flags
+    let flags = macho.header.flags;
+    let stack_canary = macho
+        .symbols()
+        .filter_map(|symbol| symbol.ok())
+        .any(|(name, _)| name == "___stack_chk_fail" || name == "___stack_chk_guard");
+
+    Hardening {
+        pie: Some(flags & 0x0020_0000 != 0),
+        nx: Some(flags & 0x0002_0000 == 0),
+        stack_canary: Some(stack_canary),
+        ..Hardening::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macos_versions() {
+        assert_eq!(format_macos_version(0x000a_0d00), "10.13");
+        assert_eq!(format_macos_version(0x000b_0000), "11.0");
+        assert_eq!(format_macos_version(0x000e_0201), "14.2.1");
+    }
+}
@@ -0,0 +1,3637 @@
+//! The Linkage Checker, which lets us detect what a binary dynamically links to (and why)
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs::{self, File},
+    io::{self, Read},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use axoasset::{LocalAsset, SourceFile};
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{
+    ArtifactId, AssetId, AssetInfo, Confidence, DependencyNode, DistManifest, DllLocation,
+    Hardening, Library, LinkReason, Linkage, SystemId,
+};
+use comfy_table::Table;
+use goblin::{
+    elf::dynamic::{tag_to_str, DT_NEEDED, DT_RPATH, DT_RUNPATH, DT_SONAME},
+    mach::{
+        load_command::{cmd_to_str, CommandVariant},
+        Mach, SingleArch,
+    },
+    Object,
+};
+use serde::Serialize;
+use temp_dir::TempDir;
+use tracing::{debug, warn, Level};
+
+use crate::{
+    cargo,
+    config::{ChecksumStyle, Config, LinkageCategory, LinkageConfig, LinkageRule},
+    errors::*,
+    gather_work, generate_checksum, get_host_target, to_hex, Artifact, ArtifactKind, DistGraph,
+    SortedMap, SortedSet,
+};
+
+mod elf;
+mod macho;
+mod pe;
+mod policy;
+mod report;
+mod resolver;
+mod sarif;
+mod wasm;
+
+use self::elf::{
+    add_min_symbol_versions, determine_min_glibc, determine_min_symbol_versions, do_elf_in_sysroot,
+    do_elf_needed, do_ldd, elf_hardening, elf_matches_host, find_dynamic_loading,
+    newest_symbol_version, resolve_elf_libraries,
+};
+use self::macho::{determine_min_macos, do_otool, macho_hardening};
+use self::pe::{
+    determine_pe_subsystem, do_pe, format_guid, is_vc_redist_dll, locate_dll, pdb_guid,
+    vc_redist_dlls,
+};
+pub use self::policy::{
+    check_hardening, check_library_lists, check_linkage_policy, check_require_static,
+    check_vc_redist,
+};
+use self::policy::{
+    evaluate_linkage, hide_system_libraries, in_home_directory, instrumentation_runtime,
+    instrumentation_runtimes, library_advisories, library_names, linkage_policy, retain_problems,
+    rpath_problem, LinkageSummary,
+};
+pub use self::report::{
+    linkage_footprint, ArtifactLinkageDisplay, CsvLinkageDisplay, ExplainLinkageDisplay,
+    FootprintEntry, FootprintLinkageDisplay, HistogramLinkageDisplay, LinkageDisplay,
+    ManifestLinkageDisplay, MarkdownLinkageDisplay, TableStyle,
+};
+use self::resolver::is_homebrew_path;
+pub use self::resolver::{
+    library_from_apt, library_from_distro, library_from_homebrew, HostPackageResolver,
+    PackageResolver,
+};
+use self::sarif::SarifLog;
+use self::wasm::do_wasm;
+
+/// Arguments for `cargo dist linkage` ([`do_linkage][])
+#[derive(Debug, Default)]
+pub struct LinkageArgs {
+    /// How to print the report
+    pub format: LinkageFormat,
+    /// Read linkage data from JSON rather than performing a live check
+    pub from_json: Option<String>,
+    /// How to group binaries in the human-readable output
+    pub group_by: LinkageGroupBy,
+    /// Fail if any binary built for a musl target has dynamic linkage
+    pub strict_static: bool,
+    /// Fail if any binary links a sanitizer or profiling runtime
+    pub deny_instrumentation: bool,
+    /// Re-run the check whenever the binaries change, until interrupted
+    pub watch: bool,
+    /// Draw tables with plain ascii characters, even if the terminal seems to support unicode
+    pub ascii: bool,
+    /// Resolve Linux libraries against this root filesystem instead of the host's
+    pub sysroot: Option<Utf8PathBuf>,
+    /// Only report binaries with warnings or violations
+    pub only_problems: bool,
+    /// Look up the license of each library's package
+    pub licenses: bool,
+    /// Instead of the full report, explain which binaries link this library, and how
+    pub explain: Option<String>,
+    /// Check binaries against their recorded checksums before analyzing them
+    pub verify_checksums: bool,
+    /// Leave system libraries out of the human-readable report (the JSON keeps them)
+    pub exclude_system: bool,
+    /// Write the linkage of every binary to this file, in a format --from-json can read
+    pub write_baseline: Option<Utf8PathBuf>,
+    /// Guess which libraries are statically linked in, from each binary's symbols
+    pub scan_symbols: bool,
+    /// Guess which libraries are loaded at runtime, from the file names in each binary
+    pub scan_strings: bool,
+    /// What to do when a binary hasn't been built
+    pub on_missing: OnMissing,
+    /// Also work out the libraries each library loads, and show the whole tree
+    pub deep: bool,
+    /// How many levels of libraries `deep` follows
+    pub max_depth: usize,
+    /// Instead of checking anything, report which external tools this machine has
+    pub probe_tools: bool,
+    /// Instead of the full report, count each binary's libraries by category
+    pub histogram: bool,
+    /// Merge the linkage policy in this file with the workspace's
+    pub policy: Option<Utf8PathBuf>,
+    /// Instead of the full report, compare two builds of the same binary
+    pub compare: Option<(Utf8PathBuf, Utf8PathBuf)>,
+    /// Instead of checking anything, compare an old linkage report to a new one
+    pub diff: Option<(Utf8PathBuf, Utf8PathBuf)>,
+    /// Fail if a binary links a library that isn't bundled with it or part of the OS
+    pub require_bundled: bool,
+}
+
+/// How the linkage report should be printed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkageFormat {
+    /// Print human-readable tables to stderr
+    #[default]
+    Human,
+    /// Print the dist manifest containing the report to stdout as JSON
+    Json,
+    /// Print the summary counts and the binaries with problems to stdout as JSON
+    JsonSummary,
+    /// Print markdown tables to stdout, e.g. for a GitHub job summary
+    Markdown,
+    /// Print violations, warnings and missing mitigations to stdout as SARIF
+    Sarif,
+    /// Print a row for each library each binary links to stdout as CSV
+    Csv,
+}
+
+impl LinkageFormat {
+    /// The name of this format, as it's passed to --format
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkageFormat::Human => "human",
+            LinkageFormat::Json => "json",
+            LinkageFormat::JsonSummary => "json-summary",
+            LinkageFormat::Markdown => "markdown",
+            LinkageFormat::Sarif => "sarif",
+            LinkageFormat::Csv => "csv",
+        }
+    }
+}
+
+/// How binaries should be grouped in the human-readable linkage report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkageGroupBy {
+    /// Print a table for each binary
+    #[default]
+    Binary,
+    /// Print binaries grouped under the artifact that ships them
+    Artifact,
+    /// Print every artifact in the manifest, with the linkage of each of its assets
+    Manifest,
+    /// Print one deduplicated list of every library the whole release depends on
+    Footprint,
+}
+
+/// What to do when a binary we expected to check hasn't been built
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnMissing {
+    /// Quietly leave it out of the report
+    Skip,
+    /// Print a warning and leave it out of the report
+    #[default]
+    Warn,
+    /// Fail the check
+    Error,
+}
+
+impl OnMissing {
+    /// Handle something that's missing according to this policy
+    fn handle(self, missing: DistError) -> DistResult<()> {
+        match self {
+            OnMissing::Skip => Ok(()),
+            OnMissing::Warn => {
+                eprintln!("{missing}; skipping check");
+                Ok(())
+            }
+            OnMissing::Error => Err(missing),
+        }
+    }
+}
+
+/// Determinage dynamic linkage of built artifacts (impl of `cargo dist linkage`)
+pub fn do_linkage(cfg: &Config, args: &LinkageArgs) -> Result<()> {
+    check_mode_format(args)?;
+    if args.probe_tools {
+        probe_tools(args);
+        Ok(())
+    } else if let Some((binary, other_binary)) = &args.compare {
+        compare_builds(cfg, args, binary, other_binary)
+    } else if let Some((old, new)) = &args.diff {
+        diff_reports(args, old, new)
+    } else if args.watch {
+        watch_linkage(cfg, args)
+    } else {
+        check_linkage(cfg, args)
+    }
+}
+
+/// Make sure the output format was one the requested mode can print
+///
+/// The modes that replace the full report only know how to print some formats, and
+/// it's better to say so than to quietly print something else.
+fn check_mode_format(args: &LinkageArgs) -> DistResult<()> {
+    use LinkageFormat::{Human, Json};
+    let (mode, supported): (&str, &[LinkageFormat]) = if args.probe_tools {
+        ("--probe-tools", &[Human, Json])
+    } else if args.compare.is_some() {
+        ("--compare", &[Human, Json])
+    } else if args.diff.is_some() {
+        ("--diff", &[Human, Json])
+    } else if args.histogram {
+        ("--histogram", &[Human, Json])
+    } else if args.explain.is_some() {
+        ("--explain", &[Human])
+    } else {
+        return Ok(());
+    };
+    if supported.contains(&args.format) {
+        return Ok(());
+    }
+    let supported = supported
+        .iter()
+        .map(|format| format.name())
+        .collect::<Vec<_>>();
+    Err(DistError::LinkageFormatUnsupported {
+        mode: mode.to_owned(),
+        format: args.format.name().to_owned(),
+        supported: supported.join(" or "),
+    })
+}
+
+/// How often to poll for changes in watch mode
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+/// How long things must stay unchanged before we re-run, so that a rebuild
+/// touching several files only triggers a single check
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Re-run the linkage check every time the binaries change, until ctrl-c
+fn watch_linkage(cfg: &Config, args: &LinkageArgs) -> Result<()> {
+    let watched = if let Some(json) = &args.from_json {
+        Utf8PathBuf::from(json)
+    } else {
+        let (dist, _) = gather_work(cfg)?;
+        dist.dist_dir
+    };
+
+    // Catch ctrl-c ourselves so we can stop between checks rather than mid-way through one
+    let interrupted = Arc::new(AtomicBool::new(false));
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        let interrupted = interrupted.clone();
+        runtime.spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                interrupted.store(true, Ordering::SeqCst);
+            }
+        });
+    }
+
+    let mut last_checked = None;
+    while !interrupted.load(Ordering::SeqCst) {
+        let current = modification_times(&watched);
+        if last_checked.as_ref() == Some(&current) {
+            std::thread::sleep(WATCH_INTERVAL);
+            continue;
+        }
+        std::thread::sleep(WATCH_DEBOUNCE);
+        if modification_times(&watched) != current {
+            continue;
+        }
+
+        // Clear the screen and move the cursor back to the top
+        eprint!("\x1B[2J\x1B[1;1H");
+        // A failing check shouldn't stop us watching, that's the whole point
+        if let Err(e) = check_linkage(cfg, args) {
+            eprintln!("{e:?}");
+        }
+        eprintln!("watching {watched} for changes (press ctrl-c to exit)");
+        last_checked = Some(current);
+    }
+
+    Ok(())
+}
+
+/// Get the modification time of every file under the given path
+///
+/// Anything we can't read is just skipped; it may be mid-rebuild.
+fn modification_times(path: &Utf8Path) -> SortedMap<Utf8PathBuf, SystemTime> {
+    let mut times = SortedMap::new();
+    let Ok(metadata) = fs::metadata(path) else {
+        return times;
+    };
+    if metadata.is_dir() {
+        if let Ok(entries) = path.read_dir_utf8() {
+            for entry in entries.flatten() {
+                times.extend(modification_times(entry.path()));
+            }
+        }
+    } else if let Ok(modified) = metadata.modified() {
+        times.insert(path.to_owned(), modified);
+    }
+    times
+}
+
+/// Run the linkage check once, printing the results
+fn check_linkage(cfg: &Config, args: &LinkageArgs) -> Result<()> {
+    let (mut manifest, config, skipped) = if let Some(target) = args.from_json.clone() {
+        let file = SourceFile::load_local(target)?;
+        // Apply the workspace's linkage policy if we're in one, but we don't need to be
+        let config = gather_work(cfg)
+            .map(|(dist, _)| dist.linkage)
+            .unwrap_or_default();
+        (manifest_from_json(&file)?, linkage_policy(config, args)?, 0)
+    } else {
+        let (dist, mut manifest) = gather_work(cfg)?;
+        let config = linkage_policy(dist.linkage.clone(), args)?;
+        let options = LinkageOptions {
+            config: config.clone(),
+            sysroot: args.sysroot.clone(),
+            licenses: args.licenses,
+            verify_checksums: args.verify_checksums,
+            scan_symbols: args.scan_symbols,
+            scan_strings: args.scan_strings,
+            deep: args.deep,
+            max_depth: args.max_depth,
+            ..LinkageOptions::default()
+        };
+        let skipped = compute_linkage_assuming_local_build(
+            &dist,
+            &mut manifest,
+            cfg,
+            &options,
+            args.on_missing,
+        )?;
+        (manifest, config, skipped)
+    };
+
+    let violations = evaluate_linkage(&mut manifest, args, &config)?;
+    // Count everything before it's filtered for display
+    let summary = LinkageSummary::new(&manifest, &violations, skipped);
+    if let Some(baseline) = &args.write_baseline {
+        write_baseline(&manifest, baseline)?;
+    }
+    if args.only_problems {
+        retain_problems(&mut manifest);
+    }
+
+    match args.format {
+        LinkageFormat::Human if args.only_problems && manifest.assets.is_empty() => {
+            eprintln!("No linkage problems found.");
+        }
+        LinkageFormat::Human if args.explain.is_some() => {
+            let query = args.explain.as_deref().unwrap_or_default();
+            eprintln!("{}", ExplainLinkageDisplay(&manifest, query));
+        }
+        LinkageFormat::Human if args.histogram => {
+            let style = if args.ascii {
+                TableStyle::Ascii
+            } else {
+                TableStyle::detect()
+            };
+            eprintln!("{}", HistogramLinkageDisplay(&manifest, style));
+        }
+        LinkageFormat::Json if args.histogram => {
+            let histograms = manifest
+                .assets
+                .iter()
+                .filter_map(|(id, asset)| Some((id, asset.linkage.as_ref()?.histogram())))
+                .collect::<SortedMap<_, _>>();
+            let string = serde_json::to_string_pretty(&histograms).unwrap();
+            println!("{string}");
+        }
+        LinkageFormat::Human => {
+            let style = if args.ascii {
+                TableStyle::Ascii
+            } else {
+                TableStyle::detect()
+            };
+            // Only the human-readable report is filtered, so it's fine to do in place
+            if args.exclude_system {
+                for linkage in manifest
+                    .assets
+                    .values_mut()
+                    .filter_map(|a| a.linkage.as_mut())
+                {
+                    hide_system_libraries(linkage);
+                }
+                eprintln!("(system libraries are hidden; pass --include-system to show them)\n");
+            }
+            match args.group_by {
+                LinkageGroupBy::Binary => eprintln!("{}", LinkageDisplay(&manifest, style)),
+                LinkageGroupBy::Artifact => {
+                    eprintln!("{}", ArtifactLinkageDisplay(&manifest, style))
+                }
+                LinkageGroupBy::Manifest => {
+                    eprintln!("{}", ManifestLinkageDisplay(&manifest, style))
+                }
+                LinkageGroupBy::Footprint => {
+                    eprintln!("{}", FootprintLinkageDisplay(&manifest, style))
+                }
+            }
+        }
+        LinkageFormat::Markdown => {
+            println!("{}", MarkdownLinkageDisplay(&manifest));
+            println!("{summary}");
+        }
+        LinkageFormat::Json => {
+            let string = serde_json::to_string_pretty(&manifest).unwrap();
+            println!("{string}");
+        }
+        LinkageFormat::JsonSummary => {
+            let string = serde_json::to_string_pretty(&summary).unwrap();
+            println!("{string}");
+        }
+        LinkageFormat::Sarif => {
+            let log = SarifLog::new(&manifest, &violations, &config);
+            let string = serde_json::to_string_pretty(&log).unwrap();
+            println!("{string}");
+        }
+        LinkageFormat::Csv => {
+            print!("{}", CsvLinkageDisplay(&manifest));
+        }
+    }
+    if args.format == LinkageFormat::Human {
+        eprintln!("{summary}");
+    }
+
+    if !violations.is_empty() {
+        return Err(DistError::LinkageViolations { violations }.into());
+    }
+
+    Ok(())
+}
+
+/// The libraries one build of a binary links that another build of it doesn't
+#[derive(Serialize)]
+struct BuildDelta<'a> {
+    /// The first build
+    binary: &'a Utf8Path,
+    /// The build it's compared to
+    other_binary: &'a Utf8Path,
+    /// Libraries only the first build links
+    only_binary: Vec<&'a String>,
+    /// Libraries only the other build links
+    only_other_binary: Vec<&'a String>,
+}
+
+impl BuildDelta<'_> {
+    /// Whether the builds link different libraries
+    fn differs(&self) -> bool {
+        !self.only_binary.is_empty() || !self.only_other_binary.is_empty()
+    }
+}
+
+/// Compare the linkage of two builds of the same binary (e.g. its debug and release
+/// builds), and fail if they link different libraries
+///
+/// The builds are either two paths to binaries, which are analyzed as builds for the
+/// --target (or the host, if none is given), or the ids of two artifacts in the
+/// workspace. Each binary the artifacts both ship is compared, as built for that
+/// artifact's target.
+///
+/// Like consistent-targets, libraries are compared by canonical name, so a library
+/// moving or changing version doesn't count as a difference.
+fn compare_builds(
+    cfg: &Config,
+    args: &LinkageArgs,
+    binary: &Utf8Path,
+    other_binary: &Utf8Path,
+) -> Result<()> {
+    // Apply the workspace's linkage policy if we're in one, but we don't need to be
+    let dist = gather_work(cfg).ok().map(|(dist, _)| dist);
+    let config = dist
+        .as_ref()
+        .map(|dist| dist.linkage.clone())
+        .unwrap_or_default();
+    let config = linkage_policy(config, args)?;
+
+    let are_files = binary.is_file() || other_binary.is_file();
+    let artifact_builds = dist.as_ref().filter(|_| !are_files).and_then(|dist| {
+        let builds = |id: &Utf8Path| artifact_binaries(dist, id.as_str());
+        Some((builds(binary)?, builds(other_binary)?))
+    });
+    let (pairs, per_binary) = if let Some((builds, other_builds)) = artifact_builds {
+        let mut pairs = vec![];
+        for (name, build) in &builds {
+            match other_builds.get(name) {
+                Some(other_build) => pairs.push((build.clone(), other_build.clone())),
+                None => eprintln!("{other_binary} doesn't ship {name}; skipping comparison"),
+            }
+        }
+        for name in other_builds
+            .keys()
+            .filter(|name| !builds.contains_key(*name))
+        {
+            eprintln!("{binary} doesn't ship {name}; skipping comparison");
+        }
+        (pairs, true)
+    } else {
+        let target = match cfg.targets.as_slice() {
+            [] => get_host_target(cargo()?)?.host_target,
+            [target] => target.clone(),
+            targets => {
+                return Err(DistError::LinkageCompareTargets {
+                    targets: targets.join(", "),
+                }
+                .into())
+            }
+        };
+        let build = |path: &Utf8Path| (path.to_owned(), target.clone());
+        (vec![(build(binary), build(other_binary))], false)
+    };
+
+    // Every build is checked in one go, so the libraries they share are only looked up once
+    let mut binaries = vec![];
+    let builds = pairs
+        .iter()
+        .flat_map(|(build, other_build)| [build, other_build]);
+    for (idx, (path, target)) in builds.enumerate() {
+        if !path.exists() {
+            return Err(DistError::LinkageBinaryMissing { path: path.clone() }.into());
+        }
+        binaries.push(BinaryToCheck {
+            id: idx.to_string(),
+            name: path.to_string(),
+            path: path.clone(),
+            target: target.clone(),
+            artifact_id: None,
+            system: String::new(),
+            checksums: SortedMap::new(),
+            bundled_files: None,
+        });
+    }
+    let options = LinkageOptions {
+        config,
+        sysroot: args.sysroot.clone(),
+        ..LinkageOptions::default()
+    };
+    let mut manifest = DistManifest::new(vec![], Default::default());
+    compute_linkage(&mut manifest, &binaries, &options)?;
+    let libraries = |binary: &BinaryToCheck| {
+        let linkage = manifest.assets[&binary.id].linkage.as_ref();
+        linkage.map(library_names).unwrap_or_default()
+    };
+    let libraries_by_pair = binaries
+        .chunks(2)
+        .map(|pair| (libraries(&pair[0]), libraries(&pair[1])))
+        .collect::<Vec<_>>();
+    let deltas = pairs
+        .iter()
+        .zip(&libraries_by_pair)
+        .map(
+            |(((binary, _), (other_binary, _)), (libraries_of_binary, libraries_of_other))| {
+                BuildDelta {
+                    binary,
+                    other_binary,
+                    only_binary: libraries_of_binary.difference(libraries_of_other).collect(),
+                    only_other_binary: libraries_of_other.difference(libraries_of_binary).collect(),
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    if args.format == LinkageFormat::Human {
+        for delta in &deltas {
+            let (binary, other_binary) = (delta.binary, delta.other_binary);
+            if delta.differs() {
+                let describe = |libraries: &[&String]| {
+                    if libraries.is_empty() {
+                        "(nothing)".to_owned()
+                    } else {
+                        let libraries = libraries.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+                        libraries.join(", ")
+                    }
+                };
+                let only_binary = describe(&delta.only_binary);
+                let only_other_binary = describe(&delta.only_other_binary);
+                eprintln!("only {binary} links: {only_binary}");
+                eprintln!("only {other_binary} links: {only_other_binary}");
+            } else {
+                eprintln!("{binary} and {other_binary} link the same libraries");
+            }
+        }
+    } else if per_binary {
+        let string = serde_json::to_string_pretty(&deltas).unwrap();
+        println!("{string}");
+    } else {
+        let string = serde_json::to_string_pretty(&deltas[0]).unwrap();
+        println!("{string}");
+    }
+
+    let mut violations = deltas
+        .iter()
+        .filter(|delta| delta.differs())
+        .map(|delta| DistError::LinkageBuildsDiffer {
+            binary: delta.binary.to_owned(),
+            other_binary: delta.other_binary.to_owned(),
+        })
+        .collect::<Vec<_>>();
+    match violations.len() {
+        0 => Ok(()),
+        1 => Err(violations.remove(0).into()),
+        _ => Err(DistError::LinkageViolations { violations }.into()),
+    }
+}
+
+/// Find where each binary an artifact ships was built, and the target it was built for,
+/// if the artifact exists
+fn artifact_binaries(
+    dist: &DistGraph,
+    id: &str,
+) -> Option<SortedMap<String, (Utf8PathBuf, String)>> {
+    let artifact = dist.artifacts.iter().find(|artifact| artifact.id == id)?;
+    let target = artifact.target_triples.first()?;
+    let dir = dist.dist_dir.join(format!("{id}-{target}"));
+    let binaries = artifact
+        .required_binaries
+        .iter()
+        .map(|(bin_idx, relpath)| {
+            let name = dist.binary(*bin_idx).name.clone();
+            (name, (dir.join(relpath), target.clone()))
+        })
+        .collect();
+    Some(binaries)
+}
+
+/// How one binary's linkage changed between two reports
+#[derive(Default, Serialize)]
+struct LinkageDiff {
+    /// Libraries only the new report links
+    added: Vec<String>,
+    /// Libraries only the old report links
+    removed: Vec<String>,
+    /// Libraries both reports link, but from different paths (e.g. a new version)
+    changed: Vec<LibraryChange>,
+}
+
+impl LinkageDiff {
+    /// Whether nothing changed at all
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A library that's linked from different paths in two reports
+#[derive(Serialize)]
+struct LibraryChange {
+    /// The library's canonical name
+    library: String,
+    /// Where the old report links it from
+    old: Vec<String>,
+    /// Where the new report links it from
+    new: Vec<String>,
+}
+
+/// Compare two saved linkage reports (e.g. the previous release's and this one's), and
+/// fail if any binary links a library it didn't before
+///
+/// Binaries are matched up by name and target, and libraries by canonical name, so a
+/// library that moved or changed version is reported as changed rather than added.
+/// Reports saved with --write-baseline don't record binary names, so their binaries are
+/// matched up in order instead.
+fn diff_reports(args: &LinkageArgs, old: &Utf8Path, new: &Utf8Path) -> Result<()> {
+    let load = |path: &Utf8Path| -> Result<_> {
+        let file = SourceFile::load_local(path)?;
+        Ok(libraries_by_binary(&manifest_from_json(&file)?))
+    };
+    let old_binaries = load(old)?;
+    let new_binaries = load(new)?;
+
+    let no_libraries = SortedMap::new();
+    let mut diffs = SortedMap::new();
+    let binaries = old_binaries
+        .keys()
+        .chain(new_binaries.keys())
+        .collect::<SortedSet<_>>();
+    for binary in binaries {
+        let diff = diff_libraries(
+            old_binaries.get(binary).unwrap_or(&no_libraries),
+            new_binaries.get(binary).unwrap_or(&no_libraries),
+        );
+        if !diff.is_empty() {
+            diffs.insert(binary, diff);
+        }
+    }
+
+    if args.format == LinkageFormat::Human {
+        if diffs.is_empty() {
+            eprintln!("{old} and {new} have the same linkage");
+        }
+        for (binary, diff) in &diffs {
+            eprintln!("{binary}:");
+            for library in &diff.added {
+                eprintln!("  + {library}");
+            }
+            for library in &diff.removed {
+                eprintln!("  - {library}");
+            }
+            for change in &diff.changed {
+                let old = change.old.join(", ");
+                let new = change.new.join(", ");
+                eprintln!("  ~ {}: {old} -> {new}", change.library);
+            }
+        }
+    } else {
+        let string = serde_json::to_string_pretty(&diffs).unwrap();
+        println!("{string}");
+    }
+
+    if diffs.values().any(|diff| !diff.added.is_empty()) {
+        return Err(DistError::LinkageLibrariesAdded {
+            old: old.to_owned(),
+            new: new.to_owned(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// For each binary (and its targets) in a report, the paths it links each library from,
+/// keyed by the library's canonical name
+fn libraries_by_binary(
+    manifest: &DistManifest,
+) -> SortedMap<String, SortedMap<String, SortedSet<String>>> {
+    let mut binaries = SortedMap::<String, SortedMap<String, SortedSet<String>>>::new();
+    for asset in manifest.assets.values() {
+        let Some(linkage) = &asset.linkage else {
+            continue;
+        };
+        let binary = if asset.target_triples.is_empty() {
+            asset.name.clone()
+        } else {
+            format!("{} ({})", asset.name, asset.target_triples.join(", "))
+        };
+        let libraries = binaries.entry(binary).or_default();
+        for library in linkage.libraries() {
+            let (name, _) = split_library_name(&library.path);
+            libraries
+                .entry(name)
+                .or_default()
+                .insert(library.path.clone());
+        }
+    }
+    binaries
+}
+
+/// Work out how a binary's libraries changed from one report to another
+fn diff_libraries(
+    old: &SortedMap<String, SortedSet<String>>,
+    new: &SortedMap<String, SortedSet<String>>,
+) -> LinkageDiff {
+    let mut diff = LinkageDiff::default();
+    for (library, new_paths) in new {
+        match old.get(library) {
+            None => diff.added.push(library.clone()),
+            Some(old_paths) if old_paths != new_paths => diff.changed.push(LibraryChange {
+                library: library.clone(),
+                old: old_paths.iter().cloned().collect(),
+                new: new_paths.iter().cloned().collect(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .keys()
+        .filter(|library| !new.contains_key(*library))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// An external tool the linkage check shells out to, and whether this machine has it
+#[derive(Serialize)]
+struct ToolProbe {
+    /// The command
+    name: &'static str,
+    /// What the linkage check uses it for
+    used_for: &'static str,
+    /// The first line of its version output, if it could be run at all
+    version: Option<String>,
+}
+
+/// A parser built into cargo-dist, so always available
+#[derive(Serialize)]
+struct ParserInfo {
+    /// The crate that does the parsing
+    name: &'static str,
+    /// What the linkage check uses it for
+    used_for: &'static str,
+}
+
+/// Everything the linkage check might rely on, to explain why two machines disagree
+#[derive(Serialize)]
+struct ToolsReport {
+    /// External tools, which may or may not be installed
+    tools: Vec<ToolProbe>,
+    /// Built-in parsers, which the tools can fall back on
+    parsers: &'static [ParserInfo],
+}
+
+/// The external tools the linkage check can use; without them some information is lost
+/// or comes from a fallback
+const PROBED_TOOLS: &[(&str, &str)] = &[
+    (
+        "ldd",
+        "resolving Linux libraries (falls back to reading the ELF directly)",
+    ),
+    ("dpkg", "finding the apt package that provides a library"),
+    (
+        "rpm",
+        "finding the rpm package that provides a library (Fedora, RHEL, SUSE)",
+    ),
+    (
+        "pacman",
+        "finding the pacman package that provides a library (Arch)",
+    ),
+    (
+        "apk",
+        "finding the apk package that provides a library (Alpine)",
+    ),
+    (
+        "brew",
+        "finding the license of a Homebrew package (with --licenses)",
+    ),
+];
+
+/// The parsers compiled into cargo-dist
+const BUILTIN_PARSERS: &[ParserInfo] = &[
+    ParserInfo {
+        name: "goblin",
+        used_for: "ELF, PE, and Mach-O headers and symbols",
+    },
+    ParserInfo {
+        name: "mach_object",
+        used_for: "Mach-O load commands and universal binaries",
+    },
+];
+
+/// Run a tool's `--version`, returning the first line it prints
+///
+/// Some tools (like musl's ldd) print this to stderr, and exit unsuccessfully.
+fn tool_version(name: &str) -> Option<String> {
+    let output = Cmd::new(name, format!("get the version of {name}"))
+        .arg("--version")
+        .check(false)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_owned)
+}
+
+/// Report which of the tools the linkage check uses are available here, and their versions
+fn probe_tools(args: &LinkageArgs) {
+    let tools = PROBED_TOOLS
+        .iter()
+        .map(|&(name, used_for)| ToolProbe {
+            name,
+            used_for,
+            version: tool_version(name),
+        })
+        .collect();
+    let report = ToolsReport {
+        tools,
+        parsers: BUILTIN_PARSERS,
+    };
+
+    if args.format != LinkageFormat::Human {
+        let string = serde_json::to_string_pretty(&report).unwrap();
+        println!("{string}");
+        return;
+    }
+
+    let style = if args.ascii {
+        TableStyle::Ascii
+    } else {
+        TableStyle::detect()
+    };
+    let mut table = Table::new();
+    table
+        .load_preset(style.preset())
+        .set_header(vec!["Tool", "Version", "Used for"]);
+    for tool in &report.tools {
+        table.add_row(vec![
+            tool.name,
+            tool.version.as_deref().unwrap_or("not found"),
+            tool.used_for,
+        ]);
+    }
+    for parser in report.parsers {
+        table.add_row(vec![parser.name, "built in", parser.used_for]);
+    }
+    eprintln!("{table}");
+}
+
+/// Write the linkage of every binary as a JSON array, which [`manifest_from_json`][]
+/// can read back in
+///
+/// Binaries are in order of their ids, and libraries are already sorted, so the file
+/// only changes when the linkage does.
+fn write_baseline(manifest: &DistManifest, path: &Utf8Path) -> Result<()> {
+    let linkages = manifest
+        .assets
+        .values()
+        .filter_map(|asset| asset.linkage.as_ref())
+        .collect::<Vec<_>>();
+    let mut contents = serde_json::to_string_pretty(&linkages).unwrap();
+    contents.push('\n');
+    LocalAsset::write_new(&contents, path)?;
+    Ok(())
+}
+
+/// Load linkage from json, which may either be a whole DistManifest (like the one
+/// `cargo dist build --output-format=json` prints) or just an array of Linkages
+fn manifest_from_json(file: &SourceFile) -> Result<DistManifest> {
+    let value: serde_json::Value = file.deserialize_json()?;
+    if !value.is_array() {
+        return Ok(file.deserialize_json()?);
+    }
+
+    // Bare linkage doesn't know what binary it's for, so make up some names
+    let linkages: Vec<Linkage> = file.deserialize_json()?;
+    let mut manifest = DistManifest::new(vec![], Default::default());
+    for (idx, linkage) in linkages.into_iter().enumerate() {
+        let id = format!("linkage-{idx}");
+        manifest.assets.insert(
+            id.clone(),
+            AssetInfo {
+                id,
+                name: format!("binary #{idx}"),
+                system: String::new(),
+                target_triples: vec![],
+                linkage: Some(linkage),
+            },
+        );
+    }
+    Ok(manifest)
+}
+
+/// A binary for [`compute_linkage`][] to check
+#[derive(Clone, Debug)]
+pub struct BinaryToCheck {
+    /// The id to record the binary under in the manifest's assets
+    pub id: AssetId,
+    /// The binary's filename
+    pub name: String,
+    /// Where the binary is
+    pub path: Utf8PathBuf,
+    /// The target the binary was built for
+    pub target: String,
+    /// The artifact that ships the binary, if any
+    pub artifact_id: Option<ArtifactId>,
+    /// The system the binary was built on
+    pub system: SystemId,
+    /// Checksums the binary is expected to have, keyed by algorithm ("sha256" or "sha512")
+    pub checksums: SortedMap<String, String>,
+    /// The (lowercased) names of the files the binary ships alongside, for telling which
+    /// libraries are bundled; if not given, they're read from the binary's directory
+    pub bundled_files: Option<SortedSet<String>>,
+}
+
+/// Settings for [`compute_linkage`][]
+#[derive(Clone)]
+pub struct LinkageOptions<'a> {
+    /// Rules and policies for the check, as in `[workspace.metadata.dist.linkage]`
+    pub config: LinkageConfig,
+    /// Resolve Linux libraries against this root filesystem instead of the host's
+    pub sysroot: Option<Utf8PathBuf>,
+    /// Look up the license of each library's package, which means asking the package
+    /// manager about each one
+    pub licenses: bool,
+    /// Check each binary against its expected checksums before analyzing it
+    pub verify_checksums: bool,
+    /// Guess which libraries are statically linked into each binary from its symbols
+    pub scan_symbols: bool,
+    /// Guess which libraries each binary loads at runtime from the file names in it
+    pub scan_strings: bool,
+    /// Follow each library's own dependencies, recording the whole tree
+    pub deep: bool,
+    /// How many levels of libraries `deep` follows before cutting the tree off
+    pub max_depth: usize,
+    /// Find each library's package with this, instead of asking the host's package
+    /// managers with a [`HostPackageResolver`][]
+    pub resolver: Option<&'a (dyn PackageResolver + Sync)>,
+    /// How many binaries to check at once; by default, as many as there are CPUs
+    pub parallelism: Option<NonZeroUsize>,
+    /// Record binaries whose linkage can't be determined without any, with a warning,
+    /// instead of failing
+    pub allow_failures: bool,
+}
+
+impl Default for LinkageOptions<'_> {
+    fn default() -> Self {
+        Self {
+            config: LinkageConfig::default(),
+            sysroot: None,
+            licenses: false,
+            verify_checksums: false,
+            scan_symbols: false,
+            scan_strings: false,
+            deep: false,
+            max_depth: 8,
+            resolver: None,
+            parallelism: None,
+            allow_failures: false,
+        }
+    }
+}
+
+/// Compute the linkage of some binaries, adding each to the manifest as an asset
+///
+/// This is what `cargo dist linkage` does once it's worked out which binaries to check,
+/// so it can be used without a cargo-dist workspace. Policies like max-glibc aren't
+/// enforced here; that happens when the report is evaluated.
+pub fn compute_linkage(
+    manifest: &mut DistManifest,
+    binaries: &[BinaryToCheck],
+    options: &LinkageOptions,
+) -> DistResult<()> {
+    // Binaries are checked independently, and checking one is mostly waiting on files
+    // and package managers, so split them between a few threads
+    let workers = options
+        .parallelism
+        .or_else(|| std::thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+    // One resolver for the whole run, so each library is only looked up once
+    let host_resolver;
+    let resolver: &(dyn PackageResolver + Sync) = match options.resolver {
+        Some(resolver) => resolver,
+        None => {
+            host_resolver = HostPackageResolver::new(&options.config);
+            &host_resolver
+        }
+    };
+
+    // Every binary is read before any library is resolved, so the packages of all their
+    // libraries can be looked up at once (one `dpkg -S`, rather than one per binary)
+    let read = in_parallel(binaries.iter().collect(), workers, |binary| {
+        read_binary_to_check(binary, options)
+    });
+    let paths = read
+        .iter()
+        .flatten()
+        .flat_map(ReadBinary::library_paths)
+        .cloned()
+        .collect::<SortedSet<_>>();
+    resolver.prefetch(&Vec::from_iter(paths), options.sysroot.as_deref());
+    let linkages = in_parallel(
+        binaries.iter().zip(read).collect(),
+        workers,
+        |(binary, read)| read.and_then(|read| binary_linkage(binary, read, options, resolver)),
+    );
+
+    for (binary, linkage) in binaries.iter().zip(linkages) {
+        let linkage = match linkage {
+            Ok(linkage) => Some(linkage),
+            Err(e) if options.allow_failures => {
+                warn!(
+                    "couldn't determine the linkage of {}, so installers that depend on it \
+                    (Homebrew formulae, debs...) won't list its dependencies: {e}",
+                    binary.name
+                );
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        manifest.assets.insert(
+            binary.id.clone(),
+            AssetInfo {
+                id: binary.id.clone(),
+                name: binary.name.clone(),
+                system: binary.system.clone(),
+                linkage,
+                target_triples: vec![binary.target.clone()],
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Run `f` on every item, split between up to `workers` threads, keeping the items' order
+fn in_parallel<T: Send, R: Send>(
+    items: Vec<T>,
+    workers: usize,
+    f: impl Fn(T) -> R + Sync,
+) -> Vec<R> {
+    let chunk_size = items.len().div_ceil(workers).max(1);
+    let mut items = items.into_iter().peekable();
+    let mut chunks = vec![];
+    while items.peek().is_some() {
+        chunks.push(items.by_ref().take(chunk_size).collect::<Vec<_>>());
+    }
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Verify a binary's checksums, then read it and find its libraries, for
+/// [`compute_linkage`][]
+fn read_binary_to_check(
+    binary: &BinaryToCheck,
+    options: &LinkageOptions,
+) -> DistResult<ReadBinary> {
+    if options.verify_checksums {
+        for (algorithm, expected) in &binary.checksums {
+            verify_checksum(&binary.path, algorithm, expected)?;
+        }
+    }
+    read_binary(
+        binary.path.as_std_path(),
+        &binary.target,
+        options.sysroot.as_deref(),
+    )
+}
+
+/// Compute the linkage of a single binary that's been read, for [`compute_linkage`][]
+fn binary_linkage(
+    binary: &BinaryToCheck,
+    read: ReadBinary,
+    options: &LinkageOptions,
+    resolver: &dyn PackageResolver,
+) -> DistResult<Linkage> {
+    let mut linkage = finish_linkage(
+        read,
+        &binary.target,
+        &options.config,
+        options.sysroot.as_deref(),
+        resolver,
+        binary.bundled_files.as_ref(),
+    )?;
+    linkage.artifact_id = binary.artifact_id.clone();
+    if options.licenses {
+        resolver.add_licenses(&mut linkage, options.sysroot.as_deref());
+    }
+    if options.scan_symbols {
+        linkage.static_bundled = scan_bundled_libraries(binary.path.as_std_path())?;
+    }
+    if options.scan_strings {
+        linkage.runtime_loaded = scan_runtime_loaded(binary.path.as_std_path(), &linkage)?;
+    }
+    if options.deep {
+        linkage.dependency_tree = dependency_tree(
+            binary.path.as_std_path(),
+            options.sysroot.as_deref(),
+            options.max_depth,
+        )?;
+    }
+    Ok(linkage)
+}
+
+/// Check a file against a checksum, failing if it doesn't match
+///
+/// Algorithms we don't know how to compute are skipped.
+fn verify_checksum(path: &Utf8Path, algorithm: &str, expected: &str) -> DistResult<()> {
+    let style = match algorithm {
+        "sha256" => ChecksumStyle::Sha256,
+        "sha512" => ChecksumStyle::Sha512,
+        _ => return Ok(()),
+    };
+    let actual = generate_checksum(&style, path)?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(DistError::LinkageChecksumMismatch {
+            path: path.to_owned(),
+            algorithm: algorithm.to_owned(),
+            expected: expected.trim().to_owned(),
+            actual,
+        })
+    }
+}
+
+/// Check an artifact's archive against the checksum file written alongside it, if any
+///
+/// The binaries we check were packaged into the archive, so if it doesn't match its
+/// checksum they may not be the ones that were released either.
+fn verify_artifact_checksum(dist: &DistGraph, artifact: &Artifact) -> DistResult<()> {
+    let Some(checksum) = artifact.checksum.map(|idx| dist.artifact(idx)) else {
+        return Ok(());
+    };
+    let ArtifactKind::Checksum(checksum) = &checksum.kind else {
+        return Ok(());
+    };
+    let Some(checksum_path) = &checksum.dest_path else {
+        return Ok(());
+    };
+    if !checksum_path.exists() || !artifact.file_path.exists() {
+        return Ok(());
+    }
+    // These are in sha256sum's format: `<checksum> *<filename>`
+    let contents = fs::read_to_string(checksum_path)?;
+    let Some(expected) = contents.split_whitespace().next() else {
+        return Ok(());
+    };
+    verify_checksum(&artifact.file_path, checksum.checksum.ext(), expected)
+}
+
+/// Assuming someone just ran `cargo dist build` on the current machine,
+/// compute the linkage by checking binaries in the temp to-be-zipped dirs.
+///
+/// Returns how many binaries were missing, and so skipped. What else happens
+/// when something is missing is up to `on_missing`.
+fn compute_linkage_assuming_local_build(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    cfg: &Config,
+    options: &LinkageOptions,
+    on_missing: OnMissing,
+) -> DistResult<usize> {
+    let targets = &cfg.targets;
+    let artifacts = &dist.artifacts;
+    let dist_dir = &dist.dist_dir;
+
+    let mut binaries = vec![];
+    let mut skipped = 0;
+    for target in targets {
+        let artifacts: Vec<Artifact> = artifacts
+            .clone()
+            .into_iter()
+            .filter(|r| r.target_triples.contains(target))
+            .collect();
+
+        if artifacts.is_empty() {
+            on_missing.handle(DistError::LinkageNoArtifact {
+                target: target.clone(),
+            })?;
+            continue;
+        }
+
+        for artifact in artifacts {
+            if options.verify_checksums {
+                verify_artifact_checksum(dist, &artifact)?;
+            }
+            let path = Utf8PathBuf::from(&dist_dir).join(format!("{}-{target}", artifact.id));
+
+            for (bin_idx, binary_relpath) in artifact.required_binaries {
+                let bin = dist.binary(bin_idx);
+                let bin_path = path.join(binary_relpath);
+                if !bin_path.exists() {
+                    on_missing.handle(DistError::LinkageBinaryMissing { path: bin_path })?;
+                    skipped += 1;
+                } else {
+                    binaries.push(BinaryToCheck {
+                        id: bin.id.clone(),
+                        name: bin.name.clone(),
+                        path: bin_path,
+                        target: target.clone(),
+                        artifact_id: Some(artifact.id.clone()),
+                        system: dist.system_id.clone(),
+                        // Binaries aren't checksummed individually, only their archives
+                        checksums: SortedMap::new(),
+                        bundled_files: None,
+                    });
+                }
+            }
+        }
+    }
+
+    compute_linkage(manifest, &binaries, options)?;
+    Ok(skipped)
+}
+
+/// Work out the whole tree of libraries a binary loads, following each library's own
+/// dependencies as far as they can be found on disk (or within the sysroot)
+///
+/// Each library's dependencies are only listed the first time it appears, which also
+/// keeps cycles from going on forever. Libraries `max_depth` levels down aren't followed
+/// any further.
+fn dependency_tree(
+    path: &Path,
+    sysroot: Option<&Utf8Path>,
+    max_depth: usize,
+) -> DistResult<Vec<DependencyNode>> {
+    let buf = read_binary(path)?;
+    let root = sysroot.unwrap_or(Utf8Path::new("/"));
+    let library_dependencies = |library: &str| {
+        // Libraries we can't read, like ones only known by name or in macOS's shared
+        // cache, are leaves
+        if !library.starts_with('/') {
+            return vec![];
+        }
+        fs::read(root.join(library.trim_start_matches('/')))
+            .map(|buf| direct_dependencies(&buf, root))
+            .unwrap_or_default()
+    };
+    let mut seen = SortedSet::new();
+    let tree = direct_dependencies(&buf, root)
+        .into_iter()
+        .map(|library| dependency_node(library, &library_dependencies, &mut seen, 1, max_depth))
+        .collect();
+    Ok(tree)
+}
+
+/// Make a library's node in a dependency tree, including its own dependencies
+fn dependency_node(
+    library: String,
+    library_dependencies: &dyn Fn(&str) -> Vec<String>,
+    seen: &mut SortedSet<String>,
+    depth: usize,
+    max_depth: usize,
+) -> DependencyNode {
+    if !seen.insert(library.clone()) {
+        return DependencyNode {
+            library,
+            dependencies: vec![],
+            depth_limited: false,
+            repeated: true,
+        };
+    }
+
+    let dependencies = library_dependencies(&library);
+    if depth >= max_depth && !dependencies.is_empty() {
+        // Let it be listed again deeper down, where it might not be cut off
+        seen.remove(&library);
+        return DependencyNode {
+            library,
+            dependencies: vec![],
+            depth_limited: true,
+            repeated: false,
+        };
+    }
+    let dependencies = dependencies
+        .into_iter()
+        .map(|dependency| {
+            dependency_node(dependency, library_dependencies, seen, depth + 1, max_depth)
+        })
+        .collect();
+    DependencyNode {
+        library,
+        dependencies,
+        depth_limited: false,
+        repeated: false,
+    }
+}
+
+/// Get the libraries a binary or library loads itself, found within `root` if possible
+///
+/// Mach-O and PE files name their libraries in a way that's already as resolved as it
+/// gets without a real loader; for universal binaries, the first architecture is used.
+fn direct_dependencies(buf: &[u8], root: &Utf8Path) -> Vec<String> {
+    let libraries = |libs: &[&str]| -> Vec<String> {
+        libs.iter()
+            // goblin lists a Mach-O's own install name as "self"
+            .filter(|lib| **lib != "self")
+            .map(|lib| lib.to_string())
+            .collect()
+    };
+    match Object::parse(buf) {
+        Ok(Object::Elf(elf)) => resolve_elf_libraries(&elf, root)
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect(),
+        Ok(Object::Mach(Mach::Binary(macho))) => libraries(&macho.libs),
+        Ok(Object::Mach(Mach::Fat(fat))) => match fat.get(0) {
+            Ok(SingleArch::MachO(macho)) => libraries(&macho.libs),
+            _ => vec![],
+        },
+        Ok(Object::PE(pe)) => libraries(&pe.libraries),
+        _ => vec![],
+    }
+}
+
+/// Get the libraries any ELF, Mach-O or PE binary imports, for targets with no specific
+/// support (like FreeBSD, illumos or windows-gnu)
+///
+/// Nothing is looked up on the host, so ELF libraries are only known by name.
+fn do_generic(object: &Object, buf: &[u8]) -> DistResult<Vec<(Option<String>, LinkedLibraries)>> {
+    match object {
+        Object::Elf(elf) => {
+            let libraries = elf.libraries.iter().map(|name| name.to_string()).collect();
+            Ok(vec![(None, without_reasons(libraries))])
+        }
+        Object::Mach(_) => Ok(do_otool(buf)),
+        Object::PE(_) => Ok(vec![(None, do_pe(object, buf)?)]),
+        _ => Err(DistError::LinkageCheckUnsupportedBinary {}),
+    }
+}
+
+/// Get the linkage for a single binary
+///
+/// The path doesn't need to be UTF-8; it's only converted (lossily) to show it in the
+/// report or errors. The resolver decides which package each library comes from;
+/// outside of tests, that's [`HostPackageResolver`][].
+///
+/// `bundled_files` are the (lowercased) names of the files the binary ships alongside.
+/// If they aren't given, the files next to it on disk are used.
+pub fn determine_linkage(
+    path: &Path,
+    target: &str,
+    config: &LinkageConfig,
+    sysroot: Option<&Utf8Path>,
+    resolver: &dyn PackageResolver,
+    bundled_files: Option<&SortedSet<String>>,
+) -> DistResult<Linkage> {
+    let binary = read_binary(path, target, sysroot)?;
+    resolver.prefetch(
+        &binary.library_paths().cloned().collect::<Vec<_>>(),
+        sysroot,
+    );
+    finish_linkage(binary, target, config, sysroot, resolver, bundled_files)
+}
+
+/// A binary that's been read and had its libraries found, but not yet sorted into
+/// categories or attributed to packages
+///
+/// Finding which package a library comes from can mean asking a package manager, which
+/// is much faster for many libraries at once, so every binary of a run is read before
+/// any of their libraries are resolved.
+struct ReadBinary {
+    /// The path the binary was requested by, which may be a symlink
+    requested_path: PathBuf,
+    /// Where that symlink points, if it is one
+    symlink_target: Option<String>,
+    /// The start of the (decompressed) binary, which identifies its format
+    header: Vec<u8>,
+    /// The whole (decompressed) binary
+    buf: Vec<u8>,
+    /// The libraries each slice links, from [`determine_linkage`][]'s per-target readers
+    slices: Vec<(Option<String>, LinkedLibraries)>,
+    /// The name an ELF binary asks for each library by, keyed by where it was found
+    needed_names: SortedMap<String, String>,
+    /// A warning about how the libraries were found, if there is one
+    ldd_warning: Option<String>,
+    /// The functions a WASM module imports, by module; it has no libraries
+    wasm_imports: Option<Vec<(String, String)>>,
+}
+
+impl ReadBinary {
+    /// Every library any slice of the binary links
+    fn library_paths(&self) -> impl Iterator<Item = &String> {
+        self.slices
+            .iter()
+            .flat_map(|(_, libraries)| libraries.iter().map(|(library, _)| library))
+    }
+}
+
+/// Read a binary and find the libraries it links, for [`determine_linkage`][]
+fn read_binary(path: &Path, target: &str, sysroot: Option<&Utf8Path>) -> DistResult<ReadBinary> {
+    // If we were handed a symlink, analyze the file it points to; the binary is still
+    // reported under the name it was requested by.
+    let requested_path = path;
+    let (path, symlink_target) = if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        let resolved = path.canonicalize()?;
+        let symlink_target = Some(resolved.to_string_lossy().into_owned());
+        (resolved, symlink_target)
+    } else {
+        (path.to_owned(), None)
+    };
+    let mut header = read_header(&path)?;
+    // Binaries compressed between pipeline stages are analyzed from a decompressed copy,
+    // which is cleaned up when we're done
+    let decompressed = match compression_format(&header) {
+        Some(format) => Some(decompress_binary(&path, format)?),
+        None => None,
+    };
+    let path = match &decompressed {
+        Some((_, decompressed)) => {
+            header = read_header(decompressed)?;
+            decompressed
+        }
+        None => &path,
+    };
+
+    // The target decides how the binary gets checked, so catch mislabeled binaries
+    // from their magic number, before parsing them as anything
+    if let Some(expected) = target_format(target) {
+        let found = magic_number(&header).and_then(binary_format);
+        if found != Some(expected) {
+            return Err(DistError::LinkageCheckFormatMismatch {
+                path: lossy_path(path),
+                target: target.to_owned(),
+                expected,
+                found: found.unwrap_or("unrecognized"),
+            });
+        }
+    }
+
+    // Everything below reads the same binary, so it's read and parsed just once here
+    let buf = fs::read(path)?;
+    let object = Object::parse(&buf)?;
+    debug_binary_structure(path, &object, &buf);
+    let mut ldd_warning = None;
+    let mut needed_names = SortedMap::new();
+    let mut wasm_imports = None;
+
+    let slices = match target {
+        // Can be run on any OS
+        "i686-apple-darwin"
+        | "x86_64-apple-darwin"
+        | "aarch64-apple-darwin"
+        | "universal2-apple-darwin" => do_otool(&buf),
+        "i686-unknown-linux-gnu"
+        | "x86_64-unknown-linux-gnu"
+        | "aarch64-unknown-linux-gnu"
+        | "i686-unknown-linux-musl"
+        | "x86_64-unknown-linux-musl"
+        | "aarch64-unknown-linux-musl" => {
+            let libraries = if let Some(sysroot) = sysroot {
+                // We find the libraries ourselves within a sysroot, so this works anywhere
+                do_elf_in_sysroot(&object, sysroot)?
+            } else if std::env::consts::OS == "linux" && elf_matches_host(&object) {
+                // The host's dynamic loader can tell us where each library really is,
+                // which is what finding its package needs
+                let (libraries, warning) = do_ldd(path, &object)?;
+                ldd_warning = warning;
+                libraries
+            } else {
+                // Otherwise we can still read what the binary asks for. The host's
+                // libraries aren't the ones it'd load, so they're only known by name.
+                ldd_warning = Some(if std::env::consts::OS == "linux" {
+                    format!(
+                        "checked on a {} host, which can't load this binary, so libraries \
+                        are only known by name",
+                        std::env::consts::ARCH
+                    )
+                } else {
+                    format!(
+                        "checked on {} without ldd, so libraries are only known by name",
+                        std::env::consts::OS
+                    )
+                });
+                do_elf_needed(&object)?
+                    .into_iter()
+                    .map(|name| (name.clone(), name))
+                    .collect()
+            };
+            let mut paths = vec![];
+            for (name, path) in libraries {
+                paths.push(path.clone());
+                needed_names.insert(path, name);
+            }
+            vec![(None, without_reasons(paths))]
+        }
+        // Can be run on any OS
+        "i686-pc-windows-msvc" | "x86_64-pc-windows-msvc" | "aarch64-pc-windows-msvc" => {
+            vec![(None, do_pe(&object, &buf)?)]
+        }
+        // Can be run on any OS
+        "wasm32-wasi" | "wasm32-wasip1" | "wasm32-wasip2" | "wasm32-unknown-unknown" => {
+            wasm_imports = Some(do_wasm(path, &buf)?);
+            vec![]
+        }
+        // Anything else still gets the list of libraries it imports, read off its format
+        _ => {
+            ldd_warning = Some(format!(
+                "cargo-dist has no specific support for {target}, so only the libraries the \
+                binary names are listed"
+            ));
+            do_generic(&object, &buf)?
+        }
+    };
+
+    Ok(ReadBinary {
+        requested_path: requested_path.to_owned(),
+        symlink_target,
+        header,
+        buf,
+        slices,
+        needed_names,
+        ldd_warning,
+        wasm_imports,
+    })
+}
+
+/// Sort a binary's libraries into categories, find their packages, and check everything
+/// else about it, for [`determine_linkage`][]
+fn finish_linkage(
+    binary: ReadBinary,
+    target: &str,
+    config: &LinkageConfig,
+    sysroot: Option<&Utf8Path>,
+    resolver: &dyn PackageResolver,
+    bundled_files: Option<&SortedSet<String>>,
+) -> DistResult<Linkage> {
+    let ReadBinary {
+        requested_path,
+        symlink_target,
+        header,
+        buf,
+        slices,
+        needed_names,
+        ldd_warning,
+        wasm_imports,
+    } = binary;
+    if let Some(imports) = wasm_imports {
+        let mut linkage = Linkage {
+            symlink_target,
+            ..Linkage::default()
+        };
+        record_format(&mut linkage, &header, target);
+        for (module, field) in imports {
+            linkage.wasm_imports.insert(Library {
+                source: Some(module),
+                ..Library::new(field)
+            });
+        }
+        return Ok(linkage);
+    }
+    let object = Object::parse(&buf)?;
+
+    // Windows searches the binary's own directory for DLLs before anywhere else,
+    // so anything shipped alongside it will take priority. Elsewhere we only care
+    // when asked to check that everything's bundled.
+    let bundled_files = if target.contains("windows") || config.require_bundled == Some(true) {
+        match bundled_files {
+            Some(files) => Some(files.clone()),
+            None => Some(sibling_files(&requested_path)?),
+        }
+    } else {
+        None
+    };
+    let mut linkage = build_linkage(
+        slices,
+        target,
+        config,
+        sysroot,
+        resolver,
+        bundled_files.as_ref(),
+    )?;
+    linkage.symlink_target = symlink_target;
+    record_format(&mut linkage, &header, target);
+    linkage.warnings.extend(ldd_warning);
+
+    let advisories = linkage
+        .libraries()
+        .flat_map(|library| library_advisories(&library.path, &config.advisories))
+        .collect::<Vec<_>>();
+    linkage.warnings.extend(advisories);
+    let home_libraries = linkage
+        .libraries()
+        .filter(|library| in_home_directory(&library.path))
+        .map(|library| {
+            format!(
+                "links {}, which is in a home directory, so it likely only exists on the \
+                build machine",
+                library.path
+            )
+        })
+        .collect::<Vec<_>>();
+    linkage.warnings.extend(home_libraries);
+    for runtime in instrumentation_runtimes(&linkage) {
+        linkage.warnings.push(format!(
+            "links the {runtime} runtime; release builds shouldn't be instrumented"
+        ));
+    }
+
+    linkage.hardening = determine_hardening(&object);
+    linkage.rpaths = determine_rpaths(&object);
+    for rpath in &linkage.rpaths {
+        if let Some(problem) = rpath_problem(rpath) {
+            linkage.warnings.push(format!(
+                "searches {rpath} for libraries, which {problem}, so it won't work on users' \
+                machines"
+            ));
+        }
+    }
+
+    let (build_id, debug_file) = determine_debug_info(&object, &buf);
+    linkage.build_id = build_id;
+    linkage.debug_file = debug_file;
+    linkage.soname = determine_soname(&object);
+    check_debug_symbols(&requested_path, &mut linkage)?;
+
+    if target.contains("linux-gnu") {
+        if let Some((version, symbol)) = determine_min_glibc(&object) {
+            linkage.min_glibc = Some(version);
+            linkage.min_glibc_symbol = symbol;
+        }
+    }
+    if target.contains("linux") {
+        let versions = determine_min_symbol_versions(&object);
+        if !versions.is_empty() {
+            add_min_symbol_versions(&mut linkage, &versions, &needed_names);
+        }
+    }
+
+    if target.contains("windows") {
+        linkage.pe_subsystem = determine_pe_subsystem(&object);
+        linkage.vc_redist = vc_redist_dlls(&linkage);
+        if !linkage.vc_redist.is_empty() {
+            linkage.warnings.push(format!(
+                "needs the Visual C++ Redistributable for {}, which doesn't come with Windows",
+                linkage.vc_redist.join(", ")
+            ));
+        }
+    }
+
+    if target.contains("apple-darwin") {
+        for (arch, version) in determine_min_macos(&object) {
+            let Some(arch) = arch else {
+                linkage.min_macos = Some(version);
+                continue;
+            };
+            let slice = linkage
+                .slices
+                .iter_mut()
+                .find(|slice| slice.arch.as_ref() == Some(&arch));
+            if let Some(slice) = slice {
+                slice.min_macos = Some(version);
+            }
+        }
+        // A universal binary runs on the oldest macOS any of its architectures does
+        if linkage.min_macos.is_none() {
+            linkage.min_macos = linkage
+                .slices
+                .iter()
+                .filter_map(|slice| slice.min_macos.clone())
+                .min_by_key(|version| parse_version(version));
+        }
+    }
+
+    if let Some((runtime, version)) = determine_cxx_runtime(&object, &linkage) {
+        linkage.cxx_runtime = Some(runtime);
+        linkage.cxx_runtime_version = version;
+    }
+
+    // A "static" binary that can dlopen things isn't as self-contained as it looks
+    if target.contains("linux") && linkage.is_static() {
+        let symbols = find_dynamic_loading(&object, &buf);
+        if !symbols.is_empty() {
+            linkage.warnings.push(format!(
+                "statically linked, but references {}, so it may still load libraries at runtime",
+                symbols.join(", ")
+            ));
+        }
+    }
+
+    Ok(linkage)
+}
+
+/// Look for debug symbols next to a binary, and check they came from the same build
+///
+/// Shipping symbols from a different build is easy to do by accident and makes crash
+/// reports useless, so a mismatch is a warning. The binary's build id must already be
+/// in the linkage.
+fn check_debug_symbols(path: &Path, linkage: &mut Linkage) -> DistResult<()> {
+    let Some(file_name) = path.file_name() else {
+        return Ok(());
+    };
+
+    let with_suffix = |name: &OsStr, suffix: &str| {
+        let mut name = name.to_owned();
+        name.push(suffix);
+        name
+    };
+
+    // foo.dSYM/Contents/Resources/DWARF/foo is itself a Mach-O, with the same LC_UUID
+    let dsym = path.with_file_name(with_suffix(file_name, ".dSYM"));
+    let dwarf = dsym.join("Contents/Resources/DWARF").join(file_name);
+    // The binary records where its PDB was, which is usually on the build machine,
+    // so look for a file with the same name next to the binary
+    let pdb_name = linkage
+        .debug_file
+        .as_deref()
+        .and_then(|pdb| pdb.rsplit(['/', '\\']).next())
+        .map(OsString::from)
+        .unwrap_or_else(|| with_suffix(path.file_stem().unwrap_or(file_name), ".pdb"));
+    let pdb = path.with_file_name(pdb_name);
+
+    let (symbols, symbols_id) = if dwarf.exists() {
+        let buf = fs::read(&dwarf)?;
+        // Not being able to read the dSYM's UUID isn't worth failing the whole check over
+        let uuid = match Object::parse(&buf) {
+            Ok(object) => determine_debug_info(&object, &buf).0,
+            Err(_) => None,
+        };
+        (dsym, uuid)
+    } else if pdb.exists() && pdb.extension() == Some(OsStr::new("pdb")) {
+        // Only the GUID is compared: the age in the PDB can legitimately be newer
+        let guid = pdb_guid(&fs::read(&pdb)?);
+        (pdb, guid)
+    } else {
+        return Ok(());
+    };
+
+    if let (Some(build_id), Some(symbols_id)) = (&linkage.build_id, &symbols_id) {
+        if !build_id.starts_with(symbols_id.as_str()) {
+            let symbols = symbols.display();
+            let warning = format!("debug symbols at {symbols} are from a different build");
+            linkage
+                .warnings
+                .push(format!("{warning} ({symbols_id}, expected {build_id})"));
+        }
+    }
+    linkage.debug_symbols = Some(symbols.to_string_lossy().into_owned());
+    Ok(())
+}
+
+/// Get the binary format used by a target we can check
+fn target_format(target: &str) -> Option<&'static str> {
+    if target.contains("apple-darwin") {
+        Some("Mach-O")
+    } else if target.contains("linux") {
+        Some("ELF")
+    } else if target.contains("windows") {
+        Some("PE")
+    } else if target.starts_with("wasm32") {
+        Some("WASM")
+    } else {
+        None
+    }
+}
+
+/// Sort the libraries of each slice of a binary into categories, and find their packages
+///
+/// The returned Linkage covers every slice; universal binaries also get a Linkage per slice.
+fn build_linkage(
+    slices: Vec<(Option<String>, LinkedLibraries)>,
+    target: &str,
+    config: &LinkageConfig,
+    sysroot: Option<&Utf8Path>,
+    resolver: &dyn PackageResolver,
+    bundled_files: Option<&SortedSet<String>>,
+) -> DistResult<Linkage> {
+    let mut linkage = Linkage::default();
+    for (arch, libraries) in slices {
+        let mut slice = Linkage {
+            arch,
+            ..Linkage::default()
+        };
+        for (library, reason) in libraries {
+            let (category, mut library) =
+                classify_library(library, target, &config.rules, sysroot, resolver)?;
+            library.reason = reason;
+            if let Some(bundled_files) = bundled_files {
+                library.location = Some(if target.contains("windows") {
+                    locate_dll(&library.path, bundled_files)
+                } else {
+                    locate_library(&library.path, category, bundled_files)
+                });
+            }
+            let bucket = match category {
+                LinkageCategory::System => &mut slice.system,
+                LinkageCategory::Homebrew => &mut slice.homebrew,
+                LinkageCategory::PublicUnmanaged => &mut slice.public_unmanaged,
+                LinkageCategory::Frameworks => &mut slice.frameworks,
+                LinkageCategory::Toolchain => &mut slice.toolchain,
+                LinkageCategory::LanguageRuntime => &mut slice.language_runtimes,
+                LinkageCategory::Graphics => &mut slice.graphics,
+                LinkageCategory::Other => &mut slice.other,
+            };
+            bucket.insert(library);
+        }
+        // The top-level linkage covers every slice, so consumers that don't
+        // care about architectures can ignore them
+        linkage.extend(&slice);
+        if slice.arch.is_some() {
+            linkage.slices.push(slice);
+        }
+    }
+    Ok(linkage)
+}
+
+/// Read enough of a file's header for its magic number (or a compression format's),
+/// and the class and byte order of ELF
+fn read_header(path: &Path) -> DistResult<Vec<u8>> {
+    let mut header = vec![];
+    File::open(path)?.take(6).read_to_end(&mut header)?;
+    Ok(header)
+}
+
+/// Work out whether a file is compressed from its header, and how
+fn compression_format(header: &[u8]) -> Option<&'static str> {
+    if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0]) {
+        Some("xz")
+    } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Some("zstd")
+    } else {
+        None
+    }
+}
+
+/// Decompress a binary into a temporary directory, returning the directory (which is
+/// deleted when dropped) and the decompressed binary's path
+///
+/// Some of the analysis, like running ldd, needs a real file rather than bytes in memory.
+/// The binary keeps its name, minus any compression extension.
+fn decompress_binary(path: &Path, format: &str) -> DistResult<(TempDir, PathBuf)> {
+    let file = File::open(path)?;
+    let mut decoder: Box<dyn Read> = match format {
+        "xz" => Box::new(xz2::read::XzDecoder::new(file)),
+        _ => Box::new(zstd::stream::read::Decoder::new(file)?),
+    };
+    let file_name = path.file_name().unwrap_or_default();
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("xz" | "zst" | "zstd") => path.file_stem().unwrap_or(file_name),
+        _ => file_name,
+    };
+
+    let dir = TempDir::new()?;
+    let decompressed = dir.path().join(name);
+    io::copy(&mut decoder, &mut File::create(&decompressed)?)?;
+    Ok((dir, decompressed))
+}
+
+/// Read a binary into memory, decompressing it if needed
+fn read_binary(path: &Path) -> DistResult<Vec<u8>> {
+    let buf = fs::read(path)?;
+    let mut decompressed = vec![];
+    match compression_format(&buf) {
+        Some("xz") => xz2::read::XzDecoder::new(&buf[..]).read_to_end(&mut decompressed)?,
+        Some(_) => zstd::stream::read::Decoder::new(&buf[..])?.read_to_end(&mut decompressed)?,
+        None => return Ok(buf),
+    };
+    Ok(decompressed)
+}
+
+/// Symbol prefixes that give away a statically linked C library
+///
+/// These are the libraries most often vendored into Rust binaries by `-sys` crates.
+const BUNDLED_LIBRARY_SYMBOLS: &[(&str, &[&str])] = &[
+    ("openssl", &["SSL_CTX_", "OPENSSL_", "EVP_"]),
+    ("bzip2", &["BZ2_"]),
+    ("zlib", &["deflate", "inflate"]),
+    ("sqlite", &["sqlite3_"]),
+    ("zstd", &["ZSTD_"]),
+    ("liblzma", &["lzma_"]),
+    ("libgit2", &["git_libgit2_"]),
+    ("libssh2", &["libssh2_"]),
+    ("libcurl", &["curl_easy_"]),
+];
+
+/// Guess which C libraries are statically linked into a binary, from the symbols it defines
+///
+/// This needs a symbol table, so stripped binaries won't turn anything up. Everything
+/// found is marked as low confidence, since it's only inferred from names.
+fn scan_bundled_libraries(path: &Path) -> DistResult<SortedSet<Library>> {
+    let buf = read_binary(path)?;
+    let mut names = vec![];
+    match Object::parse(&buf) {
+        Ok(Object::Elf(elf)) => {
+            // Undefined symbols (section 0) are imports from some other library
+            let symbols = elf.syms.iter().filter(|sym| sym.st_shndx != 0);
+            names.extend(symbols.filter_map(|sym| elf.strtab.get_at(sym.st_name)));
+            let symbols = elf.dynsyms.iter().filter(|sym| sym.st_shndx != 0);
+            names.extend(symbols.filter_map(|sym| elf.dynstrtab.get_at(sym.st_name)));
+        }
+        Ok(Object::Mach(Mach::Binary(macho))) => {
+            let symbols = macho.symbols().flatten();
+            // C symbols get an underscore prefix on macOS
+            names.extend(
+                symbols
+                    .filter(|(_, nlist)| !nlist.is_undefined())
+                    .map(|(name, _)| name.strip_prefix('_').unwrap_or(name)),
+            );
+        }
+        _ => {}
+    }
+
+    let bundled = BUNDLED_LIBRARY_SYMBOLS
+        .iter()
+        .filter(|(_, prefixes)| {
+            names
+                .iter()
+                .any(|name| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        })
+        .map(|(library, _)| Library {
+            confidence: Some(Confidence::Low),
+            ..Library::new(library.to_string())
+        })
+        .collect();
+    Ok(bundled)
+}
+
+/// Guess which libraries a binary loads at runtime, from the file names in it
+///
+/// Libraries it already links are left out, as is the binary's own name.
+fn scan_runtime_loaded(path: &Path, linkage: &Linkage) -> DistResult<SortedSet<Library>> {
+    let buf = read_binary(path)?;
+    let file_name = |library: &str| {
+        let name = library.rsplit(['/', '\\']).next().unwrap_or(library);
+        name.to_ascii_lowercase()
+    };
+    let linked = linkage
+        .libraries()
+        .map(|library| file_name(&library.path))
+        .chain(linkage.soname.as_deref().map(file_name))
+        .collect::<SortedSet<_>>();
+
+    let loaded = find_library_names(&buf)
+        .into_iter()
+        .filter(|name| !linked.contains(&name.to_ascii_lowercase()))
+        .map(|name| Library {
+            confidence: Some(Confidence::Low),
+            ..Library::new(name)
+        })
+        .collect();
+    Ok(loaded)
+}
+
+/// Find the file names of shared libraries mentioned anywhere in a binary
+///
+/// Libraries loaded with dlopen or LoadLibrary are usually named by a string literal, so
+/// this looks for anything shaped like `libfoo.so.1`, `libfoo.dylib` or `foo.dll`.
+fn find_library_names(buf: &[u8]) -> SortedSet<String> {
+    let is_name_byte = |b: &u8| b.is_ascii_alphanumeric() || b"._+-/@$".contains(b);
+    buf.split(|b| !is_name_byte(b))
+        .filter_map(|word| std::str::from_utf8(word).ok())
+        .filter_map(library_name_in)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Pick the file name of a shared library out of a run of file name characters
+///
+/// String literals aren't separated in a binary, so the name may run straight into
+/// whatever comes after it.
+fn library_name_in(word: &str) -> Option<&str> {
+    // Only the file name matters, if this is a path
+    let word = word.rsplit('/').next().unwrap_or(word);
+    if !word.contains('.') {
+        return None;
+    }
+    let lower = word.to_ascii_lowercase();
+    let (start, mut end) = [".dylib", ".dll", ".so"]
+        .iter()
+        .find_map(|ext| lower.find(ext).map(|idx| (idx, idx + ext.len())))?;
+    // Keep the version numbers after the extension, like libfoo.so.1.2
+    while let Some(rest) = word[end..].strip_prefix('.') {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            break;
+        }
+        end += 1 + digits;
+    }
+    // If the extension carries on, like .sock or .dllx, this wasn't a library after all
+    if word[end..].starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+        return None;
+    }
+
+    let stem = &word[..start];
+    // Format strings like "%s.dll" leave a single letter behind
+    let plausible = stem.len() > 1
+        && stem.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && stem.contains(|c: char| c.is_ascii_alphabetic());
+    plausible.then_some(&word[..end])
+}
+
+/// Convert a path for use in errors, replacing anything that isn't UTF-8
+///
+/// Binaries are read using their real paths, so this only affects how they're reported.
+fn lossy_path(path: &Path) -> Utf8PathBuf {
+    Utf8PathBuf::from(path.to_string_lossy().into_owned())
+}
+
+/// Get the magic number from the start of a binary, if it's long enough to have one
+fn magic_number(header: &[u8]) -> Option<&[u8; 4]> {
+    header.get(..4)?.try_into().ok()
+}
+
+/// Record a binary's container format, and for ELF its class and byte order
+///
+/// These are cheap to read from the header, and catch cross-compilation mistakes like
+/// a big-endian or 32-bit build of a target that should be neither.
+fn record_format(linkage: &mut Linkage, header: &[u8], target: &str) {
+    let Some(magic) = magic_number(header) else {
+        return;
+    };
+    linkage.format = match (binary_format(magic), magic) {
+        (Some(_), [0xca, 0xfe, 0xba, 0xbe]) => Some("fat Mach-O".to_owned()),
+        (format, _) => format.map(ToOwned::to_owned),
+    };
+    if linkage.format.as_deref() != Some("ELF") {
+        return;
+    }
+
+    linkage.elf_class = match header.get(4) {
+        Some(1) => Some("ELF32".to_owned()),
+        Some(2) => Some("ELF64".to_owned()),
+        _ => None,
+    };
+    linkage.endianness = match header.get(5) {
+        Some(1) => Some("little-endian".to_owned()),
+        Some(2) => Some("big-endian".to_owned()),
+        _ => None,
+    };
+    // Every Linux target we support is little-endian, and only i686 is 32-bit
+    let expected_class = if target.starts_with("i686") {
+        "ELF32"
+    } else {
+        "ELF64"
+    };
+    if let Some(class) = linkage
+        .elf_class
+        .as_deref()
+        .filter(|c| *c != expected_class)
+    {
+        let warning = format!("binary is {class}, but {target} should be {expected_class}");
+        linkage.warnings.push(warning);
+    }
+    if linkage.endianness.as_deref() == Some("big-endian") {
+        let warning = format!("binary is big-endian, but {target} is little-endian");
+        linkage.warnings.push(warning);
+    }
+}
+
+/// Work out a binary's format from its magic number
+fn binary_format(magic: &[u8; 4]) -> Option<&'static str> {
+    match magic {
+        [0x7f, b'E', b'L', b'F'] => Some("ELF"),
+        // Thin binaries of either endianness and bitness, then universal binaries
+        [0xfe, 0xed, 0xfa, 0xce | 0xcf]
+        | [0xce | 0xcf, 0xfa, 0xed, 0xfe]
+        | [0xca, 0xfe, 0xba, 0xbe] => Some("Mach-O"),
+        [b'M', b'Z', _, _] => Some("PE"),
+        [0, b'a', b's', b'm'] => Some("WASM"),
+        _ => None,
+    }
+}
+
+/// Get the directories a binary asks the dynamic loader to search for libraries
+///
+/// For ELF binaries that's DT_RPATH and DT_RUNPATH, which can each hold several
+/// directories separated by colons. For Mach-O binaries it's every LC_RPATH, from every
+/// architecture of a universal binary. Other formats don't have them.
+fn determine_rpaths(object: &Object) -> Vec<String> {
+    let rpaths = match object {
+        Object::Elf(elf) => elf
+            .rpaths
+            .iter()
+            .chain(&elf.runpaths)
+            .flat_map(|paths| paths.split(':'))
+            .map(str::to_owned)
+            .collect(),
+        Object::Mach(Mach::Binary(macho)) => {
+            macho.rpaths.iter().map(|rpath| rpath.to_string()).collect()
+        }
+        Object::Mach(Mach::Fat(fat)) => (0..fat.narches)
+            .filter_map(|idx| match fat.get(idx) {
+                Ok(SingleArch::MachO(macho)) => Some(macho.rpaths),
+                _ => None,
+            })
+            .flatten()
+            .map(str::to_owned)
+            .collect(),
+        _ => vec![],
+    };
+
+    let mut unique = vec![];
+    for rpath in rpaths {
+        if !rpath.is_empty() && !unique.contains(&rpath) {
+            unique.push(rpath);
+        }
+    }
+    unique
+}
+
+/// Work out which C++ standard library a binary links, and which version of it it needs
+///
+/// The flavor decides ABI compatibility: libstdc++ and libc++ can't be swapped for each
+/// other. For libstdc++ the version is the newest GLIBCXX symbol version the binary uses,
+/// which is what decides whether the system's libstdc++ is new enough. Otherwise it's the
+/// library's own ABI version.
+fn determine_cxx_runtime(object: &Object, linkage: &Linkage) -> Option<(String, Option<String>)> {
+    let runtime = linkage.libraries().find_map(|library| {
+        let (name, version) = split_library_name(&library.path);
+        let flavor = match name.as_str() {
+            "libstdc++" => "libstdc++",
+            "libc++" | "libc++abi" => "libc++",
+            "msvcp140" => "msvcp",
+            _ => return None,
+        };
+        // msvcp140.dll has its version in the name
+        let version = version.or_else(|| {
+            let version = name.strip_prefix(flavor)?;
+            (!version.is_empty()).then(|| version.to_owned())
+        });
+        Some((flavor, version))
+    });
+    let (flavor, version) = runtime?;
+
+    let version = if flavor == "libstdc++" {
+        match newest_symbol_version(object, "GLIBCXX_") {
+            Some((glibcxx, _)) => Some(glibcxx),
+            None => version,
+        }
+    } else {
+        version
+    };
+    Some((flavor.to_owned(), version))
+}
+
+/// Find out which exploit mitigations a binary was built with
+///
+/// Only what can be read off the binary itself is recorded: ELF and Mach-O header flags
+/// and program headers, PE DllCharacteristics, and for stack canaries and fortify, the
+/// libc functions they call. Universal binaries are judged by their first architecture.
+fn determine_hardening(object: &Object) -> Option<Hardening> {
+    let hardening = match object {
+        Object::Elf(elf) => elf_hardening(elf),
+        Object::Mach(Mach::Binary(macho)) => macho_hardening(macho),
+        Object::Mach(Mach::Fat(fat)) => match fat.get(0) {
+            Ok(SingleArch::MachO(macho)) => macho_hardening(&macho),
+            _ => return None,
+        },
+        Object::PE(pe) => {
+            let optional_header = pe.header.optional_header?;
+            // The IMAGE_DLLCHARACTERISTICS_* flags
+            let flags = optional_header.windows_fields.dll_characteristics;
+            Hardening {
+                nx: Some(flags & 0x0100 != 0),
+                aslr: Some(flags & 0x0040 != 0),
+                high_entropy_va: pe.is_64.then_some(flags & 0x0020 != 0),
+                cfg: Some(flags & 0x4000 != 0),
+                ..Hardening::default()
+            }
+        }
+        _ => return None,
+    };
+    Some(hardening)
+}
+
+/// Log the raw structures linkage is read from, for debugging surprising results
+///
+/// This only does anything when debug logging is enabled (`--verbose=debug`).
+fn debug_binary_structure(path: &Path, object: &Object, buf: &[u8]) {
+    if !tracing::enabled!(Level::DEBUG) {
+        return;
+    }
+    let path = path.display();
+    match object {
+        Object::Mach(Mach::Binary(macho)) => {
+            for (idx, load) in macho.load_commands.iter().enumerate() {
+                let (kind, dylib) = match &load.command {
+                    CommandVariant::IdDylib(command) => ("LC_ID_DYLIB", command.dylib),
+                    CommandVariant::LoadDylib(command) => ("LC_LOAD_DYLIB", command.dylib),
+                    CommandVariant::LoadWeakDylib(command) => ("LC_LOAD_WEAK_DYLIB", command.dylib),
+                    CommandVariant::ReexportDylib(command) => ("LC_REEXPORT_DYLIB", command.dylib),
+                    CommandVariant::LoadUpwardDylib(command) => {
+                        ("LC_LOAD_UPWARD_DYLIB", command.dylib)
+                    }
+                    CommandVariant::LazyLoadDylib(command) => ("LC_LAZY_LOAD_DYLIB", command.dylib),
+                    other => {
+                        debug!(
+                            "{path}: load command {idx} at {:#x}: {}",
+                            load.offset,
+                            cmd_to_str(other.cmd())
+                        );
+                        continue;
+                    }
+                };
+                // The name is stored inside the command, at an offset from its start
+                let name = load
+                    .offset
+                    .checked_add(dylib.name as usize)
+                    .map(|start| c_str_at(buf, start))
+                    .unwrap_or_default();
+                debug!(
+                    "{path}: load command {idx} at {:#x}: {kind} {name}",
+                    load.offset
+                );
+            }
+        }
+        Object::Mach(Mach::Fat(_)) => {
+            debug!("{path}: universal binary, not dumping load commands");
+        }
+        Object::Elf(elf) => {
+            let Some(dynamic) = &elf.dynamic else {
+                debug!("{path}: no dynamic section");
+                return;
+            };
+            for (idx, entry) in dynamic.dyns.iter().enumerate() {
+                // These entries are offsets into the dynamic string table
+                let val = match entry.d_tag {
+                    DT_NEEDED | DT_SONAME | DT_RPATH | DT_RUNPATH => elf
+                        .dynstrtab
+                        .get_at(entry.d_val as usize)
+                        .unwrap_or("<invalid string offset>")
+                        .to_owned(),
+                    _ => format!("{:#x}", entry.d_val),
+                };
+                debug!(
+                    "{path}: dynamic entry {idx}: {} {val}",
+                    tag_to_str(entry.d_tag)
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read a nul-terminated string out of a buffer
+fn c_str_at(buf: &[u8], start: usize) -> String {
+    let bytes = buf
+        .get(start..)
+        .and_then(|rest| rest.split(|&b| b == 0).next())
+        .unwrap_or_default();
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Get the name a shared library declares for itself, if it's one
+///
+/// Executables don't declare a name. For universal binaries, this isn't read yet.
+fn determine_soname(object: &Object) -> Option<String> {
+    let name = match object {
+        Object::Elf(elf) => elf.soname,
+        Object::Mach(Mach::Binary(macho)) => macho.name,
+        _ => None,
+    };
+    name.map(ToOwned::to_owned)
+}
+
+/// Get the build id and debug file reference recorded in a binary, if any
+///
+/// These are what symbol servers use to match a crash report to the exact binary
+/// (and the debug symbols) it came from.
+fn determine_debug_info(object: &Object, buf: &[u8]) -> (Option<String>, Option<String>) {
+    match object {
+        Object::Elf(elf) => {
+            let build_id = elf
+                .iter_note_sections(buf, Some(".note.gnu.build-id"))
+                .into_iter()
+                .flatten()
+                .filter_map(|note| note.ok())
+                .find(|note| note.n_type == goblin::elf::note::NT_GNU_BUILD_ID)
+                .map(|note| to_hex(note.desc));
+            // .gnu_debuglink is a nul-terminated filename followed by a checksum
+            let debug_file = elf
+                .section_headers
+                .iter()
+                .find(|header| elf.shdr_strtab.get_at(header.sh_name) == Some(".gnu_debuglink"))
+                .and_then(|header| {
+                    let start = header.sh_offset as usize;
+                    buf.get(start..start.checked_add(header.sh_size as usize)?)
+                })
+                .and_then(|data| data.split(|&b| b == 0).next())
+                .filter(|name| !name.is_empty())
+                .map(|name| String::from_utf8_lossy(name).into_owned());
+            (build_id, debug_file)
+        }
+        Object::Mach(Mach::Binary(macho)) => {
+            let build_id = macho
+                .load_commands
+                .iter()
+                .find_map(|load| match load.command {
+                    CommandVariant::Uuid(ref command) => {
+                        let uuid = to_hex(&command.uuid).to_uppercase();
+                        Some(format!(
+                            "{}-{}-{}-{}-{}",
+                            &uuid[0..8],
+                            &uuid[8..12],
+                            &uuid[12..16],
+                            &uuid[16..20],
+                            &uuid[20..32]
+                        ))
+                    }
+                    _ => None,
+                });
+            (build_id, None)
+        }
+        Object::PE(pe) => {
+            let Some(pdb) = pe
+                .debug_data
+                .and_then(|data| data.codeview_pdb70_debug_info)
+            else {
+                return (None, None);
+            };
+            // This is the GUID+age format symbol servers key PDBs by
+            let build_id = format!("{}{:X}", format_guid(&pdb.signature), pdb.age);
+            let pdb_path = pdb.filename.split(|&b| b == 0).next().unwrap_or_default();
+            let debug_file =
+                (!pdb_path.is_empty()).then(|| String::from_utf8_lossy(pdb_path).into_owned());
+            (Some(build_id), debug_file)
+        }
+        _ => (None, None),
+    }
+}
+
+/// Get the (lowercased) names of the other files in the same directory as a binary
+fn sibling_files(path: &Path) -> DistResult<SortedSet<String>> {
+    let mut files = SortedSet::new();
+    let Some(dir) = path.parent() else {
+        return Ok(files);
+    };
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        if entry.path() != path && entry.file_type()?.is_file() {
+            files.insert(entry.file_name().to_string_lossy().to_lowercase());
+        }
+    }
+    Ok(files)
+}
+
+/// Work out whether a (non-Windows) library ships alongside the binary, or can be
+/// expected on any system
+///
+/// Only system and toolchain libraries, and the frameworks in /System, come with the OS;
+/// anything else is from a package manager or somewhere else users won't necessarily have.
+fn locate_library(
+    path: &str,
+    category: LinkageCategory,
+    bundled_files: &SortedSet<String>,
+) -> DllLocation {
+    let file_name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    if bundled_files.contains(&file_name) {
+        DllLocation::Bundled
+    } else if matches!(
+        category,
+        LinkageCategory::System | LinkageCategory::Toolchain
+    ) || path.starts_with("/System/")
+    {
+        DllLocation::System
+    } else {
+        DllLocation::Unknown
+    }
+}
+
+/// Work out which category a library belongs in, and which package provides it
+///
+/// User-provided rules are consulted in order before the built-in heuristics.
+/// Paths are interpreted relative to the sysroot, if one is given.
+pub fn classify_library(
+    library: String,
+    target: &str,
+    rules: &[LinkageRule],
+    sysroot: Option<&Utf8Path>,
+    resolver: &dyn PackageResolver,
+) -> DistResult<(LinkageCategory, Library)> {
+    let category = if let Some(rule) = rules.iter().find(|r| library.starts_with(&r.prefix)) {
+        rule.category
+    } else if is_toolchain_library(&library, target) {
+        LinkageCategory::Toolchain
+    } else if language_runtime(&library).is_some() {
+        LinkageCategory::LanguageRuntime
+    } else if is_graphics_library(&library) {
+        LinkageCategory::Graphics
+    } else if is_homebrew_path(
+        &library,
+        resolver.homebrew_prefixes(),
+        resolver.homebrew_cellar(),
+    ) {
+        LinkageCategory::Homebrew
+    } else if library.starts_with("/usr/lib")
+        || library.starts_with("/lib")
+        || is_libc_library(&library)
+    {
+        LinkageCategory::System
+    } else if library.starts_with("/System/Library/Frameworks")
+        || library.starts_with("/Library/Frameworks")
+    {
+        LinkageCategory::Frameworks
+    } else if library.starts_with("/usr/local") {
+        let on_disk = match sysroot {
+            Some(sysroot) => sysroot.join(library.trim_start_matches('/')),
+            None => Utf8PathBuf::from(&library),
+        };
+        // If it's not there to resolve, all we have to go on is its path
+        let resolved = std::fs::canonicalize(&on_disk).unwrap_or_else(|_| on_disk.into());
+        // The host's Homebrew says nothing about a sysroot's, so assume the default there
+        let cellar = match sysroot {
+            Some(sysroot) => sysroot.join("usr/local/Cellar"),
+            None => resolver.homebrew_cellar().to_owned(),
+        };
+        if resolved.starts_with(cellar) {
+            LinkageCategory::Homebrew
+        } else {
+            LinkageCategory::PublicUnmanaged
+        }
+    } else {
+        LinkageCategory::Other
+    };
+
+    let mut library = resolver.resolve(category, library, sysroot)?;
+    if category == LinkageCategory::LanguageRuntime {
+        library.runtime = language_runtime(&library.path).map(|(runtime, version)| match version {
+            Some(version) => format!("{runtime} {version}"),
+            None => runtime.to_owned(),
+        });
+    }
+    Ok((category, library))
+}
+
+/// Whether a library, known only by name, is part of the C library every Linux system has
+///
+/// Libraries are only known by name when they couldn't be found, like when checking a
+/// Linux binary on another OS, but these are safe to call system libraries regardless.
+fn is_libc_library(library: &str) -> bool {
+    if library.contains('/') {
+        return false;
+    }
+    let (name, _) = split_library_name(library);
+    matches!(
+        name.as_str(),
+        "libc" | "libm" | "libpthread" | "libdl" | "librt" | "libutil" | "libresolv"
+    ) || name.starts_with("ld-linux")
+        || name.starts_with("ld-musl-")
+}
+
+/// Whether a library is a runtime the toolchain linked in, rather than a dependency
+///
+/// These come from how rustc (and the C toolchain behind it) built the binary, so there's
+/// no dependency to remove to get rid of them.
+fn is_toolchain_library(library: &str, target: &str) -> bool {
+    let (name, _) = split_library_name(library);
+    // Sanitizer runtimes, from LLVM or shipped with rustc, and GCC's instrumentation runtimes
+    if name.starts_with("libclang_rt.")
+        || name.starts_with("clang_rt.")
+        || (name.starts_with("librustc-") && name.contains("_rt."))
+        || instrumentation_runtime(library).is_some()
+    {
+        return true;
+    }
+    if ["linux", "freebsd", "netbsd", "illumos"]
+        .iter()
+        .any(|os| target.contains(os))
+    {
+        // Unwinding support for panics, from GCC or LLVM
+        matches!(name.as_str(), "libgcc_s" | "libunwind")
+    } else if target.contains("windows-msvc") {
+        // The MSVC runtime every binary built with it needs
+        is_vc_redist_dll(library)
+    } else if target.contains("windows-gnu") {
+        // MinGW's runtime, which binaries built with it need alongside them
+        matches!(
+            name.as_str(),
+            "libgcc_s_seh" | "libgcc_s_dw2" | "libwinpthread"
+        )
+    } else {
+        false
+    }
+}
+
+/// Whether a library is part of a display stack (X11, Wayland, OpenGL or Vulkan), which
+/// means the binary needs a windowing system or GPU driver and won't run headless
+fn is_graphics_library(library: &str) -> bool {
+    const GRAPHICS_LIBRARIES: &[&str] = &[
+        // X11
+        "libX11",
+        "libX11-xcb",
+        "libXau",
+        "libXcursor",
+        "libXdmcp",
+        "libXext",
+        "libXfixes",
+        "libXi",
+        "libXinerama",
+        "libXrandr",
+        "libXrender",
+        "libXxf86vm",
+        "libxkbcommon",
+        "libxkbcommon-x11",
+        // Wayland
+        "libwayland-client",
+        "libwayland-cursor",
+        "libwayland-egl",
+        "libdecor-0",
+        // OpenGL, EGL and the kernel graphics interfaces under them
+        "libGL",
+        "libGLX",
+        "libGLdispatch",
+        "libOpenGL",
+        "libEGL",
+        "libGLESv2",
+        "libgbm",
+        "libdrm",
+        "opengl32",
+        // Vulkan
+        "libvulkan",
+        "vulkan",
+        // Toolkits that always open a display
+        "libgtk-3",
+        "libgdk-3",
+        "libgtk-4",
+    ];
+    let (name, _) = split_library_name(library);
+    // libxcb comes split into many libraries, like libxcb-shm and libxcb-randr
+    GRAPHICS_LIBRARIES.contains(&name.as_str()) || name.starts_with("libxcb")
+}
+
+/// Work out which language runtime a library provides, and its version, if it's one
+///
+/// e.g. `libpython3.11.so.1.0`, `Python.framework/Versions/3.11/Python` and
+/// `python311.dll` are all Python 3.11. libnode only records its module ABI version
+/// (like `108`), so that's what's reported for Node.js.
+fn language_runtime(library: &str) -> Option<(&'static str, Option<String>)> {
+    const RUNTIMES: [(&str, &str); 6] = [
+        ("python", "Python"),
+        ("ruby", "Ruby"),
+        ("node", "Node.js"),
+        ("perl", "Perl"),
+        ("luajit", "LuaJIT"),
+        ("lua", "Lua"),
+    ];
+
+    if let Some((_, rest)) = library.split_once("Python.framework/Versions/") {
+        let version = rest.split('/').next().filter(|v| *v != "Current");
+        return Some(("Python", version.map(ToOwned::to_owned)));
+    }
+    let (name, file_version) = split_library_name(library);
+    let name = name.strip_prefix("lib").unwrap_or(&name);
+    for (prefix, runtime) in RUNTIMES {
+        // Windows builds of Ruby are named for their platform, like x64-ucrt-ruby320.dll
+        let Some(rest) = name.strip_prefix(prefix).or_else(|| {
+            name.rsplit_once(&format!("-{prefix}"))
+                .map(|(_, rest)| rest)
+        }) else {
+            continue;
+        };
+        let rest = rest.strip_prefix('-').unwrap_or(rest);
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (digits, flags) = rest.split_at(end);
+        // Python marks debug and free-threaded builds with a suffix, like libpython3.13t;
+        // anything else after the name means it's some other library (like libperlio)
+        let python_flags = runtime == "Python" && flags.chars().all(|c| "dmtu".contains(c));
+        if !flags.is_empty() && !python_flags {
+            continue;
+        }
+        let digits = digits.trim_matches('.');
+        let version = if digits.contains('.') {
+            Some(digits.to_owned())
+        } else if digits.len() > 1 && runtime == "Ruby" {
+            // DLLs squash the version together, and Ruby's spells out every part of it,
+            // like ruby320.dll for 3.2.0
+            Some(
+                digits
+                    .chars()
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+                    .join("."),
+            )
+        } else if digits.len() > 1 {
+            // ...while others only have the major and minor versions, like python311.dll
+            let (major, minor) = digits.split_at(1);
+            Some(format!("{major}.{minor}"))
+        } else if !digits.is_empty() {
+            Some(digits.to_owned())
+        } else {
+            file_version
+        };
+        return Some((runtime, version));
+    }
+    None
+}
+
+/// The libraries a binary links against, and why (if the format records it)
+type LinkedLibraries = Vec<(String, Option<LinkReason>)>;
+
+/// Pair up libraries from a format that doesn't record why they're linked
+fn without_reasons(libraries: Vec<String>) -> LinkedLibraries {
+    libraries.into_iter().map(|l| (l, None)).collect()
+}
+
+/// Count something for a human, e.g. `1 binary` or `2 binaries`
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else if let Some(stem) = noun.strip_suffix('y') {
+        format!("{count} {stem}ies")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Split a library's path into its canonical name and its version, if it has one
+///
+/// e.g. `/usr/lib/x86_64-linux-gnu/libssl.so.3`, `/opt/homebrew/lib/libssl.3.dylib`
+/// and `LIBSSL-3-X64.DLL` are all `libssl`, version `3`.
+fn split_library_name(path: &str) -> (String, Option<String>) {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let lowercase = file_name.to_ascii_lowercase();
+    let (name, version) = if let Some((stem, version)) = file_name.split_once(".so.") {
+        (stem, version.to_owned())
+    } else if let Some(stem) = file_name.strip_suffix(".so") {
+        (stem, String::new())
+    } else if let Some(stem) = file_name.strip_suffix(".dylib") {
+        let name = stem.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        (name, stem[name.len()..].trim_start_matches('.').to_owned())
+    } else if let Some(stem) = lowercase.strip_suffix(".dll") {
+        // DLL names are case-insensitive, and conventionally carry their version (and
+        // sometimes their architecture) after a dash. Other digits, like the 32 in
+        // kernel32, are part of the name.
+        let stem = ["-x64", "-x86", "-arm64"]
+            .iter()
+            .fold(stem, |stem, arch| stem.strip_suffix(arch).unwrap_or(stem));
+        let unversioned = stem.trim_end_matches(|c: char| c.is_ascii_digit() || c == '_');
+        match unversioned.strip_suffix('-') {
+            Some(name) => (name, stem[unversioned.len()..].replace('_', ".")),
+            None => (stem, String::new()),
+        }
+    } else {
+        (file_name, String::new())
+    };
+    if name.is_empty() {
+        (file_name.to_owned(), None)
+    } else {
+        (name.to_owned(), Some(version).filter(|v| !v.is_empty()))
+    }
+}
+
+/// Parse a dotted version number like "2.17"
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolver::{DEFAULT_HOMEBREW_CELLAR, DEFAULT_HOMEBREW_PREFIXES};
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Attributes libraries to made-up packages without asking any package manager
+    struct MockResolver {
+        /// Where Homebrew is, instead of wherever this machine has it
+        homebrew_prefixes: Vec<Utf8PathBuf>,
+        /// The libraries of every batch we were asked to prefetch
+        prefetched: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl Default for MockResolver {
+        fn default() -> Self {
+            Self {
+                homebrew_prefixes: DEFAULT_HOMEBREW_PREFIXES.map(Utf8PathBuf::from).to_vec(),
+                prefetched: Mutex::default(),
+            }
+        }
+    }
+
+    impl PackageResolver for MockResolver {
+        fn resolve(
+            &self,
+            category: LinkageCategory,
+            library: String,
+            _sysroot: Option<&Utf8Path>,
+        ) -> DistResult<Library> {
+            let source = match category {
+                LinkageCategory::Homebrew => Some("openssl@3"),
+                LinkageCategory::System => Some("libc6"),
+                LinkageCategory::Toolchain => Some("libgcc-s1"),
+                _ => None,
+            };
+            Ok(Library {
+                source: source.map(ToOwned::to_owned),
+                ..Library::new(library)
+            })
+        }
+
+        fn prefetch(&self, libraries: &[String], _sysroot: Option<&Utf8Path>) {
+            self.prefetched.lock().unwrap().push(libraries.to_vec());
+        }
+
+        fn homebrew_prefixes(&self) -> &[Utf8PathBuf] {
+            &self.homebrew_prefixes
+        }
+
+        fn homebrew_cellar(&self) -> &Utf8Path {
+            Utf8Path::new(DEFAULT_HOMEBREW_CELLAR)
+        }
+    }
+
+    fn linkage_of(target: &str, config: &LinkageConfig, slices: Vec<(&str, Vec<&str>)>) -> Linkage {
+        let slices = slices
+            .into_iter()
+            .map(|(arch, libraries)| {
+                let arch = (!arch.is_empty()).then(|| arch.to_string());
+                let libraries = libraries.iter().map(|l| l.to_string()).collect();
+                (arch, without_reasons(libraries))
+            })
+            .collect();
+        build_linkage(slices, target, config, None, &MockResolver::default(), None).unwrap()
+    }
+
+    #[test]
+    fn library_names_in_strings() {
+        let buf = b"\0libfoo.so.1\0/usr/lib/libbar.so.2.4Failed to load\0@rpath/libbaz.dylib\0\
+            d3d12.DLL\0unix.socket\0.so\0%s.dll\0libc.so.6\0";
+        let names = find_library_names(buf);
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![
+                "d3d12.DLL",
+                "libbar.so.2.4",
+                "libbaz.dylib",
+                "libc.so.6",
+                "libfoo.so.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn classify_vc_redist_dlls() {
+        assert!(is_vc_redist_dll("VCRUNTIME140.dll"));
+        assert!(is_vc_redist_dll("vcruntime140_1.dll"));
+        assert!(is_vc_redist_dll("MSVCP140_2.dll"));
+        assert!(!is_vc_redist_dll("ucrtbase.dll"));
+        assert!(!is_vc_redist_dll("api-ms-win-crt-runtime-l1-1-0.dll"));
+        assert!(!is_vc_redist_dll("msvcrt.dll"));
+    }
+
+    #[test]
+    fn classify_toolchain_on_other_targets() {
+        assert!(is_toolchain_library(
+            "libgcc_s_seh-1.dll",
+            "x86_64-pc-windows-gnu"
+        ));
+        assert!(is_toolchain_library(
+            "libwinpthread-1.dll",
+            "x86_64-pc-windows-gnu"
+        ));
+        assert!(!is_toolchain_library(
+            "libwinpthread-1.dll",
+            "x86_64-pc-windows-msvc"
+        ));
+        assert!(is_toolchain_library(
+            "libgcc_s.so.1",
+            "x86_64-unknown-freebsd"
+        ));
+        assert!(!is_toolchain_library("libc.so.7", "x86_64-unknown-freebsd"));
+    }
+
+    #[test]
+    fn homebrew_packages_in_other_prefixes() {
+        let resolver = MockResolver::default();
+        let is_homebrew = |library: &str, resolver: &MockResolver| {
+            is_homebrew_path(
+                library,
+                resolver.homebrew_prefixes(),
+                resolver.homebrew_cellar(),
+            )
+        };
+
+        let linuxbrew = "/home/linuxbrew/.linuxbrew/opt/zstd/lib/libzstd.so.1";
+        assert!(is_homebrew(linuxbrew, &resolver));
+        let library = library_from_homebrew(linuxbrew.to_owned(), resolver.homebrew_prefixes());
+        assert_eq!(library.source.as_deref(), Some("zstd"));
+
+        let intel = "/usr/local/Cellar/zstd/1.5.6/lib/libzstd.1.dylib";
+        assert!(is_homebrew(intel, &resolver));
+        assert!(!is_homebrew("/usr/local/lib/libzstd.1.dylib", &resolver));
+
+        let custom = "/srv/brew/Cellar/openssl@3/3.3.1/lib/libssl.3.dylib";
+        assert!(!is_homebrew(custom, &resolver));
+        let resolver = MockResolver {
+            homebrew_prefixes: vec![Utf8PathBuf::from("/srv/brew")],
+        };
+        assert!(is_homebrew(custom, &resolver));
+        let library = library_from_homebrew(custom.to_owned(), resolver.homebrew_prefixes());
+        assert_eq!(library.source.as_deref(), Some("openssl@3"));
+    }
+
+    fn libraries_from(paths: &[&str]) -> SortedMap<String, SortedSet<String>> {
+        let mut libraries = SortedMap::<String, SortedSet<String>>::new();
+        for path in paths {
+            let (name, _) = split_library_name(path);
+            libraries.entry(name).or_default().insert(path.to_string());
+        }
+        libraries
+    }
+
+    #[test]
+    fn diff_linkage_reports() {
+        let old = libraries_from(&[
+            "/lib/x86_64-linux-gnu/libc.so.6",
+            "/lib/x86_64-linux-gnu/libssl.so.1.1",
+            "/lib/x86_64-linux-gnu/libz.so.1",
+        ]);
+        let new = libraries_from(&[
+            "/lib/x86_64-linux-gnu/libc.so.6",
+            "/lib/x86_64-linux-gnu/libssl.so.3",
+            "/lib/x86_64-linux-gnu/libzstd.so.1",
+        ]);
+        let diff = diff_libraries(&old, &new);
+        assert_eq!(diff.added, vec!["libzstd"]);
+        assert_eq!(diff.removed, vec!["libz"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].library, "libssl");
+        assert_eq!(
+            diff.changed[0].old,
+            vec!["/lib/x86_64-linux-gnu/libssl.so.1.1"]
+        );
+        assert_eq!(
+            diff.changed[0].new,
+            vec!["/lib/x86_64-linux-gnu/libssl.so.3"]
+        );
+        assert!(diff_libraries(&new, &new).is_empty());
+    }
+
+    fn paths(libraries: &SortedSet<Library>) -> Vec<(&str, Option<&str>)> {
+        libraries
+            .iter()
+            .map(|l| (l.path.as_str(), l.source.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn classify_macos() {
+        let linkage = linkage_of(
+            "aarch64-apple-darwin",
+            &LinkageConfig::default(),
+            vec![(
+                "",
+                vec![
+                    "/opt/homebrew/opt/openssl@3/lib/libssl.3.dylib",
+                    "/usr/lib/libSystem.B.dylib",
+                    "/System/Library/Frameworks/Security.framework/Versions/A/Security",
+                    "/usr/local/lib/libnot-really-installed.dylib",
+                ],
+            )],
+        );
+        assert_eq!(
+            paths(&linkage.homebrew),
+            vec![(
+                "/opt/homebrew/opt/openssl@3/lib/libssl.3.dylib",
+                Some("openssl@3")
+            )]
+        );
+        assert_eq!(
+            paths(&linkage.system),
+            vec![("/usr/lib/libSystem.B.dylib", Some("libc6"))]
+        );
+        assert_eq!(
+            paths(&linkage.frameworks),
+            vec![(
+                "/System/Library/Frameworks/Security.framework/Versions/A/Security",
+                None
+            )]
+        );
+        assert_eq!(
+            paths(&linkage.public_unmanaged),
+            vec![("/usr/local/lib/libnot-really-installed.dylib", None)]
+        );
+        assert!(linkage.slices.is_empty());
+    }
+
+    #[test]
+    fn classify_linux_with_rules() {
+        let config = LinkageConfig {
+            rules: vec![LinkageRule {
+                prefix: "/app/lib".to_owned(),
+                category: LinkageCategory::System,
+            }],
+            ..LinkageConfig::default()
+        };
+        let linkage = linkage_of(
+            "x86_64-unknown-linux-gnu",
+            &config,
+            vec![(
+                "",
+                vec![
+                    "/lib/x86_64-linux-gnu/libc.so.6",
+                    "/lib/x86_64-linux-gnu/libgcc_s.so.1",
+                    "/app/lib/libfoo.so.1",
+                    "/home/me/libbar.so",
+                ],
+            )],
+        );
+        assert_eq!(
+            paths(&linkage.system),
+            vec![
+                ("/app/lib/libfoo.so.1", Some("libc6")),
+                ("/lib/x86_64-linux-gnu/libc.so.6", Some("libc6")),
+            ]
+        );
+        assert_eq!(
+            paths(&linkage.toolchain),
+            vec![("/lib/x86_64-linux-gnu/libgcc_s.so.1", Some("libgcc-s1"))]
+        );
+        assert_eq!(paths(&linkage.other), vec![("/home/me/libbar.so", None)]);
+    }
+
+    #[test]
+    fn classify_linux_graphics() {
+        let linkage = linkage_of(
+            "x86_64-unknown-linux-gnu",
+            &LinkageConfig::default(),
+            vec![(
+                "",
+                vec![
+                    "/lib/x86_64-linux-gnu/libc.so.6",
+                    "/lib/x86_64-linux-gnu/libX11.so.6",
+                    "/lib/x86_64-linux-gnu/libxcb-shm.so.0",
+                    "/lib/x86_64-linux-gnu/libvulkan.so.1",
+                ],
+            )],
+        );
+        assert_eq!(
+            paths(&linkage.system),
+            vec![("/lib/x86_64-linux-gnu/libc.so.6", Some("libc6"))]
+        );
+        assert_eq!(
+            paths(&linkage.graphics),
+            vec![
+                ("/lib/x86_64-linux-gnu/libX11.so.6", None),
+                ("/lib/x86_64-linux-gnu/libvulkan.so.1", None),
+                ("/lib/x86_64-linux-gnu/libxcb-shm.so.0", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn classify_universal_slices() {
+        let linkage = linkage_of(
+            "universal2-apple-darwin",
+            &LinkageConfig::default(),
+            vec![
+                ("arm64", vec!["/usr/lib/libSystem.B.dylib"]),
+                (
+                    "x86_64",
+                    vec![
+                        "/usr/lib/libSystem.B.dylib",
+                        "/opt/homebrew/opt/openssl@3/lib/libssl.3.dylib",
+                    ],
+                ),
+            ],
+        );
+        assert_eq!(linkage.slices.len(), 2);
+        assert!(linkage.slices[0].homebrew.is_empty());
+        // The top level covers every slice
+        assert_eq!(linkage.system.len(), 1);
+        assert_eq!(
+            paths(&linkage.homebrew),
+            vec![(
+                "/opt/homebrew/opt/openssl@3/lib/libssl.3.dylib",
+                Some("openssl@3")
+            )]
+        );
+    }
+
+    /// Build the dependency tree of a made-up binary, rendered one library per line
+    fn tree_of(top: &[&str], graph: &[(&str, &[&str])], max_depth: usize) -> Vec<String> {
+        fn render(nodes: &[DependencyNode], indent: &str, out: &mut Vec<String>) {
+            for node in nodes {
+                let mut line = format!("{indent}{}", node.library);
+                if node.repeated {
+                    line.push_str(" (*)");
+                }
+                if node.depth_limited {
+                    line.push_str(" (depth limit reached)");
+                }
+                out.push(line);
+                render(&node.dependencies, &format!("{indent}  "), out);
+            }
+        }
+
+        let library_dependencies = |library: &str| {
+            graph
+                .iter()
+                .find(|(name, _)| *name == library)
+                .map(|(_, deps)| deps.iter().map(|dep| dep.to_string()).collect())
+                .unwrap_or_default()
+        };
+        let mut seen = SortedSet::new();
+        let nodes = top
+            .iter()
+            .map(|library| {
+                dependency_node(
+                    library.to_string(),
+                    &library_dependencies,
+                    &mut seen,
+                    1,
+                    max_depth,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut out = vec![];
+        render(&nodes, "", &mut out);
+        out
+    }
+
+    #[test]
+    fn dependency_tree_repeated() {
+        let tree = tree_of(
+            &["libcurl", "libssl"],
+            &[
+                ("libcurl", &["libssl", "libz"]),
+                ("libssl", &["libcrypto"]),
+                ("libcrypto", &["libz"]),
+            ],
+            8,
+        );
+        assert_eq!(
+            tree,
+            vec![
+                "libcurl",
+                "  libssl",
+                "    libcrypto",
+                "      libz",
+                "  libz (*)",
+                "libssl (*)",
+            ]
+        );
+    }
+
+    #[test]
+    fn dependency_tree_cycles() {
+        let tree = tree_of(
+            &["liba"],
+            &[
+                ("liba", &["libb"]),
+                ("libb", &["libc"]),
+                ("libc", &["liba"]),
+            ],
+            8,
+        );
+        assert_eq!(tree, vec!["liba", "  libb", "    libc", "      liba (*)"]);
+    }
+
+    #[test]
+    fn dependency_tree_depth_limited() {
+        let graph: &[(&str, &[&str])] = &[
+            ("liba", &["libb"]),
+            ("libb", &["libc"]),
+            ("libc", &["libd"]),
+        ];
+        assert_eq!(
+            tree_of(&["liba"], graph, 2),
+            vec!["liba", "  libb (depth limit reached)"]
+        );
+        // Leaves at the limit have nothing to cut off
+        assert_eq!(
+            tree_of(&["liba"], graph, 4),
+            vec!["liba", "  libb", "    libc", "      libd"]
+        );
+        // A limit of 0 still lists the binary's own libraries
+        assert_eq!(
+            tree_of(&["liba"], graph, 0),
+            vec!["liba (depth limit reached)"]
+        );
+    }
+
+    #[test]
+    fn dependency_tree_depth_limited_listed_again() {
+        // libc is cut off under liba, but is shallow enough to follow under libx
+        let tree = tree_of(
+            &["liba", "libx"],
+            &[
+                ("liba", &["libb"]),
+                ("libb", &["libc"]),
+                ("libc", &["libd"]),
+                ("libx", &["libc"]),
+            ],
+            3,
+        );
+        assert_eq!(
+            tree,
+            vec![
+                "liba",
+                "  libb",
+                "    libc (depth limit reached)",
+                "libx",
+                "  libc",
+                "    libd",
+            ]
+        );
+    }
+
+    #[test]
+    fn mode_formats() {
+        let args = LinkageArgs {
+            format: LinkageFormat::Sarif,
+            ..LinkageArgs::default()
+        };
+        assert!(check_mode_format(&args).is_ok());
+
+        let args = LinkageArgs {
+            format: LinkageFormat::Json,
+            histogram: true,
+            ..LinkageArgs::default()
+        };
+        assert!(check_mode_format(&args).is_ok());
+
+        let args = LinkageArgs {
+            format: LinkageFormat::Markdown,
+            diff: Some(("old.json".into(), "new.json".into())),
+            ..LinkageArgs::default()
+        };
+        let err = check_mode_format(&args).unwrap_err();
+        assert_eq!(err.to_string(), "--diff can't print its output as markdown");
+
+        let args = LinkageArgs {
+            format: LinkageFormat::Json,
+            explain: Some("libssl".to_owned()),
+            ..LinkageArgs::default()
+        };
+        let err = check_mode_format(&args).unwrap_err();
+        assert_eq!(err.to_string(), "--explain can't print its output as json");
+    }
+
+    #[test]
+    fn csv_report() {
+        let mut linkage = Linkage::default();
+        linkage
+            .system
+            .insert(Library::new("/lib/libc.so.6".to_owned()));
+        linkage.homebrew.insert(Library {
+            source: Some("foo".to_owned()),
+            ..Library::new("/opt/homebrew/lib/libfoo,bar.dylib".to_owned())
+        });
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        manifest.assets.insert(
+            "app".to_owned(),
+            AssetInfo {
+                id: "app".to_owned(),
+                name: "app".to_owned(),
+                system: String::new(),
+                target_triples: vec!["x86_64-unknown-linux-gnu".to_owned()],
+                linkage: Some(linkage),
+            },
+        );
+        assert_eq!(
+            CsvLinkageDisplay(&manifest).to_string(),
+            "binary,targets,category,library,source\n\
+             app,x86_64-unknown-linux-gnu,System,/lib/libc.so.6,\n\
+             app,x86_64-unknown-linux-gnu,Homebrew,\"/opt/homebrew/lib/libfoo,bar.dylib\",foo\n"
+        );
+    }
+
+    #[test]
+    fn explain_counts_binaries() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        for name in ["app", "helper"] {
+            let mut linkage = Linkage::default();
+            linkage
+                .system
+                .insert(Library::new(format!("/lib/lib{name}.so.1")));
+            linkage
+                .system
+                .insert(Library::new("/lib/libc.so.6".to_owned()));
+            manifest.assets.insert(
+                name.to_owned(),
+                AssetInfo {
+                    id: name.to_owned(),
+                    name: name.to_owned(),
+                    system: String::new(),
+                    target_triples: vec![],
+                    linkage: Some(linkage),
+                },
+            );
+        }
+        let explain = |query| ExplainLinkageDisplay(&manifest, query).to_string();
+        assert!(explain("libc.so.6").starts_with("libc.so.6 is linked by 2 binaries\n"));
+        assert!(explain("libapp.so.1").starts_with("libapp.so.1 is linked by 1 binary\n"));
+    }
+
+    /// Write `value` into `buf` at `offset`, growing `buf` to fit
+    pub(super) fn put(buf: &mut Vec<u8>, offset: usize, value: &[u8]) {
+        if buf.len() < offset + value.len() {
+            buf.resize(offset + value.len(), 0);
+        }
+        buf[offset..offset + value.len()].copy_from_slice(value);
+    }
+
+    /// A minimal 64-bit ELF that needs each of `needed`, with one segment covering the
+    /// whole file and no sections
+    pub(super) fn elf_fixture(needed: &[&str]) -> Vec<u8> {
+        versioned_elf_fixture(needed, &[])
+    }
+
+    /// As [`elf_fixture`][], but also importing each `(symbol, library, version)` in
+    /// `symbols`, with `.gnu.version` and `.gnu.version_r` sections saying which version
+    /// of which library each symbol needs
+    pub(super) fn versioned_elf_fixture(
+        needed: &[&str],
+        symbols: &[(&str, &str, &str)],
+    ) -> Vec<u8> {
+        const DYNAMIC: usize = 176;
+        let mut strtab = vec![0u8];
+        let mut intern = |name: &str| {
+            let offset = strtab.len() as u32;
+            strtab.extend_from_slice(name.as_bytes());
+            strtab.push(0);
+            offset
+        };
+        let needed = needed.iter().map(|name| intern(name)).collect::<Vec<_>>();
+        // Each library's versions, numbered from 2 like the linker does (0 and 1 mean
+        // local and global); and each symbol's name and version number
+        let mut libraries = Vec::<(&str, u32, Vec<(&str, u32, u16)>)>::new();
+        let mut imports = vec![];
+        for &(symbol, library, version) in symbols {
+            let next_index = 2 + libraries.iter().map(|lib| lib.2.len()).sum::<usize>() as u16;
+            let position = match libraries.iter().position(|lib| lib.0 == library) {
+                Some(position) => position,
+                None => {
+                    libraries.push((library, intern(library), vec![]));
+                    libraries.len() - 1
+                }
+            };
+            let versions = &mut libraries[position].2;
+            let index = match versions.iter().find(|known| known.0 == version) {
+                Some(known) => known.2,
+                None => {
+                    versions.push((version, intern(version), next_index));
+                    next_index
+                }
+            };
+            imports.push((intern(symbol), index));
+        }
+
+        let mut dynamic = needed
+            .into_iter()
+            .map(|name| (1u64, name as u64)) // DT_NEEDED
+            .collect::<Vec<_>>();
+        // DT_HASH, DT_SYMTAB, DT_SYMENT, DT_VERSYM, DT_VERNEED, DT_VERNEEDNUM
+        let versioned_tags = [4, 6, 11, 0x6fff_fff0, 0x6fff_fffe, 0x6fff_ffff];
+        let tag_count = if symbols.is_empty() {
+            0
+        } else {
+            versioned_tags.len()
+        };
+        let strtab_offset = DYNAMIC + (dynamic.len() + tag_count + 3) * 16;
+        // The tables symbol versioning needs, each 8-byte aligned after the strings
+        let align = |offset: usize| (offset + 7) & !7;
+        let symtab_offset = align(strtab_offset + strtab.len());
+        let hash_offset = symtab_offset + (imports.len() + 1) * 24;
+        let versym_offset = align(hash_offset + (imports.len() + 4) * 4);
+        let verneed_offset = align(versym_offset + (imports.len() + 1) * 2);
+        let verneed_size = libraries
+            .iter()
+            .map(|lib| 16 + lib.2.len() * 16)
+            .sum::<usize>();
+        let shdrs_offset = align(verneed_offset + verneed_size);
+        if !symbols.is_empty() {
+            let values = [
+                hash_offset,
+                symtab_offset,
+                24,
+                versym_offset,
+                verneed_offset,
+                libraries.len(),
+            ];
+            dynamic.extend(
+                versioned_tags
+                    .into_iter()
+                    .zip(values.map(|value| value as u64)),
+            );
+        }
+        // DT_STRTAB, DT_STRSZ, DT_NULL
+        dynamic.extend([(5, strtab_offset as u64), (10, strtab.len() as u64), (0, 0)]);
+        let file_size = if symbols.is_empty() {
+            (strtab_offset + strtab.len()) as u64
+        } else {
+            (shdrs_offset + 3 * 64) as u64
+        };
+
+        let mut buf = vec![];
+        put(&mut buf, 0, b"\x7fELF\x02\x01\x01");
+        put(&mut buf, 16, &3u16.to_le_bytes()); // e_type: ET_DYN
+        put(&mut buf, 18, &62u16.to_le_bytes()); // e_machine: x86_64
+        put(&mut buf, 20, &1u32.to_le_bytes()); // e_version
+        put(&mut buf, 32, &64u64.to_le_bytes()); // e_phoff
+        put(&mut buf, 52, &64u16.to_le_bytes()); // e_ehsize
+        put(&mut buf, 54, &56u16.to_le_bytes()); // e_phentsize
+        put(&mut buf, 56, &2u16.to_le_bytes()); // e_phnum
+        put(&mut buf, 58, &64u16.to_le_bytes()); // e_shentsize
+
+        // (type, offset, size) of each program header, loaded at the same address
+        let segments = [
+            (1u32, 0, file_size),
+            (2, DYNAMIC as u64, (dynamic.len() * 16) as u64),
+        ];
+        for (index, (kind, offset, size)) in segments.into_iter().enumerate() {
+            let header = 64 + index * 56;
+            put(&mut buf, header, &kind.to_le_bytes());
+            put(&mut buf, header + 4, &6u32.to_le_bytes()); // p_flags: rw
+            put(&mut buf, header + 8, &offset.to_le_bytes());
+            put(&mut buf, header + 16, &offset.to_le_bytes());
+            put(&mut buf, header + 24, &offset.to_le_bytes());
+            put(&mut buf, header + 32, &size.to_le_bytes());
+            put(&mut buf, header + 40, &size.to_le_bytes());
+            put(&mut buf, header + 48, &8u64.to_le_bytes());
+        }
+        for (index, (tag, value)) in dynamic.into_iter().enumerate() {
+            put(&mut buf, DYNAMIC + index * 16, &tag.to_le_bytes());
+            put(&mut buf, DYNAMIC + index * 16 + 8, &value.to_le_bytes());
+        }
+        put(&mut buf, strtab_offset, &strtab);
+        if symbols.is_empty() {
+            return buf;
+        }
+
+        // Symbol 0 is the null symbol, with version 0 (local)
+        for (index, (name, version)) in imports.iter().enumerate() {
+            let symbol = symtab_offset + (index + 1) * 24;
+            put(&mut buf, symbol, &name.to_le_bytes());
+            put(&mut buf, symbol + 4, &[0x12]); // st_info: global function
+            let versym = versym_offset + (index + 1) * 2;
+            put(&mut buf, versym, &version.to_le_bytes());
+        }
+        // One bucket; the dynamic symbols are counted from the number of chains
+        let symbol_count = (imports.len() + 1) as u32;
+        put(&mut buf, hash_offset, &1u32.to_le_bytes());
+        put(&mut buf, hash_offset + 4, &symbol_count.to_le_bytes());
+
+        let mut offset = verneed_offset;
+        for (index, (_, file, versions)) in libraries.iter().enumerate() {
+            let next = if index + 1 < libraries.len() {
+                16 + versions.len() as u32 * 16
+            } else {
+                0
+            };
+            put(&mut buf, offset, &1u16.to_le_bytes()); // vn_version
+            put(&mut buf, offset + 2, &(versions.len() as u16).to_le_bytes());
+            put(&mut buf, offset + 4, &file.to_le_bytes());
+            put(&mut buf, offset + 8, &16u32.to_le_bytes()); // vn_aux
+            put(&mut buf, offset + 12, &next.to_le_bytes());
+            offset += 16;
+            for (aux, (_, name, version)) in versions.iter().enumerate() {
+                let next = if aux + 1 < versions.len() { 16u32 } else { 0 };
+                put(&mut buf, offset + 6, &version.to_le_bytes()); // vna_other
+                put(&mut buf, offset + 8, &name.to_le_bytes());
+                put(&mut buf, offset + 12, &next.to_le_bytes());
+                offset += 16;
+            }
+        }
+
+        // A null section, then .gnu.version and .gnu.version_r
+        put(&mut buf, 40, &(shdrs_offset as u64).to_le_bytes()); // e_shoff
+        put(&mut buf, 60, &3u16.to_le_bytes()); // e_shnum
+        let sections = [
+            (
+                0x6fff_ffffu32,
+                versym_offset,
+                symbol_count as usize * 2,
+                0,
+                2,
+            ),
+            (
+                0x6fff_fffe,
+                verneed_offset,
+                verneed_size,
+                libraries.len() as u32,
+                0,
+            ),
+        ];
+        for (index, (kind, offset, size, info, entsize)) in sections.into_iter().enumerate() {
+            let header = shdrs_offset + (index + 1) * 64;
+            put(&mut buf, header + 4, &kind.to_le_bytes());
+            put(&mut buf, header + 8, &2u64.to_le_bytes()); // sh_flags: SHF_ALLOC
+            put(&mut buf, header + 16, &(offset as u64).to_le_bytes());
+            put(&mut buf, header + 24, &(offset as u64).to_le_bytes());
+            put(&mut buf, header + 32, &(size as u64).to_le_bytes());
+            put(&mut buf, header + 44, &info.to_le_bytes());
+            put(&mut buf, header + 48, &8u64.to_le_bytes());
+            put(&mut buf, header + 56, &(entsize as u64).to_le_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn decompress_binaries() {
+        use std::io::Write;
+
+        let binary = elf_fixture(&["libc.so.6"]);
+        let dir = TempDir::new().unwrap();
+
+        let mut xz = xz2::write::XzEncoder::new(vec![], 6);
+        xz.write_all(&binary).unwrap();
+        let xz = xz.finish().unwrap();
+        let zstd = zstd::encode_all(&binary[..], 0).unwrap();
+        for (name, compressed, format) in [("app.xz", &xz, "xz"), ("app.zst", &zstd, "zstd")] {
+            assert_eq!(compression_format(compressed), Some(format));
+            let path = dir.path().join(name);
+            fs::write(&path, compressed).unwrap();
+            let (_dir, decompressed) = decompress_binary(&path, format).unwrap();
+            assert_eq!(decompressed.file_name().unwrap(), "app");
+            assert_eq!(fs::read(&decompressed).unwrap(), binary);
+
+            // Truncated or corrupted data is an error rather than a short binary
+            fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+            assert!(
+                decompress_binary(&path, format).is_err(),
+                "{name} truncated"
+            );
+            let mut corrupted = compressed.clone();
+            for byte in &mut corrupted[12..] {
+                *byte ^= 0x55;
+            }
+            fs::write(&path, &corrupted).unwrap();
+            assert!(
+                decompress_binary(&path, format).is_err(),
+                "{name} corrupted"
+            );
+        }
+        assert_eq!(compression_format(&binary), None);
+    }
+
+    #[test]
+    fn mislabeled_binaries() {
+        let dir = TempDir::new().unwrap();
+        let config = LinkageConfig::default();
+        let resolver = MockResolver::default();
+        let check = |contents: &[u8], target: &str| {
+            let path = dir.path().join("app");
+            fs::write(&path, contents).unwrap();
+            determine_linkage(&path, target, &config, None, &resolver, None)
+        };
+
+        // The magic number is checked before parsing, so even a binary cut short
+        // says what it is rather than failing to parse as what it should be
+        let elf = elf_fixture(&["libc.so.6"]);
+        for binary in [&elf[..], &elf[..16]] {
+            let err = check(binary, "x86_64-pc-windows-msvc").unwrap_err();
+            let DistError::LinkageCheckFormatMismatch {
+                target,
+                expected,
+                found,
+                ..
+            } = err
+            else {
+                panic!("expected a format mismatch, got {err:?}");
+            };
+            assert_eq!(target, "x86_64-pc-windows-msvc");
+            assert_eq!(expected, "PE");
+            assert_eq!(found, "ELF");
+        }
+
+        let err = check(b"#!/bin/sh\necho hi\n", "aarch64-apple-darwin").unwrap_err();
+        assert!(matches!(
+            err,
+            DistError::LinkageCheckFormatMismatch {
+                expected: "Mach-O",
+                found: "unrecognized",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn compute_with_injected_resolver() {
+        // A sysroot with libc in it, so the binaries' libraries are found without the host
+        let sysroot = TempDir::new().unwrap();
+        let sysroot = Utf8PathBuf::from_path_buf(sysroot.path().to_owned()).unwrap();
+        fs::create_dir_all(sysroot.join("usr/lib")).unwrap();
+        fs::write(sysroot.join("usr/lib/libc.so.6"), "").unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let binary = |name: &str, contents: &[u8]| {
+            let path = Utf8PathBuf::from_path_buf(dir.path().join(name)).unwrap();
+            fs::write(&path, contents).unwrap();
+            BinaryToCheck {
+                id: name.to_owned(),
+                name: name.to_owned(),
+                path,
+                target: "x86_64-unknown-linux-gnu".to_owned(),
+                artifact_id: None,
+                system: String::new(),
+                checksums: SortedMap::new(),
+                bundled_files: None,
+            }
+        };
+        let binaries = [
+            binary("app", &elf_fixture(&["libc.so.6", "libcargo-dist.so.1"])),
+            binary("helper", &elf_fixture(&["libc.so.6"])),
+        ];
+        let resolver = MockResolver::default();
+        let options = LinkageOptions {
+            sysroot: Some(sysroot.clone()),
+            resolver: Some(&resolver),
+            parallelism: NonZeroUsize::new(2),
+            ..LinkageOptions::default()
+        };
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        compute_linkage(&mut manifest, &binaries, &options).unwrap();
+
+        // Both binaries' libraries are prefetched together, before either is resolved
+        assert_eq!(
+            *resolver.prefetched.lock().unwrap(),
+            [["/usr/lib/libc.so.6", "libcargo-dist.so.1"]]
+        );
+
+        // Every library is classified, then attributed by the resolver, not the host
+        let sources = |libraries: &SortedSet<Library>| {
+            libraries
+                .iter()
+                .map(|library| (library.path.clone(), library.source.clone()))
+                .collect::<Vec<_>>()
+        };
+        let libc = ("/usr/lib/libc.so.6".to_owned(), Some("libc6".to_owned()));
+        let app = manifest.assets["app"].linkage.as_ref().unwrap();
+        assert_eq!(sources(&app.system), [libc.clone()]);
+        assert_eq!(
+            sources(&app.other),
+            [("libcargo-dist.so.1".to_owned(), None)]
+        );
+        let helper = manifest.assets["helper"].linkage.as_ref().unwrap();
+        assert_eq!(sources(&helper.system), [libc]);
+        assert!(helper.other.is_empty());
+
+        // A binary that can't be checked fails the run, unless failures are allowed
+        let broken = [binary("broken", b"not a binary")];
+        assert!(compute_linkage(&mut manifest, &broken, &options).is_err());
+        let options = LinkageOptions {
+            allow_failures: true,
+            ..options
+        };
+        compute_linkage(&mut manifest, &broken, &options).unwrap();
+        assert!(manifest.assets["broken"].linkage.is_none());
+    }
+}
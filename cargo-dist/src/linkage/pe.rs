@@ -0,0 +1,430 @@
+//! Reading the linkage of PE binaries, and their debug info
+
+use camino::Utf8PathBuf;
+use cargo_dist_schema::{DllLocation, LinkReason, Linkage};
+use goblin::Object;
+
+use crate::{errors::*, to_hex, SortedSet};
+
+use super::{split_library_name, without_reasons, LinkedLibraries};
+
+pub(crate) fn do_pe(object: &Object, buf: &[u8]) -> DistResult<LinkedLibraries> {
+    match object {
+        Object::PE(pe) => {
+            let mut libraries =
+                without_reasons(pe.libraries.iter().map(|s| (*s).to_owned()).collect());
+            for dll in delay_loaded_dlls(pe, buf) {
+                libraries.push((dll, Some(LinkReason::Delay)));
+            }
+            Ok(libraries)
+        }
+        _ => Err(DistError::LinkageCheckUnsupportedBinary {}),
+    }
+}
+
+/// Get the DLLs listed in a PE's delay-import directory
+///
+/// These are only loaded the first time one of their functions is called, but they're
+/// still real dependencies. goblin only parses the regular import directory, so we
+/// walk the delay-load descriptors ourselves.
+fn delay_loaded_dlls(pe: &goblin::pe::PE, buf: &[u8]) -> Vec<String> {
+    // Each descriptor is eight u32s, terminated by an all-zero descriptor
+    const DESCRIPTOR_SIZE: usize = 32;
+
+    let mut dlls = vec![];
+    let Some(optional_header) = pe.header.optional_header else {
+        return dlls;
+    };
+    let Some(directory) = optional_header
+        .data_directories
+        .get_delay_import_descriptor()
+    else {
+        return dlls;
+    };
+    let file_alignment = optional_header.windows_fields.file_alignment;
+    let opts = goblin::pe::options::ParseOptions::default();
+    let to_offset = |rva: u32| {
+        goblin::pe::utils::find_offset(rva as usize, &pe.sections, file_alignment, &opts)
+    };
+
+    let Some(mut offset) = to_offset(directory.virtual_address) else {
+        return dlls;
+    };
+    while let Some(descriptor) = buf.get(offset..offset + DESCRIPTOR_SIZE) {
+        let field = |idx: usize| {
+            u32::from_le_bytes([
+                descriptor[idx * 4],
+                descriptor[idx * 4 + 1],
+                descriptor[idx * 4 + 2],
+                descriptor[idx * 4 + 3],
+            ])
+        };
+        let attributes = field(0);
+        let name = field(1);
+        if name == 0 {
+            break;
+        }
+        // Descriptors from very old linkers use virtual addresses instead of RVAs
+        let name_rva = if attributes & 1 == 0 {
+            name.wrapping_sub(pe.image_base as u32)
+        } else {
+            name
+        };
+        // A name that runs off the end of the file has been cut short
+        let dll = to_offset(name_rva)
+            .and_then(|start| buf.get(start..))
+            .and_then(|rest| Some(&rest[..rest.iter().position(|&b| b == 0)?]));
+        if let Some(dll) = dll {
+            dlls.push(String::from_utf8_lossy(dll).into_owned());
+        }
+        offset += DESCRIPTOR_SIZE;
+    }
+
+    dlls
+}
+
+/// Format a GUID the way symbol servers do: the first three fields are stored little-endian
+pub(crate) fn format_guid(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08X}{:04X}{:04X}{}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        to_hex(&guid[8..]).to_uppercase(),
+    )
+}
+
+/// Get the GUID of a PDB, formatted like [`format_guid`][]
+///
+/// PDBs are MSF files: a sequence of fixed-size blocks, with a directory listing the blocks
+/// that make up each stream. The GUID is in stream 1, the PDB info stream, after its
+/// version, signature and age.
+pub(crate) fn pdb_guid(buf: &[u8]) -> Option<String> {
+    const MAGIC: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+    if !buf.starts_with(MAGIC) {
+        return None;
+    }
+    let u32_at = |data: &[u8], offset: usize| -> Option<usize> {
+        let bytes = data.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+    };
+    let block_size = u32_at(buf, 32)?;
+    let directory_size = u32_at(buf, 44)?;
+    let block_map = u32_at(buf, 52)?.checked_mul(block_size)?;
+    // The directory can't be bigger than the file it's in
+    if block_size == 0 || directory_size > buf.len() {
+        return None;
+    }
+
+    // The directory can span several blocks, which the block map lists
+    let mut directory = vec![];
+    for index in 0..directory_size.div_ceil(block_size) {
+        let start = u32_at(buf, block_map + index * 4)?.checked_mul(block_size)?;
+        directory.extend_from_slice(buf.get(start..start.checked_add(block_size)?)?);
+    }
+    directory.truncate(directory_size);
+
+    // The directory is the number of streams, each stream's size, then each stream's
+    // blocks in turn. A size of u32::MAX marks a stream that doesn't exist.
+    let stream_count = u32_at(&directory, 0)?;
+    if stream_count < 2 {
+        return None;
+    }
+    let stream0_size = u32_at(&directory, 4)?;
+    let stream0_blocks = if stream0_size == u32::MAX as usize {
+        0
+    } else {
+        stream0_size.div_ceil(block_size)
+    };
+    let info_block = u32_at(&directory, (1 + stream_count + stream0_blocks) * 4)?;
+    let start = info_block.checked_mul(block_size)?;
+    let guid = buf.get(start.checked_add(12)?..start.checked_add(28)?)?;
+    Some(format_guid(guid.try_into().ok()?))
+}
+
+/// Find out which subsystem a PE binary runs under
+///
+/// This is what decides whether Windows gives a program a console: a CLI tool
+/// built as "gui" will print nothing, and a GUI app built as "console" will
+/// open a console window alongside itself.
+pub(crate) fn determine_pe_subsystem(object: &Object) -> Option<String> {
+    let Object::PE(pe) = object else {
+        return None;
+    };
+    let optional_header = pe.header.optional_header?;
+
+    // The IMAGE_SUBSYSTEM_* constants
+    let subsystem = match optional_header.windows_fields.subsystem {
+        1 => "native",
+        2 => "gui",
+        3 => "console",
+        9 => "windows-ce-gui",
+        10 => "efi-application",
+        11 => "efi-boot-service-driver",
+        12 => "efi-runtime-driver",
+        13 => "efi-rom",
+        16 => "boot-application",
+        other => return Some(format!("unknown ({other})")),
+    };
+    Some(subsystem.to_owned())
+}
+
+/// Work out where Windows will load a DLL from
+pub(crate) fn locate_dll(name: &str, bundled_dlls: &SortedSet<String>) -> DllLocation {
+    let name = name.to_lowercase();
+    if bundled_dlls.contains(&name) {
+        DllLocation::Bundled
+    } else if is_windows_system_dll(&name) {
+        DllLocation::System
+    } else {
+        DllLocation::Unknown
+    }
+}
+
+/// Check if a (lowercased) DLL name is provided by Windows itself
+fn is_windows_system_dll(name: &str) -> bool {
+    // API sets are virtual DLLs that the loader always resolves to system libraries
+    if name.starts_with("api-ms-win-") || name.starts_with("ext-ms-") {
+        return true;
+    }
+    // If we're on Windows we can just look
+    if let Ok(system_root) = std::env::var("SystemRoot") {
+        if Utf8PathBuf::from(system_root)
+            .join("System32")
+            .join(name)
+            .exists()
+        {
+            return true;
+        }
+    }
+    // Otherwise fall back to the DLLs Rust programs commonly import that ship with every
+    // version of Windows we support
+    const SYSTEM_DLLS: &[&str] = &[
+        "advapi32.dll",
+        "bcrypt.dll",
+        "bcryptprimitives.dll",
+        "comctl32.dll",
+        "comdlg32.dll",
+        "crypt32.dll",
+        "dbghelp.dll",
+        "dwmapi.dll",
+        "gdi32.dll",
+        "imm32.dll",
+        "iphlpapi.dll",
+        "kernel32.dll",
+        "msvcrt.dll",
+        "ncrypt.dll",
+        "netapi32.dll",
+        "ntdll.dll",
+        "ole32.dll",
+        "oleaut32.dll",
+        "propsys.dll",
+        "psapi.dll",
+        "secur32.dll",
+        "setupapi.dll",
+        "shell32.dll",
+        "shlwapi.dll",
+        "synchronization.dll",
+        "ucrtbase.dll",
+        "user32.dll",
+        "userenv.dll",
+        "uxtheme.dll",
+        "version.dll",
+        "winhttp.dll",
+        "winmm.dll",
+        "ws2_32.dll",
+    ];
+    SYSTEM_DLLS.contains(&name)
+}
+
+/// Whether a DLL is part of the Visual C++ runtime, which comes with the Visual C++
+/// Redistributable
+///
+/// The Universal CRT (`ucrtbase.dll`) isn't: it's been part of Windows since Windows 10.
+pub(crate) fn is_vc_redist_dll(library: &str) -> bool {
+    let (name, _) = split_library_name(library);
+    name.starts_with("vcruntime140")
+        || name.starts_with("msvcp140")
+        || matches!(name.as_str(), "concrt140" | "vccorlib140" | "vcomp140")
+}
+
+/// Get the Visual C++ runtime DLLs a Windows binary needs that aren't bundled with it
+pub(crate) fn vc_redist_dlls(linkage: &Linkage) -> Vec<String> {
+    linkage
+        .libraries()
+        .filter(|library| library.location != Some(DllLocation::Bundled))
+        .filter(|library| is_vc_redist_dll(&library.path))
+        .map(|library| library.path.clone())
+        .collect::<SortedSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linkage::tests::put;
+
+    use super::*;
+
+    /// A minimal x86_64 PE whose delay-import directory lists `dlls`
+    ///
+    /// Its one section is at RVA 0x1000, or 0x200 in the file, and holds the descriptors
+    /// followed by the DLL names.
+    fn pe_fixture(dlls: &[&str]) -> Vec<u8> {
+        const SECTION_RVA: u32 = 0x1000;
+        const SECTION_OFFSET: usize = 0x200;
+        let mut buf = vec![];
+        put(&mut buf, 0, b"MZ");
+        put(&mut buf, 0x3c, &0x40u32.to_le_bytes()); // e_lfanew
+        put(&mut buf, 0x40, b"PE\0\0");
+        put(&mut buf, 0x44, &0x8664u16.to_le_bytes()); // machine
+        put(&mut buf, 0x46, &1u16.to_le_bytes()); // number of sections
+        put(&mut buf, 0x54, &240u16.to_le_bytes()); // size of optional header
+        put(&mut buf, 0x56, &0x22u16.to_le_bytes()); // executable, large address aware
+        let optional = 0x58;
+        put(&mut buf, optional, &0x20bu16.to_le_bytes()); // PE32+
+        put(&mut buf, optional + 24, &0x1_4000_0000u64.to_le_bytes()); // image base
+        put(&mut buf, optional + 32, &0x1000u32.to_le_bytes()); // section alignment
+        put(&mut buf, optional + 36, &0x200u32.to_le_bytes()); // file alignment
+        put(&mut buf, optional + 56, &0x2000u32.to_le_bytes()); // size of image
+        put(&mut buf, optional + 60, &0x200u32.to_le_bytes()); // size of headers
+        put(&mut buf, optional + 68, &3u16.to_le_bytes()); // subsystem: console
+        put(&mut buf, optional + 108, &16u32.to_le_bytes()); // number of data directories
+        let descriptors_size = (dlls.len() as u32 + 1) * 32;
+        let delay_import = optional + 112 + 13 * 8;
+        put(&mut buf, delay_import, &SECTION_RVA.to_le_bytes());
+        put(&mut buf, delay_import + 4, &descriptors_size.to_le_bytes());
+        let section = optional + 240;
+        put(&mut buf, section, b".didat\0\0");
+        put(&mut buf, section + 8, &0x200u32.to_le_bytes()); // virtual size
+        put(&mut buf, section + 12, &SECTION_RVA.to_le_bytes());
+        put(&mut buf, section + 16, &0x200u32.to_le_bytes()); // size of raw data
+        put(
+            &mut buf,
+            section + 20,
+            &(SECTION_OFFSET as u32).to_le_bytes(),
+        );
+        put(&mut buf, section + 36, &0x4000_0040u32.to_le_bytes()); // readable data
+
+        let mut name_offset = descriptors_size as usize;
+        for (index, dll) in dlls.iter().enumerate() {
+            let descriptor = SECTION_OFFSET + index * 32;
+            // Attributes say the name is an RVA
+            put(&mut buf, descriptor, &1u32.to_le_bytes());
+            let name_rva = SECTION_RVA + name_offset as u32;
+            put(&mut buf, descriptor + 4, &name_rva.to_le_bytes());
+            put(&mut buf, SECTION_OFFSET + name_offset, dll.as_bytes());
+            name_offset += dll.len() + 1;
+        }
+        buf.resize(SECTION_OFFSET + 0x200, 0);
+        buf
+    }
+
+    /// A minimal PDB with 64-byte blocks, whose info stream has the given GUID
+    ///
+    /// The blocks are: the superblock, the block map, the stream directory, then the
+    /// info stream.
+    fn pdb_fixture(guid: [u8; 16]) -> Vec<u8> {
+        const BLOCK: usize = 64;
+        let mut buf = vec![];
+        put(&mut buf, 0, b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0");
+        put(&mut buf, 32, &(BLOCK as u32).to_le_bytes()); // block size
+        put(&mut buf, 40, &4u32.to_le_bytes()); // number of blocks
+        put(&mut buf, 44, &16u32.to_le_bytes()); // directory size
+        put(&mut buf, 52, &1u32.to_le_bytes()); // block map's block
+        put(&mut buf, BLOCK, &2u32.to_le_bytes()); // the directory's block
+
+        // Two streams: an empty stream 0, and the 28 bytes of the info stream in block 3
+        for (index, value) in [2u32, 0, 28, 3].into_iter().enumerate() {
+            put(&mut buf, 2 * BLOCK + index * 4, &value.to_le_bytes());
+        }
+        // Version, signature and age, then the GUID
+        put(&mut buf, 3 * BLOCK, &20000404u32.to_le_bytes());
+        put(&mut buf, 3 * BLOCK + 8, &1u32.to_le_bytes());
+        put(&mut buf, 3 * BLOCK + 12, &guid);
+        buf.resize(4 * BLOCK, 0);
+        buf
+    }
+
+    #[test]
+    fn read_delay_loaded_dlls() {
+        let buf = pe_fixture(&["DELAYED.dll", "other.dll"]);
+        let Object::PE(pe) = Object::parse(&buf).unwrap() else {
+            panic!("fixture isn't a PE");
+        };
+        assert_eq!(
+            delay_loaded_dlls(&pe, &buf),
+            vec!["DELAYED.dll", "other.dll"]
+        );
+        assert_eq!(do_pe(&Object::PE(pe), &buf).unwrap().len(), 2);
+
+        // Descriptors and names cut off partway through are ignored rather than misread
+        assert!(delay_loaded_dlls(&pe_of(&buf), &buf[..0x210]).is_empty());
+        assert!(delay_loaded_dlls(&pe_of(&buf), &buf[..0x230]).is_empty());
+        assert_eq!(
+            delay_loaded_dlls(&pe_of(&buf), &buf[..0x270]),
+            vec!["DELAYED.dll"]
+        );
+
+        // As are names that point outside the file
+        let mut bad_name = buf.clone();
+        put(&mut bad_name, 0x204, &0x5000u32.to_le_bytes());
+        assert_eq!(
+            delay_loaded_dlls(&pe_of(&bad_name), &bad_name),
+            vec!["other.dll"]
+        );
+
+        // A directory that isn't in any section has nothing to read
+        let mut bad_directory = buf.clone();
+        put(
+            &mut bad_directory,
+            0x58 + 112 + 13 * 8,
+            &0x9000u32.to_le_bytes(),
+        );
+        assert!(delay_loaded_dlls(&pe_of(&bad_directory), &bad_directory).is_empty());
+
+        // And a PE that's cut short is an error rather than a panic
+        for len in [0x10, 0x60, 0x150] {
+            assert!(Object::parse(&buf[..len]).is_err(), "parsed {len} bytes");
+        }
+    }
+
+    fn pe_of(buf: &[u8]) -> goblin::pe::PE<'_> {
+        goblin::pe::PE::parse(buf).unwrap()
+    }
+
+    #[test]
+    fn read_pdb_guid() {
+        let guid = [
+            0x78, 0x56, 0x34, 0x12, 0x34, 0x12, 0x78, 0x56, 1, 2, 3, 4, 5, 6, 7, 8,
+        ];
+        let buf = pdb_fixture(guid);
+        assert_eq!(
+            pdb_guid(&buf).as_deref(),
+            Some("12345678123456780102030405060708")
+        );
+
+        // Any truncation leaves something out
+        for len in 0..buf.len() - 36 {
+            assert_eq!(pdb_guid(&buf[..len]), None, "read {len} bytes");
+        }
+        assert_eq!(pdb_guid(b"not a pdb"), None);
+
+        let mut zero_blocks = buf.clone();
+        put(&mut zero_blocks, 32, &0u32.to_le_bytes());
+        assert_eq!(pdb_guid(&zero_blocks), None);
+
+        // A directory bigger than the file shouldn't be read block by block
+        let mut huge_directory = buf.clone();
+        put(&mut huge_directory, 44, &u32::MAX.to_le_bytes());
+        assert_eq!(pdb_guid(&huge_directory), None);
+
+        let mut no_info_stream = buf.clone();
+        put(&mut no_info_stream, 128, &1u32.to_le_bytes());
+        assert_eq!(pdb_guid(&no_info_stream), None);
+
+        let mut bad_block = buf.clone();
+        put(&mut bad_block, 140, &99u32.to_le_bytes());
+        assert_eq!(pdb_guid(&bad_block), None);
+    }
+}
@@ -0,0 +1,1126 @@
+//! Checking linkage against the workspace's linkage policy
+
+use camino::Utf8Path;
+use cargo_dist_schema::{DistManifest, DllLocation, Hardening, Linkage, LinkageStatus, Relro};
+use serde::Serialize;
+
+use crate::{
+    config::{HardeningFeature, LinkageAdvisory, LinkageConfig, LinkageLibraryList},
+    errors::*,
+    SortedMap, SortedSet,
+};
+
+use super::{parse_version, plural, split_library_name, LinkageArgs};
+
+/// Add the policy from the command line (a --policy file, or --require-bundled) to the
+/// workspace's linkage policy
+pub(crate) fn linkage_policy(
+    mut config: LinkageConfig,
+    args: &LinkageArgs,
+) -> DistResult<LinkageConfig> {
+    if let Some(policy) = &args.policy {
+        config.merge_policy_file(policy)?;
+    }
+    if args.require_bundled {
+        config.require_bundled = Some(true);
+    }
+    Ok(config)
+}
+
+/// Every mitigation [`LinkageConfig::require_hardening`][] can ask for
+pub(crate) const HARDENING_FEATURES: [HardeningFeature; 8] = [
+    HardeningFeature::Pie,
+    HardeningFeature::Relro,
+    HardeningFeature::Nx,
+    HardeningFeature::StackCanary,
+    HardeningFeature::Fortify,
+    HardeningFeature::Aslr,
+    HardeningFeature::HighEntropyVa,
+    HardeningFeature::Cfg,
+];
+
+/// The counts for the line that ends a human-readable linkage report, which are also
+/// the compact JSON summary (along with the binaries that have problems)
+#[derive(Serialize)]
+pub(crate) struct LinkageSummary {
+    /// How many binaries were checked
+    binaries: usize,
+    /// How many distinct targets those binaries are for
+    targets: usize,
+    /// How many binaries have warnings, whether or not they also have violations
+    warnings: usize,
+    /// How many policy violations were found
+    violations: usize,
+    /// How many binaries were missing, and so weren't checked
+    skipped: usize,
+    /// Every binary with warnings or violations
+    problems: Vec<LinkageProblem>,
+    /// A description of each policy violation
+    violation_messages: Vec<String>,
+}
+
+/// A binary with warnings or violations, for the compact JSON summary
+#[derive(Serialize)]
+struct LinkageProblem {
+    /// Name of the binary
+    binary: String,
+    /// The targets it was built for
+    targets: Vec<String>,
+    /// Whether it has warnings or violations
+    status: LinkageStatus,
+    /// Public libraries it links that no package manager provides
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    public_unmanaged: Vec<String>,
+    /// Everything else noticed about it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+impl LinkageSummary {
+    pub(crate) fn new(manifest: &DistManifest, violations: &[DistError], skipped: usize) -> Self {
+        let mut binaries = 0;
+        let mut warnings = 0;
+        let mut targets = SortedSet::new();
+        let mut problems = vec![];
+        for asset in manifest.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            binaries += 1;
+            if has_warnings(linkage) {
+                warnings += 1;
+            }
+            targets.extend(&asset.target_triples);
+            if let Some(status @ (LinkageStatus::Warning | LinkageStatus::Violation)) =
+                linkage.status
+            {
+                problems.push(LinkageProblem {
+                    binary: asset.name.clone(),
+                    targets: asset.target_triples.clone(),
+                    status,
+                    public_unmanaged: linkage
+                        .public_unmanaged
+                        .iter()
+                        .map(|l| l.path.clone())
+                        .collect(),
+                    warnings: linkage.warnings.clone(),
+                });
+            }
+        }
+        Self {
+            binaries,
+            targets: targets.len(),
+            warnings,
+            violations: violations.len(),
+            skipped,
+            problems,
+            violation_messages: violations.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for LinkageSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Analyzed {}", plural(self.binaries, "binary"))?;
+        // Bare linkage loaded from json doesn't know its targets
+        if self.targets > 0 {
+            write!(f, " across {}", plural(self.targets, "target"))?;
+        }
+        write!(
+            f,
+            ": {} with warnings, {}",
+            plural(self.warnings, "binary"),
+            plural(self.violations, "violation")
+        )?;
+        if self.skipped > 0 {
+            write!(f, ", {} missing", plural(self.skipped, "binary"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a binary has anything to warn about, short of a policy violation
+fn has_warnings(linkage: &Linkage) -> bool {
+    // Unmanaged libraries are unlikely to exist on a user's machine
+    !linkage.public_unmanaged.is_empty() || !linkage.warnings.is_empty()
+}
+
+/// Apply the linkage checks to every binary, recording the resulting status
+/// on each Linkage so consumers of the json don't need to redo this work.
+///
+/// Returns every violation found.
+pub(crate) fn evaluate_linkage(
+    manifest: &mut DistManifest,
+    args: &LinkageArgs,
+    config: &LinkageConfig,
+) -> DistResult<Vec<DistError>> {
+    let mut violations = vec![];
+    for asset in manifest.assets.values_mut() {
+        let Some(linkage) = &mut asset.linkage else {
+            continue;
+        };
+
+        let mut status = LinkageStatus::Ok;
+        if has_warnings(linkage) {
+            status = LinkageStatus::Warning;
+        }
+        let mut found = check_linkage_policy(&asset.name, &asset.target_triples, linkage, config)?;
+        // This reports the same problem as require-static, so only report it once
+        let requires_static = asset
+            .target_triples
+            .iter()
+            .any(|t| config.require_static.contains(t));
+        if args.strict_static && !requires_static {
+            found.extend(check_strict_static(
+                &asset.name,
+                &asset.target_triples,
+                linkage,
+            ));
+        }
+        if args.deny_instrumentation {
+            found.extend(check_instrumentation(&asset.name, linkage));
+        }
+        if config.require_bundled == Some(true) {
+            found.extend(check_bundled(&asset.name, linkage));
+        }
+        if !found.is_empty() {
+            status = LinkageStatus::Violation;
+            violations.extend(found);
+        }
+
+        linkage.status = Some(status);
+    }
+
+    for group in &config.consistent_targets {
+        violations.extend(check_consistent_targets(manifest, group));
+    }
+
+    Ok(violations)
+}
+
+/// Check a binary's linkage against the policies in the linkage config
+///
+/// `cargo dist build` applies these to each binary as it builds it, and `cargo dist linkage`
+/// adds the policies from its command line on top. Policies that compare binaries with
+/// each other, like consistent-targets, aren't checked here, and neither is
+/// require-bundled, since what's bundled depends on the artifact the binary ships in.
+pub fn check_linkage_policy(
+    name: &str,
+    target_triples: &[String],
+    linkage: &Linkage,
+    config: &LinkageConfig,
+) -> DistResult<Vec<DistError>> {
+    let mut violations = check_library_lists(name, target_triples, linkage, config);
+    violations.extend(check_require_static(name, target_triples, linkage, config));
+    if config.deny_vc_redist == Some(true) {
+        violations.extend(check_vc_redist(name, linkage));
+    }
+    violations.extend(check_hardening(name, linkage, config));
+    if let Some(max) = &config.max_glibc {
+        let max_glibc = parse_version(max).ok_or_else(|| DistError::InvalidGlibcVersion {
+            version: max.clone(),
+        })?;
+        violations.extend(check_max_glibc(name, linkage, max, &max_glibc));
+    }
+    Ok(violations)
+}
+
+/// Check that a binary doesn't require a newer glibc than the configured maximum
+fn check_max_glibc(
+    name: &str,
+    linkage: &Linkage,
+    max: &str,
+    max_version: &[u32],
+) -> Option<DistError> {
+    let required = linkage.min_glibc.as_ref()?;
+    if parse_version(required)?.as_slice() <= max_version {
+        return None;
+    }
+
+    Some(DistError::LinkageGlibcTooNew {
+        binary: name.to_owned(),
+        required: required.clone(),
+        max: max.to_owned(),
+        symbol: linkage
+            .min_glibc_symbol
+            .clone()
+            .unwrap_or_else(|| format!("GLIBC_{required}")),
+    })
+}
+
+/// Remove the system libraries from a linkage, and from each of its slices
+pub(crate) fn hide_system_libraries(linkage: &mut Linkage) {
+    linkage.system.clear();
+    for slice in &mut linkage.slices {
+        hide_system_libraries(slice);
+    }
+}
+
+/// Remove every binary with nothing noteworthy about its linkage from the manifest
+///
+/// This must run after [`evaluate_linkage`][], which decides what's noteworthy.
+/// Artifacts are only kept if they still ship one of the remaining binaries.
+pub(crate) fn retain_problems(manifest: &mut DistManifest) {
+    manifest.assets.retain(|_, asset| {
+        let status = asset.linkage.as_ref().and_then(|linkage| linkage.status);
+        status.is_some_and(|status| status != LinkageStatus::Ok)
+    });
+    let assets = &manifest.assets;
+    manifest.artifacts.retain(|_, artifact| {
+        artifact
+            .assets
+            .iter()
+            .any(|asset| asset.id.as_ref().is_some_and(|id| assets.contains_key(id)))
+    });
+}
+
+/// Check that each binary links the same libraries on every target in a group
+///
+/// Libraries are compared by canonical name, since the same library has a different
+/// path (and file name) on each platform. Binaries that differ are marked as violations.
+fn check_consistent_targets(manifest: &mut DistManifest, group: &[String]) -> Vec<DistError> {
+    // For each binary: the targets in the group it was built for, and what it links there
+    let mut builds = SortedMap::<String, Vec<(String, String, SortedSet<String>)>>::new();
+    for (asset_id, asset) in &manifest.assets {
+        let Some(linkage) = &asset.linkage else {
+            continue;
+        };
+        let Some(target) = asset.target_triples.iter().find(|t| group.contains(t)) else {
+            continue;
+        };
+        builds.entry(asset.name.clone()).or_default().push((
+            target.clone(),
+            asset_id.clone(),
+            library_names(linkage),
+        ));
+    }
+
+    let describe = |libraries: Vec<&String>| {
+        if libraries.is_empty() {
+            "(nothing)".to_owned()
+        } else {
+            libraries
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+    let mut violations = vec![];
+    for (binary, builds) in builds {
+        let Some(((target, _, libraries), others)) = builds.split_first() else {
+            continue;
+        };
+        let mut consistent = true;
+        for (other_target, _, other_libraries) in others {
+            if other_libraries == libraries {
+                continue;
+            }
+            consistent = false;
+            violations.push(DistError::LinkageInconsistent {
+                binary: binary.clone(),
+                target: target.clone(),
+                other_target: other_target.clone(),
+                only_target: describe(libraries.difference(other_libraries).collect()),
+                only_other_target: describe(other_libraries.difference(libraries).collect()),
+            });
+        }
+        if consistent {
+            continue;
+        }
+        for (_, asset_id, _) in &builds {
+            let linkage = manifest
+                .assets
+                .get_mut(asset_id)
+                .and_then(|asset| asset.linkage.as_mut());
+            if let Some(linkage) = linkage {
+                linkage.status = Some(LinkageStatus::Violation);
+            }
+        }
+    }
+    violations
+}
+
+/// The canonical names of every library a binary links, for comparing it to other builds
+pub(crate) fn library_names(linkage: &Linkage) -> SortedSet<String> {
+    linkage
+        .libraries()
+        .map(|library| split_library_name(&library.path).0)
+        .collect()
+}
+
+/// Check that a binary built for a musl target is fully static
+///
+/// We build musl targets precisely to get static binaries, so any dynamic
+/// dependency at all means something (glibc NSS, a dlopen'd lib...) slipped in.
+fn check_strict_static(
+    name: &str,
+    target_triples: &[String],
+    linkage: &Linkage,
+) -> Option<DistError> {
+    let target = target_triples.iter().find(|t| t.ends_with("-musl"))?;
+    check_static(name, target, linkage)
+}
+
+/// Check that a binary is fully static if it's for a target the linkage config's
+/// require-static lists
+pub fn check_require_static(
+    name: &str,
+    target_triples: &[String],
+    linkage: &Linkage,
+    config: &LinkageConfig,
+) -> Option<DistError> {
+    let target = target_triples
+        .iter()
+        .find(|t| config.require_static.contains(*t))?;
+    check_static(name, target, linkage)
+}
+
+/// Check that a binary built for the given target has no dynamic linkage at all
+fn check_static(name: &str, target: &str, linkage: &Linkage) -> Option<DistError> {
+    if linkage.is_static() {
+        return None;
+    }
+
+    Some(DistError::LinkageNotStatic {
+        binary: name.to_owned(),
+        target: target.to_owned(),
+        libraries: linkage
+            .libraries()
+            .map(|l| l.path.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+    })
+}
+
+/// Check a binary's libraries against the allow and deny lists for its target
+///
+/// A library is denied if any applicable deny list names it, even if it's also allowed.
+/// If no allow list applies to the binary, anything not denied is allowed.
+pub fn check_library_lists(
+    name: &str,
+    target_triples: &[String],
+    linkage: &Linkage,
+    config: &LinkageConfig,
+) -> Vec<DistError> {
+    let applicable = |lists: &[LinkageLibraryList]| {
+        lists
+            .iter()
+            .filter(|list| list.applies_to(target_triples))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let allow = applicable(&config.allow);
+    let deny = applicable(&config.deny);
+    let target = target_triples.join(", ");
+
+    let mut denied = vec![];
+    let mut not_allowed = vec![];
+    for library in linkage.libraries() {
+        let (library_name, _) = split_library_name(&library.path);
+        if deny.iter().any(|list| list.contains(&library_name)) {
+            denied.push(library.path.clone());
+        } else if !allow.is_empty() && !allow.iter().any(|list| list.contains(&library_name)) {
+            not_allowed.push(library.path.clone());
+        }
+    }
+
+    let mut violations = vec![];
+    if !denied.is_empty() {
+        violations.push(DistError::LinkageDenied {
+            binary: name.to_owned(),
+            target: target.clone(),
+            libraries: denied.join(", "),
+        });
+    }
+    if !not_allowed.is_empty() {
+        violations.push(DistError::LinkageNotAllowed {
+            binary: name.to_owned(),
+            target,
+            libraries: not_allowed.join(", "),
+        });
+    }
+    violations
+}
+
+/// Check that every library a binary links is either bundled alongside it or part of the OS
+///
+/// Libraries with no recorded location (e.g. from a report made without
+/// require-bundled) can't be judged, so they're let through.
+fn check_bundled(name: &str, linkage: &Linkage) -> Option<DistError> {
+    let missing = linkage
+        .libraries()
+        .filter(|library| library.location == Some(DllLocation::Unknown))
+        .map(|library| library.path.clone())
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(DistError::LinkageNotBundled {
+        binary: name.to_owned(),
+        libraries: missing.join(", "),
+    })
+}
+
+/// Check that a binary doesn't link any sanitizer or profiling runtimes
+fn check_instrumentation(name: &str, linkage: &Linkage) -> Option<DistError> {
+    let runtimes = instrumentation_runtimes(linkage);
+    if runtimes.is_empty() {
+        return None;
+    }
+
+    Some(DistError::LinkageInstrumented {
+        binary: name.to_owned(),
+        runtimes: runtimes.into_iter().collect::<Vec<_>>().join(", "),
+    })
+}
+
+/// Check that a Windows binary doesn't need the Visual C++ Redistributable
+pub fn check_vc_redist(name: &str, linkage: &Linkage) -> Option<DistError> {
+    if linkage.vc_redist.is_empty() {
+        return None;
+    }
+
+    Some(DistError::LinkageNeedsVcRedist {
+        binary: name.to_owned(),
+        dlls: linkage.vc_redist.join(", "),
+    })
+}
+
+/// Check that a binary was built with the mitigations the linkage config's
+/// require-hardening lists
+///
+/// Mitigations that don't apply to the binary's format are skipped.
+pub fn check_hardening(name: &str, linkage: &Linkage, config: &LinkageConfig) -> Option<DistError> {
+    let hardening = linkage.hardening.as_ref()?;
+    let missing = config
+        .require_hardening
+        .iter()
+        .filter(|feature| hardening_enabled(hardening, **feature) == Some(false))
+        .map(|feature| feature.to_string())
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(DistError::LinkageNotHardened {
+        binary: name.to_owned(),
+        missing: missing.join(", "),
+    })
+}
+
+/// Whether a binary has a mitigation, or `None` if it doesn't apply to the binary
+pub(crate) fn hardening_enabled(hardening: &Hardening, feature: HardeningFeature) -> Option<bool> {
+    match feature {
+        HardeningFeature::Pie => hardening.pie,
+        HardeningFeature::Relro => hardening.relro.map(|relro| relro == Relro::Full),
+        HardeningFeature::Nx => hardening.nx,
+        HardeningFeature::StackCanary => hardening.stack_canary,
+        HardeningFeature::Fortify => hardening.fortify,
+        HardeningFeature::Aslr => hardening.aslr,
+        HardeningFeature::HighEntropyVa => hardening.high_entropy_va,
+        HardeningFeature::Cfg => hardening.cfg,
+    }
+}
+
+/// Libraries that are known to be a problem to depend on: (name, versions, why)
+///
+/// Libraries are matched like [`LinkageAdvisory`][], and an empty list of versions
+/// matches every version.
+const BUILTIN_ADVISORIES: &[(&str, &[&str], &str)] = &[
+    (
+        "libssl",
+        &["0.9", "1.0", "1.1"],
+        "OpenSSL 1.1 and older are end-of-life; consider OpenSSL 3",
+    ),
+    (
+        "libcrypto",
+        &["0.9", "1.0", "1.1"],
+        "OpenSSL 1.1 and older are end-of-life; consider OpenSSL 3",
+    ),
+    ("libpython2.7", &[], "Python 2 is end-of-life"),
+    ("libpython", &["2"], "Python 2 is end-of-life"),
+    (
+        "libpcre",
+        &[],
+        "PCRE is end-of-life and no longer maintained; consider PCRE2",
+    ),
+    (
+        "libgcrypt",
+        &["11"],
+        "this version of libgcrypt is end-of-life and missing from current distros",
+    ),
+    (
+        "msvcr100",
+        &[],
+        "this Visual C++ runtime is no longer supported; consider the Universal CRT",
+    ),
+    (
+        "msvcr120",
+        &[],
+        "this Visual C++ runtime is no longer supported; consider the Universal CRT",
+    ),
+];
+
+/// Get a warning for each advisory (built-in or user-provided) that applies to a library
+pub(crate) fn library_advisories(path: &str, extra: &[LinkageAdvisory]) -> Vec<String> {
+    let (name, version) = split_library_name(path);
+    let builtin = BUILTIN_ADVISORIES
+        .iter()
+        .map(|(library, versions, message)| (*library, versions.to_vec(), *message));
+    let extra = extra.iter().map(|advisory| {
+        let versions = advisory.versions.iter().map(|v| v.as_str()).collect();
+        (
+            advisory.library.as_str(),
+            versions,
+            advisory.message.as_str(),
+        )
+    });
+
+    let mut warnings = vec![];
+    for (library, versions, message) in builtin.chain(extra) {
+        if !library.eq_ignore_ascii_case(&name) {
+            continue;
+        }
+        let version_matches = versions.is_empty()
+            || version.as_deref().is_some_and(|version| {
+                versions
+                    .iter()
+                    .any(|v| version == *v || version.starts_with(&format!("{v}.")))
+            });
+        if version_matches {
+            warnings.push(format!("links against {path}: {message}"));
+        }
+    }
+    warnings
+}
+
+/// Work out why a runtime search path won't exist on users' machines, if it won't
+///
+/// Paths relative to the binary itself (`$ORIGIN` on Linux, `@loader_path` or
+/// `@executable_path` on macOS) keep working wherever it's installed. Absolute paths
+/// are only a problem if they look like they're from the build machine.
+pub(crate) fn rpath_problem(rpath: &str) -> Option<&'static str> {
+    const RELOCATABLE: &[&str] = &["$ORIGIN", "${ORIGIN}", "@loader_path", "@executable_path"];
+    if RELOCATABLE.iter().any(|prefix| rpath.starts_with(prefix)) {
+        None
+    } else if !rpath.starts_with('/') {
+        Some("is relative to whatever directory it's run from")
+    } else if in_home_directory(rpath) {
+        Some("is in a home directory")
+    } else if ["/tmp/", "/private/tmp/", "/var/folders/"]
+        .iter()
+        .any(|prefix| rpath.starts_with(prefix))
+    {
+        Some("is a temporary directory")
+    } else if Utf8Path::new(rpath).iter().any(|part| part == "target") {
+        Some("looks like a cargo target directory")
+    } else {
+        None
+    }
+}
+
+/// Whether a library is in someone's home directory
+///
+/// That almost always means a developer's local build of it leaked into the release,
+/// and it'll be missing on every other machine.
+pub(crate) fn in_home_directory(library: &str) -> bool {
+    const HOME_PREFIXES: &[&str] = &["/home/", "/Users/", "/root/"];
+    if HOME_PREFIXES
+        .iter()
+        .any(|prefix| library.starts_with(prefix))
+    {
+        return true;
+    }
+    // Home directories can be elsewhere, but the build machine's is probably set in the env
+    ["HOME", "USERPROFILE"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .filter(|home| !home.is_empty() && home != "/")
+        .any(|home| Utf8Path::new(library).starts_with(home))
+}
+
+/// Get the names of the sanitizer and profiling runtimes a binary links
+pub(crate) fn instrumentation_runtimes(linkage: &Linkage) -> SortedSet<&'static str> {
+    linkage
+        .libraries()
+        .filter_map(|library| instrumentation_runtime(&library.path))
+        .collect()
+}
+
+/// Work out which sanitizer or profiling runtime a library is, if it's one
+///
+/// GCC's are named like `libasan.so.8`. LLVM's are named like `libclang_rt.asan-x86_64.so`
+/// or `libclang_rt.asan_osx_dynamic.dylib`, and rustc ships its own copies named like
+/// `librustc-nightly_rt.asan.so`.
+pub(crate) fn instrumentation_runtime(library: &str) -> Option<&'static str> {
+    const RUNTIMES: [(&str, &str); 7] = [
+        ("asan", "AddressSanitizer"),
+        ("hwasan", "HWAddressSanitizer"),
+        ("lsan", "LeakSanitizer"),
+        ("msan", "MemorySanitizer"),
+        ("tsan", "ThreadSanitizer"),
+        ("ubsan", "UndefinedBehaviorSanitizer"),
+        ("profile", "profiling"),
+    ];
+
+    let (name, _) = split_library_name(library);
+    let name = name.strip_prefix("lib").unwrap_or(&name);
+    let (runtime, llvm) = match name.split_once("_rt.") {
+        Some((_, runtime)) => (runtime, true),
+        None => (name, false),
+    };
+    // Drop the architecture or platform, like -x86_64, _osx_dynamic or _standalone
+    let runtime = runtime.split(['-', '_']).next().unwrap_or(runtime);
+    // Plenty of unrelated libraries are called "profile"
+    if runtime == "profile" && !llvm {
+        return None;
+    }
+    RUNTIMES
+        .iter()
+        .find(|(short, _)| *short == runtime)
+        .map(|(_, runtime)| *runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8PathBuf;
+    use cargo_dist_schema::{AssetInfo, Library};
+    use temp_dir::TempDir;
+
+    #[test]
+    fn rpath_problems() {
+        assert_eq!(rpath_problem("$ORIGIN/../lib"), None);
+        assert_eq!(rpath_problem("@executable_path/../Frameworks"), None);
+        assert_eq!(rpath_problem("/usr/local/lib"), None);
+        assert!(rpath_problem("lib").is_some());
+        assert!(rpath_problem("/home/runner/work/app/vendor/lib").is_some());
+        assert!(rpath_problem("/tmp/build-1234/lib").is_some());
+        assert!(rpath_problem("/srv/app/target/release/deps").is_some());
+    }
+
+    #[test]
+    fn require_hardening() {
+        let config = LinkageConfig {
+            require_hardening: vec![
+                HardeningFeature::Pie,
+                HardeningFeature::Relro,
+                HardeningFeature::Cfg,
+            ],
+            ..LinkageConfig::default()
+        };
+        let mut linkage = Linkage {
+            hardening: Some(Hardening {
+                pie: Some(true),
+                relro: Some(Relro::Full),
+                nx: Some(false),
+                ..Hardening::default()
+            }),
+            ..Linkage::default()
+        };
+        // Only missing mitigations that were asked for count, and CFG doesn't apply to ELF
+        assert!(check_hardening("app", &linkage, &config).is_none());
+
+        linkage.hardening.as_mut().unwrap().relro = Some(Relro::Partial);
+        let Some(DistError::LinkageNotHardened { missing, .. }) =
+            check_hardening("app", &linkage, &config)
+        else {
+            panic!("partial RELRO should be a violation");
+        };
+        assert_eq!(missing, "relro");
+    }
+
+    #[test]
+    fn library_lists() {
+        let list = |targets: &[&str], libraries: &[&str]| LinkageLibraryList {
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            libraries: libraries.iter().map(|l| l.to_string()).collect(),
+        };
+        let config = LinkageConfig {
+            allow: vec![list(
+                &["x86_64-unknown-linux-gnu"],
+                &["libc", "libssl", "libz"],
+            )],
+            deny: vec![
+                list(&[], &["libssl"]),
+                list(&["aarch64-apple-darwin"], &["libz"]),
+            ],
+            ..LinkageConfig::default()
+        };
+        let mut linkage = Linkage::default();
+        for path in [
+            "/lib/libc.so.6",
+            "/lib/libssl.so.3",
+            "/lib/libz.so.1",
+            "/lib/libm.so.6",
+        ] {
+            linkage.system.insert(Library::new(path.to_owned()));
+        }
+
+        // Deny wins over allow, and libm isn't on the allow list
+        let linux = ["x86_64-unknown-linux-gnu".to_owned()];
+        let violations = check_library_lists("app", &linux, &linkage, &config);
+        let [DistError::LinkageDenied {
+            libraries: denied, ..
+        }, DistError::LinkageNotAllowed {
+            libraries: not_allowed,
+            ..
+        }] = violations.as_slice()
+        else {
+            panic!("expected a denied and a not-allowed violation, got {violations:?}");
+        };
+        assert_eq!(denied, "/lib/libssl.so.3");
+        assert_eq!(not_allowed, "/lib/libm.so.6");
+
+        // No allow list applies here, so anything not denied is fine, and the libz deny
+        // list is scoped to macOS
+        let musl = ["x86_64-unknown-linux-musl".to_owned()];
+        let violations = check_library_lists("app", &musl, &linkage, &config);
+        let [DistError::LinkageDenied { libraries, .. }] = violations.as_slice() else {
+            panic!("expected just a denied violation, got {violations:?}");
+        };
+        assert_eq!(libraries, "/lib/libssl.so.3");
+
+        let mac = ["aarch64-apple-darwin".to_owned()];
+        let violations = check_library_lists("app", &mac, &linkage, &config);
+        let [DistError::LinkageDenied { libraries, .. }] = violations.as_slice() else {
+            panic!("expected just a denied violation, got {violations:?}");
+        };
+        assert_eq!(libraries, "/lib/libssl.so.3, /lib/libz.so.1");
+
+        assert!(list(&[], &[]).applies_to(&mac));
+        assert!(!list(&["x86_64-apple-darwin"], &[]).applies_to(&mac));
+        assert!(list(&["x86_64-apple-darwin", "aarch64-apple-darwin"], &[]).applies_to(&mac));
+    }
+
+    #[test]
+    fn max_glibc() {
+        assert_eq!(parse_version("2.17"), Some(vec![2, 17]));
+        assert_eq!(parse_version("2.x"), None);
+
+        let mut config = LinkageConfig {
+            max_glibc: Some("2.17".to_owned()),
+            ..LinkageConfig::default()
+        };
+        let mut linkage = Linkage {
+            min_glibc: Some("2.9".to_owned()),
+            ..Linkage::default()
+        };
+        // Versions compare numerically, so 2.9 is older than 2.17
+        assert!(check_linkage_policy("app", &[], &linkage, &config)
+            .unwrap()
+            .is_empty());
+
+        linkage.min_glibc = Some("2.28".to_owned());
+        linkage.min_glibc_symbol = Some("fcntl64@GLIBC_2.28".to_owned());
+        let violations = check_linkage_policy("app", &[], &linkage, &config).unwrap();
+        let [DistError::LinkageGlibcTooNew {
+            required, symbol, ..
+        }] = violations.as_slice()
+        else {
+            panic!("expected glibc 2.28 to be too new, got {violations:?}");
+        };
+        assert_eq!(required, "2.28");
+        assert_eq!(symbol, "fcntl64@GLIBC_2.28");
+
+        config.max_glibc = Some("latest".to_owned());
+        assert!(matches!(
+            check_linkage_policy("app", &[], &linkage, &config),
+            Err(DistError::InvalidGlibcVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn require_static() {
+        let config = LinkageConfig {
+            require_static: vec!["x86_64-unknown-linux-musl".to_owned()],
+            ..LinkageConfig::default()
+        };
+        let musl = ["x86_64-unknown-linux-musl".to_owned()];
+        let gnu = ["x86_64-unknown-linux-gnu".to_owned()];
+        let mut linkage = Linkage::default();
+        assert!(check_require_static("app", &musl, &linkage, &config).is_none());
+
+        linkage
+            .system
+            .insert(Library::new("/lib/libc.so".to_owned()));
+        // Only the listed targets have to be static
+        assert!(check_require_static("app", &gnu, &linkage, &config).is_none());
+        let Some(DistError::LinkageNotStatic {
+            target, libraries, ..
+        }) = check_require_static("app", &musl, &linkage, &config)
+        else {
+            panic!("a dynamically linked musl binary should be a violation");
+        };
+        assert_eq!(target, "x86_64-unknown-linux-musl");
+        assert_eq!(libraries, "/lib/libc.so");
+    }
+
+    #[test]
+    fn consistent_targets() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let mut add = |id: &str, name: &str, target: &str, libraries: &[&str]| {
+            let mut linkage = Linkage::default();
+            for path in libraries {
+                linkage.other.insert(Library::new(path.to_string()));
+            }
+            manifest.assets.insert(
+                id.to_owned(),
+                AssetInfo {
+                    id: id.to_owned(),
+                    name: name.to_owned(),
+                    system: String::new(),
+                    target_triples: vec![target.to_owned()],
+                    linkage: Some(linkage),
+                },
+            );
+        };
+        // The same library has a different path on each platform
+        add(
+            "app-linux",
+            "app",
+            "x86_64-unknown-linux-gnu",
+            &["/lib/libz.so.1"],
+        );
+        add(
+            "app-mac",
+            "app",
+            "aarch64-apple-darwin",
+            &["/usr/lib/libz.1.dylib"],
+        );
+        add(
+            "tool-linux",
+            "tool",
+            "x86_64-unknown-linux-gnu",
+            &["/lib/libz.so.1"],
+        );
+        add(
+            "tool-mac",
+            "tool",
+            "aarch64-apple-darwin",
+            &["/usr/lib/libz.1.dylib", "/usr/lib/libiconv.2.dylib"],
+        );
+        add("tool-windows", "tool", "x86_64-pc-windows-msvc", &[]);
+
+        let group = [
+            "x86_64-unknown-linux-gnu".to_owned(),
+            "aarch64-apple-darwin".to_owned(),
+        ];
+        let violations = check_consistent_targets(&mut manifest, &group);
+        let [DistError::LinkageInconsistent {
+            binary,
+            only_target,
+            only_other_target,
+            ..
+        }] = violations.as_slice()
+        else {
+            panic!("expected only tool to be inconsistent, got {violations:?}");
+        };
+        assert_eq!(binary, "tool");
+        assert_eq!(only_target, "(nothing)");
+        assert_eq!(only_other_target, "libiconv");
+
+        let status = |id: &str| manifest.assets[id].linkage.as_ref().unwrap().status;
+        assert_eq!(status("app-mac"), None);
+        assert_eq!(status("tool-linux"), Some(LinkageStatus::Violation));
+        assert_eq!(status("tool-mac"), Some(LinkageStatus::Violation));
+        // Targets outside the group aren't compared
+        assert_eq!(status("tool-windows"), None);
+    }
+
+    #[test]
+    fn bundled() {
+        let library = |path: &str, location| Library {
+            location,
+            ..Library::new(path.to_owned())
+        };
+        let mut linkage = Linkage::default();
+        linkage
+            .system
+            .insert(library("KERNEL32.dll", Some(DllLocation::System)));
+        linkage
+            .other
+            .insert(library("zlib1.dll", Some(DllLocation::Bundled)));
+        // Reports made without require-bundled don't know where libraries are
+        linkage.other.insert(library("old.dll", None));
+        assert!(check_bundled("app.exe", &linkage).is_none());
+
+        linkage
+            .other
+            .insert(library("libssl-3.dll", Some(DllLocation::Unknown)));
+        let Some(DistError::LinkageNotBundled { libraries, .. }) =
+            check_bundled("app.exe", &linkage)
+        else {
+            panic!("a library that isn't bundled or part of the OS should be a violation");
+        };
+        assert_eq!(libraries, "libssl-3.dll");
+    }
+
+    #[test]
+    fn sanitizer_runtimes() {
+        assert_eq!(
+            instrumentation_runtime("/usr/lib/libasan.so.8"),
+            Some("AddressSanitizer")
+        );
+        assert_eq!(
+            instrumentation_runtime("/usr/lib/clang/17/lib/libclang_rt.tsan-x86_64.so"),
+            Some("ThreadSanitizer")
+        );
+        assert_eq!(
+            instrumentation_runtime("@rpath/libclang_rt.asan_osx_dynamic.dylib"),
+            Some("AddressSanitizer")
+        );
+        assert_eq!(
+            instrumentation_runtime("librustc-nightly_rt.lsan.so"),
+            Some("LeakSanitizer")
+        );
+        assert_eq!(
+            instrumentation_runtime("libclang_rt.profile-x86_64.a"),
+            Some("profiling")
+        );
+        // Only LLVM's profiling runtime counts, not any library called libprofile
+        assert_eq!(instrumentation_runtime("/usr/lib/libprofile.so.1"), None);
+        assert_eq!(instrumentation_runtime("/usr/lib/libssl.so.3"), None);
+    }
+
+    #[test]
+    fn advisories() {
+        let openssl = "links against /usr/lib/libssl.so.1.1: OpenSSL 1.1 and older are \
+                       end-of-life; consider OpenSSL 3";
+        assert_eq!(
+            library_advisories("/usr/lib/libssl.so.1.1", &[]),
+            vec![openssl]
+        );
+        assert_eq!(
+            library_advisories("/usr/lib/libssl.1.0.0.dylib", &[]).len(),
+            1
+        );
+        assert!(library_advisories("/usr/lib/libssl.so.3", &[]).is_empty());
+        // Versions match whole components, so 1.1 doesn't cover 1.10
+        assert!(library_advisories("/usr/lib/libssl.so.1.10", &[]).is_empty());
+
+        let extra = [LinkageAdvisory {
+            library: "libfoo".to_owned(),
+            versions: vec![],
+            message: "use libbar instead".to_owned(),
+        }];
+        assert_eq!(
+            library_advisories("/opt/lib/libfoo.so.2", &extra),
+            vec!["links against /opt/lib/libfoo.so.2: use libbar instead"]
+        );
+        assert!(library_advisories("/opt/lib/libfoobar.so.2", &extra).is_empty());
+    }
+
+    #[test]
+    fn policy_files() {
+        let dir = TempDir::new().unwrap();
+        let toml = Utf8PathBuf::try_from(dir.path().join("policy.toml")).unwrap();
+        std::fs::write(
+            &toml,
+            r#"
+max-glibc = "2.31"
+require-bundled = true
+deny = [{ libraries = ["libssl"] }]
+policy = "elsewhere.toml"
+"#,
+        )
+        .unwrap();
+        let json = Utf8PathBuf::try_from(dir.path().join("policy.json")).unwrap();
+        std::fs::write(&json, r#"{ "deny": [{ "libraries": ["libpcre"] }] }"#).unwrap();
+
+        let workspace = LinkageConfig {
+            max_glibc: Some("2.17".to_owned()),
+            deny: vec![LinkageLibraryList {
+                targets: vec![],
+                libraries: vec!["libz".to_owned()],
+            }],
+            ..LinkageConfig::default()
+        };
+        let args = LinkageArgs {
+            policy: Some(toml),
+            ..LinkageArgs::default()
+        };
+        let mut config = linkage_policy(workspace, &args).unwrap();
+        // The workspace's own settings win, and lists are combined
+        assert_eq!(config.max_glibc.as_deref(), Some("2.17"));
+        assert_eq!(config.require_bundled, Some(true));
+        let denied = |config: &LinkageConfig| {
+            config
+                .deny
+                .iter()
+                .flat_map(|list| list.libraries.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(denied(&config), vec!["libz", "libssl"]);
+        // A policy file's own policy isn't followed
+        assert_eq!(config.policy, None);
+
+        config.merge_policy_file(&json).unwrap();
+        assert_eq!(denied(&config), vec!["libz", "libssl", "libpcre"]);
+    }
+
+    #[test]
+    fn statuses_and_summary() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let mut add = |name: &str, target: &str, linkage: Linkage| {
+            manifest.assets.insert(
+                name.to_owned(),
+                AssetInfo {
+                    id: name.to_owned(),
+                    name: name.to_owned(),
+                    system: String::new(),
+                    target_triples: vec![target.to_owned()],
+                    linkage: Some(linkage),
+                },
+            );
+        };
+        add("clean", "x86_64-unknown-linux-gnu", Linkage::default());
+        let mut unmanaged = Linkage::default();
+        unmanaged
+            .public_unmanaged
+            .insert(Library::new("/usr/local/lib/libfoo.so".to_owned()));
+        add("unmanaged", "x86_64-unknown-linux-gnu", unmanaged);
+        let mut denied = Linkage::default();
+        denied
+            .system
+            .insert(Library::new("/usr/lib/libssl.3.dylib".to_owned()));
+        // Its warning still counts, even though the violation decides its status
+        denied.warnings.push("not hardened".to_owned());
+        add("denied", "aarch64-apple-darwin", denied);
+
+        let config = LinkageConfig {
+            deny: vec![LinkageLibraryList {
+                targets: vec![],
+                libraries: vec!["libssl".to_owned()],
+            }],
+            ..LinkageConfig::default()
+        };
+        let violations = evaluate_linkage(&mut manifest, &LinkageArgs::default(), &config).unwrap();
+        assert_eq!(violations.len(), 1);
+        let status = |name: &str| manifest.assets[name].linkage.as_ref().unwrap().status;
+        assert_eq!(status("clean"), Some(LinkageStatus::Ok));
+        assert_eq!(status("unmanaged"), Some(LinkageStatus::Warning));
+        assert_eq!(status("denied"), Some(LinkageStatus::Violation));
+
+        let summary = LinkageSummary::new(&manifest, &violations, 2);
+        assert_eq!(
+            summary.to_string(),
+            "Analyzed 3 binaries across 2 targets: 2 binaries with warnings, 1 violation, \
+            2 binaries missing"
+        );
+        let problems = summary
+            .problems
+            .iter()
+            .map(|problem| (problem.binary.as_str(), problem.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                ("denied", LinkageStatus::Violation),
+                ("unmanaged", LinkageStatus::Warning)
+            ]
+        );
+    }
+}
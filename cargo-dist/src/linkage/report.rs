@@ -0,0 +1,693 @@
+//! Printing linkage reports, as tables, markdown, CSV, or summaries of many binaries
+
+use cargo_dist_schema::{
+    AssetInfo, DependencyNode, DistManifest, Hardening, Library, Linkage, LinkageHistogram,
+    LinkageStatus, Relro,
+};
+use comfy_table::{
+    presets::{ASCII_FULL, UTF8_FULL},
+    Table,
+};
+
+use crate::{SortedMap, SortedSet};
+
+use super::{plural, split_library_name};
+
+/// Which characters to draw the human-readable linkage tables with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableStyle {
+    /// Unicode box-drawing characters
+    #[default]
+    Unicode,
+    /// Plain `+`, `-`, and `|`
+    Ascii,
+}
+
+impl TableStyle {
+    /// Guess whether the terminal we're printing to can display unicode
+    pub fn detect() -> Self {
+        if cfg!(windows) {
+            // The legacy console mangles box-drawing characters, but Windows Terminal
+            // and CI log viewers cope fine
+            if std::env::var_os("WT_SESSION").is_some() || std::env::var_os("CI").is_some() {
+                TableStyle::Unicode
+            } else {
+                TableStyle::Ascii
+            }
+        } else {
+            let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|val| !val.is_empty()));
+            match locale {
+                // Lots of minimal environments don't set a locale at all, and they
+                // generally manage unicode fine, so only trust an explicit answer
+                None => TableStyle::Unicode,
+                Some(locale) => {
+                    let locale = locale.to_lowercase();
+                    if locale.contains("utf-8") || locale.contains("utf8") {
+                        TableStyle::Unicode
+                    } else {
+                        TableStyle::Ascii
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the comfy_table preset for this style
+    pub(crate) fn preset(self) -> &'static str {
+        match self {
+            TableStyle::Unicode => UTF8_FULL,
+            TableStyle::Ascii => ASCII_FULL,
+        }
+    }
+}
+
+/// Formatter for a DistManifest that prints the linkage human-readably
+pub struct LinkageDisplay<'a>(pub &'a DistManifest, pub TableStyle);
+
+impl std::fmt::Display for LinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for asset in self.0.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            let name = &asset.name;
+            let targets = asset.target_triples.join(", ");
+            write!(f, "{name}")?;
+            if !targets.is_empty() {
+                write!(f, " ({targets})")?;
+            }
+            write_status(f, linkage.status)?;
+            writeln!(f, "\n")?;
+            format_linkage_table(f, linkage, self.1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formatter for a DistManifest that prints the linkage as markdown tables, which
+/// render nicely in a GitHub job summary or PR comment
+pub struct MarkdownLinkageDisplay<'a>(pub &'a DistManifest);
+
+impl std::fmt::Display for MarkdownLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Library names can't break out of a table cell
+        let escape = |text: &str| text.replace('|', "\\|");
+        writeln!(f, "## Linkage")?;
+        for asset in self.0.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            write!(f, "\n### {}", escape(&asset.name))?;
+            let targets = asset.target_triples.join(", ");
+            if !targets.is_empty() {
+                write!(f, " ({targets})")?;
+            }
+            write_status(f, linkage.status)?;
+            writeln!(f, "\n")?;
+            for warning in &linkage.warnings {
+                writeln!(f, "> **warning:** {warning}\n")?;
+            }
+
+            let categories = linkage_categories(linkage)
+                .into_iter()
+                .filter(|(_, libraries)| !libraries.is_empty())
+                .collect::<Vec<_>>();
+            if categories.is_empty() {
+                writeln!(f, "No dynamic libraries.")?;
+                continue;
+            }
+            writeln!(f, "| Category | Libraries |")?;
+            writeln!(f, "| --- | --- |")?;
+            for (category, libraries) in categories {
+                let libraries = libraries
+                    .iter()
+                    .map(|library| escape(&library.to_string()))
+                    .collect::<Vec<_>>()
+                    .join("<br>");
+                writeln!(f, "| {category} | {libraries} |")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formatter for a DistManifest that prints the linkage as CSV, with a row for each
+/// library a binary links, e.g. for loading into a spreadsheet
+pub struct CsvLinkageDisplay<'a>(pub &'a DistManifest);
+
+impl std::fmt::Display for CsvLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "binary,targets,category,library,source")?;
+        for asset in self.0.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            let binary = csv_field(&asset.name);
+            let targets = csv_field(&asset.target_triples.join(" "));
+            for (category, libraries) in linkage_categories(linkage) {
+                let category = csv_field(category);
+                for library in libraries {
+                    let path = csv_field(&library.path);
+                    let source = csv_field(library.source.as_deref().unwrap_or_default());
+                    writeln!(f, "{binary},{targets},{category},{path},{source}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quote a CSV field if it needs it, doubling any quotes inside
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Formatter for a DistManifest that prints the linkage human-readably,
+/// with binaries grouped under the artifact they ship in
+pub struct ArtifactLinkageDisplay<'a>(pub &'a DistManifest, pub TableStyle);
+
+impl std::fmt::Display for ArtifactLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut groups = SortedMap::<Option<&str>, Vec<&AssetInfo>>::new();
+        for asset in self.0.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            groups
+                .entry(linkage.artifact_id.as_deref())
+                .or_default()
+                .push(asset);
+        }
+
+        for (artifact_id, assets) in groups {
+            if let Some(artifact_id) = artifact_id {
+                writeln!(f, "artifact {artifact_id}:\n")?;
+            } else {
+                writeln!(f, "(not part of any checked artifact):\n")?;
+            }
+            for asset in assets {
+                let Some(linkage) = &asset.linkage else {
+                    continue;
+                };
+                let name = &asset.name;
+                let targets = asset.target_triples.join(", ");
+                write!(f, "  binary {name}")?;
+                if !targets.is_empty() {
+                    write!(f, " ({targets})")?;
+                }
+                write_status(f, linkage.status)?;
+                writeln!(f, "\n")?;
+                format_linkage_table(f, linkage, self.1)?;
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formatter for a DistManifest that prints every artifact it describes,
+/// with the linkage of the binaries inside them joined in
+pub struct ManifestLinkageDisplay<'a>(pub &'a DistManifest, pub TableStyle);
+
+impl std::fmt::Display for ManifestLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (artifact_id, artifact) in &self.0.artifacts {
+            let name = artifact.name.as_deref().unwrap_or(artifact_id);
+            let targets = artifact.target_triples.join(", ");
+            write!(f, "{name}")?;
+            if !targets.is_empty() {
+                write!(f, " ({targets})")?;
+            }
+            let linkage = self.0.linkage_for_artifact(artifact_id);
+            let status = artifact
+                .assets
+                .iter()
+                .filter_map(|asset| self.0.assets.get(asset.id.as_ref()?)?.linkage.as_ref())
+                .filter_map(|linkage| linkage.status)
+                .max();
+            write_status(f, status)?;
+            writeln!(f, "\n")?;
+
+            if artifact.assets.is_empty() {
+                writeln!(f, "(no assets)\n")?;
+                continue;
+            }
+
+            let mut table = Table::new();
+            table
+                .load_preset(self.1.preset())
+                .set_header(vec!["Asset", "Category", "Libraries"]);
+            for asset in &artifact.assets {
+                let asset_name = asset
+                    .name
+                    .as_deref()
+                    .or(asset.path.as_deref())
+                    .unwrap_or("(unnamed)");
+                let asset_linkage = asset
+                    .id
+                    .as_ref()
+                    .and_then(|id| self.0.assets.get(id))
+                    .and_then(|info| info.linkage.as_ref());
+                let Some(asset_linkage) = asset_linkage else {
+                    table.add_row(vec![asset_name, "", ""]);
+                    continue;
+                };
+                if asset_linkage.is_static()
+                    && asset_linkage.wasm_imports.is_empty()
+                    && asset_linkage.static_bundled.is_empty()
+                    && asset_linkage.runtime_loaded.is_empty()
+                {
+                    table.add_row(vec![asset_name, "", "(statically linked)"]);
+                    continue;
+                }
+                let mut first_row = true;
+                for (category, libraries) in linkage_categories(asset_linkage) {
+                    if libraries.is_empty() {
+                        continue;
+                    }
+                    let libraries = libraries
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n");
+                    let asset_name = if first_row { asset_name } else { "" };
+                    table.add_row(vec![asset_name, category, libraries.as_str()]);
+                    first_row = false;
+                }
+            }
+            writeln!(f, "{table}")?;
+
+            // Everything the artifact needs from the system, for quick reference
+            let mut needed = linkage
+                .libraries()
+                .map(|l| l.source.as_deref().unwrap_or(&l.path))
+                .collect::<Vec<_>>();
+            needed.sort();
+            needed.dedup();
+            if !needed.is_empty() {
+                writeln!(f, "requires: {}", needed.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A library the release depends on, identified independently of where it was found
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FootprintEntry {
+    /// The library's name, without directories or version suffixes (e.g. `libssl`)
+    pub name: String,
+    /// The package that provides it, if known
+    pub source: Option<String>,
+}
+
+/// Collect every library any binary in the release links against, deduplicated
+///
+/// The same library is often found at different paths for different targets (or even
+/// different versions of it), so entries are keyed on the library's canonical name and
+/// package. Each maps to the binaries that link against it.
+pub fn linkage_footprint(manifest: &DistManifest) -> SortedMap<FootprintEntry, SortedSet<String>> {
+    let mut footprint = SortedMap::<FootprintEntry, SortedSet<String>>::new();
+    for asset in manifest.assets.values() {
+        let Some(linkage) = &asset.linkage else {
+            continue;
+        };
+        for library in linkage.libraries() {
+            let entry = FootprintEntry {
+                name: split_library_name(&library.path).0,
+                source: library.source.clone(),
+            };
+            footprint
+                .entry(entry)
+                .or_default()
+                .insert(asset.name.clone());
+        }
+    }
+    footprint
+}
+
+/// Formatter for a DistManifest that prints every library the release depends on, once
+pub struct FootprintLinkageDisplay<'a>(pub &'a DistManifest, pub TableStyle);
+
+impl std::fmt::Display for FootprintLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let footprint = linkage_footprint(self.0);
+        if footprint.is_empty() {
+            return writeln!(f, "This release has no external dependencies");
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(self.1.preset())
+            .set_header(vec!["Library", "Package", "Used by"]);
+        for (entry, binaries) in &footprint {
+            let binaries = binaries.iter().cloned().collect::<Vec<_>>().join(", ");
+            table.add_row(vec![
+                entry.name.as_str(),
+                entry.source.as_deref().unwrap_or(""),
+                binaries.as_str(),
+            ]);
+        }
+        writeln!(f, "{table}")?;
+
+        // Suitable for pasting into release notes
+        let mut needed = footprint
+            .keys()
+            .map(|entry| entry.source.as_deref().unwrap_or(&entry.name))
+            .collect::<Vec<_>>();
+        needed.sort();
+        needed.dedup();
+        writeln!(f, "this release requires: {}", needed.join(", "))
+    }
+}
+
+/// Formatter for a DistManifest that counts each binary's libraries by category, in one table
+///
+/// Categories no binary uses are left out, to keep it compact.
+pub struct HistogramLinkageDisplay<'a>(pub &'a DistManifest, pub TableStyle);
+
+impl std::fmt::Display for HistogramLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let histograms = self
+            .0
+            .assets
+            .values()
+            .filter_map(|asset| {
+                let histogram = asset.linkage.as_ref()?.histogram();
+                let targets = asset.target_triples.join(", ");
+                let name = if targets.is_empty() {
+                    asset.name.clone()
+                } else {
+                    format!("{} ({targets})", asset.name)
+                };
+                Some((name, histogram))
+            })
+            .collect::<Vec<_>>();
+
+        let columns: [HistogramColumn; 10] = [
+            ("System", |h| h.system),
+            ("Homebrew", |h| h.homebrew),
+            ("Public (unmanaged)", |h| h.public_unmanaged),
+            ("Frameworks", |h| h.frameworks),
+            ("Toolchain", |h| h.toolchain),
+            ("Language runtimes", |h| h.language_runtimes),
+            ("Graphics/display", |h| h.graphics),
+            ("Other", |h| h.other),
+            ("Weak", |h| h.weak),
+            ("Deferred", |h| h.deferred),
+        ];
+        let columns = columns
+            .into_iter()
+            .filter(|(_, count)| histograms.iter().any(|(_, h)| count(h) > 0))
+            .collect::<Vec<_>>();
+
+        let mut table = Table::new();
+        let mut header = vec!["Binary"];
+        header.extend(columns.iter().map(|(name, _)| *name));
+        header.push("Total");
+        table.load_preset(self.1.preset()).set_header(header);
+        for (name, histogram) in &histograms {
+            let mut row = vec![name.clone()];
+            row.extend(
+                columns
+                    .iter()
+                    .map(|(_, count)| count(histogram).to_string()),
+            );
+            row.push(histogram.total.to_string());
+            table.add_row(row);
+        }
+        write!(f, "{table}")
+    }
+}
+
+/// Formatter for a DistManifest that explains which binaries link a particular library
+///
+/// The library can be given by path, by file name (`libssl.so.3`), or by canonical
+/// name (`libssl`), which matches every version of it.
+pub struct ExplainLinkageDisplay<'a>(pub &'a DistManifest, pub &'a str);
+
+impl std::fmt::Display for ExplainLinkageDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let query = self.1;
+        let (query_name, _) = split_library_name(query);
+        let matches = |path: &str| {
+            let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+            path == query
+                || file_name.eq_ignore_ascii_case(query)
+                || (query_name == query && split_library_name(path).0 == query_name)
+        };
+
+        let mut found = vec![];
+        for asset in self.0.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            for (category, libraries) in linkage_categories(linkage) {
+                for library in libraries.iter().filter(|library| matches(&library.path)) {
+                    found.push((asset, category, library));
+                }
+            }
+        }
+        if found.is_empty() {
+            return writeln!(f, "No binaries link against {query}");
+        }
+
+        let binaries = found
+            .iter()
+            .map(|(asset, _, _)| &asset.id)
+            .collect::<SortedSet<_>>()
+            .len();
+        writeln!(f, "{query} is linked by {}\n", plural(binaries, "binary"))?;
+        for (asset, category, library) in found {
+            write!(f, "{}", asset.name)?;
+            if !asset.target_triples.is_empty() {
+                write!(f, " ({})", asset.target_triples.join(", "))?;
+            }
+            let linkage = asset.linkage.as_ref();
+            if let Some(artifact_id) = linkage.and_then(|l| l.artifact_id.as_ref()) {
+                write!(f, ", shipped in {artifact_id}")?;
+            }
+            writeln!(f)?;
+            writeln!(f, "  path: {}", library.path)?;
+            writeln!(f, "  category: {category}")?;
+            if let Some(source) = &library.source {
+                writeln!(f, "  package: {source}")?;
+            }
+            // Not every format records this, so don't guess
+            if let Some(reason) = library.reason {
+                writeln!(f, "  linked as: {reason}")?;
+            }
+            if let Some(location) = library.location {
+                writeln!(f, "  loaded from: {location}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get each category of library in a Linkage, along with its human-readable name
+fn linkage_categories(linkage: &Linkage) -> [(&'static str, &SortedSet<Library>); 11] {
+    [
+        ("System", &linkage.system),
+        ("Homebrew", &linkage.homebrew),
+        ("Public (unmanaged)", &linkage.public_unmanaged),
+        ("Frameworks", &linkage.frameworks),
+        ("Toolchain", &linkage.toolchain),
+        ("Language runtimes", &linkage.language_runtimes),
+        ("Graphics/display", &linkage.graphics),
+        ("Other", &linkage.other),
+        ("WASM imports", &linkage.wasm_imports),
+        ("Bundled (inferred from symbols)", &linkage.static_bundled),
+        ("Runtime-loaded (heuristic)", &linkage.runtime_loaded),
+    ]
+}
+
+/// Note the status of a binary (or artifact) next to its name, if there's anything to note
+fn write_status(
+    f: &mut std::fmt::Formatter<'_>,
+    status: Option<LinkageStatus>,
+) -> std::fmt::Result {
+    match status {
+        Some(status @ (LinkageStatus::Warning | LinkageStatus::Violation)) => {
+            write!(f, " [{status}]")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Formatted human-readable output
+fn format_linkage_table(
+    f: &mut std::fmt::Formatter<'_>,
+    linkage: &Linkage,
+    style: TableStyle,
+) -> std::fmt::Result {
+    if let Some(build_id) = &linkage.build_id {
+        writeln!(f, "build id: {build_id}")?;
+    }
+    if let Some(debug_file) = &linkage.debug_file {
+        writeln!(f, "debug file: {debug_file}")?;
+    }
+    if let Some(debug_symbols) = &linkage.debug_symbols {
+        writeln!(f, "debug symbols: {debug_symbols}")?;
+    }
+    if let Some(symlink_target) = &linkage.symlink_target {
+        writeln!(f, "symlink to: {symlink_target}")?;
+    }
+    if let Some(soname) = &linkage.soname {
+        writeln!(f, "soname: {soname}")?;
+    }
+    if let Some(format) = &linkage.format {
+        write!(f, "format: {format}")?;
+        let details = [&linkage.elf_class, &linkage.endianness]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        writeln!(f)?;
+    }
+    if let Some(min_glibc) = &linkage.min_glibc {
+        write!(f, "minimum glibc: {min_glibc}")?;
+        if let Some(symbol) = &linkage.min_glibc_symbol {
+            write!(f, " (required by {symbol})")?;
+        }
+        writeln!(f)?;
+    }
+    if let Some(min_macos) = &linkage.min_macos {
+        writeln!(f, "minimum macOS: {min_macos}")?;
+    }
+    if !linkage.rpaths.is_empty() {
+        writeln!(f, "runtime search paths: {}", linkage.rpaths.join(", "))?;
+    }
+    if let Some(cxx_runtime) = &linkage.cxx_runtime {
+        write!(f, "C++ runtime: {cxx_runtime}")?;
+        if let Some(version) = &linkage.cxx_runtime_version {
+            write!(f, " {version}")?;
+        }
+        writeln!(f)?;
+    }
+    if let Some(subsystem) = &linkage.pe_subsystem {
+        writeln!(f, "subsystem: {subsystem}")?;
+    }
+    if let Some(hardening) = &linkage.hardening {
+        writeln!(f, "hardening: {}", describe_hardening(hardening).join(", "))?;
+    }
+    for warning in &linkage.warnings {
+        writeln!(f, "warning: {warning}")?;
+    }
+    // Universal binaries get a table per architecture, since that's where the differences are
+    if !linkage.slices.is_empty() {
+        for slice in &linkage.slices {
+            writeln!(f, "{}:", slice.arch.as_deref().unwrap_or("(unknown arch)"))?;
+            format_linkage_table(f, slice, style)?;
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table
+        .load_preset(style.preset())
+        .set_header(vec!["Category", "Libraries"]);
+    // The categories every platform has are always shown, even when empty; the rest only
+    // when something's in them
+    let always_shown = [
+        "System",
+        "Homebrew",
+        "Public (unmanaged)",
+        "Frameworks",
+        "Other",
+    ];
+    for (category, libraries) in linkage_categories(linkage) {
+        if libraries.is_empty() && !always_shown.contains(&category) {
+            continue;
+        }
+        let libraries = libraries
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+        table.add_row(vec![category, libraries.as_str()]);
+    }
+    write!(f, "{table}")?;
+    if !linkage.dependency_tree.is_empty() {
+        writeln!(
+            f,
+            "\n\ndependency tree ((*) means its dependencies are listed above):"
+        )?;
+        write_dependency_tree(f, &linkage.dependency_tree, "", style)?;
+    }
+    Ok(())
+}
+
+/// Write the dependency tree of a binary, one library per line, like `cargo tree`
+fn write_dependency_tree(
+    f: &mut std::fmt::Formatter<'_>,
+    nodes: &[DependencyNode],
+    prefix: &str,
+    style: TableStyle,
+) -> std::fmt::Result {
+    for (idx, node) in nodes.iter().enumerate() {
+        let last = idx + 1 == nodes.len();
+        let (branch, indent) = match (style, last) {
+            (TableStyle::Unicode, false) => ("├── ", "│   "),
+            (TableStyle::Unicode, true) => ("└── ", "    "),
+            (TableStyle::Ascii, false) => ("|-- ", "|   "),
+            (TableStyle::Ascii, true) => ("`-- ", "    "),
+        };
+        write!(f, "{prefix}{branch}{}", node.library)?;
+        if node.repeated {
+            write!(f, " (*)")?;
+        }
+        if node.depth_limited {
+            write!(f, " (depth limit reached)")?;
+        }
+        writeln!(f)?;
+        write_dependency_tree(f, &node.dependencies, &format!("{prefix}{indent}"), style)?;
+    }
+    Ok(())
+}
+
+/// Describe the mitigations a binary has and lacks, like "PIE" or "no stack canaries"
+fn describe_hardening(hardening: &Hardening) -> Vec<String> {
+    let flags = [
+        ("PIE", hardening.pie),
+        ("NX", hardening.nx),
+        ("stack canaries", hardening.stack_canary),
+        ("fortify", hardening.fortify),
+        ("ASLR", hardening.aslr),
+        ("high-entropy ASLR", hardening.high_entropy_va),
+        ("CFG", hardening.cfg),
+    ];
+    let relro = hardening.relro.map(|relro| match relro {
+        Relro::None => "no RELRO".to_owned(),
+        Relro::Partial => "partial RELRO".to_owned(),
+        Relro::Full => "full RELRO".to_owned(),
+    });
+    let mut described = flags
+        .into_iter()
+        .filter_map(|(name, enabled)| {
+            enabled.map(|enabled| {
+                if enabled {
+                    name.to_owned()
+                } else {
+                    format!("no {name}")
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    described.extend(relro);
+    described
+}
+
+/// A column of the histogram table: its heading, and how to count it
+type HistogramColumn = (&'static str, fn(&LinkageHistogram) -> usize);
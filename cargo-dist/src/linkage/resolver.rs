@@ -0,0 +1,823 @@
+//! Finding which package provides each library a binary links
+
+use std::{
+    fs,
+    sync::{Mutex, OnceLock},
+};
+
+use axoasset::SourceFile;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{Confidence, Library, Linkage};
+
+use crate::{
+    config::{LinkageCategory, LinkageConfig},
+    errors::*,
+    SortedMap, SortedSet,
+};
+
+/// Create a homebrew library for the given path
+///
+/// The package is read off the path, which is `<prefix>/opt/<package>/...` or
+/// `<prefix>/Cellar/<package>/...` for any of the given Homebrew `prefixes` (see
+/// [`PackageResolver::homebrew_prefixes`][]), or the default Intel Mac prefix `/usr/local`.
+pub fn library_from_homebrew(library: String, prefixes: &[Utf8PathBuf]) -> Library {
+    let mut prefixes = prefixes.to_vec();
+    prefixes.push(Utf8PathBuf::from("/usr/local"));
+    let brew_prefix = prefixes
+        .iter()
+        .flat_map(|prefix| [prefix.join("opt"), prefix.join("Cellar")])
+        .find(|dir| Utf8Path::new(&library).starts_with(dir));
+
+    if let Some(prefix) = brew_prefix {
+        let stripped = Utf8Path::new(&library).strip_prefix(&prefix).unwrap();
+        let mut package = stripped.iter().next().unwrap_or_default().to_owned();
+        // Without the receipt all we have to go on is the path
+        let mut confidence = Confidence::Low;
+
+        // The path alone isn't enough to determine the tap the formula
+        // came from. If the install receipt exists, we can use it to
+        // get the name of the source tap.
+        let receipt = prefix.join(&package).join("INSTALL_RECEIPT.json");
+
+        // If the receipt doesn't exist or can't be loaded, that's not an
+        // error; we can fall back to the package basename we parsed out
+        // of the path.
+        if receipt.exists() {
+            let _ = SourceFile::load_local(&receipt)
+                .and_then(|file| file.deserialize_json())
+                .map(|parsed: serde_json::Value| {
+                    if let Some(tap) = parsed["source"]["tap"].as_str() {
+                        if tap != "homebrew/core" {
+                            package = format!("{tap}/{package}");
+                        }
+                        confidence = Confidence::High;
+                    }
+                });
+        }
+
+        Library {
+            source: Some(package.to_owned()),
+            confidence: Some(confidence),
+            ..Library::new(library)
+        }
+    } else {
+        Library::new(library)
+    }
+}
+
+/// Create an apt library for the given path
+///
+/// If a sysroot is given, the path is looked up in that system's package database.
+pub fn library_from_apt(library: String, sysroot: Option<&Utf8Path>) -> DistResult<Library> {
+    // We can't get this information on other OSs
+    if std::env::consts::OS != "linux" {
+        return Ok(Library::new(library));
+    }
+
+    let mut cmd = Cmd::new("dpkg", "get linkage info from dpkg");
+    if let Some(sysroot) = sysroot {
+        cmd.arg(format!("--admindir={}", sysroot.join("var/lib/dpkg")));
+    }
+    let packages = match cmd.arg("--search").arg(&library).output() {
+        Ok(output) => {
+            let output = String::from_utf8(output.stdout)?;
+            packages_from_dpkg_search(&output, &library)
+        }
+        // Couldn't find a package for this file
+        Err(_) => vec![],
+    };
+    Ok(library_from_dpkg_owners(library, packages))
+}
+
+/// Create an apt library owned by the packages dpkg named
+fn library_from_dpkg_owners(library: String, packages: Vec<String>) -> Library {
+    let source = if packages.is_empty() {
+        None
+    } else {
+        Some(packages.join(", "))
+    };
+    // dpkg is the authority on which package owns which file
+    let confidence = source.as_ref().map(|_| Confidence::High);
+
+    Library {
+        source,
+        confidence,
+        ..Library::new(library)
+    }
+}
+
+/// Ask dpkg which packages own a batch of libraries all at once, so that looking each
+/// one up afterwards doesn't have to start a process per library
+///
+/// Only full paths are batched: `dpkg --search` treats anything else as a pattern,
+/// and the results for several patterns can't be told apart.
+fn prefetch_dpkg_owners(
+    libraries: &[&String],
+    sysroot: Option<&Utf8Path>,
+) -> SortedMap<String, Vec<String>> {
+    let libraries = libraries
+        .iter()
+        .filter(|library| library.starts_with('/'))
+        .collect::<SortedSet<_>>();
+    if std::env::consts::OS != "linux" || libraries.is_empty() {
+        return SortedMap::new();
+    }
+
+    let mut cmd = Cmd::new("dpkg", "get linkage info from dpkg");
+    if let Some(sysroot) = sysroot {
+        cmd.arg(format!("--admindir={}", sysroot.join("var/lib/dpkg")));
+    }
+    // dpkg fails if any of them isn't owned by a package, but still reports the rest
+    cmd.arg("--search");
+    for library in &libraries {
+        cmd.arg(library.as_str());
+    }
+    let output = cmd.check(false).output();
+    let Some(output) = output.ok().and_then(|o| String::from_utf8(o.stdout).ok()) else {
+        return SortedMap::new();
+    };
+    libraries
+        .into_iter()
+        .map(|library| {
+            let packages = packages_from_dpkg_search(&output, library);
+            ((*library).clone(), packages)
+        })
+        .collect()
+}
+
+/// The package managers Linux distros use, which we can ask who owns a library
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DistroPackageManager {
+    /// Debian, Ubuntu and friends
+    Dpkg,
+    /// Fedora, RHEL, SUSE and friends
+    Rpm,
+    /// Arch and friends
+    Pacman,
+    /// Alpine
+    Apk,
+}
+
+impl DistroPackageManager {
+    /// Work out which package manager a distro uses from its os-release file
+    ///
+    /// Derivatives name the distro they're based on in `ID_LIKE`, so that's checked
+    /// too. Anything unrecognized is assumed to be Debian-like, since that's what
+    /// most CI runners are.
+    fn from_os_release(os_release: &str) -> Self {
+        let ids = os_release
+            .lines()
+            .filter_map(|line| {
+                line.strip_prefix("ID=")
+                    .or_else(|| line.strip_prefix("ID_LIKE="))
+            })
+            .flat_map(|value| value.trim_matches(['"', '\'']).split_whitespace())
+            .collect::<Vec<_>>();
+        for id in ids {
+            match id {
+                "debian" | "ubuntu" => return Self::Dpkg,
+                "fedora" | "rhel" | "centos" | "suse" | "opensuse" | "amzn" => return Self::Rpm,
+                "arch" => return Self::Pacman,
+                "alpine" => return Self::Apk,
+                _ => {}
+            }
+        }
+        Self::Dpkg
+    }
+
+    /// Work out which package manager the host (or sysroot) uses
+    fn detect(sysroot: Option<&Utf8Path>) -> Self {
+        let root = sysroot.unwrap_or(Utf8Path::new("/"));
+        let os_release = ["etc/os-release", "usr/lib/os-release"]
+            .iter()
+            .find_map(|path| fs::read_to_string(root.join(path)).ok())
+            .unwrap_or_default();
+        Self::from_os_release(&os_release)
+    }
+}
+
+/// Create a library provided by whichever package manager this Linux distro uses
+pub fn library_from_distro(library: String, sysroot: Option<&Utf8Path>) -> DistResult<Library> {
+    // We can't get this information on other OSs
+    if std::env::consts::OS != "linux" {
+        return Ok(Library::new(library));
+    }
+
+    let manager = DistroPackageManager::detect(sysroot);
+    // Only dpkg can search for a bare file name; the rest need to know the exact file
+    if !library.starts_with('/') {
+        return library_from_apt(library, sysroot);
+    }
+    let (name, args): (&str, &[&str]) = match manager {
+        DistroPackageManager::Dpkg => return library_from_apt(library, sysroot),
+        DistroPackageManager::Rpm => ("rpm", &["--query", "--file", "--queryformat=%{NAME}\n"]),
+        DistroPackageManager::Pacman => ("pacman", &["--query", "--owns", "--quiet"]),
+        DistroPackageManager::Apk => ("apk", &["info", "--who-owns"]),
+    };
+    let mut cmd = Cmd::new(name, format!("get linkage info from {name}"));
+    if let Some(sysroot) = sysroot {
+        cmd.arg(format!("--root={sysroot}"));
+    }
+    for arg in args {
+        cmd.arg(arg);
+    }
+    let process = cmd.arg(&library).output();
+    match process {
+        Ok(output) => {
+            let output = String::from_utf8(output.stdout)?;
+            let packages = match manager {
+                DistroPackageManager::Apk => packages_from_apk_who_owns(&output),
+                _ => output
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+            };
+            let source = if packages.is_empty() {
+                None
+            } else {
+                Some(packages.join(", "))
+            };
+            // Like dpkg, these are the authority on which package owns which file
+            let confidence = source.as_ref().map(|_| Confidence::High);
+
+            Ok(Library {
+                source,
+                confidence,
+                ..Library::new(library)
+            })
+        }
+        // Couldn't find a package for this file
+        Err(_) => Ok(Library::new(library)),
+    }
+}
+
+/// Get the packages `apk info --who-owns` says ship a library
+///
+/// Each line is `<path> is owned by <package>-<version>-r<release>`, so the version has
+/// to be cut off the end.
+fn packages_from_apk_who_owns(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(" is owned by "))
+        .filter_map(|(_, owner)| owner.trim().rsplitn(3, '-').nth(2))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Create a library provided by a Snap or Flatpak, if that's where it lives
+///
+/// Both keep each package in its own directory, so the package can be read off the path:
+/// `/snap/<name>/<revision>/...`, or `<flatpak dir>/<kind>/<id>/<arch>/<branch>/...` for
+/// system-wide and per-user Flatpak installations alike. Inside a Flatpak sandbox the app
+/// itself is mounted at `/app`, and `/.flatpak-info` says which app that is.
+fn library_from_sandbox(library: &str, sysroot: Option<&Utf8Path>) -> Option<Library> {
+    let source = if let Some(rest) = library.strip_prefix("/snap/") {
+        let name = rest.split('/').next().filter(|name| !name.is_empty())?;
+        format!("snap:{name}")
+    } else if let Some((kind, rest)) = ["runtime", "app"].into_iter().find_map(|kind| {
+        let (_, rest) = library.split_once(&format!("/flatpak/{kind}/"))?;
+        Some((kind, rest))
+    }) {
+        // Flatpak refs are kind/id/arch/branch, which is exactly how the path goes on
+        let parts = rest.splitn(4, '/').take(3).collect::<Vec<_>>();
+        if parts.len() < 3 {
+            return None;
+        }
+        format!("flatpak:{kind}/{}", parts.join("/"))
+    } else if library.starts_with("/app/") {
+        let info = match sysroot {
+            Some(sysroot) => sysroot.join(".flatpak-info"),
+            None => Utf8PathBuf::from("/.flatpak-info"),
+        };
+        let info = fs::read_to_string(info).ok()?;
+        let name = info
+            .lines()
+            .find_map(|line| line.strip_prefix("name="))?
+            .trim();
+        format!("flatpak:app/{name}")
+    } else {
+        return None;
+    };
+
+    Some(Library {
+        source: Some(source),
+        confidence: Some(Confidence::High),
+        ..Library::new(library.to_owned())
+    })
+}
+
+/// Create a library provided by a Nix package, if it's in the Nix store
+///
+/// Store paths are `/nix/store/<hash>-<name>-<version>[-<output>]/...`, where the hash
+/// has no dashes and the version is the first part that starts with a digit, like
+/// `/nix/store/xxx-openssl-3.0.13/lib/libssl.so.3` or `/nix/store/xxx-gcc-13.2.0-lib/...`.
+fn library_from_nix(library: &str) -> Option<Library> {
+    let entry = library.strip_prefix("/nix/store/")?.split('/').next()?;
+    let (_hash, derivation) = entry.split_once('-')?;
+    let name = derivation
+        .split('-')
+        .take_while(|part| !part.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join("-");
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(Library {
+        source: Some(format!("nix:{name}")),
+        confidence: Some(Confidence::High),
+        ..Library::new(library.to_owned())
+    })
+}
+
+/// Create a library provided by a Conda package, if it's in a Conda environment
+///
+/// Every Conda environment, including the base one (like `~/miniconda3`), has a
+/// `conda-meta` directory with a JSON record of each package installed in it, listing
+/// the files it ships relative to the environment.
+fn library_from_conda(library: &str, sysroot: Option<&Utf8Path>) -> Option<Library> {
+    if !library.starts_with('/') {
+        return None;
+    }
+    let root = sysroot.unwrap_or(Utf8Path::new("/"));
+    let on_disk = |dir: &Utf8Path| root.join(dir.as_str().trim_start_matches('/'));
+    let path = Utf8Path::new(library);
+    let env = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| on_disk(dir).join("conda-meta").is_dir())?;
+    let relative = path.strip_prefix(env).ok()?;
+    let package = on_disk(env)
+        .join("conda-meta")
+        .read_dir_utf8()
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().extension() == Some("json"))
+        .find_map(|entry| {
+            let record = fs::read_to_string(entry.path()).ok()?;
+            let record: serde_json::Value = serde_json::from_str(&record).ok()?;
+            let ships = record["files"]
+                .as_array()?
+                .iter()
+                .any(|file| file.as_str() == Some(relative.as_str()));
+            ships.then(|| record["name"].as_str().map(str::to_owned))?
+        })?;
+
+    Some(Library {
+        source: Some(format!("conda:{package}")),
+        confidence: Some(Confidence::High),
+        ..Library::new(library.to_owned())
+    })
+}
+
+/// Get every package `dpkg --search` says ships a library
+///
+/// Each line of output is `package[, package...]: path`, where packages may have an
+/// architecture qualifier (`libssl3:amd64`). A file can be shipped by several packages,
+/// and a name that isn't an absolute path can match several files, so there may be
+/// more than one; they're all kept.
+fn packages_from_dpkg_search(output: &str, library: &str) -> Vec<String> {
+    let mut packages = vec![];
+    for line in output.lines() {
+        // dpkg-divert notes aren't about who owns the file
+        if line.starts_with("diversion by") {
+            continue;
+        }
+        let Some((owners, path)) = line.split_once(": ") else {
+            continue;
+        };
+        if library.starts_with('/') && path.trim() != library {
+            continue;
+        }
+        for owner in owners.split(", ") {
+            let package = owner.split(':').next().unwrap_or(owner).trim();
+            if !package.is_empty() && !packages.iter().any(|p| p == package) {
+                packages.push(package.to_owned());
+            }
+        }
+    }
+    packages
+}
+
+/// Get the license of a Homebrew formula, as declared in the formula
+fn license_from_homebrew(package: &str) -> Option<String> {
+    let output = Cmd::new("brew", "get license info from homebrew")
+        .arg("info")
+        .arg("--json=v2")
+        .arg(package)
+        .check(false)
+        .output()
+        .ok()?;
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    info["formulae"][0]["license"]
+        .as_str()
+        .map(|l| l.to_owned())
+}
+
+/// Get the license of a Debian package from its copyright file
+///
+/// dpkg doesn't record licenses itself, but most packages ship a machine-readable
+/// copyright file, whose first License field covers the package as a whole.
+fn license_from_apt(package: &str, sysroot: Option<&Utf8Path>) -> Option<String> {
+    let copyright = format!("usr/share/doc/{package}/copyright");
+    let copyright = match sysroot {
+        Some(sysroot) => sysroot.join(copyright),
+        None => Utf8PathBuf::from("/").join(copyright),
+    };
+    let copyright = fs::read_to_string(copyright).ok()?;
+    copyright
+        .lines()
+        .find_map(|line| line.strip_prefix("License:"))
+        .map(|license| license.trim().to_owned())
+        .filter(|license| !license.is_empty())
+}
+
+/// Whether a library lives in one of the Homebrew `prefixes`, or its `cellar`
+pub(crate) fn is_homebrew_path(library: &str, prefixes: &[Utf8PathBuf], cellar: &Utf8Path) -> bool {
+    let library = Utf8Path::new(library);
+    prefixes.iter().any(|prefix| library.starts_with(prefix)) || library.starts_with(cellar)
+}
+
+/// Where Homebrew installs itself by default on Apple Silicon and Linux
+pub(crate) const DEFAULT_HOMEBREW_PREFIXES: [&str; 2] =
+    ["/opt/homebrew", "/home/linuxbrew/.linuxbrew"];
+
+/// Where Homebrew installs packages by default on Intel Macs
+pub(crate) const DEFAULT_HOMEBREW_CELLAR: &str = "/usr/local/Cellar";
+
+/// Find every place Homebrew may be installed on this machine
+///
+/// That's the `configured` prefixes from the linkage config, the one this machine's Homebrew uses
+/// (`HOMEBREW_PREFIX`, which `brew shellenv` sets, or else `brew --prefix`), and the
+/// defaults for Apple Silicon and Linuxbrew. `/usr/local`, the default on Intel Macs,
+/// is left out: plenty of other things install there too, so libraries in it are only
+/// Homebrew's if they're really in the [`host_homebrew_cellar`][].
+fn host_homebrew_prefixes(configured: &[Utf8PathBuf]) -> Vec<Utf8PathBuf> {
+    let host_prefix = std::env::var("HOMEBREW_PREFIX").ok().or_else(|| {
+        Cmd::new("brew", "find the homebrew prefix")
+            .arg("--prefix")
+            .check(false)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+    });
+    let mut prefixes = configured.to_vec();
+    let host_prefix = host_prefix
+        .map(|prefix| prefix.trim().to_owned())
+        .filter(|prefix| !prefix.is_empty() && prefix != "/usr/local");
+    for prefix in host_prefix
+        .into_iter()
+        .chain(DEFAULT_HOMEBREW_PREFIXES.map(str::to_owned))
+    {
+        if !prefixes.iter().any(|p| p == &prefix) {
+            prefixes.push(Utf8PathBuf::from(prefix));
+        }
+    }
+    prefixes
+}
+
+/// Find where Homebrew installs packages on this machine
+///
+/// This is `/usr/local/Cellar` for a default install on an Intel Mac, but Homebrew can
+/// be installed under any prefix, or have its Cellar moved. `brew shellenv` sets
+/// `HOMEBREW_CELLAR` and `HOMEBREW_PREFIX`, and otherwise we ask brew itself.
+fn host_homebrew_cellar() -> Utf8PathBuf {
+    if let Ok(cellar) = std::env::var("HOMEBREW_CELLAR") {
+        return Utf8PathBuf::from(cellar);
+    }
+    if let Ok(prefix) = std::env::var("HOMEBREW_PREFIX") {
+        return Utf8PathBuf::from(prefix).join("Cellar");
+    }
+    Cmd::new("brew", "find the homebrew cellar")
+        .arg("--cellar")
+        .check(false)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|cellar| cellar.trim().to_owned())
+        .filter(|cellar| !cellar.is_empty())
+        .map(Utf8PathBuf::from)
+        .unwrap_or_else(|| Utf8PathBuf::from(DEFAULT_HOMEBREW_CELLAR))
+}
+
+/// Works out which package provides a library
+///
+/// Normally this asks the host's package managers, but tests can substitute their own
+/// canned answers.
+pub trait PackageResolver {
+    /// Find the package providing a library that's been sorted into the given category
+    fn resolve(
+        &self,
+        category: LinkageCategory,
+        library: String,
+        sysroot: Option<&Utf8Path>,
+    ) -> DistResult<Library>;
+
+    /// Get ready to resolve a batch of libraries, e.g. by looking them all up at once
+    fn prefetch(&self, _libraries: &[String], _sysroot: Option<&Utf8Path>) {}
+
+    /// Every prefix Homebrew may be installed under
+    fn homebrew_prefixes(&self) -> &[Utf8PathBuf];
+
+    /// Where Homebrew installs packages
+    fn homebrew_cellar(&self) -> &Utf8Path;
+
+    /// Fill in the license of every library whose package we know, if this resolver
+    /// can look licenses up
+    fn add_licenses(&self, _linkage: &mut Linkage, _sysroot: Option<&Utf8Path>) {}
+}
+
+/// Library sources [`HostPackageResolver`][] has looked up, by sysroot, category and path
+type SourceCache = Mutex<SortedMap<(Option<Utf8PathBuf>, LinkageCategory, String), Library>>;
+
+/// Packages dpkg says own each library, by sysroot and path
+type DpkgOwnerCache = Mutex<SortedMap<(Option<Utf8PathBuf>, String), Vec<String>>>;
+
+/// Package licenses, by sysroot, package manager and package
+type LicenseCache = Mutex<SortedMap<(Option<Utf8PathBuf>, &'static str, String), Option<String>>>;
+
+/// Finds packages by asking the host's package managers (Homebrew, dpkg, snap...)
+///
+/// Many binaries link against the same few libraries (libc...), and working out which
+/// package they come from can mean spawning a process, so answers are remembered for
+/// as long as the resolver lives. Make one per run, and share it between the binaries
+/// of that run.
+#[derive(Debug, Default)]
+pub struct HostPackageResolver {
+    /// Homebrew prefixes set in the linkage config
+    configured_homebrew_prefixes: Vec<Utf8PathBuf>,
+    /// Those and the ones found on this machine, from [`host_homebrew_prefixes`][]
+    homebrew_prefixes: OnceLock<Vec<Utf8PathBuf>>,
+    /// This machine's Homebrew Cellar, from [`host_homebrew_cellar`][]
+    homebrew_cellar: OnceLock<Utf8PathBuf>,
+    /// Libraries we've already looked up the source of, by sysroot, category and path
+    sources: SourceCache,
+    /// The packages dpkg says own each library, by sysroot, from [`prefetch_dpkg_owners`][]
+    dpkg_owners: DpkgOwnerCache,
+    /// Licenses we've already looked up, by sysroot, package manager and package
+    licenses: LicenseCache,
+}
+
+impl PackageResolver for HostPackageResolver {
+    fn resolve(
+        &self,
+        category: LinkageCategory,
+        library: String,
+        sysroot: Option<&Utf8Path>,
+    ) -> DistResult<Library> {
+        self.library_source(category, library, sysroot)
+    }
+
+    fn prefetch(&self, libraries: &[String], sysroot: Option<&Utf8Path>) {
+        if DistroPackageManager::detect(sysroot) == DistroPackageManager::Dpkg {
+            let sysroot_key = sysroot.map(Utf8Path::to_owned);
+            let unknown = {
+                let known = self.dpkg_owners.lock().unwrap();
+                libraries
+                    .iter()
+                    .filter(|library| {
+                        !known.contains_key(&(sysroot_key.clone(), (*library).clone()))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            // compute_linkage prefetches a whole run's libraries at once, so nothing
+            // else is waiting on this, and the lock isn't held while dpkg runs
+            let found = prefetch_dpkg_owners(&unknown, sysroot);
+            let mut known = self.dpkg_owners.lock().unwrap();
+            for (library, packages) in found {
+                known.insert((sysroot_key.clone(), library), packages);
+            }
+        }
+    }
+
+    fn homebrew_prefixes(&self) -> &[Utf8PathBuf] {
+        self.homebrew_prefixes
+            .get_or_init(|| host_homebrew_prefixes(&self.configured_homebrew_prefixes))
+    }
+
+    fn homebrew_cellar(&self) -> &Utf8Path {
+        self.homebrew_cellar.get_or_init(host_homebrew_cellar)
+    }
+
+    fn add_licenses(&self, linkage: &mut Linkage, sysroot: Option<&Utf8Path>) {
+        self.add_package_licenses(linkage, sysroot);
+    }
+}
+
+impl HostPackageResolver {
+    /// Make a resolver for one run of the linkage check, with the given settings
+    pub fn new(config: &LinkageConfig) -> Self {
+        Self {
+            configured_homebrew_prefixes: config.homebrew_prefix.iter().cloned().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Work out which package provides a library, consulting the cache first
+    fn library_source(
+        &self,
+        category: LinkageCategory,
+        library: String,
+        sysroot: Option<&Utf8Path>,
+    ) -> DistResult<Library> {
+        let key = (sysroot.map(Utf8Path::to_owned), category, library);
+        if let Some(cached) = self.sources.lock().unwrap().get(&key) {
+            return Ok(cached.clone());
+        }
+
+        let (_, category, library) = key;
+        let homebrew = |library: String| library_from_homebrew(library, self.homebrew_prefixes());
+        let distro = |library: String| self.library_from_distro(library, sysroot);
+        let resolved = match category {
+            LinkageCategory::Homebrew => homebrew(library.clone()),
+            LinkageCategory::System | LinkageCategory::Toolchain => distro(library.clone())?,
+            LinkageCategory::LanguageRuntime | LinkageCategory::Graphics
+                if is_homebrew_path(&library, self.homebrew_prefixes(), self.homebrew_cellar()) =>
+            {
+                homebrew(library.clone())
+            }
+            LinkageCategory::LanguageRuntime | LinkageCategory::Graphics => {
+                distro(library.clone())?
+            }
+            LinkageCategory::Other => {
+                let resolved = library_from_sandbox(&library, sysroot)
+                    .or_else(|| library_from_nix(&library))
+                    .or_else(|| library_from_conda(&library, sysroot));
+                match resolved {
+                    Some(resolved) => resolved,
+                    None => distro(library.clone())?,
+                }
+            }
+            LinkageCategory::PublicUnmanaged | LinkageCategory::Frameworks => {
+                Library::new(library.clone())
+            }
+        };
+        self.sources.lock().unwrap().insert(
+            (sysroot.map(Utf8Path::to_owned), category, library),
+            resolved.clone(),
+        );
+        Ok(resolved)
+    }
+
+    /// [`library_from_distro`][], using what [`prefetch_dpkg_owners`][] found if it can
+    fn library_from_distro(
+        &self,
+        library: String,
+        sysroot: Option<&Utf8Path>,
+    ) -> DistResult<Library> {
+        let key = (sysroot.map(Utf8Path::to_owned), library);
+        let prefetched = self.dpkg_owners.lock().unwrap().get(&key).cloned();
+        let (_, library) = key;
+        match prefetched {
+            Some(packages) => Ok(library_from_dpkg_owners(library, packages)),
+            None => library_from_distro(library, sysroot),
+        }
+    }
+
+    /// Fill in the license of every library whose package we know
+    ///
+    /// Failing to find a license isn't an error; plenty of packages don't declare one in a
+    /// way we can read.
+    fn add_package_licenses(&self, linkage: &mut Linkage, sysroot: Option<&Utf8Path>) {
+        let with_licenses = |libraries: &SortedSet<Library>, manager: &'static str| {
+            libraries
+                .iter()
+                .cloned()
+                .map(|mut library| {
+                    if let Some(source) = &library.source {
+                        // dpkg can name several packages for one file
+                        let mut licenses = source
+                            .split(", ")
+                            .filter_map(|package| self.package_license(manager, package, sysroot))
+                            .collect::<Vec<_>>();
+                        licenses.dedup();
+                        library.license = (!licenses.is_empty()).then(|| licenses.join(", "));
+                    }
+                    library
+                })
+                .collect::<SortedSet<_>>()
+        };
+        linkage.homebrew = with_licenses(&linkage.homebrew, "homebrew");
+        linkage.system = with_licenses(&linkage.system, "apt");
+        linkage.toolchain = with_licenses(&linkage.toolchain, "apt");
+        // Language runtimes and graphics libraries can come from either package manager
+        let with_either_licenses = |libraries: &SortedSet<Library>| {
+            let (homebrew, apt): (SortedSet<_>, SortedSet<_>) =
+                libraries.iter().cloned().partition(|library| {
+                    is_homebrew_path(
+                        &library.path,
+                        self.homebrew_prefixes(),
+                        self.homebrew_cellar(),
+                    )
+                });
+            let mut libraries = with_licenses(&homebrew, "homebrew");
+            libraries.extend(with_licenses(&apt, "apt"));
+            libraries
+        };
+        linkage.language_runtimes = with_either_licenses(&linkage.language_runtimes);
+        linkage.graphics = with_either_licenses(&linkage.graphics);
+        linkage.other = with_licenses(&linkage.other, "apt");
+        for slice in &mut linkage.slices {
+            self.add_package_licenses(slice, sysroot);
+        }
+    }
+
+    /// Get the license of a package, consulting the cache first
+    fn package_license(
+        &self,
+        manager: &'static str,
+        package: &str,
+        sysroot: Option<&Utf8Path>,
+    ) -> Option<String> {
+        let key = (sysroot.map(Utf8Path::to_owned), manager, package.to_owned());
+        if let Some(cached) = self.licenses.lock().unwrap().get(&key) {
+            return cached.clone();
+        }
+
+        let license = match manager {
+            "homebrew" => license_from_homebrew(package),
+            // Snap and Flatpak libraries end up in "other" too, but dpkg knows nothing of them
+            _ if ["snap:", "flatpak:", "nix:", "conda:"]
+                .iter()
+                .any(|store| package.starts_with(store)) =>
+            {
+                None
+            }
+            _ => license_from_apt(package, sysroot),
+        };
+        self.licenses.lock().unwrap().insert(key, license.clone());
+        license
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn nix_store_packages() {
+        let source = |path: &str| library_from_nix(path).and_then(|library| library.source);
+        assert_eq!(
+            source("/nix/store/0c2dqk4mdrsjnqmbdnsgrjq7bc8kczn5-openssl-3.0.13/lib/libssl.so.3"),
+            Some("nix:openssl".to_owned())
+        );
+        assert_eq!(
+            source("/nix/store/9fy3ss5i3b7qb6zbzp3kmnla1s1m7ng1-gcc-13.2.0-lib/lib/libgcc_s.so.1"),
+            Some("nix:gcc".to_owned())
+        );
+        assert_eq!(
+            source("/nix/store/5qy9fcnxsh8v5ls1wmvryf5cf3wn8q3x-xorg-libX11-1.8.7/lib/libX11.so.6"),
+            Some("nix:xorg-libX11".to_owned())
+        );
+        assert_eq!(source("/usr/lib/libssl.so.3"), None);
+    }
+
+    #[test]
+    fn conda_packages() {
+        let env = TempDir::new().unwrap();
+        let env = Utf8Path::from_path(env.path()).unwrap().to_owned();
+        fs::create_dir_all(env.join("conda-meta")).unwrap();
+        fs::create_dir_all(env.join("lib")).unwrap();
+        fs::write(
+            env.join("conda-meta/zlib-1.3.1-h4ab18f5_1.json"),
+            r#"{"name": "zlib", "version": "1.3.1", "files": ["lib/libz.so", "lib/libz.so.1"]}"#,
+        )
+        .unwrap();
+
+        let library = library_from_conda(env.join("lib/libz.so.1").as_str(), None).unwrap();
+        assert_eq!(library.source.as_deref(), Some("conda:zlib"));
+        assert!(library_from_conda(env.join("lib/libssl.so.3").as_str(), None).is_none());
+    }
+
+    #[test]
+    fn detect_distro_package_manager() {
+        let fedora = "NAME=\"Fedora Linux\"\nID=fedora\nVERSION_ID=40\n";
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        let manjaro = "ID=manjaro\nID_LIKE=arch\n";
+        let alpine = "ID=alpine\nVERSION_ID=3.20.0\n";
+        let mint = "ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n";
+        let detect = DistroPackageManager::from_os_release;
+        assert_eq!(detect(fedora), DistroPackageManager::Rpm);
+        assert_eq!(detect(rocky), DistroPackageManager::Rpm);
+        assert_eq!(detect(manjaro), DistroPackageManager::Pacman);
+        assert_eq!(detect(alpine), DistroPackageManager::Apk);
+        assert_eq!(detect(mint), DistroPackageManager::Dpkg);
+        assert_eq!(detect(""), DistroPackageManager::Dpkg);
+    }
+
+    #[test]
+    fn parse_apk_who_owns() {
+        let output = "/usr/lib/libz.so.1 is owned by zlib-1.3.1-r1\n\
+                      /usr/lib/libssl.so.3 is owned by libssl3-3.3.0-r2\n";
+        assert_eq!(packages_from_apk_who_owns(output), vec!["zlib", "libssl3"]);
+        let not_found = "ERROR: /nope: Could not find owner package\n";
+        assert!(packages_from_apk_who_owns(not_found).is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+//! Linkage problems as SARIF, for code scanning tools
+
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use crate::{config::LinkageConfig, errors::*};
+
+use super::policy::{hardening_enabled, HARDENING_FEATURES};
+
+/// The SARIF rule for linkage policy violations
+const SARIF_VIOLATION: &str = "linkage-violation";
+/// The SARIF rule for linkage warnings
+const SARIF_WARNING: &str = "linkage-warning";
+/// The SARIF rule for mitigations a binary wasn't built with
+const SARIF_HARDENING: &str = "missing-hardening";
+
+/// The problems in a linkage report as a SARIF log, for GitHub code scanning
+///
+/// Only the parts of SARIF 2.1.0 that code scanning needs are included.
+#[derive(Serialize)]
+pub(crate) struct SarifLog {
+    /// The SARIF schema this log follows
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    /// The SARIF version this log follows
+    version: &'static str,
+    /// The runs of analysis tools in this log, which for us is always one
+    runs: Vec<SarifRun>,
+}
+
+/// A run of an analysis tool in a [`SarifLog`][]
+#[derive(Serialize)]
+struct SarifRun {
+    /// The tool that produced the results
+    tool: SarifTool,
+    /// The problems it found
+    results: Vec<SarifResult>,
+}
+
+/// The analysis tool that produced a [`SarifRun`][]
+#[derive(Serialize)]
+struct SarifTool {
+    /// The tool itself
+    driver: SarifDriver,
+}
+
+/// The details of the analysis tool that produced a [`SarifRun`][]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    /// The tool's name
+    name: &'static str,
+    /// The tool's version
+    version: &'static str,
+    /// Where to find out more about the tool
+    information_uri: &'static str,
+    /// The kinds of problem the tool reports
+    rules: Vec<SarifRule>,
+}
+
+/// A kind of problem a [`SarifDriver`][] reports
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    /// The rule's id, which results refer to it by
+    id: &'static str,
+    /// What the rule checks
+    short_description: SarifMessage,
+}
+
+/// Some text in a SARIF log
+#[derive(Serialize)]
+struct SarifMessage {
+    /// The text itself
+    text: String,
+}
+
+/// A problem found in a [`SarifRun`][]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    /// The id of the [`SarifRule`][] the problem breaks
+    rule_id: &'static str,
+    /// How serious it is: "error", "warning" or "note"
+    level: &'static str,
+    /// What the problem is
+    message: SarifMessage,
+    /// Where the problem is
+    locations: Vec<SarifLocation>,
+}
+
+/// Where a [`SarifResult`][] was found
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    /// The file it was found in
+    physical_location: SarifPhysicalLocation,
+}
+
+/// The file a [`SarifResult`][] was found in
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    /// The file itself
+    artifact_location: SarifArtifactLocation,
+}
+
+/// A file in a SARIF log
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    /// The file's path, relative to the repository root
+    uri: &'static str,
+}
+
+impl SarifLog {
+    /// Collect a linkage report's violations, warnings and missing mitigations
+    ///
+    /// Binaries aren't part of the repository, so every result points at the workspace's
+    /// Cargo.toml, and names the binary in its message. Mitigations the config requires
+    /// are left out, since they're already reported as violations.
+    pub(crate) fn new(
+        manifest: &DistManifest,
+        violations: &[DistError],
+        config: &LinkageConfig,
+    ) -> Self {
+        let rule = |id, description: &str| SarifRule {
+            id,
+            short_description: SarifMessage {
+                text: description.to_owned(),
+            },
+        };
+        let rules = vec![
+            rule(
+                SARIF_VIOLATION,
+                "A binary breaks the workspace's linkage policy",
+            ),
+            rule(
+                SARIF_WARNING,
+                "A binary links something that may cause problems",
+            ),
+            rule(
+                SARIF_HARDENING,
+                "A binary wasn't built with an exploit mitigation",
+            ),
+        ];
+
+        let result = |rule_id, level, text| SarifResult {
+            rule_id,
+            level,
+            message: SarifMessage { text },
+            locations: vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation { uri: "Cargo.toml" },
+                },
+            }],
+        };
+        let mut results = violations
+            .iter()
+            .map(|violation| result(SARIF_VIOLATION, "error", violation.to_string()))
+            .collect::<Vec<_>>();
+        for asset in manifest.assets.values() {
+            let Some(linkage) = &asset.linkage else {
+                continue;
+            };
+            let binary = format!("{} ({})", asset.name, asset.target_triples.join(", "));
+            for warning in &linkage.warnings {
+                results.push(result(
+                    SARIF_WARNING,
+                    "warning",
+                    format!("{binary}: {warning}"),
+                ));
+            }
+            let Some(hardening) = &linkage.hardening else {
+                continue;
+            };
+            let missing = HARDENING_FEATURES
+                .into_iter()
+                .filter(|feature| !config.require_hardening.contains(feature))
+                .filter(|feature| hardening_enabled(hardening, *feature) == Some(false));
+            for feature in missing {
+                let text = format!("{binary} wasn't built with {feature}");
+                results.push(result(SARIF_HARDENING, "note", text));
+            }
+        }
+
+        SarifLog {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "cargo-dist",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: "https://opensource.axo.dev/cargo-dist/",
+                        rules,
+                    },
+                },
+                results,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cargo_dist_schema::{AssetInfo, Hardening, Linkage, Relro};
+
+    use crate::config::HardeningFeature;
+
+    use super::*;
+
+    #[test]
+    fn sarif_findings() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let linkage = Linkage {
+            warnings: vec!["links libfoo.so, which is in a home directory".to_owned()],
+            hardening: Some(Hardening {
+                pie: Some(false),
+                relro: Some(Relro::Partial),
+                nx: Some(true),
+                ..Hardening::default()
+            }),
+            ..Linkage::default()
+        };
+        manifest.assets.insert(
+            "app".to_owned(),
+            AssetInfo {
+                id: "app".to_owned(),
+                name: "app".to_owned(),
+                system: "host".to_owned(),
+                target_triples: vec!["x86_64-unknown-linux-gnu".to_owned()],
+                linkage: Some(linkage),
+            },
+        );
+        let config = LinkageConfig {
+            require_hardening: vec![HardeningFeature::Pie],
+            ..LinkageConfig::default()
+        };
+        let violations = vec![DistError::LinkageNotHardened {
+            binary: "app".to_owned(),
+            missing: "pie".to_owned(),
+        }];
+
+        let log = SarifLog::new(&manifest, &violations, &config);
+        let results = log.runs[0]
+            .results
+            .iter()
+            .map(|result| (result.rule_id, result.level, result.message.text.as_str()))
+            .collect::<Vec<_>>();
+        // PIE is required, so it's only reported as a violation
+        assert_eq!(
+            results,
+            vec![
+                (SARIF_VIOLATION, "error", "app wasn't built with pie"),
+                (
+                    SARIF_WARNING,
+                    "warning",
+                    "app (x86_64-unknown-linux-gnu): links libfoo.so, which is in a home directory"
+                ),
+                (
+                    SARIF_HARDENING,
+                    "note",
+                    "app (x86_64-unknown-linux-gnu) wasn't built with relro"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Reading the imports of WebAssembly modules and components
+
+use std::path::Path;
+
+use crate::errors::*;
+
+use super::lossy_path;
+
+/// Get the (module, field) pairs a WebAssembly module imports from its host
+///
+/// Wasm has no dynamic libraries, but these imports (WASI functions, custom host
+/// functions...) are the moral equivalent: capabilities the runtime must provide.
+///
+/// Components import whole interfaces (like `wasi:cli/environment@0.2.0`) rather than
+/// functions, so for them the pairs are (kind, name) instead. The core modules embedded
+/// in a component are wired up to each other by the component, so only the component's
+/// own imports are needed from the host.
+pub(crate) fn do_wasm(path: &Path, buf: &[u8]) -> DistResult<Vec<(String, String)>> {
+    let malformed = |details: &str| DistError::LinkageCheckMalformedWasm {
+        path: lossy_path(path),
+        details: details.to_owned(),
+    };
+
+    let component = match buf.get(0..8) {
+        // Core modules are version 1
+        Some(b"\0asm\x01\0\0\0") => false,
+        // Components are version 0xd of layer 1
+        Some(b"\0asm\x0d\0\x01\0") => true,
+        _ => return Err(malformed("not a wasm module or component")),
+    };
+    let import_section = if component { 10 } else { 2 };
+
+    let mut reader = WasmReader { buf, pos: 8 };
+    let mut imports = vec![];
+    while !reader.is_empty() {
+        let section_id = reader
+            .byte()
+            .ok_or_else(|| malformed("truncated section"))?;
+        let size = reader.leb().ok_or_else(|| malformed("truncated section"))? as usize;
+        let section_end = reader.pos.saturating_add(size);
+        // The import section is the only one we care about
+        if section_id != import_section {
+            reader.pos = section_end;
+            continue;
+        }
+
+        let count = reader.leb().ok_or_else(|| malformed("bad import count"))?;
+        for _ in 0..count {
+            let import = if component {
+                reader.component_import()
+            } else {
+                reader.import()
+            };
+            imports.push(import.ok_or_else(|| malformed("bad import entry"))?);
+        }
+        // Modules only have the one import section, but components can have several
+        if !component {
+            break;
+        }
+        reader.pos = section_end;
+    }
+
+    Ok(imports)
+}
+
+/// Minimal cursor over the bytes of a wasm module or component
+struct WasmReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl WasmReader<'_> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.buf.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    /// Read an unsigned LEB128 integer
+    fn leb(&mut self) -> Option<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            result |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+            if shift >= 64 {
+                return None;
+            }
+        }
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let len = self.leb()? as usize;
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.buf.get(self.pos..end)?;
+        self.pos = end;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn limits(&mut self) -> Option<()> {
+        let flags = self.byte()?;
+        self.leb()?;
+        if flags & 0x01 != 0 {
+            self.leb()?;
+        }
+        Some(())
+    }
+
+    /// Read an import entry, returning its (module, field)
+    fn import(&mut self) -> Option<(String, String)> {
+        let module = self.name()?;
+        let field = self.name()?;
+        match self.byte()? {
+            // function: type index
+            0x00 => {
+                self.leb()?;
+            }
+            // table: reftype + limits
+            0x01 => {
+                self.byte()?;
+                self.limits()?;
+            }
+            // memory: limits
+            0x02 => self.limits()?,
+            // global: valtype + mutability
+            0x03 => {
+                self.byte()?;
+                self.byte()?;
+            }
+            // tag: attribute + type index
+            0x04 => {
+                self.byte()?;
+                self.leb()?;
+            }
+            _ => return None,
+        }
+        Some((module, field))
+    }
+
+    /// Read a component's import entry, returning its (kind, name)
+    fn component_import(&mut self) -> Option<(String, String)> {
+        // Older components mark interface names with 0x01, which are now told apart by
+        // their syntax instead
+        if self.byte()? > 0x01 {
+            return None;
+        }
+        let name = self.name()?;
+        let kind = match self.byte()? {
+            // core module: 0x11 + core type index
+            0x00 => {
+                if self.byte()? != 0x11 {
+                    return None;
+                }
+                self.leb()?;
+                "core module"
+            }
+            // func: type index
+            0x01 => {
+                self.leb()?;
+                "func"
+            }
+            // value: either a value index or a value type, both LEB128-encoded
+            0x02 => {
+                if self.byte()? > 0x01 {
+                    return None;
+                }
+                self.leb()?;
+                "value"
+            }
+            // type: equal to a type index, or a fresh resource
+            0x03 => {
+                match self.byte()? {
+                    0x00 => {
+                        self.leb()?;
+                    }
+                    0x01 => {}
+                    _ => return None,
+                }
+                "type"
+            }
+            // component: type index
+            0x04 => {
+                self.leb()?;
+                "component"
+            }
+            // instance: type index
+            0x05 => {
+                self.leb()?;
+                "instance"
+            }
+            _ => return None,
+        };
+        Some((kind.to_owned(), name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A wasm section with the given id, prefixed with its size
+    fn wasm_section(id: u8, contents: &[u8]) -> Vec<u8> {
+        assert!(contents.len() < 0x80, "sizes here are single-byte LEB128");
+        let mut section = vec![id, contents.len() as u8];
+        section.extend_from_slice(contents);
+        section
+    }
+
+    /// A wasm name, prefixed with its length
+    fn wasm_name(name: &str) -> Vec<u8> {
+        let mut bytes = vec![name.len() as u8];
+        bytes.extend_from_slice(name.as_bytes());
+        bytes
+    }
+
+    /// A core module with a type section, then an import of each kind
+    fn wasm_module_fixture() -> Vec<u8> {
+        let mut imports = vec![4];
+        // func of type 0
+        imports.extend(wasm_name("wasi_snapshot_preview1"));
+        imports.extend(wasm_name("fd_write"));
+        imports.extend([0x00, 0x00]);
+        // funcref table with a minimum and maximum
+        imports.extend(wasm_name("env"));
+        imports.extend(wasm_name("table"));
+        imports.extend([0x01, 0x70, 0x01, 0x01, 0x10]);
+        // memory with a multi-byte minimum
+        imports.extend(wasm_name("env"));
+        imports.extend(wasm_name("memory"));
+        imports.extend([0x02, 0x00, 0x80, 0x02]);
+        // mutable i32 global
+        imports.extend(wasm_name("env"));
+        imports.extend(wasm_name("stack_pointer"));
+        imports.extend([0x03, 0x7f, 0x01]);
+
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        // (func (param i32 i32 i32 i32) (result i32))
+        module.extend(wasm_section(
+            1,
+            &[1, 0x60, 4, 0x7f, 0x7f, 0x7f, 0x7f, 1, 0x7f],
+        ));
+        module.extend(wasm_section(2, &imports));
+        module
+    }
+
+    /// A component embedding a core module, with its imports split over two sections
+    fn wasm_component_fixture() -> Vec<u8> {
+        let mut first = vec![2];
+        // instance of type 0
+        first.extend([0x00]);
+        first.extend(wasm_name("wasi:cli/environment@0.2.0"));
+        first.extend([0x05, 0x00]);
+        // func of type 1, with an older component's interface marker
+        first.extend([0x01]);
+        first.extend(wasm_name("log"));
+        first.extend([0x01, 0x01]);
+        let mut second = vec![2];
+        // a fresh resource type
+        second.extend([0x00]);
+        second.extend(wasm_name("handle"));
+        second.extend([0x03, 0x01]);
+        // core module of core type 0
+        second.extend([0x00]);
+        second.extend(wasm_name("plugin"));
+        second.extend([0x00, 0x11, 0x00]);
+
+        let mut component = b"\0asm\x0d\0\x01\0".to_vec();
+        // The embedded module's imports are satisfied within the component
+        component.extend(wasm_section(1, &wasm_module_fixture()));
+        component.extend(wasm_section(10, &first));
+        component.extend(wasm_section(10, &second));
+        component
+    }
+
+    fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    #[test]
+    fn wasm_reader() {
+        let mut reader = WasmReader {
+            buf: &[0xe5, 0x8e, 0x26, 0x7f],
+            pos: 0,
+        };
+        assert_eq!(reader.leb(), Some(624485));
+        assert_eq!(reader.leb(), Some(0x7f));
+        assert_eq!(reader.leb(), None);
+        assert!(reader.is_empty());
+
+        // Cut off mid-number, and too long to fit in a u64
+        let mut reader = WasmReader {
+            buf: &[0x80, 0x80],
+            pos: 0,
+        };
+        assert_eq!(reader.leb(), None);
+        let mut reader = WasmReader {
+            buf: &[0xff; 11],
+            pos: 0,
+        };
+        assert_eq!(reader.leb(), None);
+
+        let name = wasm_name("env");
+        let mut reader = WasmReader { buf: &name, pos: 0 };
+        assert_eq!(reader.name().as_deref(), Some("env"));
+        let mut reader = WasmReader {
+            buf: &name[..2],
+            pos: 0,
+        };
+        assert_eq!(reader.name(), None);
+        // A length that overflows the position
+        let mut reader = WasmReader {
+            buf: &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            pos: 0,
+        };
+        assert_eq!(reader.name(), None);
+    }
+
+    #[test]
+    fn wasm_module_imports() {
+        let path = Path::new("app.wasm");
+        let module = wasm_module_fixture();
+        let import = |module: &str, field: &str| (module.to_owned(), field.to_owned());
+        assert_eq!(
+            do_wasm(path, &module).unwrap(),
+            vec![
+                import("wasi_snapshot_preview1", "fd_write"),
+                import("env", "table"),
+                import("env", "memory"),
+                import("env", "stack_pointer"),
+            ]
+        );
+
+        // Cutting the module short anywhere in its imports is an error, not a panic. The
+        // import section starts after the preamble and the 11-byte type section.
+        for len in 0..module.len() {
+            let result = do_wasm(path, &module[..len]);
+            if !(8..=19).contains(&len) {
+                assert!(result.is_err(), "read {len} bytes");
+            }
+        }
+
+        let mut unknown_kind = module.clone();
+        let kind = find_bytes(&module, b"\x08fd_write").unwrap() + 9;
+        assert_eq!(unknown_kind[kind], 0x00);
+        unknown_kind[kind] = 0x09;
+        let err = do_wasm(path, &unknown_kind).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unable to parse app.wasm as WebAssembly: bad import entry"
+        );
+        assert!(do_wasm(path, b"\0asm\x02\0\0\0").is_err());
+        assert!(do_wasm(path, b"\x7fELF\x02\x01\x01\0").is_err());
+    }
+
+    #[test]
+    fn wasm_component_imports() {
+        let path = Path::new("app.wasm");
+        let component = wasm_component_fixture();
+        let import = |kind: &str, name: &str| (kind.to_owned(), name.to_owned());
+        assert_eq!(
+            do_wasm(path, &component).unwrap(),
+            vec![
+                import("instance", "wasi:cli/environment@0.2.0"),
+                import("func", "log"),
+                import("type", "handle"),
+                import("core module", "plugin"),
+            ]
+        );
+
+        // Cutting the component short partway through its imports is an error, not a
+        // panic. Cuts before them only lose the embedded module, and between the import
+        // sections only lose the second one.
+        let imports = 8 + 2 + wasm_module_fixture().len();
+        let second = find_bytes(&component, b"\x06handle").unwrap() - 1;
+        let between = second - 3;
+        for len in 0..component.len() {
+            let result = do_wasm(path, &component[..len]);
+            if len < 8 || (len > imports && len != between) {
+                assert!(result.is_err(), "read {len} bytes");
+            }
+        }
+        assert_eq!(do_wasm(path, &component[..between]).unwrap().len(), 2);
+
+        // Unknown name markers and kinds are errors too
+        let mut bad_marker = component.clone();
+        assert_eq!(bad_marker[second], 0x00);
+        bad_marker[second] = 0x02;
+        assert!(do_wasm(path, &bad_marker).is_err());
+        let mut bad_kind = component.clone();
+        assert_eq!(bad_kind[second + 8], 0x03);
+        bad_kind[second + 8] = 0x06;
+        assert!(do_wasm(path, &bad_kind).is_err());
+
+        // Only the layer of components we know about
+        assert!(do_wasm(path, b"\0asm\x0d\0\x02\0").is_err());
+    }
+}
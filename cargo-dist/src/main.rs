@@ -7,7 +7,10 @@ use std::io::Write;
 use axoasset::LocalAsset;
 use camino::Utf8PathBuf;
 // Import everything from the lib version of ourselves
-use cargo_dist::{linkage::LinkageDisplay, *};
+use cargo_dist::{
+    linkage::{LinkageDisplay, TableStyle},
+    *,
+};
 use cargo_dist_schema::{AssetKind, DistManifest};
 use clap::Parser;
 use cli::{
@@ -183,7 +186,7 @@ fn print_json(out: &mut Term, report: &DistManifest) -> Result<(), std::io::Erro
 }
 
 fn print_human_linkage(out: &mut Term, report: &DistManifest) -> Result<(), std::io::Error> {
-    writeln!(out, "{}", LinkageDisplay(report))
+    writeln!(out, "{}", LinkageDisplay(report, TableStyle::detect()))
 }
 
 fn cmd_build(cli: &Cli, args: &BuildArgs) -> Result<(), miette::Report> {
@@ -323,14 +326,44 @@ fn cmd_linkage(cli: &Cli, args: &LinkageArgs) -> Result<(), miette::Report> {
         announcement_tag: cli.tag.clone(),
         root_cmd: "linkage".to_owned(),
     };
-    let mut options = cargo_dist::linkage::LinkageArgs {
-        print_output: args.print_output,
-        print_json: args.print_json,
+    let format = match args.format {
+        Some(format) => format.to_lib(),
+        None if args.print_json => cargo_dist::linkage::LinkageFormat::Json,
+        None => cargo_dist::linkage::LinkageFormat::Human,
+    };
+    let options = cargo_dist::linkage::LinkageArgs {
+        format,
         from_json: args.from_json.clone(),
+        group_by: args.group_by.to_lib(),
+        strict_static: args.strict_static,
+        deny_instrumentation: args.deny_instrumentation,
+        watch: args.watch,
+        ascii: args.ascii,
+        sysroot: args.sysroot.clone(),
+        only_problems: args.only_problems,
+        licenses: args.licenses,
+        explain: args.explain.clone(),
+        verify_checksums: args.verify_checksums,
+        exclude_system: args.exclude_system && !args.include_system,
+        write_baseline: args.write_baseline.clone(),
+        scan_symbols: args.scan_symbols,
+        scan_strings: args.scan_strings,
+        on_missing: args.on_missing.to_lib(),
+        probe_tools: args.probe_tools,
+        histogram: args.histogram,
+        policy: args.policy.clone(),
+        compare: match args.compare.as_slice() {
+            [binary, other_binary] => Some((binary.clone(), other_binary.clone())),
+            _ => None,
+        },
+        diff: match args.diff.as_slice() {
+            [old, new] => Some((old.clone(), new.clone())),
+            _ => None,
+        },
+        require_bundled: args.require_bundled,
+        deep: args.deep,
+        max_depth: args.max_depth,
     };
-    if !args.print_output && !args.print_json {
-        options.print_output = true;
-    }
     cargo_dist::linkage::do_linkage(&config, &options)
 }
 
@@ -45,7 +45,17 @@ use tracing::warn;
 use crate::{
     announce::AnnouncementTag,
     backend::{
-        installer::{homebrew::HomebrewInstallerInfo, npm::NpmInstallerInfo, InstallerImpl},
+        installer::{
+            aur::AurInstallerInfo,
+            chocolatey::ChocolateyInstallerInfo,
+            flatpak::FlatpakInstallerInfo,
+            homebrew::{HomebrewCaskInfo, HomebrewInstallerInfo},
+            nix::NixInstallerInfo,
+            npm::NpmInstallerInfo,
+            scoop::ScoopInstallerInfo,
+            snap::SnapInstallerInfo,
+            InstallerImpl,
+        },
         templates::{TemplateEntry, TEMPLATE_INSTALLER_NPM},
     },
     config::Config,
@@ -332,6 +342,13 @@ fn add_manifest_artifact(
             InstallerImpl::Powershell(info)
             | InstallerImpl::Shell(info)
             | InstallerImpl::Homebrew(HomebrewInstallerInfo { inner: info, .. })
+            | InstallerImpl::HomebrewCask(HomebrewCaskInfo { inner: info, .. })
+            | InstallerImpl::Aur(AurInstallerInfo { inner: info, .. })
+            | InstallerImpl::Flatpak(FlatpakInstallerInfo { inner: info, .. })
+            | InstallerImpl::Snap(SnapInstallerInfo { inner: info, .. })
+            | InstallerImpl::Nix(NixInstallerInfo { inner: info, .. })
+            | InstallerImpl::Scoop(ScoopInstallerInfo { inner: info, .. })
+            | InstallerImpl::Chocolatey(ChocolateyInstallerInfo { inner: info, .. })
             | InstallerImpl::Npm(NpmInstallerInfo { inner: info, .. }),
         ) => {
             install_hint = Some(info.hint.clone());
@@ -343,6 +360,41 @@ fn add_manifest_artifact(
             description = Some("install via msi".to_owned());
             kind = cargo_dist_schema::ArtifactKind::Installer;
         }
+        ArtifactKind::Installer(InstallerImpl::Deb(..)) => {
+            install_hint = None;
+            description = Some("install via deb".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Rpm(..)) => {
+            install_hint = None;
+            description = Some("install via rpm".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Apk(..)) => {
+            install_hint = None;
+            description = Some("install via apk".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::AppImage(..)) => {
+            install_hint = None;
+            description = Some("install via AppImage".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Pkg(..)) => {
+            install_hint = None;
+            description = Some("install via pkg".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Dmg(..)) => {
+            install_hint = None;
+            description = Some("install via dmg".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Msix(..)) => {
+            install_hint = None;
+            description = Some("install via msix".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
         ArtifactKind::Checksum(_) => {
             install_hint = None;
             description = None;
@@ -363,6 +415,11 @@ fn add_manifest_artifact(
             description = None;
             kind = cargo_dist_schema::ArtifactKind::Updater;
         }
+        ArtifactKind::Zsync(_) => {
+            install_hint = None;
+            description = None;
+            kind = cargo_dist_schema::ArtifactKind::Zsync;
+        }
     };
 
     let checksum = artifact.checksum.map(|idx| dist.artifact(idx).id.clone());
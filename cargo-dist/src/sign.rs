@@ -0,0 +1,169 @@
+//! Signing and notarizing macOS artifacts
+//!
+//! All of these shell out to tools that only exist on macOS (`codesign`, `ditto`, `xcrun`).
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use temp_dir::TempDir;
+use tracing::info;
+
+use crate::errors::*;
+
+/// Sign a file with codesign, using the hardened runtime for executables
+pub fn codesign(identity: &str, path: &Utf8Path, executable: bool) -> DistResult<()> {
+    info!("signing {path}");
+    let mut cmd = Cmd::new("codesign", "sign for macOS");
+    cmd.stdout_to_stderr();
+    cmd.arg("--force").arg("--timestamp");
+    // Notarization requires the hardened runtime
+    if executable {
+        cmd.arg("--options").arg("runtime");
+    }
+    cmd.arg("--sign").arg(identity).arg(path);
+    cmd.run()?;
+    Ok(())
+}
+
+/// Send a zip, dmg or pkg to Apple's notary service, and wait for it to be accepted
+///
+/// The Apple ID to submit as is read from `APPLE_ID`, `APPLE_TEAM_ID` and
+/// `APPLE_APP_SPECIFIC_PASSWORD`.
+pub fn notarize(path: &Utf8Path) -> DistResult<()> {
+    submit(path, path)
+}
+
+/// The name of the keychain profile [`submit`][] stores notarization credentials under
+const NOTARY_PROFILE: &str = "cargo-dist";
+
+/// Submit a file to the notary service, on behalf of the given artifact
+///
+/// The credentials are stored in a temporary keychain first, so the submission (which
+/// can take minutes) doesn't have the password on its command line for all that time.
+fn submit(path: &Utf8Path, artifact: &Utf8Path) -> DistResult<()> {
+    info!("notarizing {artifact}");
+    let credential = |var: &str| {
+        std::env::var(var).map_err(|_| DistError::MissingNotarizationCredentials {
+            artifact: artifact.to_owned(),
+            var: var.to_owned(),
+        })
+    };
+    let apple_id = credential("APPLE_ID")?;
+    let team_id = credential("APPLE_TEAM_ID")?;
+    let password = credential("APPLE_APP_SPECIFIC_PASSWORD")?;
+
+    let temp = TempDir::new()?;
+    let keychain = Utf8PathBuf::try_from(temp.path().join("notarization.keychain-db"))?;
+    let mut cmd = Cmd::new("security", "create a keychain for notarization");
+    cmd.stdout_to_stderr();
+    cmd.arg("create-keychain").arg("-p").arg("").arg(&keychain);
+    cmd.run()?;
+
+    let mut cmd = Cmd::new("xcrun", "store notarization credentials");
+    cmd.stdout_to_stderr();
+    // The password is on the command line, so don't log it
+    cmd.log(None);
+    cmd.arg("notarytool")
+        .arg("store-credentials")
+        .arg(NOTARY_PROFILE)
+        .arg("--apple-id")
+        .arg(&apple_id)
+        .arg("--team-id")
+        .arg(&team_id)
+        .arg("--password")
+        .arg(&password)
+        .arg("--keychain")
+        .arg(&keychain);
+    let stored = cmd.run();
+
+    let submitted = stored.and_then(|_| {
+        let mut cmd = Cmd::new("xcrun", "notarize for macOS");
+        cmd.stdout_to_stderr();
+        cmd.arg("notarytool")
+            .arg("submit")
+            .arg(path)
+            .arg("--keychain-profile")
+            .arg(NOTARY_PROFILE)
+            .arg("--keychain")
+            .arg(&keychain)
+            .arg("--wait");
+        cmd.run()
+    });
+
+    // Don't leave the credentials lying around, whether or not that worked
+    let mut cmd = Cmd::new("security", "delete the notarization keychain");
+    cmd.stdout_to_stderr();
+    cmd.arg("delete-keychain").arg(&keychain);
+    let deleted = cmd.run();
+
+    submitted?;
+    deleted?;
+    Ok(())
+}
+
+/// Staple a notarization ticket to a dmg or pkg, so Gatekeeper can check it offline
+pub fn staple(path: &Utf8Path) -> DistResult<()> {
+    let mut cmd = Cmd::new("xcrun", "staple a notarization ticket");
+    cmd.stdout_to_stderr();
+    cmd.arg("stapler").arg("staple").arg(path);
+    cmd.run()?;
+    Ok(())
+}
+
+/// Notarize some signed binaries, given with the target each was built for
+///
+/// The notary service doesn't take bare executables, so this zips them up to submit them.
+/// Their tickets can't be stapled either; Gatekeeper looks them up online instead, which
+/// covers the binaries wherever they end up (archives, dmgs, pkgs...).
+pub fn notarize_binaries(binaries: &[(String, Utf8PathBuf)]) -> DistResult<()> {
+    let Some((_, first)) = binaries.first() else {
+        return Ok(());
+    };
+    let temp = TempDir::new()?;
+    let temp_path = Utf8PathBuf::try_from(temp.path().to_owned())?;
+    let stage = temp_path.join("binaries");
+    for (target, binary) in binaries {
+        // Binaries for different targets usually share a name
+        let dest = stage_path(&stage, target, binary);
+        LocalAsset::create_dir_all(dest.parent().unwrap())?;
+        LocalAsset::copy_named(binary, &dest)?;
+    }
+
+    // ditto keeps the signatures intact, unlike some zip implementations
+    let zip_path = temp_path.join("binaries.zip");
+    let mut cmd = Cmd::new("ditto", "zip binaries to notarize");
+    cmd.stdout_to_stderr();
+    cmd.arg("-c").arg("-k").arg(&stage).arg(&zip_path);
+    cmd.run()?;
+
+    submit(&zip_path, first)
+}
+
+/// Where a binary goes in the zip submitted by [`notarize_binaries`][]
+fn stage_path(stage: &Utf8Path, target: &str, binary: &Utf8Path) -> Utf8PathBuf {
+    stage.join(target).join(binary.file_name().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8Path;
+
+    use super::stage_path;
+
+    #[test]
+    fn staging() {
+        let stage = Utf8Path::new("/tmp/binaries");
+        let intel = stage_path(
+            stage,
+            "x86_64-apple-darwin",
+            Utf8Path::new("target/x86_64-apple-darwin/dist/axolotlsay"),
+        );
+        let arm = stage_path(
+            stage,
+            "aarch64-apple-darwin",
+            Utf8Path::new("target/aarch64-apple-darwin/dist/axolotlsay"),
+        );
+        assert_eq!(intel, "/tmp/binaries/x86_64-apple-darwin/axolotlsay");
+        assert_eq!(arm, "/tmp/binaries/aarch64-apple-darwin/axolotlsay");
+    }
+}
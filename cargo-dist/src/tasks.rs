@@ -63,20 +63,47 @@ use crate::announce::{self, AnnouncementTag};
 use crate::backend::ci::github::GithubCiInfo;
 use crate::backend::ci::CiInfo;
 use crate::backend::installer::UpdaterFragment;
-use crate::config::{DependencyKind, DirtyMode, ExtraArtifact, ProductionMode, SystemDependencies};
+use crate::config::{
+    ApkConfig, AppImageConfig, AptConfig, AurConfig, ChocolateyConfig, ChocolateyInstaller,
+    DependencyKind, DirtyMode, DmgConfig, ExtraArtifact, FlatpakConfig, LinkageConfig,
+    MacosSignConfig, MsiConfig, MsixConfig, NixConfig, PkgConfig, ProductionMode, RpmConfig,
+    SnapConfig, SystemDependencies, SystemDependency, SystemDependencyComplex, YumConfig,
+};
 use crate::{
     backend::{
         installer::{
-            homebrew::{to_class_case, HomebrewInstallerInfo},
+            apk::{apk_architecture, apk_version, ApkInstallerInfo},
+            appimage::{appimage_architecture, AppImageInstallerInfo},
+            aur::{aur_version, pacman_architecture, AurInstallerInfo, AurSource},
+            chocolatey::{
+                chocolatey_architecture, chocolatey_package_id, ChocolateyInstallerInfo,
+                ChocolateySource,
+            },
+            deb::{deb_architecture, deb_package_name, DebInstallerInfo},
+            dmg::DmgInstallerInfo,
+            flatpak::{flatpak_app_id, flatpak_architecture, FlatpakInstallerInfo, FlatpakSource},
+            homebrew::{
+                to_cask_token, to_class_case, CaskBundle, HomebrewCaskInfo, HomebrewInstallerInfo,
+            },
+            linux::package_version,
             msi::MsiInstallerInfo,
+            msix::{
+                msix_application_id, msix_architecture, msix_identity_name, msix_version,
+                MsixApplication, MsixInstallerInfo,
+            },
+            nix::{nix_system, spdx_ids, NixInstallerInfo, NixSource},
             npm::NpmInstallerInfo,
+            pkg::{pkg_identifier, PkgInstallerInfo},
+            rpm::{rpm_architecture, RpmInstallerInfo},
+            scoop::{scoop_architecture, ScoopInstallerInfo, ScoopSource},
+            snap::{snap_architecture, snap_name, SnapInstallerInfo, SnapSource},
             ExecutableZipFragment, InstallerImpl, InstallerInfo,
         },
         templates::Templates,
     },
     config::{
         self, ArtifactMode, ChecksumStyle, CiStyle, CompressionImpl, Config, DistMetadata,
-        HostingStyle, InstallPathStrategy, InstallerStyle, PublishStyle, ZipStyle,
+        HomebrewStyle, HostingStyle, InstallPathStrategy, InstallerStyle, PublishStyle, ZipStyle,
     },
     errors::{DistError, DistResult, Result},
 };
@@ -215,6 +242,8 @@ pub struct DistGraph {
     pub post_announce_jobs: Vec<String>,
     /// A GitHub repo to publish the Homebrew formula to
     pub tap: Option<String>,
+    /// A GitHub repo to publish the Scoop manifest to
+    pub bucket: Option<String>,
     /// Whether msvc targets should statically link the crt
     pub msvc_crt_static: bool,
     /// List of hosting providers to use
@@ -229,6 +258,18 @@ pub struct DistGraph {
     pub tag_namespace: Option<String>,
     /// Whether to install updaters alongside with binaries
     pub install_updater: bool,
+    /// Settings for the linkage checker
+    pub linkage: LinkageConfig,
+    /// Settings for rpm installers
+    pub rpm: RpmConfig,
+    /// Settings for the APT repository the "apt" publish job maintains
+    pub apt: AptConfig,
+    /// Settings for the YUM/DNF repository the "yum" publish job maintains
+    pub yum: YumConfig,
+    /// Settings for the Alpine repository the "apk" publish job maintains
+    pub apk: ApkConfig,
+    /// Settings for signing and notarizing macOS binaries
+    pub macos_sign: MacosSignConfig,
 }
 
 /// Info about artifacts should be hosted
@@ -537,6 +578,8 @@ pub enum ArtifactKind {
     ExtraArtifact(ExtraArtifactImpl),
     /// An updater executable
     Updater(UpdaterImpl),
+    /// A zsync file for delta updates of an AppImage
+    Zsync(ZsyncImpl),
 }
 
 /// An Archive containing binaries (aka ExecutableZip)
@@ -577,6 +620,10 @@ pub struct ExtraArtifactImpl {
 #[derive(Clone, Debug)]
 pub struct UpdaterImpl {}
 
+/// A zsync file for delta updates of an AppImage
+#[derive(Clone, Debug)]
+pub struct ZsyncImpl {}
+
 /// A logical release of an application that artifacts are grouped under
 #[derive(Clone, Debug)]
 pub struct Release {
@@ -631,6 +678,34 @@ pub struct Release {
     pub tap: Option<String>,
     /// Customize the name of the Homebrew formula
     pub formula: Option<String>,
+    /// Whether to publish a Homebrew formula or a cask
+    pub homebrew_style: HomebrewStyle,
+    /// GitHub repository to push the Scoop manifest to, if built
+    pub bucket: Option<String>,
+    /// The macOS bundle a Homebrew cask installs (a dmg or pkg installer), if any
+    pub cask_bundle: Option<InstallerStyle>,
+    /// Settings for the AUR package
+    pub aur: Option<AurConfig>,
+    /// Settings for AppImages
+    pub appimage: Option<AppImageConfig>,
+    /// Settings for Flatpaks
+    pub flatpak: Option<FlatpakConfig>,
+    /// Settings for snaps
+    pub snap: Option<SnapConfig>,
+    /// Settings for Nix flakes
+    pub nix: Option<NixConfig>,
+    /// Settings for macOS pkgs
+    pub pkg: Option<PkgConfig>,
+    /// Settings for macOS dmgs
+    pub dmg: Option<DmgConfig>,
+    /// Settings for Windows msis
+    pub msi: Option<MsiConfig>,
+    /// Settings for Windows MSIX packages
+    pub msix: Option<MsixConfig>,
+    /// Settings for Chocolatey packages
+    pub chocolatey: Option<ChocolateyConfig>,
+    /// What the Chocolatey package installs
+    pub chocolatey_installer: ChocolateyInstaller,
     /// Packages to install from a system package manager
     pub system_dependencies: SystemDependencies,
 }
@@ -770,6 +845,30 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             // Only the final value merged into a package_config matters
             formula: _,
             // Only the final value merged into a package_config matters
+            homebrew_style: _,
+            // Only the final value merged into a package_config matters
+            bucket: _,
+            // Only the final value merged into a package_config matters
+            aur: _,
+            // Only the final value merged into a package_config matters
+            appimage: _,
+            // Only the final value merged into a package_config matters
+            flatpak: _,
+            // Only the final value merged into a package_config matters
+            snap: _,
+            // Only the final value merged into a package_config matters
+            nix: _,
+            // Only the final value merged into a package_config matters
+            pkg: _,
+            // Only the final value merged into a package_config matters
+            dmg: _,
+            // Only the final value merged into a package_config matters
+            msi: _,
+            // Only the final value merged into a package_config matters
+            msix: _,
+            // Only the final value merged into a package_config matters
+            chocolatey: _,
+            // Only the final value merged into a package_config matters
             system_dependencies: _,
             // Only the final value merged into a package_config matters
             windows_archive: _,
@@ -807,6 +906,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             extra_artifacts,
             github_custom_runners: _,
             install_updater,
+            linkage,
+            rpm,
+            apt,
+            yum,
+            apk,
+            macos_sign,
         } = &workspace_metadata;
 
         let desired_cargo_dist_version = cargo_dist_version.clone();
@@ -814,6 +919,18 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         if desired_rust_toolchain.is_some() {
             warn!("rust-toolchain-version is deprecated, use rust-toolchain.toml if you want pinned toolchains");
         }
+        if let Some(MacosSignConfig {
+            identity: None,
+            notarize: Some(true),
+            ..
+        }) = macos_sign
+        {
+            return Err(DistError::NotarizeWithoutIdentity {
+                setting: "macos-sign.notarize".to_owned(),
+                what: "binaries".to_owned(),
+                identity: "macos-sign.identity".to_owned(),
+            });
+        }
         let merge_tasks = merge_tasks.unwrap_or(false);
         let fail_fast = fail_fast.unwrap_or(false);
         let create_release = create_release.unwrap_or(true);
@@ -973,6 +1090,11 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         };
         let systems = SortedMap::from_iter([(system_id.clone(), system)]);
 
+        let mut linkage = linkage.clone().unwrap_or_default();
+        if let Some(policy) = linkage.policy.clone() {
+            linkage.merge_policy_file(&policy)?;
+        }
+
         Ok(Self {
             inner: DistGraph {
                 system_id,
@@ -1003,6 +1125,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 ci: CiInfo::default(),
                 pr_run_mode: workspace_metadata.pr_run_mode.unwrap_or_default(),
                 tap: workspace_metadata.tap.clone(),
+                bucket: workspace_metadata.bucket.clone(),
                 plan_jobs,
                 local_artifacts_jobs,
                 global_artifacts_jobs,
@@ -1019,6 +1142,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     .clone()
                     .unwrap_or_default(),
                 install_updater: install_updater.unwrap_or_default(),
+                linkage,
+                rpm: rpm.clone().unwrap_or_default(),
+                apt: apt.clone().unwrap_or_default(),
+                yum: yum.clone().unwrap_or_default(),
+                apk: apk.clone().unwrap_or_default(),
+                macos_sign: macos_sign.clone().unwrap_or_default(),
             },
             manifest: DistManifest {
                 dist_version: Some(env!("CARGO_PKG_VERSION").to_owned()),
@@ -1073,6 +1202,36 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             .unwrap_or(InstallPathStrategy::CargoHome);
         let tap = package_config.tap.clone();
         let formula = package_config.formula.clone();
+        let homebrew_style = package_config.homebrew_style.unwrap_or_default();
+        let bucket = package_config.bucket.clone();
+        // Casks prefer dmgs, since installing those doesn't need root
+        let installers = package_config.installers.as_deref().unwrap_or_default();
+        let cask_bundle = [InstallerStyle::Dmg, InstallerStyle::Pkg]
+            .into_iter()
+            .find(|style| installers.contains(style));
+        let aur = package_config.aur.clone();
+        let appimage = package_config.appimage.clone();
+        let flatpak = package_config.flatpak.clone();
+        let snap = package_config.snap.clone();
+        let nix = package_config.nix.clone();
+        let pkg = package_config.pkg.clone();
+        let dmg = package_config.dmg.clone();
+        let msi = package_config.msi.clone();
+        let msix = package_config.msix.clone();
+        let chocolatey = package_config.chocolatey.clone();
+        // Wrapping the msi only works if we're building one
+        let mut chocolatey_installer = chocolatey
+            .as_ref()
+            .and_then(|chocolatey| chocolatey.installer)
+            .unwrap_or_default();
+        if chocolatey_installer == ChocolateyInstaller::Msi
+            && !installers.contains(&InstallerStyle::Msi)
+        {
+            if installers.contains(&InstallerStyle::Chocolatey) {
+                warn!("chocolatey.installer is \"msi\" but the msi installer isn't enabled for {app_name}, so the Chocolatey package will install the zip instead\n  consider adding \"msi\" to installers in Cargo.toml");
+            }
+            chocolatey_installer = ChocolateyInstaller::Zip;
+        }
 
         let windows_archive = package_config.windows_archive.unwrap_or(ZipStyle::Zip);
         let unix_archive = package_config
@@ -1132,6 +1291,20 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             install_path,
             tap,
             formula,
+            homebrew_style,
+            bucket,
+            cask_bundle,
+            aur,
+            appimage,
+            flatpak,
+            snap,
+            nix,
+            pkg,
+            dmg,
+            msi,
+            msix,
+            chocolatey,
+            chocolatey_installer,
             system_dependencies,
         });
         idx
@@ -1533,6 +1706,63 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         )
     }
 
+    /// Pick the archive each architecture's package should be made from
+    ///
+    /// `arch` maps a target to the package's name for its architecture, if it has one.
+    /// When several targets share an architecture, ones built against the `preferred` ABI
+    /// win over ones built against `fallback`, which win over anything else (e.g. glibc
+    /// builds over musl ones, or msvc builds over gnu ones).
+    ///
+    /// Each archive is the one its variant *would* make *if* it were built.
+    fn executable_zips_by_arch(
+        &self,
+        to_release: ReleaseIdx,
+        arch: impl Fn(&str) -> Option<&'static str>,
+        preferred: &str,
+        fallback: &str,
+    ) -> SortedMap<&'static str, (ReleaseVariantIdx, Artifact, ExecutableZipFragment)> {
+        let rank = |variant_idx: ReleaseVariantIdx| {
+            let target = &self.variant(variant_idx).target;
+            if target.contains(preferred) {
+                0
+            } else if target.contains(fallback) {
+                1
+            } else {
+                2
+            }
+        };
+        let mut picked = SortedMap::<&'static str, ReleaseVariantIdx>::new();
+        for &variant_idx in &self.release(to_release).variants {
+            let Some(arch) = arch(&self.variant(variant_idx).target) else {
+                continue;
+            };
+            if picked
+                .get(arch)
+                .map_or(true, |&other_idx| rank(variant_idx) < rank(other_idx))
+            {
+                picked.insert(arch, variant_idx);
+            }
+        }
+
+        picked
+            .into_iter()
+            .map(|(arch, variant_idx)| {
+                let (artifact, variant_binaries) =
+                    self.make_executable_zip_for_variant(to_release, variant_idx);
+                let fragment = ExecutableZipFragment {
+                    id: artifact.id.clone(),
+                    target_triples: artifact.target_triples.clone(),
+                    zip_style: artifact.archive.as_ref().unwrap().zip_style,
+                    binaries: variant_binaries
+                        .into_iter()
+                        .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
+                        .collect(),
+                };
+                (arch, (variant_idx, artifact, fragment))
+            })
+            .collect()
+    }
+
     /// Register that `for_artifact` requires `binary_idx` to actually be built for
     /// `for_variant`.
     ///
@@ -1618,7 +1848,20 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             InstallerStyle::Powershell => self.add_powershell_installer(to_release),
             InstallerStyle::Npm => self.add_npm_installer(to_release),
             InstallerStyle::Homebrew => self.add_homebrew_installer(to_release),
+            InstallerStyle::Scoop => self.add_scoop_installer(to_release),
             InstallerStyle::Msi => self.add_msi_installer(to_release)?,
+            InstallerStyle::Deb => self.add_deb_installer(to_release),
+            InstallerStyle::Rpm => self.add_rpm_installer(to_release),
+            InstallerStyle::Aur => self.add_aur_installer(to_release),
+            InstallerStyle::Apk => self.add_apk_installer(to_release),
+            InstallerStyle::AppImage => self.add_appimage_installer(to_release),
+            InstallerStyle::Flatpak => self.add_flatpak_installer(to_release),
+            InstallerStyle::Snap => self.add_snap_installer(to_release),
+            InstallerStyle::Nix => self.add_nix_installer(to_release),
+            InstallerStyle::Pkg => self.add_pkg_installer(to_release)?,
+            InstallerStyle::Dmg => self.add_dmg_installer(to_release)?,
+            InstallerStyle::Msix => self.add_msix_installer(to_release),
+            InstallerStyle::Chocolatey => self.add_chocolatey_installer(to_release),
         }
         Ok(())
     }
@@ -1822,6 +2065,10 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             return;
         }
         let release = self.release(to_release);
+        if release.homebrew_style == HomebrewStyle::Cask {
+            self.add_homebrew_cask(to_release);
+            return;
+        }
         let formula = if let Some(formula) = &release.formula {
             formula
         } else {
@@ -2063,6 +2310,8 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 homepage: app_homepage_url,
                 tap,
                 dependencies,
+                macos_dependencies: vec![],
+                linux_dependencies: vec![],
                 inner: InstallerInfo {
                     dest_path: artifact_path,
                     app_name: release.app_name.clone(),
@@ -2082,89 +2331,125 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         self.add_global_artifact(to_release, installer_artifact);
     }
 
-    fn add_powershell_installer(&mut self, to_release: ReleaseIdx) {
-        if !self.global_artifacts_enabled() {
-            return;
-        }
-
-        // Get the basic info about the installer
+    fn add_homebrew_cask(&mut self, to_release: ReleaseIdx) {
         let release = self.release(to_release);
-        let release_id = &release.id;
+        let Some(bundle_style) = release.cask_bundle else {
+            warn!("skipping Homebrew cask for {}: casks install a dmg or pkg\n  consider adding \"dmg\" to installers in Cargo.toml", release.app_name);
+            return;
+        };
         let Some(download_url) = self
             .manifest
-            .release_by_name(&release.app_name)
+            .release_by_name(&release.id)
             .and_then(|r| r.artifact_download_url())
         else {
-            warn!(
-                "skipping powershell installer: couldn't compute a URL to download artifacts from"
-            );
+            warn!("skipping Homebrew cask: couldn't compute a URL to download artifacts from");
             return;
         };
-        let artifact_name = format!("{release_id}-installer.ps1");
+        let token = to_cask_token(release.formula.as_ref().unwrap_or(&release.app_name));
+        let artifact_name = format!("{token}.cask.rb");
         let artifact_path = self.inner.dist_dir.join(&artifact_name);
-        let installer_url = format!("{download_url}/{artifact_name}");
-        let hint = format!(r#"powershell -c "irm {installer_url} | iex""#);
-        let desc = "Install prebuilt binaries via powershell script".to_owned();
 
-        // Gather up the bundles the installer supports
-        let mut artifacts = vec![];
-        let mut updaters = vec![];
-        let mut target_triples = SortedSet::new();
+        // If tap is specified, include that in the `brew install` message
+        let mut install_target = token.clone();
+        if let Some(tap) = &release.tap {
+            install_target = format!("{tap}/{install_target}");
+        }
+        let hint = format!("brew install --cask {install_target}");
+        let desc = "Install prebuilt binaries via Homebrew".to_owned();
+
+        // Find the bundles the cask can install; these are named after their variant
+        let bundle_ext = match bundle_style {
+            InstallerStyle::Pkg => "pkg",
+            _ => "dmg",
+        };
+        let mut arm64_macos = None;
+        let mut x86_64_macos = None;
+        let mut binaries = vec![];
         for &variant_idx in &release.variants {
             let variant = self.variant(variant_idx);
-            let target = &variant.target;
-            if !target.contains("windows") {
-                continue;
-            }
-            // Compute the artifact zip this variant *would* make *if* it were built
-            // FIXME: this is a kind of hacky workaround for the fact that we don't have a good
-            // way to add artifacts to the graph and then say "ok but don't build it".
-            let (artifact, binaries) =
-                self.make_executable_zip_for_variant(to_release, variant_idx);
-            target_triples.insert(target.clone());
-            artifacts.push(ExecutableZipFragment {
-                id: artifact.id,
-                target_triples: artifact.target_triples,
-                zip_style: artifact.archive.as_ref().unwrap().zip_style,
-                binaries: binaries
-                    .into_iter()
-                    .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
-                    .collect(),
-            });
-
-            // Create the "pretend" updaters similar to the above for exezips
-            if self.inner.install_updater {
-                let artifact = self.make_updater_for_variant(variant_idx);
-                updaters.push(UpdaterFragment {
-                    id: artifact.id.to_owned(),
-                    target_triple: variant.target.to_owned(),
-                    binary: artifact.id.to_owned(),
-                })
+            let bundle = CaskBundle {
+                id: format!("{}.{bundle_ext}", variant.id),
+                target: variant.target.clone(),
+                sha256: None,
+            };
+            match variant.target.as_str() {
+                "aarch64-apple-darwin" => arm64_macos = Some(bundle),
+                "x86_64-apple-darwin" => x86_64_macos = Some(bundle),
+                _ => continue,
             }
+            binaries = variant
+                .binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
         }
-        if artifacts.is_empty() {
-            warn!("skipping powershell installer: not building any supported platforms (use --artifacts=global)");
-            return;
+        // Like formulas, let arm64 Macs fall back to running the x64 build under Rosetta 2
+        let (arm64_macos, x86_64_macos, arm64_only) = match (arm64_macos, x86_64_macos) {
+            (Some(arm64), Some(x86_64)) => (arm64, x86_64, false),
+            (None, Some(x86_64)) => (x86_64.clone(), x86_64, false),
+            (Some(arm64), None) => (arm64.clone(), arm64, true),
+            (None, None) => {
+                warn!("skipping Homebrew cask: not building any macOS platforms (use --artifacts=global)");
+                return;
+            }
         };
+        let target_triples =
+            SortedSet::from_iter([arm64_macos.target.clone(), x86_64_macos.target.clone()]);
+
+        let pkg_identifier = if bundle_style == InstallerStyle::Pkg {
+            let Some(identifier) = self.release_pkg_identifier(to_release) else {
+                warn!("skipping Homebrew cask: couldn't determine the pkg's identifier to uninstall it by\n  consider setting pkg.identifier in Cargo.toml");
+                return;
+            };
+            Some(identifier)
+        } else {
+            None
+        };
+
+        let release = self.release(to_release);
+        let tap = release.tap.clone();
+        if tap.is_some() && !self.inner.publish_jobs.contains(&PublishStyle::Homebrew) {
+            warn!("A Homebrew tap was specified but the Homebrew publish job is disabled\n  consider adding \"homebrew\" to publish-jobs in Cargo.toml");
+        }
+        if self.inner.publish_jobs.contains(&PublishStyle::Homebrew) && tap.is_none() {
+            warn!("The Homebrew publish job is enabled but no tap was specified\n  consider setting the tap field in Cargo.toml");
+        }
 
         let installer_artifact = Artifact {
             id: artifact_name,
             target_triples: target_triples.into_iter().collect(),
+            archive: None,
             file_path: artifact_path.clone(),
             required_binaries: FastMap::new(),
-            archive: None,
             checksum: None,
-            kind: ArtifactKind::Installer(InstallerImpl::Powershell(InstallerInfo {
-                dest_path: artifact_path,
-                app_name: release.app_name.clone(),
-                app_version: release.version.to_string(),
-                install_path: release.install_path.clone().into_jinja(),
-                base_url: download_url.to_owned(),
-                artifacts,
-                updaters,
-                hint,
-                desc,
-                receipt: InstallReceipt::from_metadata(&self.inner, release),
+            kind: ArtifactKind::Installer(InstallerImpl::HomebrewCask(HomebrewCaskInfo {
+                token,
+                name: release.app_name.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                desc: release.app_desc.clone(),
+                tap,
+                release_id: release.id.clone(),
+                bundle: bundle_ext.to_owned(),
+                arm64_macos,
+                x86_64_macos,
+                arm64_only,
+                binaries,
+                pkg_identifier,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts: vec![],
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
             })),
             is_global: true,
         };
@@ -2172,242 +2457,1914 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         self.add_global_artifact(to_release, installer_artifact);
     }
 
-    fn add_npm_installer(&mut self, to_release: ReleaseIdx) {
+    fn add_aur_installer(&mut self, to_release: ReleaseIdx) {
         if !self.global_artifacts_enabled() {
             return;
         }
         let release = self.release(to_release);
-        let release_id = &release.id;
+        let config = release.aur.clone().unwrap_or_default();
+        let package = config
+            .package
+            .unwrap_or_else(|| format!("{}-bin", release.app_name.to_lowercase()));
         let Some(download_url) = self
             .manifest
-            .release_by_name(&release.app_name)
+            .release_by_name(&release.id)
             .and_then(|r| r.artifact_download_url())
         else {
-            warn!("skipping npm installer: couldn't compute a URL to download artifacts from");
+            warn!("skipping AUR PKGBUILD: couldn't compute a URL to download artifacts from");
             return;
         };
 
-        if release.bins.len() > 1 {
-            warn!("skipping npm installer: packages with multiple binaries are unsupported\n  let us know if you have a use for this, and what should happen!");
+        let artifact_name = format!("{package}.PKGBUILD");
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let hint = format!("yay -S {package}");
+        let desc = "Install prebuilt binaries from the AUR".to_owned();
+
+        // Pick an archive for each architecture, preferring glibc builds over musl ones
+        let linux_arch = |target: &str| {
+            if target.contains("linux") {
+                pacman_architecture(target)
+            } else {
+                None
+            }
+        };
+        let mut sources = SortedMap::<&str, AurSource>::new();
+        let mut binaries = vec![];
+        for (arch, (variant_idx, artifact, fragment)) in
+            self.executable_zips_by_arch(to_release, linux_arch, "gnu", "musl")
+        {
+            let root = artifact
+                .archive
+                .unwrap()
+                .with_root
+                .map(|root| root.to_string());
+            binaries.clone_from(&fragment.binaries);
+            // Rust binaries built against glibc also need libgcc_s
+            let depends = if self.variant(variant_idx).target.contains("gnu") {
+                vec!["gcc-libs".to_owned(), "glibc".to_owned()]
+            } else {
+                vec![]
+            };
+            sources.insert(
+                arch,
+                AurSource {
+                    arch: arch.to_owned(),
+                    artifact: fragment,
+                    root,
+                    sha256: None,
+                    depends,
+                },
+            );
+        }
+        if sources.is_empty() {
+            warn!("skipping AUR PKGBUILD: not building any supported platforms (use --artifacts=global)");
             return;
         }
-        let bin = release.bins[0].1.clone();
 
-        let npm_package_name = if let Some(scope) = &release.npm_scope {
-            format!("{scope}/{}", release.app_name)
-        } else {
-            release.app_name.clone()
+        let mut docs = vec![];
+        let mut licenses = vec![];
+        for (kind, path) in &release.static_assets {
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            match kind {
+                StaticAssetKind::Readme | StaticAssetKind::Changelog => {
+                    docs.push(file_name.to_owned())
+                }
+                StaticAssetKind::License => licenses.push(file_name.to_owned()),
+                StaticAssetKind::Other => {}
+            }
+        }
+
+        let sources = sources.into_values().collect::<Vec<_>>();
+        let target_triples = sources
+            .iter()
+            .flat_map(|source| source.artifact.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .iter()
+            .map(|source| source.artifact.clone())
+            .collect();
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: None,
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Aur(AurInstallerInfo {
+                package,
+                pkgver: aur_version(&release.version),
+                maintainer: release.app_authors.first().cloned(),
+                desc: release.app_desc.clone(),
+                license: release.app_license.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                binaries,
+                licenses,
+                docs,
+                sources,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
         };
-        let npm_package_version = release.version.to_string();
-        let npm_package_desc = release.app_desc.clone();
-        let npm_package_authors = release.app_authors.clone();
-        let npm_package_license = release.app_license.clone();
-        let npm_package_repository_url = release.app_repository_url.clone();
-        let npm_package_homepage_url = release.app_homepage_url.clone();
-        let npm_package_keywords = release.app_keywords.clone();
 
-        let static_assets = release.static_assets.clone();
-        let dir_name = format!("{release_id}-npm-package");
-        let dir_path = self.inner.dist_dir.join(&dir_name);
-        let zip_style = ZipStyle::Tar(CompressionImpl::Gzip);
-        let zip_ext = zip_style.ext();
-        let artifact_name = format!("{dir_name}{zip_ext}");
-        let artifact_path = self.inner.dist_dir.join(&artifact_name);
-        // let installer_url = format!("{download_url}/{artifact_name}");
-        let hint = format!("npm install {npm_package_name}@{npm_package_version}");
-        let desc = "Install prebuilt binaries into your npm project".to_owned();
+        self.add_global_artifact(to_release, installer_artifact);
+    }
 
-        // See comments above
-        const X64_MACOS: &str = "x86_64-apple-darwin";
-        const ARM64_MACOS: &str = "aarch64-apple-darwin";
-        const ARM64_GNU: &str = "aarch64-unknown-linux-gnu";
-        const ARM64_MUSL: &str = "aarch64-unknown-linux-musl";
-        const X64_GNU: &str = "x86_64-unknown-linux-gnu";
-        const X64_MUSL: &str = "x86_64-unknown-linux-musl";
-        const X64_MUSL_STATIC: &str = "x86_64-unknown-linux-musl-static";
-        const X64_MUSL_DYNAMIC: &str = "x86_64-unknown-linux-musl-dynamic";
-        const ARM64_MUSL_STATIC: &str = "aarch64-unknown-linux-musl-static";
-        const ARM64_MUSL_DYNAMIC: &str = "aarch64-unknown-linux-musl-dynamic";
+    fn add_flatpak_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let config = release.flatpak.clone().unwrap_or_default();
+        let Some(icon) = config.icon else {
+            warn!("skipping Flatpak manifest for {}, as it needs an icon\n  consider setting flatpak.icon in Cargo.toml", release.app_name);
+            return;
+        };
+        let github_owner = self
+            .inner
+            .hosting
+            .as_ref()
+            .filter(|hosting| hosting.source_host == "github")
+            .map(|hosting| hosting.owner.clone());
+        let Some(app_id) = config
+            .app_id
+            .or_else(|| github_owner.map(|owner| flatpak_app_id(&owner, &release.app_name)))
+        else {
+            warn!("skipping Flatpak manifest for {}, as it needs an app id\n  consider setting flatpak.app-id in Cargo.toml", release.app_name);
+            return;
+        };
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.id)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping Flatpak manifest: couldn't compute a URL to download artifacts from");
+            return;
+        };
 
-        let mut has_x64_apple = false;
+        let artifact_name = format!("{}-flathub.tar.gz", release.app_name);
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let dir_name = format!("{}_flathub", release.app_name);
+        let dir_path = self.inner.dist_dir.join(dir_name);
+        let hint = format!("flatpak install flathub {app_id}");
+        let desc = "Install from Flathub".to_owned();
+
+        // Pick an archive for each architecture, preferring glibc builds over musl ones
+        let linux_arch = |target: &str| {
+            if target.contains("linux") {
+                flatpak_architecture(target)
+            } else {
+                None
+            }
+        };
+        let mut sources = SortedMap::<&str, FlatpakSource>::new();
+        let mut binaries = vec![];
+        for (arch, (_, artifact, fragment)) in
+            self.executable_zips_by_arch(to_release, linux_arch, "gnu", "musl")
+        {
+            let strip_components = u32::from(artifact.archive.unwrap().with_root.is_some());
+            binaries.clone_from(&fragment.binaries);
+            sources.insert(
+                arch,
+                FlatpakSource {
+                    arch: arch.to_owned(),
+                    artifact: fragment,
+                    strip_components,
+                    sha256: None,
+                },
+            );
+        }
+        if sources.is_empty() {
+            warn!("skipping Flatpak manifest: not building any supported platforms (use --artifacts=global)");
+            return;
+        }
+
+        let sources = sources.into_values().collect::<Vec<_>>();
+        let target_triples = sources
+            .iter()
+            .flat_map(|source| source.artifact.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .iter()
+            .map(|source| source.artifact.clone())
+            .collect();
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            // Flathub wants a repo named after the app id, so that's the root
+            archive: Some(Archive {
+                with_root: Some(app_id.clone().into()),
+                dir_path: dir_path.clone(),
+                zip_style: ZipStyle::Tar(CompressionImpl::Gzip),
+                static_assets: vec![],
+            }),
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Flatpak(FlatpakInstallerInfo {
+                app_id,
+                layout_dir: dir_path,
+                runtime: config
+                    .runtime
+                    .unwrap_or_else(|| "org.freedesktop.Platform".to_owned()),
+                runtime_version: config.runtime_version.unwrap_or_else(|| "23.08".to_owned()),
+                sdk: config
+                    .sdk
+                    .unwrap_or_else(|| "org.freedesktop.Sdk".to_owned()),
+                finish_args: config.finish_args.unwrap_or_default(),
+                binaries,
+                sources,
+                icon,
+                categories: config
+                    .categories
+                    .unwrap_or_else(|| vec!["Utility".to_owned()]),
+                terminal: config.terminal.unwrap_or(true),
+                bundle: config.bundle.unwrap_or(false),
+                desc: release.app_desc.clone(),
+                license: release.app_license.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                developer: release.app_authors.first().cloned(),
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_snap_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let config = release.snap.clone().unwrap_or_default();
+        let name = config.name.unwrap_or_else(|| snap_name(&release.app_name));
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.id)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping snapcraft.yaml: couldn't compute a URL to download artifacts from");
+            return;
+        };
+
+        let confinement = config.confinement.unwrap_or_else(|| "strict".to_owned());
+        let artifact_name = format!("{name}.snapcraft.yaml");
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let hint = if confinement == "classic" {
+            format!("snap install {name} --classic")
+        } else {
+            format!("snap install {name}")
+        };
+        let desc = "Install from the Snap Store".to_owned();
+
+        // Pick an archive for each architecture, preferring glibc builds over musl ones
+        let linux_arch = |target: &str| {
+            if target.contains("linux") {
+                snap_architecture(target)
+            } else {
+                None
+            }
+        };
+        let mut sources = SortedMap::<&str, SnapSource>::new();
+        let mut binaries = vec![];
+        for (arch, (_, _, fragment)) in
+            self.executable_zips_by_arch(to_release, linux_arch, "gnu", "musl")
+        {
+            binaries.clone_from(&fragment.binaries);
+            sources.insert(
+                arch,
+                SnapSource {
+                    arch: arch.to_owned(),
+                    artifact: fragment,
+                },
+            );
+        }
+        if sources.is_empty() {
+            warn!("skipping snapcraft.yaml: not building any supported platforms (use --artifacts=global)");
+            return;
+        }
+
+        let sources = sources.into_values().collect::<Vec<_>>();
+        let target_triples = sources
+            .iter()
+            .flat_map(|source| source.artifact.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .iter()
+            .map(|source| source.artifact.clone())
+            .collect();
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: None,
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Snap(SnapInstallerInfo {
+                name,
+                base: config.base.unwrap_or_else(|| "core22".to_owned()),
+                grade: config.grade.unwrap_or_else(|| "stable".to_owned()),
+                confinement,
+                plugs: config
+                    .plugs
+                    .unwrap_or_else(|| vec!["home".to_owned(), "network".to_owned()]),
+                channel: config.channel.unwrap_or_else(|| "stable".to_owned()),
+                binaries,
+                sources,
+                desc: release.app_desc.clone(),
+                license: release.app_license.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_scoop_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.id)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping Scoop manifest: couldn't compute a URL to download artifacts from");
+            return;
+        };
+        let bucket = release.bucket.clone();
+        if bucket.is_some() && !self.inner.publish_jobs.contains(&PublishStyle::Scoop) {
+            warn!("A Scoop bucket was specified but the Scoop publish job is disabled\n  consider adding \"scoop\" to publish-jobs in Cargo.toml");
+        }
+        if self.inner.publish_jobs.contains(&PublishStyle::Scoop) && bucket.is_none() {
+            warn!("The Scoop publish job is enabled but no bucket was specified\n  consider setting the bucket field in Cargo.toml");
+        }
+
+        let artifact_name = format!("{}.scoop.json", release.app_name);
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        // Users add the bucket under its repository's name, and can install straight
+        // from the release without one
+        let hint = if let Some(bucket) = &bucket {
+            let bucket_name = bucket.rsplit('/').next().unwrap_or(bucket);
+            format!(
+                "scoop bucket add {bucket_name} https://github.com/{bucket}; scoop install {bucket_name}/{}",
+                release.app_name
+            )
+        } else {
+            format!("scoop install {download_url}/{artifact_name}")
+        };
+        let desc = "Install prebuilt binaries via Scoop".to_owned();
+        let github_repo_url = self
+            .inner
+            .hosting
+            .as_ref()
+            .filter(|hosting| hosting.source_host == "github")
+            .map(|hosting| format!("https://github.com/{}/{}", hosting.owner, hosting.project));
+
+        // Pick an archive for each architecture, preferring msvc builds over gnu ones
+        let mut sources = SortedMap::<&str, ScoopSource>::new();
+        let mut binaries = vec![];
+        for (arch, (_, artifact, fragment)) in
+            self.executable_zips_by_arch(to_release, scoop_architecture, "msvc", "gnu")
+        {
+            let root = artifact
+                .archive
+                .unwrap()
+                .with_root
+                .map(|root| root.to_string());
+            binaries.clone_from(&fragment.binaries);
+            sources.insert(
+                arch,
+                ScoopSource {
+                    arch: arch.to_owned(),
+                    artifact: fragment,
+                    root,
+                },
+            );
+        }
+        if sources.is_empty() {
+            warn!("skipping Scoop manifest: not building any supported platforms (use --artifacts=global)");
+            return;
+        }
+
+        let sources = sources.into_values().collect::<Vec<_>>();
+        let target_triples = sources
+            .iter()
+            .flat_map(|source| source.artifact.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .iter()
+            .map(|source| source.artifact.clone())
+            .collect();
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: None,
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Scoop(ScoopInstallerInfo {
+                desc: release.app_desc.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                license: release.app_license.clone(),
+                github_repo_url,
+                binaries,
+                sources,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_chocolatey_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.id)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping Chocolatey package: couldn't compute a URL to download artifacts from");
+            return;
+        };
+        let installer = release.chocolatey_installer;
+        if installer == ChocolateyInstaller::Zip && release.windows_archive != ZipStyle::Zip {
+            warn!("skipping Chocolatey package: Chocolatey can only install .zip archives\n  consider setting windows-archive = \".zip\" in Cargo.toml");
+            return;
+        }
+
+        let config = release.chocolatey.clone().unwrap_or_default();
+        let package_id = config
+            .package_id
+            .unwrap_or_else(|| chocolatey_package_id(&release.app_name));
+        let source = config
+            .source
+            .unwrap_or_else(|| "https://push.chocolatey.org/".to_owned());
+        let version = release.version.to_string();
+        let dir_name = format!("{package_id}_chocolatey");
+        let dir_path = self.inner.dist_dir.join(dir_name);
+        let artifact_name = format!("{package_id}.{version}.nupkg");
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let hint = format!("choco install {package_id}");
+        let desc = "Install prebuilt binaries via Chocolatey".to_owned();
+
+        // Pick a download for each architecture, preferring msvc builds over gnu ones
+        let mut sources = SortedMap::<&str, (ChocolateySource, ExecutableZipFragment)>::new();
+        for (arch, (variant_idx, _, fragment)) in
+            self.executable_zips_by_arch(to_release, chocolatey_architecture, "msvc", "gnu")
+        {
+            let id = match installer {
+                ChocolateyInstaller::Zip => fragment.id.clone(),
+                ChocolateyInstaller::Msi => format!("{}.msi", self.variant(variant_idx).id),
+            };
+            sources.insert(arch, (ChocolateySource { id, sha256: None }, fragment));
+        }
+        if sources.is_empty() {
+            warn!("skipping Chocolatey package: not building any supported platforms (use --artifacts=global)");
+            return;
+        }
+
+        let target_triples = sources
+            .values()
+            .flat_map(|(_, fragment)| fragment.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .values()
+            .map(|(_, fragment)| fragment.clone())
+            .collect();
+        let x86 = sources.get("x86").map(|(source, _)| source.clone());
+        let x64 = sources.get("x64").map(|(source, _)| source.clone());
+
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: Some(Archive {
+                with_root: None,
+                dir_path: dir_path.clone(),
+                zip_style: ZipStyle::Zip,
+                static_assets: vec![],
+            }),
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Chocolatey(ChocolateyInstallerInfo {
+                package_id,
+                package_dir: dir_path,
+                title: release.app_name.clone(),
+                authors: if release.app_authors.is_empty() {
+                    release.app_name.clone()
+                } else {
+                    release.app_authors.join(", ")
+                },
+                desc: release
+                    .app_desc
+                    .clone()
+                    .unwrap_or_else(|| release.app_name.clone()),
+                homepage: release.app_homepage_url.clone(),
+                repository: release.app_repository_url.clone(),
+                tags: release
+                    .app_keywords
+                    .as_ref()
+                    .filter(|keywords| !keywords.is_empty())
+                    // Tags are space-separated, so they can't contain spaces themselves
+                    .map(|keywords| {
+                        keywords
+                            .iter()
+                            .map(|keyword| keyword.replace(' ', "-"))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    }),
+                installer,
+                software_name: format!("{}*", release.app_name),
+                x86,
+                x64,
+                source,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: version,
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_nix_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let config = release.nix.clone().unwrap_or_default();
+        let flake_dir = config
+            .dir
+            .map(|dir| dir.trim_matches('/').to_owned())
+            .filter(|dir| !dir.is_empty() && dir != ".")
+            .unwrap_or_else(|| ".".to_owned());
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.id)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping Nix flake: couldn't compute a URL to download artifacts from");
+            return;
+        };
+        // Work out where users will find the flake once it's published
+        let flake_ref = if let Some(hosting) = self
+            .inner
+            .hosting
+            .as_ref()
+            .filter(|hosting| hosting.source_host == "github")
+        {
+            format!("github:{}/{}", hosting.owner, hosting.project)
+        } else if let Some(repo_url) = &release.app_repository_url {
+            format!("git+{repo_url}")
+        } else {
+            warn!("skipping Nix flake for {}, as it needs a repository to be published to\n  consider setting repository in Cargo.toml", release.app_name);
+            return;
+        };
+        let flake_ref = if flake_dir == "." {
+            flake_ref
+        } else {
+            format!("{flake_ref}?dir={flake_dir}")
+        };
+
+        let artifact_name = format!("{}.flake.nix", release.app_name);
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let hint = format!("nix profile install {flake_ref}");
+        let desc = "Install prebuilt binaries with Nix".to_owned();
+
+        // Pick an archive for each system, preferring glibc builds over musl ones
+        let mut sources = SortedMap::<&str, NixSource>::new();
+        let mut binaries = vec![];
+        for (system, (_, artifact, fragment)) in
+            self.executable_zips_by_arch(to_release, nix_system, "gnu", "musl")
+        {
+            let root = artifact
+                .archive
+                .unwrap()
+                .with_root
+                .map(|root| root.to_string());
+            binaries.clone_from(&fragment.binaries);
+            sources.insert(
+                system,
+                NixSource {
+                    system: system.to_owned(),
+                    artifact: fragment,
+                    root,
+                    sha256: None,
+                },
+            );
+        }
+        if sources.is_empty() {
+            warn!(
+                "skipping Nix flake: not building any supported platforms (use --artifacts=global)"
+            );
+            return;
+        }
+
+        let sources = sources.into_values().collect::<Vec<_>>();
+        let target_triples = sources
+            .iter()
+            .flat_map(|source| source.artifact.target_triples.clone())
+            .collect();
+        let artifacts = sources
+            .iter()
+            .map(|source| source.artifact.clone())
+            .collect();
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: None,
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Nix(NixInstallerInfo {
+                pname: release.app_name.clone(),
+                nixpkgs: config
+                    .nixpkgs
+                    .unwrap_or_else(|| "github:NixOS/nixpkgs/nixos-unstable".to_owned()),
+                flake_dir,
+                desc: release.app_desc.clone(),
+                homepage: release
+                    .app_homepage_url
+                    .clone()
+                    .or_else(|| release.app_repository_url.clone()),
+                licenses: release
+                    .app_license
+                    .as_deref()
+                    .map(spdx_ids)
+                    .unwrap_or_default(),
+                binaries,
+                sources,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_powershell_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+
+        // Get the basic info about the installer
+        let release = self.release(to_release);
+        let release_id = &release.id;
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.app_name)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!(
+                "skipping powershell installer: couldn't compute a URL to download artifacts from"
+            );
+            return;
+        };
+        let artifact_name = format!("{release_id}-installer.ps1");
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        let installer_url = format!("{download_url}/{artifact_name}");
+        let hint = format!(r#"powershell -c "irm {installer_url} | iex""#);
+        let desc = "Install prebuilt binaries via powershell script".to_owned();
+
+        // Gather up the bundles the installer supports
+        let mut artifacts = vec![];
+        let mut updaters = vec![];
+        let mut target_triples = SortedSet::new();
+        for &variant_idx in &release.variants {
+            let variant = self.variant(variant_idx);
+            let target = &variant.target;
+            if !target.contains("windows") {
+                continue;
+            }
+            // Compute the artifact zip this variant *would* make *if* it were built
+            // FIXME: this is a kind of hacky workaround for the fact that we don't have a good
+            // way to add artifacts to the graph and then say "ok but don't build it".
+            let (artifact, binaries) =
+                self.make_executable_zip_for_variant(to_release, variant_idx);
+            target_triples.insert(target.clone());
+            artifacts.push(ExecutableZipFragment {
+                id: artifact.id,
+                target_triples: artifact.target_triples,
+                zip_style: artifact.archive.as_ref().unwrap().zip_style,
+                binaries: binaries
+                    .into_iter()
+                    .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
+                    .collect(),
+            });
+
+            // Create the "pretend" updaters similar to the above for exezips
+            if self.inner.install_updater {
+                let artifact = self.make_updater_for_variant(variant_idx);
+                updaters.push(UpdaterFragment {
+                    id: artifact.id.to_owned(),
+                    target_triple: variant.target.to_owned(),
+                    binary: artifact.id.to_owned(),
+                })
+            }
+        }
+        if artifacts.is_empty() {
+            warn!("skipping powershell installer: not building any supported platforms (use --artifacts=global)");
+            return;
+        };
+
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples: target_triples.into_iter().collect(),
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            archive: None,
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Powershell(InstallerInfo {
+                dest_path: artifact_path,
+                app_name: release.app_name.clone(),
+                app_version: release.version.to_string(),
+                install_path: release.install_path.clone().into_jinja(),
+                base_url: download_url.to_owned(),
+                artifacts,
+                updaters,
+                hint,
+                desc,
+                receipt: InstallReceipt::from_metadata(&self.inner, release),
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_npm_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(to_release);
+        let release_id = &release.id;
+        let Some(download_url) = self
+            .manifest
+            .release_by_name(&release.app_name)
+            .and_then(|r| r.artifact_download_url())
+        else {
+            warn!("skipping npm installer: couldn't compute a URL to download artifacts from");
+            return;
+        };
+
+        if release.bins.len() > 1 {
+            warn!("skipping npm installer: packages with multiple binaries are unsupported\n  let us know if you have a use for this, and what should happen!");
+            return;
+        }
+        let bin = release.bins[0].1.clone();
+
+        let npm_package_name = if let Some(scope) = &release.npm_scope {
+            format!("{scope}/{}", release.app_name)
+        } else {
+            release.app_name.clone()
+        };
+        let npm_package_version = release.version.to_string();
+        let npm_package_desc = release.app_desc.clone();
+        let npm_package_authors = release.app_authors.clone();
+        let npm_package_license = release.app_license.clone();
+        let npm_package_repository_url = release.app_repository_url.clone();
+        let npm_package_homepage_url = release.app_homepage_url.clone();
+        let npm_package_keywords = release.app_keywords.clone();
+
+        let static_assets = release.static_assets.clone();
+        let dir_name = format!("{release_id}-npm-package");
+        let dir_path = self.inner.dist_dir.join(&dir_name);
+        let zip_style = ZipStyle::Tar(CompressionImpl::Gzip);
+        let zip_ext = zip_style.ext();
+        let artifact_name = format!("{dir_name}{zip_ext}");
+        let artifact_path = self.inner.dist_dir.join(&artifact_name);
+        // let installer_url = format!("{download_url}/{artifact_name}");
+        let hint = format!("npm install {npm_package_name}@{npm_package_version}");
+        let desc = "Install prebuilt binaries into your npm project".to_owned();
+
+        // See comments above
+        const X64_MACOS: &str = "x86_64-apple-darwin";
+        const ARM64_MACOS: &str = "aarch64-apple-darwin";
+        const ARM64_GNU: &str = "aarch64-unknown-linux-gnu";
+        const ARM64_MUSL: &str = "aarch64-unknown-linux-musl";
+        const X64_GNU: &str = "x86_64-unknown-linux-gnu";
+        const X64_MUSL: &str = "x86_64-unknown-linux-musl";
+        const X64_MUSL_STATIC: &str = "x86_64-unknown-linux-musl-static";
+        const X64_MUSL_DYNAMIC: &str = "x86_64-unknown-linux-musl-dynamic";
+        const ARM64_MUSL_STATIC: &str = "aarch64-unknown-linux-musl-static";
+        const ARM64_MUSL_DYNAMIC: &str = "aarch64-unknown-linux-musl-dynamic";
+
+        let mut has_x64_apple = false;
         let mut has_arm_apple = false;
         let mut has_x86_gnu_linux = false;
         let mut has_arm_gnu_linux = false;
         let mut has_x86_static_musl_linux = false;
         let mut has_arm_static_musl_linux = false;
 
-        // Currently always false, someday these builds will exist
-        let has_x86_dynamic_musl_linux = false;
-        let has_arm_dynamic_musl_linux = false;
+        // Currently always false, someday these builds will exist
+        let has_x86_dynamic_musl_linux = false;
+        let has_arm_dynamic_musl_linux = false;
+
+        for &variant_idx in &release.variants {
+            let variant = self.variant(variant_idx);
+            let target = &variant.target;
+            if target == X64_MACOS {
+                has_x64_apple = true;
+            }
+            if target == ARM64_MACOS {
+                has_arm_apple = true;
+            }
+            if target == X64_GNU {
+                has_x86_gnu_linux = true;
+            }
+            if target == ARM64_GNU {
+                has_arm_gnu_linux = true;
+            }
+            if target == X64_MUSL {
+                has_x86_static_musl_linux = true;
+            }
+            if target == ARM64_MUSL {
+                has_arm_static_musl_linux = true;
+            }
+        }
+
+        let do_rosetta_fallback = has_x64_apple && !has_arm_apple;
+        let do_x86_gnu_to_musl_fallback = !has_x86_gnu_linux && has_x86_static_musl_linux;
+        let do_arm_gnu_to_musl_fallback = !has_arm_gnu_linux && has_arm_static_musl_linux;
+        let do_x86_musl_to_musl_fallback = has_x86_static_musl_linux && !has_x86_dynamic_musl_linux;
+        let do_arm_musl_to_musl_fallback = has_arm_static_musl_linux && !has_arm_dynamic_musl_linux;
+
+        // Gather up the bundles the installer supports
+        let mut artifacts = vec![];
+        let mut target_triples = SortedSet::new();
+
+        let mut has_sketchy_archives = false;
+        for &variant_idx in &release.variants {
+            let variant = self.variant(variant_idx);
+            let target = &variant.target;
+
+            // Compute the artifact zip this variant *would* make *if* it were built
+            // FIXME: this is a kind of hacky workaround for the fact that we don't have a good
+            // way to add artifacts to the graph and then say "ok but don't build it".
+            let (artifact, binaries) =
+                self.make_executable_zip_for_variant(to_release, variant_idx);
+
+            target_triples.insert(target.clone());
+
+            let variant_zip_style = artifact.archive.as_ref().unwrap().zip_style;
+            if variant_zip_style != ZipStyle::Tar(CompressionImpl::Gzip) {
+                has_sketchy_archives = true;
+            }
+            let mut fragment = ExecutableZipFragment {
+                id: artifact.id,
+                target_triples: artifact.target_triples,
+                zip_style: artifact.archive.as_ref().unwrap().zip_style,
+                binaries: binaries
+                    .into_iter()
+                    .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
+                    .collect(),
+            };
+
+            if do_rosetta_fallback && target == X64_MACOS {
+                // Copy the info but respecify it to be arm64 macos
+                let mut arm_fragment = fragment.clone();
+                arm_fragment.target_triples = vec![ARM64_MACOS.to_owned()];
+                artifacts.push(arm_fragment.clone());
+            }
+
+            // musl-static is actually kind of a fake triple we've invented
+            // to let us specify which is which; we want to ensure it exists
+            // for the installer to act on
+            if target == X64_MUSL {
+                fragment.target_triples = vec![X64_MUSL_STATIC.to_owned()];
+            }
+
+            if target == ARM64_MUSL {
+                fragment.target_triples = vec![ARM64_MUSL_STATIC.to_owned()];
+            }
+
+            // Copy the info but lie that it's actually glibc
+            if do_x86_gnu_to_musl_fallback && target == X64_MUSL {
+                let mut musl_fragment = fragment.clone();
+                musl_fragment.target_triples = vec![X64_GNU.to_owned()];
+                artifacts.push(musl_fragment);
+            }
+
+            if do_x86_musl_to_musl_fallback && target == X64_MUSL {
+                let mut musl_fragment = fragment.clone();
+                musl_fragment.target_triples = vec![X64_MUSL_DYNAMIC.to_owned()];
+                artifacts.push(musl_fragment);
+            }
+
+            if do_arm_gnu_to_musl_fallback && target == ARM64_MUSL {
+                let mut musl_fragment = fragment.clone();
+                musl_fragment.target_triples = vec![ARM64_GNU.to_owned()];
+                artifacts.push(musl_fragment);
+            }
+
+            if do_arm_musl_to_musl_fallback && target == ARM64_MUSL {
+                let mut musl_fragment = fragment.clone();
+                musl_fragment.target_triples = vec![ARM64_MUSL_DYNAMIC.to_owned()];
+                artifacts.push(musl_fragment);
+            }
+
+            artifacts.push(fragment);
+        }
+
+        if has_sketchy_archives {
+            warn!("the npm installer currently only knows how to unpack .tar.gz archives\n  consider setting windows-archive and unix-archive to .tar.gz in your config");
+        }
+        if artifacts.is_empty() {
+            warn!("skipping npm installer: not building any supported platforms (use --artifacts=global)");
+            return;
+        };
+
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples: target_triples.into_iter().collect(),
+            archive: Some(Archive {
+                // npm specifically expects the dir inside the tarball to be called "package"
+                with_root: Some("package".into()),
+                dir_path: dir_path.clone(),
+                zip_style,
+                static_assets,
+            }),
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            checksum: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Npm(NpmInstallerInfo {
+                npm_package_name,
+                npm_package_version,
+                npm_package_desc,
+                npm_package_authors,
+                npm_package_license,
+                npm_package_repository_url,
+                npm_package_homepage_url,
+                npm_package_keywords,
+                package_dir: dir_path,
+                bin,
+                inner: InstallerInfo {
+                    dest_path: artifact_path,
+                    app_name: release.app_name.clone(),
+                    app_version: release.version.to_string(),
+                    install_path: release.install_path.clone().into_jinja(),
+                    base_url: download_url.to_owned(),
+                    artifacts,
+                    updaters: vec![],
+                    hint,
+                    desc,
+                    receipt: None,
+                },
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+    }
+
+    fn add_msi_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.local_artifacts_enabled() {
+            return Ok(());
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let app_name = release.app_name.clone();
+        let msi = release.msi.clone().unwrap_or_default();
+
+        // Make an msi for every windows platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let target = &variant.target;
+            if !target.contains("windows") {
+                continue;
+            }
+
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.msi");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_msi");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+
+            // Compute which package we're actually building, based on the binaries
+            let mut package_info: Option<(String, PackageIdx)> = None;
+            for &binary_idx in &binaries {
+                let binary = self.binary(binary_idx);
+                if let Some((existing_spec, _)) = &package_info {
+                    // cargo-wix doesn't clearly support multi-package, so bail
+                    if existing_spec != &binary.pkg_spec {
+                        return Err(DistError::MultiPackageMsi {
+                            artifact_name,
+                            spec1: existing_spec.clone(),
+                            spec2: binary.pkg_spec.clone(),
+                        })?;
+                    }
+                } else {
+                    package_info = Some((binary.pkg_spec.clone(), binary.pkg_idx));
+                }
+            }
+            let Some((pkg_spec, pkg_idx)) = package_info else {
+                return Err(DistError::NoPackageMsi { artifact_name })?;
+            };
+            let manifest_path = self.workspace.package(pkg_idx).manifest_path.clone();
+            let wxs_path = manifest_path
+                .parent()
+                .expect("Cargo.toml had no parent dir!?")
+                .join("wix")
+                .join("main.wxs");
+
+            // Shortcuts and services can only point at binaries this msi installs
+            let binary_names = binaries
+                .iter()
+                .map(|&idx| self.binary(idx).name.clone())
+                .collect::<Vec<_>>();
+            let known_binary = |name: &str, what: &str| {
+                let known = binary_names.iter().any(|b| b == name);
+                if !known {
+                    warn!("msi.{what} mentions {name}, but {pkg_spec} has no such binary; skipping it");
+                }
+                known
+            };
+            let shortcuts = msi
+                .shortcuts
+                .iter()
+                .flatten()
+                .filter(|name| known_binary(name, "shortcuts"))
+                .cloned()
+                .collect();
+            let services = msi
+                .services
+                .iter()
+                .flatten()
+                .filter(|service| known_binary(&service.binary, "services"))
+                .cloned()
+                .collect();
+
+            // Gather up the bundles the installer supports
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Msi(MsiInstallerInfo {
+                    package_dir: dir_path.clone(),
+                    pkg_spec,
+                    target: target.clone(),
+                    file_path: artifact_path.clone(),
+                    wxs_path,
+                    manifest_path,
+                    app_name: app_name.clone(),
+                    shortcuts,
+                    path: msi.path.unwrap_or_default(),
+                    services,
+                    fragments: msi.fragments.clone().unwrap_or_default(),
+                    component_groups: msi.component_groups.clone().unwrap_or_default(),
+                    upgrade_code: msi.upgrade_code.clone(),
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_deb_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+
+        let apt = &self.inner.apt;
+        if self.inner.publish_jobs.contains(&PublishStyle::Apt)
+            && apt.repository.is_none()
+            && apt.s3_bucket.is_none()
+        {
+            warn!("The apt publish job is enabled but there's nowhere to publish to\n  consider setting apt.repository or apt.s3-bucket in Cargo.toml");
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let package = deb_package_name(&release.app_name);
+        let version = package_version(&release.version);
+        let maintainer = release.app_authors.first().cloned().or_else(|| {
+            let app_name = &release.app_name;
+            let makes_debs = variants
+                .iter()
+                .any(|&variant_idx| self.variant(variant_idx).target.contains("linux"));
+            // Debian wants a maintainer, so make one up from the GitHub owner if we can
+            let maintainer = self
+                .inner
+                .hosting
+                .as_ref()
+                .filter(|hosting| hosting.source_host == "github")
+                .map(|hosting| {
+                    let owner = &hosting.owner;
+                    format!("{owner} <{owner}@users.noreply.github.com>")
+                });
+            if makes_debs {
+                if let Some(maintainer) = &maintainer {
+                    warn!("{app_name} has no authors, so its debs will say they're maintained by {maintainer}\n  consider setting authors in Cargo.toml");
+                } else {
+                    warn!("{app_name} has no authors, so its debs won't have a maintainer\n  consider setting authors in Cargo.toml");
+                }
+            }
+            maintainer
+        });
+        let desc = release.app_desc.clone();
+        let license = release.app_license.clone();
+        let homepage = release
+            .app_homepage_url
+            .clone()
+            .or_else(|| release.app_repository_url.clone());
+
+        // Every deb shares one package name, so there can only be one per architecture.
+        // Prefer the glibc build, and only fall back to musl if there isn't one.
+        let gnu_archs = variants
+            .iter()
+            .map(|&variant_idx| &self.variant(variant_idx).target)
+            .filter(|target| target.contains("linux-gnu"))
+            .filter_map(|target| deb_architecture(target))
+            .collect::<SortedSet<_>>();
+
+        // Make a deb for every linux platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let target = &variant.target;
+            if !target.contains("linux") {
+                continue;
+            }
+            let Some(arch) = deb_architecture(target) else {
+                warn!("skipping deb for {target}, as Debian has no architecture for it");
+                continue;
+            };
+            if !target.contains("linux-gnu") && gnu_archs.contains(arch) {
+                continue;
+            }
+
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.deb");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_deb");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let static_assets = variant.static_assets.clone();
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
+
+            let installer_artifact = Artifact {
+                id: artifact_name.clone(),
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Deb(DebInstallerInfo {
+                    artifact_id: artifact_name,
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    package: package.clone(),
+                    version: version.clone(),
+                    arch: arch.to_owned(),
+                    maintainer: maintainer.clone(),
+                    glibc: target.contains("linux-gnu"),
+                    desc: desc.clone(),
+                    license: license.clone(),
+                    homepage: homepage.clone(),
+                    binaries: binary_names,
+                    static_assets,
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+        }
+    }
 
-        for &variant_idx in &release.variants {
+    fn add_rpm_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+
+        let yum = &self.inner.yum;
+        if self.inner.publish_jobs.contains(&PublishStyle::Yum)
+            && yum.repository.is_none()
+            && yum.s3_bucket.is_none()
+        {
+            warn!("The yum publish job is enabled but there's nowhere to publish to\n  consider setting yum.repository or yum.s3-bucket in Cargo.toml");
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let package = release.app_name.clone();
+        let version = package_version(&release.version);
+        let packager = release.app_authors.first().cloned();
+        let desc = release.app_desc.clone();
+        let license = release.app_license.clone();
+        let homepage = release
+            .app_homepage_url
+            .clone()
+            .or_else(|| release.app_repository_url.clone());
+        let signing_key = self.inner.rpm.signing_key.clone();
+        let mut rpm_targets = vec![];
+
+        // Every rpm shares one package name, so there can only be one per architecture.
+        // Prefer the glibc build, and only fall back to musl if there isn't one.
+        let gnu_archs = variants
+            .iter()
+            .map(|&variant_idx| &self.variant(variant_idx).target)
+            .filter(|target| target.contains("linux-gnu"))
+            .filter_map(|target| rpm_architecture(target))
+            .collect::<SortedSet<_>>();
+
+        // Make an rpm for every x86_64/aarch64 linux platform
+        for variant_idx in variants {
             let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
             let target = &variant.target;
-            if target == X64_MACOS {
-                has_x64_apple = true;
+            if !target.contains("linux") {
+                continue;
             }
-            if target == ARM64_MACOS {
-                has_arm_apple = true;
+            let Some(arch) = rpm_architecture(target) else {
+                continue;
+            };
+            if !target.contains("linux-gnu") && gnu_archs.contains(arch) {
+                continue;
             }
-            if target == X64_GNU {
-                has_x86_gnu_linux = true;
+            rpm_targets.push(target.clone());
+
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.rpm");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_rpm");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let static_assets = variant.static_assets.clone();
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
+
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Rpm(RpmInstallerInfo {
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    package: package.clone(),
+                    version: version.clone(),
+                    arch: arch.to_owned(),
+                    packager: packager.clone(),
+                    desc: desc.clone(),
+                    license: license.clone(),
+                    homepage: homepage.clone(),
+                    binaries: binary_names,
+                    static_assets,
+                    signing_key: signing_key.clone(),
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
             }
-            if target == ARM64_GNU {
-                has_arm_gnu_linux = true;
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
             }
-            if target == X64_MUSL {
-                has_x86_static_musl_linux = true;
+        }
+
+        // The machines building rpms need rpmbuild
+        if !rpm_targets.is_empty() {
+            self.release_mut(to_release)
+                .system_dependencies
+                .apt
+                .entry("rpm".to_owned())
+                .or_insert(SystemDependency(SystemDependencyComplex {
+                    version: None,
+                    stage: vec![DependencyKind::Build],
+                    targets: rpm_targets,
+                }));
+        }
+    }
+
+    fn add_apk_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+
+        let apk = &self.inner.apk;
+        if self.inner.publish_jobs.contains(&PublishStyle::Apk)
+            && apk.repository.is_none()
+            && apk.s3_bucket.is_none()
+        {
+            warn!("The apk publish job is enabled but there's nowhere to publish to\n  consider setting apk.repository or apk.s3-bucket in Cargo.toml");
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let package = release.app_name.to_lowercase();
+        let version = apk_version(&release.version);
+        let packager = release.app_authors.first().cloned();
+        let desc = release.app_desc.clone();
+        let license = release.app_license.clone();
+        let homepage = release
+            .app_homepage_url
+            .clone()
+            .or_else(|| release.app_repository_url.clone());
+
+        // Make an apk for every musl linux platform, since Alpine doesn't have glibc
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let target = &variant.target;
+            if !target.contains("linux-musl") {
+                continue;
             }
-            if target == ARM64_MUSL {
-                has_arm_static_musl_linux = true;
+            let Some(arch) = apk_architecture(target) else {
+                warn!("skipping apk for {target}, as Alpine has no architecture for it");
+                continue;
+            };
+
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.apk");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_apk");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let static_assets = variant.static_assets.clone();
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
+
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Apk(ApkInstallerInfo {
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    package: package.clone(),
+                    version: version.clone(),
+                    arch: arch.to_owned(),
+                    packager: packager.clone(),
+                    desc: desc.clone(),
+                    license: license.clone(),
+                    homepage: homepage.clone(),
+                    binaries: binary_names,
+                    static_assets,
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
             }
         }
+    }
 
-        let do_rosetta_fallback = has_x64_apple && !has_arm_apple;
-        let do_x86_gnu_to_musl_fallback = !has_x86_gnu_linux && has_x86_static_musl_linux;
-        let do_arm_gnu_to_musl_fallback = !has_arm_gnu_linux && has_arm_static_musl_linux;
-        let do_x86_musl_to_musl_fallback = has_x86_static_musl_linux && !has_x86_dynamic_musl_linux;
-        let do_arm_musl_to_musl_fallback = has_arm_static_musl_linux && !has_arm_dynamic_musl_linux;
+    fn add_appimage_installer(&mut self, to_release: ReleaseIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
 
-        // Gather up the bundles the installer supports
-        let mut artifacts = vec![];
-        let mut target_triples = SortedSet::new();
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let config = release.appimage.clone().unwrap_or_default();
+        let Some(icon) = config.icon else {
+            warn!("skipping AppImages for {}, as they need an icon\n  consider setting appimage.icon in Cargo.toml", release.app_name);
+            return;
+        };
+        let categories = config
+            .categories
+            .unwrap_or_else(|| vec!["Utility".to_owned()]);
+        let terminal = config.terminal.unwrap_or(true);
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let app_name = release.app_name.clone();
+        let desc = release.app_desc.clone();
+        // AppImages can only find their updates on GitHub Releases
+        let github = self.inner.hosting.as_ref().filter(|hosting| {
+            hosting.source_host == "github" && hosting.hosts.contains(&HostingStyle::Github)
+        });
+        let github = github.map(|hosting| (hosting.owner.clone(), hosting.project.clone()));
+        let mut appimage_targets = vec![];
+
+        // Make an AppImage for every linux platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let target = &variant.target;
+            if !target.contains("linux") {
+                continue;
+            }
+            let Some(arch) = appimage_architecture(target) else {
+                continue;
+            };
+            appimage_targets.push(target.clone());
+
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.AppImage");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let zsync_name = format!("{artifact_name}.zsync");
+            let zsync_path = self.inner.dist_dir.join(&zsync_name);
+            let dir_name = format!("{variant_id}_appimage");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let static_assets = variant.static_assets.clone();
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
+            let update_info = github.as_ref().map(|(owner, project)| {
+                format!("gh-releases-zsync|{owner}|{project}|latest|{zsync_name}")
+            });
+
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::AppImage(AppImageInstallerInfo {
+                    file_path: artifact_path.clone(),
+                    zsync_path: update_info.as_ref().map(|_| zsync_path.clone()),
+                    package_dir: dir_path.clone(),
+                    app_name: app_name.clone(),
+                    arch: arch.to_owned(),
+                    desc: desc.clone(),
+                    binaries: binary_names,
+                    static_assets,
+                    icon: icon.clone(),
+                    categories: categories.clone(),
+                    terminal,
+                    update_info: update_info.clone(),
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+
+            // The zsync file lets the AppImage download just what changed in an update
+            if update_info.is_some() {
+                let target = self.variant(variant_idx).target.clone();
+                let zsync_artifact = Artifact {
+                    id: zsync_name,
+                    target_triples: vec![target],
+                    file_path: zsync_path,
+                    required_binaries: FastMap::new(),
+                    archive: None,
+                    checksum: None,
+                    kind: ArtifactKind::Zsync(ZsyncImpl {}),
+                    is_global: false,
+                };
+                self.add_local_artifact(variant_idx, zsync_artifact);
+            }
+        }
+
+        // appimagetool needs zsyncmake to make the zsync files
+        if !appimage_targets.is_empty() && github.is_some() {
+            self.release_mut(to_release)
+                .system_dependencies
+                .apt
+                .entry("zsync".to_owned())
+                .or_insert(SystemDependency(SystemDependencyComplex {
+                    version: None,
+                    stage: vec![DependencyKind::Build],
+                    targets: appimage_targets,
+                }));
+        }
+    }
+
+    fn add_pkg_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.local_artifacts_enabled() {
+            return Ok(());
+        }
+
+        let Some(identifier) = self.release_pkg_identifier(to_release) else {
+            let app_name = &self.release(to_release).app_name;
+            warn!("skipping pkgs for {app_name}, as they need an identifier\n  consider setting pkg.identifier in Cargo.toml");
+            return Ok(());
+        };
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let config = release.pkg.clone().unwrap_or_default();
+        let install_location = config
+            .install_location
+            .unwrap_or_else(|| "/usr/local".to_owned());
+        // Fall back to the workspace's macOS signing settings
+        let macos_sign = &self.inner.macos_sign;
+        let signing_identity = config
+            .signing_identity
+            .or_else(|| macos_sign.installer_identity.clone());
+        let notarize = macos_sign.notarize.unwrap_or(false);
+        if notarize && signing_identity.is_none() {
+            return Err(DistError::NotarizeWithoutIdentity {
+                setting: "macos-sign.notarize".to_owned(),
+                what: "pkgs".to_owned(),
+                identity: "pkg.signing-identity or macos-sign.installer-identity".to_owned(),
+            });
+        }
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
+        let version = release.version.to_string();
 
-        let mut has_sketchy_archives = false;
-        for &variant_idx in &release.variants {
+        // Make a pkg for every macOS platform
+        for variant_idx in variants {
             let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
             let target = &variant.target;
+            if !target.contains("apple-darwin") {
+                continue;
+            }
 
-            // Compute the artifact zip this variant *would* make *if* it were built
-            // FIXME: this is a kind of hacky workaround for the fact that we don't have a good
-            // way to add artifacts to the graph and then say "ok but don't build it".
-            let (artifact, binaries) =
-                self.make_executable_zip_for_variant(to_release, variant_idx);
-
-            target_triples.insert(target.clone());
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.pkg");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_pkg");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
 
-            let variant_zip_style = artifact.archive.as_ref().unwrap().zip_style;
-            if variant_zip_style != ZipStyle::Tar(CompressionImpl::Gzip) {
-                has_sketchy_archives = true;
-            }
-            let mut fragment = ExecutableZipFragment {
-                id: artifact.id,
-                target_triples: artifact.target_triples,
-                zip_style: artifact.archive.as_ref().unwrap().zip_style,
-                binaries: binaries
-                    .into_iter()
-                    .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
-                    .collect(),
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Pkg(PkgInstallerInfo {
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    identifier: identifier.clone(),
+                    version: version.clone(),
+                    install_location: install_location.clone(),
+                    binaries: binary_names,
+                    preinstall: config.preinstall.clone(),
+                    postinstall: config.postinstall.clone(),
+                    signing_identity: signing_identity.clone(),
+                    notarize,
+                })),
+                is_global: false,
             };
 
-            if do_rosetta_fallback && target == X64_MACOS {
-                // Copy the info but respecify it to be arm64 macos
-                let mut arm_fragment = fragment.clone();
-                arm_fragment.target_triples = vec![ARM64_MACOS.to_owned()];
-                artifacts.push(arm_fragment.clone());
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
             }
-
-            // musl-static is actually kind of a fake triple we've invented
-            // to let us specify which is which; we want to ensure it exists
-            // for the installer to act on
-            if target == X64_MUSL {
-                fragment.target_triples = vec![X64_MUSL_STATIC.to_owned()];
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
             }
+        }
 
-            if target == ARM64_MUSL {
-                fragment.target_triples = vec![ARM64_MUSL_STATIC.to_owned()];
-            }
+        Ok(())
+    }
 
-            // Copy the info but lie that it's actually glibc
-            if do_x86_gnu_to_musl_fallback && target == X64_MUSL {
-                let mut musl_fragment = fragment.clone();
-                musl_fragment.target_triples = vec![X64_GNU.to_owned()];
-                artifacts.push(musl_fragment);
-            }
+    /// Get the identifier a release's pkgs are built with, if there is one
+    fn release_pkg_identifier(&self, to_release: ReleaseIdx) -> Option<String> {
+        let release = self.release(to_release);
+        let github_owner = self
+            .inner
+            .hosting
+            .as_ref()
+            .filter(|hosting| hosting.source_host == "github")
+            .map(|hosting| hosting.owner.clone());
+        release
+            .pkg
+            .as_ref()
+            .and_then(|config| config.identifier.clone())
+            .or_else(|| github_owner.map(|owner| pkg_identifier(&owner, &release.app_name)))
+    }
 
-            if do_x86_musl_to_musl_fallback && target == X64_MUSL {
-                let mut musl_fragment = fragment.clone();
-                musl_fragment.target_triples = vec![X64_MUSL_DYNAMIC.to_owned()];
-                artifacts.push(musl_fragment);
-            }
+    fn add_dmg_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.local_artifacts_enabled() {
+            return Ok(());
+        }
 
-            if do_arm_gnu_to_musl_fallback && target == ARM64_MUSL {
-                let mut musl_fragment = fragment.clone();
-                musl_fragment.target_triples = vec![ARM64_GNU.to_owned()];
-                artifacts.push(musl_fragment);
-            }
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let config = release.dmg.clone().unwrap_or_default();
+        let volume_name = config
+            .volume_name
+            .unwrap_or_else(|| format!("{} {}", release.app_name, release.version));
+        // Fall back to the workspace's macOS signing settings
+        let macos_sign = &self.inner.macos_sign;
+        let signing_identity = config
+            .signing_identity
+            .or_else(|| macos_sign.identity.clone());
+        let notarize = config.notarize.or(macos_sign.notarize).unwrap_or(false);
+        if notarize && signing_identity.is_none() {
+            return Err(DistError::NotarizeWithoutIdentity {
+                setting: "dmg.notarize".to_owned(),
+                what: "dmgs".to_owned(),
+                identity: "dmg.signing-identity or macos-sign.identity".to_owned(),
+            });
+        }
+        let variants = release.variants.clone();
+        let checksum = release.checksum;
 
-            if do_arm_musl_to_musl_fallback && target == ARM64_MUSL {
-                let mut musl_fragment = fragment.clone();
-                musl_fragment.target_triples = vec![ARM64_MUSL_DYNAMIC.to_owned()];
-                artifacts.push(musl_fragment);
+        // Make a dmg for every macOS platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let target = &variant.target;
+            if !target.contains("apple-darwin") {
+                continue;
             }
 
-            artifacts.push(fragment);
-        }
-
-        if has_sketchy_archives {
-            warn!("the npm installer currently only knows how to unpack .tar.gz archives\n  consider setting windows-archive and unix-archive to .tar.gz in your config");
-        }
-        if artifacts.is_empty() {
-            warn!("skipping npm installer: not building any supported platforms (use --artifacts=global)");
-            return;
-        };
+            let variant_id = &variant.id;
+            let artifact_name = format!("{variant_id}.dmg");
+            let artifact_path = self.inner.dist_dir.join(&artifact_name);
+            let dir_name = format!("{variant_id}_dmg");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let binary_names = binaries
+                .iter()
+                .map(|&binary_idx| self.binary(binary_idx).file_name.clone())
+                .collect();
 
-        let installer_artifact = Artifact {
-            id: artifact_name,
-            target_triples: target_triples.into_iter().collect(),
-            archive: Some(Archive {
-                // npm specifically expects the dir inside the tarball to be called "package"
-                with_root: Some("package".into()),
-                dir_path: dir_path.clone(),
-                zip_style,
-                static_assets,
-            }),
-            file_path: artifact_path.clone(),
-            required_binaries: FastMap::new(),
-            checksum: None,
-            kind: ArtifactKind::Installer(InstallerImpl::Npm(NpmInstallerInfo {
-                npm_package_name,
-                npm_package_version,
-                npm_package_desc,
-                npm_package_authors,
-                npm_package_license,
-                npm_package_repository_url,
-                npm_package_homepage_url,
-                npm_package_keywords,
-                package_dir: dir_path,
-                bin,
-                inner: InstallerInfo {
-                    dest_path: artifact_path,
-                    app_name: release.app_name.clone(),
-                    app_version: release.version.to_string(),
-                    install_path: release.install_path.clone().into_jinja(),
-                    base_url: download_url.to_owned(),
-                    artifacts,
-                    updaters: vec![],
-                    hint,
-                    desc,
-                    receipt: None,
-                },
-            })),
-            is_global: true,
-        };
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                }),
+                checksum: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Dmg(DmgInstallerInfo {
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    volume_name: volume_name.clone(),
+                    binaries: binary_names,
+                    background: config.background.clone(),
+                    applications_symlink: config.applications_symlink.unwrap_or(true),
+                    signing_identity: signing_identity.clone(),
+                    notarize,
+                })),
+                is_global: false,
+            };
 
-        self.add_global_artifact(to_release, installer_artifact);
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+        }
+        Ok(())
     }
 
-    fn add_msi_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+    fn add_msix_installer(&mut self, to_release: ReleaseIdx) {
         if !self.local_artifacts_enabled() {
-            return Ok(());
+            return;
         }
 
         // Clone info we need from the release to avoid borrowing across the loop
         let release = self.release(to_release);
+        let config = release.msix.clone().unwrap_or_default();
+        let Some(publisher) = config.publisher else {
+            warn!("skipping msixes for {}, as they need a publisher\n  consider setting msix.publisher in Cargo.toml", release.app_name);
+            return;
+        };
+        let Some(logo) = config.logo else {
+            warn!("skipping msixes for {}, as they need a logo\n  consider setting msix.logo in Cargo.toml", release.app_name);
+            return;
+        };
+        let identity_name = config
+            .identity_name
+            .unwrap_or_else(|| msix_identity_name(&release.app_name));
+        let display_name = config
+            .display_name
+            .unwrap_or_else(|| release.app_name.clone());
+        // Authors usually look like "Name <email>", and only the name is for display
+        let publisher_display_name = config.publisher_display_name.unwrap_or_else(|| {
+            release
+                .app_authors
+                .first()
+                .and_then(|author| author.split('<').next())
+                .map(|name| name.trim().to_owned())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| display_name.clone())
+        });
+        let description = release
+            .app_desc
+            .clone()
+            .unwrap_or_else(|| display_name.clone());
+        let version = msix_version(&release.version);
+        let sign = config.sign.unwrap_or(false);
         let variants = release.variants.clone();
         let checksum = release.checksum;
 
-        // Make an msi for every windows platform
+        // Make an msix for every windows platform
         for variant_idx in variants {
             let variant = self.variant(variant_idx);
             let binaries = variant.binaries.clone();
@@ -2415,41 +4372,28 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             if !target.contains("windows") {
                 continue;
             }
+            let Some(architecture) = msix_architecture(target) else {
+                warn!("skipping msix for {target}, as it's not an architecture MSIX supports");
+                continue;
+            };
 
             let variant_id = &variant.id;
-            let artifact_name = format!("{variant_id}.msi");
+            let artifact_name = format!("{variant_id}.msix");
             let artifact_path = self.inner.dist_dir.join(&artifact_name);
-            let dir_name = format!("{variant_id}_msi");
+            let dir_name = format!("{variant_id}_msix");
             let dir_path = self.inner.dist_dir.join(&dir_name);
-
-            // Compute which package we're actually building, based on the binaries
-            let mut package_info: Option<(String, PackageIdx)> = None;
-            for &binary_idx in &binaries {
-                let binary = self.binary(binary_idx);
-                if let Some((existing_spec, _)) = &package_info {
-                    // cargo-wix doesn't clearly support multi-package, so bail
-                    if existing_spec != &binary.pkg_spec {
-                        return Err(DistError::MultiPackageMsi {
-                            artifact_name,
-                            spec1: existing_spec.clone(),
-                            spec2: binary.pkg_spec.clone(),
-                        })?;
+            let applications = binaries
+                .iter()
+                .map(|&binary_idx| {
+                    let binary = self.binary(binary_idx);
+                    MsixApplication {
+                        id: msix_application_id(&binary.name),
+                        name: binary.name.clone(),
+                        file_name: binary.file_name.clone(),
                     }
-                } else {
-                    package_info = Some((binary.pkg_spec.clone(), binary.pkg_idx));
-                }
-            }
-            let Some((pkg_spec, pkg_idx)) = package_info else {
-                return Err(DistError::NoPackageMsi { artifact_name })?;
-            };
-            let manifest_path = self.workspace.package(pkg_idx).manifest_path.clone();
-            let wxs_path = manifest_path
-                .parent()
-                .expect("Cargo.toml had no parent dir!?")
-                .join("wix")
-                .join("main.wxs");
+                })
+                .collect();
 
-            // Gather up the bundles the installer supports
             let installer_artifact = Artifact {
                 id: artifact_name,
                 target_triples: vec![target.clone()],
@@ -2462,13 +4406,19 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     static_assets: vec![],
                 }),
                 checksum: None,
-                kind: ArtifactKind::Installer(InstallerImpl::Msi(MsiInstallerInfo {
-                    package_dir: dir_path.clone(),
-                    pkg_spec,
-                    target: target.clone(),
+                kind: ArtifactKind::Installer(InstallerImpl::Msix(MsixInstallerInfo {
                     file_path: artifact_path.clone(),
-                    wxs_path,
-                    manifest_path,
+                    package_dir: dir_path.clone(),
+                    identity_name: identity_name.clone(),
+                    publisher: publisher.clone(),
+                    publisher_display_name: publisher_display_name.clone(),
+                    display_name: display_name.clone(),
+                    description: description.clone(),
+                    version: version.clone(),
+                    architecture: architecture.to_owned(),
+                    applications,
+                    logo: logo.clone(),
+                    sign,
                 })),
                 is_global: false,
             };
@@ -2488,8 +4438,6 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 self.add_artifact_checksum(variant_idx, installer_idx, checksum);
             }
         }
-
-        Ok(())
     }
 
     fn add_local_artifact(
@@ -2625,6 +4573,9 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                         target_filename: artifact.file_path.to_owned(),
                     }))
                 }
+                ArtifactKind::Zsync(_) => {
+                    // The AppImage's build makes this
+                }
             }
 
             if let Some(archive) = &artifact.archive {
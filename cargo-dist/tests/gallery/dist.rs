@@ -110,6 +110,13 @@ pub struct DistResult {
     homebrew_installer_path: Option<Utf8PathBuf>,
     powershell_installer_path: Option<Utf8PathBuf>,
     npm_installer_package_path: Option<Utf8PathBuf>,
+    aur_pkgbuild_path: Option<Utf8PathBuf>,
+    flathub_package_path: Option<Utf8PathBuf>,
+    snapcraft_path: Option<Utf8PathBuf>,
+    nix_flake_path: Option<Utf8PathBuf>,
+    homebrew_cask_path: Option<Utf8PathBuf>,
+    scoop_manifest_path: Option<Utf8PathBuf>,
+    chocolatey_package_dir: Option<Utf8PathBuf>,
 }
 
 pub struct PlanResult {
@@ -244,9 +251,20 @@ impl<'a> TestContext<'a, Tools> {
         let target_dir = Utf8PathBuf::from("target/distrib");
         let ps_installer = Utf8PathBuf::from(format!("{target_dir}/{app_name}-installer.ps1"));
         let sh_installer = Utf8PathBuf::from(format!("{target_dir}/{app_name}-installer.sh"));
-        let homebrew_installer = Self::load_file_with_suffix(target_dir.clone(), ".rb");
+        // Casks are .rb files too, so don't mistake one for the formula
+        let homebrew_formulas = Self::load_files_with_suffix(target_dir.clone(), ".rb")
+            .into_iter()
+            .filter(|path| !path.as_str().ends_with(".cask.rb"))
+            .collect::<Vec<_>>();
+        assert!(
+            homebrew_formulas.len() <= 1,
+            "found {} homebrew formulas, expected 1 or 0",
+            homebrew_formulas.len()
+        );
+        let homebrew_installer = homebrew_formulas.first().cloned();
         let npm_installer =
             Utf8PathBuf::from(format!("{target_dir}/{app_name}-npm-package.tar.gz"));
+        let flathub_package = Utf8PathBuf::from(format!("{target_dir}/{app_name}-flathub.tar.gz"));
 
         Ok(DistResult {
             test_name: test_name.to_owned(),
@@ -255,6 +273,13 @@ impl<'a> TestContext<'a, Tools> {
             powershell_installer_path: ps_installer.exists().then_some(ps_installer),
             homebrew_installer_path: homebrew_installer,
             npm_installer_package_path: npm_installer.exists().then_some(npm_installer),
+            aur_pkgbuild_path: Self::load_file_with_suffix(target_dir.clone(), ".PKGBUILD"),
+            flathub_package_path: flathub_package.exists().then_some(flathub_package),
+            snapcraft_path: Self::load_file_with_suffix(target_dir.clone(), ".snapcraft.yaml"),
+            nix_flake_path: Self::load_file_with_suffix(target_dir.clone(), ".flake.nix"),
+            homebrew_cask_path: Self::load_file_with_suffix(target_dir.clone(), ".cask.rb"),
+            scoop_manifest_path: Self::load_file_with_suffix(target_dir.clone(), ".scoop.json"),
+            chocolatey_package_dir: Self::load_file_with_suffix(target_dir.clone(), "_chocolatey"),
         })
     }
 
@@ -837,6 +862,37 @@ impl DistResult {
             "npm-package.tar.gz",
             self.npm_installer_package_path.as_deref(),
         )?;
+        append_snapshot_file(
+            &mut snapshots,
+            "PKGBUILD",
+            self.aur_pkgbuild_path.as_deref(),
+        )?;
+        append_snapshot_tarball(
+            &mut snapshots,
+            "flathub.tar.gz",
+            self.flathub_package_path.as_deref(),
+        )?;
+        append_snapshot_file(
+            &mut snapshots,
+            "snapcraft.yaml",
+            self.snapcraft_path.as_deref(),
+        )?;
+        append_snapshot_file(&mut snapshots, "flake.nix", self.nix_flake_path.as_deref())?;
+        append_snapshot_file(
+            &mut snapshots,
+            "cask.rb",
+            self.homebrew_cask_path.as_deref(),
+        )?;
+        append_snapshot_file(
+            &mut snapshots,
+            "scoop.json",
+            self.scoop_manifest_path.as_deref(),
+        )?;
+        append_snapshot_dir(
+            &mut snapshots,
+            "chocolatey",
+            self.chocolatey_package_dir.as_deref(),
+        )?;
 
         Ok(Snapshots {
             settings: snapshot_settings_with_gallery_filter(),
@@ -1066,6 +1122,37 @@ fn append_snapshot_file(out: &mut String, name: &str, src_path: Option<&Utf8Path
     append_snapshot_string(out, name, &src)
 }
 
+fn append_snapshot_dir(out: &mut String, name: &str, src_dir: Option<&Utf8Path>) -> Result<()> {
+    // Skip snapshotting this dir if absent
+    let Some(src_dir) = src_dir else {
+        return Ok(());
+    };
+
+    // We shove everything in a BTreeMap to keep ordering stable
+    let mut results = BTreeMap::new();
+    let mut dirs = vec![src_dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        for entry in dir.read_dir_utf8().expect("couldn't read dir") {
+            let path = entry.expect("couldn't read dir entry").into_path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let relpath = path
+                    .strip_prefix(src_dir)
+                    .unwrap()
+                    .as_str()
+                    .replace('\\', "/");
+                results.insert(relpath, axoasset::LocalAsset::load_string(&path)?);
+            }
+        }
+    }
+
+    for (path, val) in &results {
+        append_snapshot_string(out, &format!("{name}/{path}"), val)?;
+    }
+    Ok(())
+}
+
 fn append_snapshot_string(out: &mut String, name: &str, val: &str) -> Result<()> {
     use std::fmt::Write;
 